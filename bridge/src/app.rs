@@ -1,40 +1,137 @@
 use std::{
     collections::HashMap,
     error::Error,
-    fs, panic,
+    fs,
+    io::ErrorKind,
+    panic,
     path::{Path, PathBuf},
+    time::{Duration, Instant},
 };
 
 use mapgen_core::{
-    generator::{Generator, GeneratorParams},
-    brush::Brush,
-    map::Map,
+    branding::{logo_pixels, rasterize_text, Branding},
+    config::{GenerationPreset, Severity},
+    generator::Generator,
+    map::{BlockType, Map, PastePolicy},
+    migration::{self, PRESET_SCHEMA},
+    palette::Palette,
+    pipeline::{check_parity, GenerationPipeline, Parity},
     random::{random_seed, Random, Seed},
-    walker::{NormalWaypoints, Walker, WalkerParams},
+    snapshot::{diff_tiles, Mismatch, Snapshot},
+    stream::{StreamConfig, StreamDirection, StripStream},
+    transform::Transform,
+    tuning::{fitness, tune, TuneConfig},
 };
-use mapgen_exporter::{Exporter, ExporterConfig};
 
-use clap::{crate_version, Parser};
+use clap::{crate_version, Parser, Subcommand, ValueEnum};
 use itertools::Itertools;
-use log::{error, info, warn};
+use log::{debug, error, info, warn};
+use ndarray::Array2;
 use serde::de::DeserializeOwned;
-use twmap::TwMap;
+use twmap::{GameLayer, TwMap};
 
-use crate::econ::*;
+use crate::{econ::*, logging};
 
 #[derive(Parser, Debug)]
 #[command(name = "DDNet Bridge")]
 #[command(version = crate_version!())]
 #[command(about = "Detect DDNet-Server votes via econ to trigger map generations", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    /// log generation phases, repeat to log every walker step
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// log one json object per line
+    #[arg(long, global = true)]
+    json_logs: bool,
+}
+
+#[derive(Subcommand, Debug)]
 enum Command {
     #[clap(name = "start", about = "Start the ddnet bridge")]
     StartBridge(BridgeArgs),
 
+    #[clap(name = "list", about = "Print a list of available generation presets")]
+    ListConfigs(BridgeArgs),
+
+    #[clap(name = "tune", about = "Tune preset parameters for the map fitness")]
+    Tune(TuneArgs),
+
     #[clap(
-        name = "list",
-        about = "Print a list of available map- & generation configs"
+        name = "verify-snapshots",
+        about = "Regenerate the snapshot maps and compare their hashes"
     )]
-    ListConfigs(BridgeArgs),
+    VerifySnapshots(SnapshotArgs),
+
+    #[clap(
+        name = "verify-parity",
+        about = "Generate maps in one go and step by step and compare them"
+    )]
+    VerifyParity(ParityArgs),
+
+    #[clap(name = "stream", about = "Generate the strips of an endless map")]
+    Stream(StreamArgs),
+
+    #[clap(name = "compose", about = "Stitch a map into another one at an offset")]
+    Compose(ComposeArgs),
+
+    #[clap(subcommand, name = "config", about = "Work with preset files")]
+    Config(ConfigCommand),
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    #[clap(
+        name = "convert",
+        about = "Migrate presets to the current schema, validate them and write them in a format"
+    )]
+    Convert(ConvertArgs),
+}
+
+#[derive(Parser, Debug)]
+struct ConvertArgs {
+    /// preset files, json or toml by their extension
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
+
+    /// format written
+    #[arg(long, value_enum, default_value_t = ConfigFormat::Json)]
+    to: ConfigFormat,
+
+    /// write every field of the current schema, otherwise only the fields in the input are kept
+    #[arg(short, long, default_value_t = false)]
+    upgrade: bool,
+
+    /// directory the converted presets are written to, next to the inputs by default
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// write presets with validation errors too
+    #[arg(short, long, default_value_t = false)]
+    force: bool,
+
+    /// merge or drop main route waypoints with spacing problems before validating, the fixed
+    /// presets are written with every field
+    #[arg(long, default_value_t = false)]
+    fix_waypoints: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ConfigFormat {
+    Json,
+    Toml,
+}
+
+impl ConfigFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Toml => "toml",
+        }
+    }
 }
 
 #[derive(Parser, Debug)]
@@ -45,119 +142,273 @@ struct BridgeArgs {
     /// ec_port
     port: u16,
 
+    /// ec_bindaddr
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+
     /// debug to console
     #[arg(short, long, default_value_t = false)]
     debug: bool,
 
-    /// path to maps directory
-    maps: PathBuf,
+    /// generate and change to a new map every given amount of minutes
+    #[arg(short, long)]
+    rotate: Option<u64>,
 
-    /// path to base maps directory
-    #[arg(default_value = "../data/maps")]
-    base_maps: PathBuf,
+    /// clockwise quarter turns of the generated maps
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..4))]
+    turns: u8,
 
-    /// path to generation configurations directory
-    #[arg(default_value = "../data/configs/generator")]
-    gen_configs: PathBuf,
+    /// flip the columns of the generated maps, before turning them
+    #[arg(long)]
+    mirror_x: bool,
 
-    /// path to walker configurations directory
-    #[arg(default_value = "../data/configs/walker")]
-    wal_configs: PathBuf,
+    /// flip the rows of the generated maps, before turning them
+    #[arg(long)]
+    mirror_y: bool,
 
-    /// path to walker configurations directory
-    #[arg(default_value = "../data/configs/waypoints")]
-    way_configs: PathBuf,
-}
+    /// generate every map twice with audited random draws, dump the draw sequence next to the
+    /// map and report where the two runs diverge
+    #[arg(long)]
+    audit_rng: bool,
 
-/// keeps track of the server bridge state
-pub struct ServerBridge {
-    /// econ connection to game server
-    econ: Option<Econ>,
+    /// text stamped into every generated map, for the server's branding
+    #[arg(long, conflicts_with = "brand_logo")]
+    brand_text: Option<String>,
 
-    /// loaded base maps
-    base_maps: Vec<TwMap>,
+    /// monochrome png stamped into every generated map, its opaque dark pixels become blocks
+    #[arg(long)]
+    brand_logo: Option<PathBuf>,
 
-    /// stores all available generation configs
-    generator_configs: HashMap<String, GeneratorParams>,
+    /// map tile the top left corner of the branding is stamped at
+    #[arg(long, num_args = 2, value_names = ["X", "Y"], default_values_t = [2, 2])]
+    brand_at: Vec<usize>,
 
-    /// stores all available walker configs
-    walker_configs: HashMap<String, WalkerParams>,
+    /// tiles per font pixel of the branding text
+    #[arg(long, default_value_t = 1)]
+    brand_scale: usize,
 
-    /// stores all available map configs
-    waypoints_configs: HashMap<String, NormalWaypoints>,
+    /// stamp the branding with unhookable instead of hookable blocks
+    #[arg(long)]
+    brand_unhookable: bool,
 
-    /// selected generator config
-    current_generator_params: String,
+    /// path to the server maps directory, generated maps are saved there
+    maps: PathBuf,
 
-    /// selected walker config
-    current_walker_params: String,
+    /// path to generation presets directory
+    #[arg(default_value = "../data/configs/presets")]
+    presets: PathBuf,
+}
 
-    /// selected waypoints
-    current_waypoints: String,
+#[derive(Parser, Debug)]
+struct TuneArgs {
+    /// preset the search starts from
+    #[arg(long)]
+    preset: String,
+
+    /// mutations tried
+    #[arg(short, long, default_value_t = 100)]
+    iterations: usize,
+
+    /// seeds every mutation is generated with
+    #[arg(short, long, default_value_t = 4)]
+    seeds: Seed,
+
+    /// path the best preset is written to, `<preset>_tuned.json` in the presets directory by
+    /// default
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// path to generation presets directory
+    #[arg(default_value = "../data/configs/presets")]
+    presets: PathBuf,
+}
 
-    /// stores start arguments
-    args: BridgeArgs,
+#[derive(Parser, Debug)]
+struct ParityArgs {
+    #[arg(long)]
+    seed: Seed,
 
-    /// map generator
-    generator: Generator,
+    /// every preset by default
+    #[arg(long)]
+    preset: Option<String>,
+
+    /// path to generation presets directory
+    #[arg(default_value = "../data/configs/presets")]
+    presets: PathBuf,
 }
 
-impl ServerBridge {
-    fn new(args: BridgeArgs) -> ServerBridge {
-        let mut base_maps = Vec::new();
+#[derive(Parser, Debug)]
+struct StreamArgs {
+    /// preset the strips are generated with
+    #[arg(long)]
+    preset: String,
+
+    /// strips generated before stopping
+    #[arg(short, long, default_value_t = 10)]
+    strips: usize,
+
+    /// side new strips are appended at
+    #[arg(long, value_enum, default_value_t = StreamSide::Up)]
+    direction: StreamSide,
+
+    /// route length of a strip in tiles
+    #[arg(long, default_value_t = 200)]
+    length: usize,
+
+    /// room for the route across the direction in tiles
+    #[arg(long, default_value_t = 100)]
+    width: usize,
+
+    /// random by default
+    #[arg(long)]
+    seed: Option<Seed>,
+
+    /// directory the strips and their offsets are written to
+    #[arg(short, long, default_value = "strips")]
+    output: PathBuf,
+
+    /// path to generation presets directory
+    #[arg(default_value = "../data/configs/presets")]
+    presets: PathBuf,
+}
+
+#[derive(Parser, Debug)]
+struct ComposeArgs {
+    /// map the other one is stitched into, its design is kept
+    base: PathBuf,
+
+    /// map stitched into the base, only its physics layers are copied
+    other: PathBuf,
+
+    /// tile the top left corner of the other map is placed at, the base grows to fit it
+    #[arg(
+        long,
+        value_delimiter = ',',
+        num_args = 2,
+        value_names = ["X", "Y"],
+        default_values_t = [0, 0]
+    )]
+    offset: Vec<usize>,
+
+    /// how tiles of the other map combine with the ones under them
+    #[arg(long, value_enum, default_value_t = ComposePolicy::Overwrite)]
+    policy: ComposePolicy,
+
+    /// path the composed map is written to
+    #[arg(short, long, default_value = "composed.map")]
+    output: PathBuf,
+}
 
-        for path in load_base_maps_paths(args.base_maps.as_path()) {
-            let mut tw_map = TwMap::parse_file(path).expect("failed to parse base map");
-            tw_map.load().expect("failed to load base map");
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ComposePolicy {
+    Overwrite,
+    SkipEmpty,
+    FillEmpty,
+}
+
+impl From<ComposePolicy> for PastePolicy {
+    fn from(policy: ComposePolicy) -> Self {
+        match policy {
+            ComposePolicy::Overwrite => Self::Overwrite,
+            ComposePolicy::SkipEmpty => Self::SkipEmpty,
+            ComposePolicy::FillEmpty => Self::FillEmpty,
+        }
+    }
+}
 
-            base_maps.push(tw_map);
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum StreamSide {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl From<StreamSide> for StreamDirection {
+    fn from(side: StreamSide) -> Self {
+        match side {
+            StreamSide::Up => Self::Up,
+            StreamSide::Down => Self::Down,
+            StreamSide::Left => Self::Left,
+            StreamSide::Right => Self::Right,
         }
+    }
+}
 
-        let generator_configs =
-            load_configs_from_dir::<GeneratorParams, _>(args.gen_configs.as_path()).unwrap();
-        let walker_configs =
-            load_configs_from_dir::<WalkerParams, _>(args.wal_configs.as_path()).unwrap();
-        let waypoints_configs =
-            load_configs_from_dir::<NormalWaypoints, _>(args.way_configs.as_path()).unwrap();
+#[derive(Parser, Debug)]
+struct SnapshotArgs {
+    /// record the current maps as the new snapshots instead of comparing
+    #[arg(short, long, default_value_t = false)]
+    update: bool,
 
-        let current_generator_params = generator_configs.iter().last().unwrap().0.clone();
-        let current_walker_params = walker_configs.iter().last().unwrap().0.clone();
-        let current_waypoints = waypoints_configs.iter().last().unwrap().0.clone();
+    /// seeds recorded per preset on update
+    #[arg(short, long, default_value_t = 4)]
+    seeds: Seed,
 
-        let gen = &generator_configs[&current_generator_params];
-        let wal = &walker_configs[&current_walker_params];
-        let way = &waypoints_configs[&current_waypoints];
+    /// path to the snapshots directory, holds the hashes, the maps and the diffs
+    #[arg(long, default_value = "../data/snapshots")]
+    snapshots: PathBuf,
 
-        let prng = Random::new(random_seed());
+    /// path to generation presets directory
+    #[arg(default_value = "../data/configs/presets")]
+    presets: PathBuf,
+}
 
-        let mut walker = Walker::new(Brush::new(5, 0.0), Brush::new(7, 0.0), prng, wal.clone());
+/// keeps track of the server bridge state
+pub struct ServerBridge {
+    /// econ connection to game server
+    econ: Option<Econ>,
 
-        walker.set_waypoints(way.clone()).set_bounds(500, 500);
+    /// stores all available generation presets
+    presets: HashMap<String, GenerationPreset>,
 
-        let map = Map::new(500, 500);
+    /// selected generation preset
+    current_preset: String,
 
-        let generator = Generator::new(map, walker, gen.clone());
+    /// stores start arguments
+    args: BridgeArgs,
+
+    /// map generator
+    generator: Generator,
+
+    /// time of the last map change, used for the rotation
+    last_change: Instant,
+
+    /// stamped into every generated map
+    branding: Option<Branding>,
+}
+
+impl ServerBridge {
+    fn new(args: BridgeArgs) -> ServerBridge {
+        let presets = load_presets(args.presets.as_path());
+
+        let current_preset = presets
+            .keys()
+            .sorted()
+            .next()
+            .expect("no generation presets found")
+            .clone();
+
+        let generator = Generator::from_preset(presets[&current_preset].clone());
+        let branding = load_branding(&args);
 
         ServerBridge {
             econ: None,
-            base_maps,
-            generator_configs,
-            walker_configs,
-            waypoints_configs,
-            current_generator_params,
-            current_walker_params,
-            current_waypoints,
+            presets,
+            current_preset,
             args,
             generator,
+            last_change: Instant::now(),
+            branding,
         }
     }
 
     fn start(&mut self) {
-        self.econ = Some(
-            Econ::connect(&format!("127.0.0.1:{}", self.args.port), 1024).unwrap_or_else(|error| {
-                panic!("Failed to establish stream connection: {}", error);
-            }),
-        );
+        let address = format!("{}:{}", self.args.host, self.args.port);
+
+        self.econ = Some(Econ::connect(&address, 1024).unwrap_or_else(|error| {
+            panic!("Failed to establish stream connection: {}", error);
+        }));
 
         info!(auth!("Trying to authenticate..."));
 
@@ -171,6 +422,13 @@ impl ServerBridge {
             panic!();
         }
 
+        // wake up regularly to check whether the rotation is due
+        if self.args.rotate.is_some() {
+            self.econ_unchecked()
+                .set_read_timeout(Some(Duration::from_secs(1)))
+                .unwrap();
+        }
+
         loop {
             match self.econ_unchecked().read() {
                 Ok(()) => {
@@ -185,8 +443,16 @@ impl ServerBridge {
                         self.check_call(line);
                     }
                 }
+                Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
                 Err(err) => error!(recv!("{}"), err),
             }
+
+            if let Some(minutes) = self.args.rotate {
+                if self.last_change.elapsed() >= Duration::from_secs(minutes * 60) {
+                    info!(gen!("Rotating map"));
+                    self.generate_and_change(random_seed());
+                }
+            }
         }
     }
 
@@ -206,7 +472,7 @@ impl ServerBridge {
         let mut gap_size = 1;
 
         let mut gap = || {
-            let gap = " ".repeat(gap_size);
+            let gap = " ".repeat(gap_size);
 
             gap_size += 1;
 
@@ -219,60 +485,19 @@ impl ServerBridge {
         );
         self.add_vote(&gap(), "info");
 
-        self.add_vote(
-            &format!(
-                "Current generator configuration: {}",
-                self.current_generator_params
-            ),
-            "info",
-        );
-        self.add_vote(
-            &format!(
-                "Current walker configuration: {}",
-                self.current_walker_params
-            ),
-            "info",
-        );
-        self.add_vote(
-            &format!("Current map layout: {}", self.current_waypoints),
-            "info",
-        );
+        self.add_vote(&format!("Current preset: {}", self.current_preset), "info");
         self.add_vote(&gap(), "info");
 
         self.add_vote("Generate Random Map", "echo call generate");
         self.add_vote(&gap(), "info");
 
-        fn list_available<T>(
-            configs: &HashMap<String, T>,
-            config_type: &str,
-            config_inner_type: &str,
-        ) -> Vec<(String, String)> {
-            let mut votes = Vec::new();
-
-            for name in configs.keys() {
-                votes.push((
-                    format!("Set {} configuration: {}", config_type, name),
-                    format!("echo call configurate {} {}", config_inner_type, name),
-                ));
-            }
-
-            return votes;
-        }
-
-        for (desc, command) in &list_available(&self.generator_configs, "generator", "generator") {
-            self.add_vote(desc, command);
-        }
-
-        self.add_vote(&gap(), "info");
-
-        for (desc, command) in &list_available(&self.walker_configs, "walker", "walker") {
-            self.add_vote(desc, command);
-        }
+        let names = self.presets.keys().sorted().cloned().collect_vec();
 
-        self.add_vote(&gap(), "info");
-
-        for (desc, command) in &list_available(&self.waypoints_configs, "layout", "waypoints") {
-            self.add_vote(desc, command);
+        for name in &names {
+            self.add_vote(
+                &format!("Set preset: {}", name),
+                &format!("echo call configurate preset {}", name),
+            );
         }
     }
 
@@ -300,17 +525,12 @@ impl ServerBridge {
             idx += 1;
         }
 
-        match callback_args[0] {
-            "generate" => {
-                let seed = random_seed();
-                let mut map_name = self.generate_map(seed);
-
-                while map_name.is_none() {
-                    map_name = self.generate_map(seed);
-                }
+        if callback_args.is_empty() {
+            return;
+        }
 
-                self.change_map(&map_name.unwrap());
-            }
+        match callback_args[0] {
+            "generate" => self.generate_and_change(random_seed()),
             "configurate" => {
                 if callback_args.len() < 3 {
                     warn!(gen!("Missing arguments on configuration call"));
@@ -318,49 +538,17 @@ impl ServerBridge {
                 }
 
                 match callback_args[1] {
-                    "generator" => {
-                        if !self.generator_configs.contains_key(callback_args[2]) {
-                            warn!(
-                                gen!("Unknown generator configuration: {}"),
-                                callback_args[2]
-                            );
-                            return;
-                        }
-
-                        // TODO: quotation marks?
-                        self.current_generator_params = callback_args[2].to_string();
-
-                        self.generator.params =
-                            self.generator_configs[&self.current_generator_params].clone();
-                    }
-                    "walker" => {
-                        if !self.walker_configs.contains_key(callback_args[2]) {
-                            warn!(gen!("Unknown walker configuration: {}"), callback_args[2]);
-                            return;
-                        }
-
-                        // TODO: quotation marks?
-                        self.current_walker_params = callback_args[2].to_string();
-
-                        self.generator.walker.params =
-                            self.walker_configs[&self.current_walker_params].clone();
-                    }
-                    "waypoints" => {
-                        if !self.waypoints_configs.contains_key(callback_args[2]) {
-                            warn!(
-                                gen!("Unknown waypoints configuration: {}"),
-                                callback_args[2]
-                            );
+                    "preset" => {
+                        if !self.presets.contains_key(callback_args[2]) {
+                            warn!(gen!("Unknown preset: {}"), callback_args[2]);
                             return;
                         }
 
                         // TODO: quotation marks?
-                        self.current_waypoints = callback_args[2].to_string();
+                        self.current_preset = callback_args[2].to_string();
 
                         self.generator
-                            .walker
-                            .set_waypoints(self.waypoints_configs[&self.current_waypoints].clone())
-                            .set_bounds(500, 500);
+                            .set_preset(self.presets[&self.current_preset].clone());
                     }
                     s => warn!(gen!("Unknown configuration: {}"), s),
                 }
@@ -371,50 +559,120 @@ impl ServerBridge {
         self.update_votes()
     }
 
+    fn generate_and_change(&mut self, seed: Seed) {
+        // don't retry right away if saving failed, wait for the next rotation instead
+        self.last_change = Instant::now();
+
+        if let Some(map_name) = self.generate_map(seed) {
+            self.change_map(&map_name);
+        }
+    }
+
     fn generate_map(&mut self, seed: Seed) -> Option<String> {
-        let map_name = format!(
-            "{}_{}_{}_{}",
-            &self.current_generator_params,
-            &self.current_walker_params,
-            &self.current_waypoints,
-            seed
-        );
+        self.generator.set_rng_audit(self.args.audit_rng);
+        self.generator.set_seed(seed);
 
-        let map_path = self
-            .args
-            .maps
-            .canonicalize()
-            .unwrap()
-            .join(map_name.clone() + ".map");
+        let map_name = self
+            .generator
+            .preset()
+            .export
+            .map_name(&self.generator.metadata());
+        let map_path = self.args.maps.join(map_name.clone() + ".map");
 
         info!(gen!("Generating {}"), map_name);
 
-        self.generator.map.clear();
+        let pipeline = GenerationPipeline {
+            transform: Transform {
+                quarter_turns: self.args.turns,
+                mirror_x: self.args.mirror_x,
+                mirror_y: self.args.mirror_y,
+            },
+            ..Default::default()
+        };
 
-        match self.generator.finalize(100_000) {
-            Ok(()) => {
-                info!(gen!("Finished map generation"));
+        let mut map = match pipeline.run(&mut self.generator, seed, |_| {}) {
+            Ok(map) => map,
+            Err(err) => {
+                error!(gen!("Failed to generate {}: {}"), map_name, err);
 
-                let idx = random_seed() as usize % self.base_maps.len();
+                return None;
+            }
+        };
 
-                let mut exporter = Exporter::new(
-                    self.base_maps.get_mut(idx).unwrap(),
-                    &self.generator.map,
-                    ExporterConfig::default(),
-                );
+        info!(gen!("Finished map generation\n{}"), self.generator.timing());
+
+        if let Some(branding) = &self.branding {
+            let mut branded = Map::from_raw(map);
+            branding.stamp(&mut branded);
+            map = branded.into_raw();
+        }
 
-                exporter.finalize().save_map(&map_path);
+        if self.args.audit_rng {
+            self.audit_rng(seed, &map_name);
+        }
 
+        match save_map(&mut map, &map_path) {
+            Ok(()) => {
                 info!(gen!("Finished map exporting"));
 
-                return Some(map_name);
+                Some(map_name)
             }
-            Err(generation_error) => {
-                warn!(gen!("Generation Error: {:?}"), generation_error);
+            Err(err) => {
+                error!(gen!("Failed to save {}: {}"), map_path.display(), err);
+
+                None
             }
         }
+    }
+
+    /// generates the map again and compares the random draws of both runs
+    fn audit_rng(&self, seed: Seed, map_name: &str) {
+        let Some(audit) = self.generator.rng_audit() else {
+            return;
+        };
+
+        let path = self.args.maps.join(map_name.to_string() + ".rng");
+
+        match audit.write_sequence(&path) {
+            Ok(()) => info!(
+                gen!("Saved {} random draws to {}"),
+                audit.draws.len(),
+                path.display()
+            ),
+            Err(err) => error!(gen!("Failed to save {}: {}"), path.display(), err),
+        }
 
-        None
+        for (site, count) in audit.counts.iter().sorted_by_key(|(_, &count)| count).rev() {
+            debug!(gen!("{} draws at {}"), count, site);
+        }
+
+        let mut rerun = Generator::from_preset(self.generator.preset().clone());
+        rerun.set_rng_audit(true);
+        rerun.set_seed(seed);
+
+        if let Err(err) = rerun.try_generate_observed(|_| {}) {
+            warn!(gen!("Audit run failed: {}"), err);
+        }
+
+        let Some(rerun_audit) = rerun.rng_audit() else {
+            return;
+        };
+
+        match audit.first_divergence(rerun_audit) {
+            None => info!(gen!("Both runs drew the same random values")),
+            Some(i) => warn!(
+                gen!("Runs diverge at draw {}: {} against {}"),
+                i,
+                audit
+                    .draws
+                    .get(i)
+                    .map_or("nothing", |draw| draw.site.as_str()),
+                rerun_audit
+                    .draws
+                    .get(i)
+                    .map_or("nothing", |draw| draw.site.as_str())
+            ),
+        }
     }
 
     fn change_map(&mut self, map_name: &str) {
@@ -435,50 +693,528 @@ impl ServerBridge {
     }
 
     pub fn run() {
-        match Command::parse() {
+        let cli = Cli::parse();
+
+        logging::init(cli.verbose, cli.json_logs);
+
+        match cli.command {
             Command::StartBridge(args) => ServerBridge::new(args).start(),
             Command::ListConfigs(args) => print_configs(args),
+            Command::Tune(args) => tune_preset(args),
+            Command::VerifySnapshots(args) if args.update => record_snapshots(args),
+            Command::VerifySnapshots(args) => verify_snapshots(args),
+            Command::VerifyParity(args) => verify_parity(args),
+            Command::Stream(args) => stream_strips(args),
+            Command::Compose(args) => compose_maps(args),
+            Command::Config(ConfigCommand::Convert(args)) => convert_configs(args),
         }
     }
 }
 
 fn print_configs(args: BridgeArgs) {
     println!(
-        "GeneratorParams: {}",
-        load_configs_from_dir::<GeneratorParams, _>(args.gen_configs.as_path())
+        "Presets: {}",
+        load_configs_from_dir::<GenerationPreset, _>(args.presets.as_path())
             .unwrap()
             .keys()
-            .into_iter()
+            .sorted()
             .join(",")
     );
-    println!(
-        "WalkerParams: {}",
-        load_configs_from_dir::<WalkerParams, _>(args.wal_configs.as_path())
-            .unwrap()
-            .keys()
-            .into_iter()
-            .join(",")
+}
+
+fn tune_preset(args: TuneArgs) {
+    let presets = load_configs_from_dir::<GenerationPreset, _>(args.presets.as_path()).unwrap();
+
+    let Some(preset) = presets.get(&args.preset) else {
+        error!(gen!("Unknown preset: {}"), args.preset);
+        return;
+    };
+
+    let config = TuneConfig {
+        iterations: args.iterations,
+        seeds: (0..args.seeds).collect(),
+        ..Default::default()
+    };
+    let mut prng = Random::new(random_seed());
+
+    let tuned = tune(
+        preset,
+        &config,
+        &mut prng,
+        fitness,
+        |iteration, mutant, best| {
+            info!(
+                gen!("Iteration {}/{}: fitness {:.3}, best {:.3}"),
+                iteration + 1,
+                args.iterations,
+                mutant,
+                best
+            );
+        },
     );
-    println!(
-        "Waypoints: {}",
-        load_configs_from_dir::<NormalWaypoints, _>(args.way_configs.as_path())
+
+    let (mut best, best_fitness) = match tuned {
+        Ok(tuned) => tuned,
+        Err(err) => {
+            error!(gen!("Failed to generate {}: {}"), args.preset, err);
+            return;
+        }
+    };
+
+    best.name = format!("{}_tuned", args.preset);
+
+    let output = args
+        .output
+        .unwrap_or_else(|| args.presets.join(format!("{}.json", best.name)));
+
+    match fs::write(&output, serde_json::to_string_pretty(&best).unwrap()) {
+        Ok(()) => info!(
+            gen!("Saved {} with fitness {:.3}"),
+            output.display(),
+            best_fitness
+        ),
+        Err(err) => error!(gen!("Failed to save {}: {}"), output.display(), err),
+    }
+}
+
+fn verify_parity(args: ParityArgs) {
+    let presets = load_presets(args.presets.as_path());
+
+    let names: Vec<&String> = match &args.preset {
+        Some(name) if !presets.contains_key(name) => {
+            error!(gen!("Unknown preset: {}"), name);
+            std::process::exit(1);
+        }
+        Some(name) => vec![name],
+        None => presets.keys().sorted().collect(),
+    };
+
+    let mut failures = 0;
+
+    for name in names.iter() {
+        match check_parity(&presets[*name], args.seed) {
+            Ok(Parity::Match) => info!(gen!("{} seed {} matches"), name, args.seed),
+            Ok(Parity::Size(direct, stepped)) => {
+                failures += 1;
+                error!(
+                    gen!("{} seed {} differs in size: {:?} in one go, {:?} step by step"),
+                    name, args.seed, direct, stepped
+                );
+            }
+            Ok(Parity::Tiles(differing)) => {
+                failures += 1;
+                error!(
+                    gen!("{} seed {} differs in {} tiles"),
+                    name, args.seed, differing
+                );
+            }
+            Err(err) => {
+                failures += 1;
+                error!(gen!("{} seed {} failed: {}"), name, args.seed, err);
+            }
+        }
+    }
+
+    if failures > 0 {
+        error!(gen!("{}/{} presets differ"), failures, names.len());
+        std::process::exit(1);
+    }
+
+    info!(gen!("All {} presets match"), names.len());
+}
+
+fn stream_strips(args: StreamArgs) {
+    let presets = load_presets(args.presets.as_path());
+
+    let Some(preset) = presets.get(&args.preset) else {
+        error!(gen!("Unknown preset: {}"), args.preset);
+        return;
+    };
+
+    let config = StreamConfig {
+        direction: args.direction.into(),
+        strip_length: args.length,
+        strip_width: args.width,
+        ..Default::default()
+    };
+    let seed = args.seed.unwrap_or_else(random_seed);
+    let stream = StripStream::new(preset.clone(), config, seed);
+
+    fs::create_dir_all(&args.output).unwrap();
+
+    let mut offsets = Vec::new();
+
+    // strips are saved as they come, the stream only keeps the last one
+    for strip in stream.take(args.strips) {
+        let mut strip = match strip {
+            Ok(strip) => strip,
+            Err(err) => {
+                error!(gen!("Failed to generate a strip: {}"), err);
+                return;
+            }
+        };
+
+        let name = format!("{}_{}_{}", args.preset, seed, strip.index);
+        let path = args.output.join(format!("{}.map", name));
+
+        if let Err(err) = save_map(&mut strip.map, &path) {
+            error!(gen!("Failed to save {}: {}"), path.display(), err);
+            return;
+        }
+
+        info!(
+            gen!("Saved strip {} at {}, {}"),
+            strip.index, strip.offset[0], strip.offset[1]
+        );
+
+        offsets.push(serde_json::json!({ "map": name, "offset": strip.offset }));
+    }
+
+    let path = args.output.join("strips.json");
+
+    fs::write(&path, serde_json::to_string_pretty(&offsets).unwrap()).unwrap();
+}
+
+fn compose_maps(args: ComposeArgs) {
+    let load = |path: &Path| -> Result<Map, Box<dyn Error>> {
+        let mut map = TwMap::parse_path(path)?;
+        map.load()?;
+
+        Ok(Map::from_raw(map))
+    };
+
+    let (mut base, other) = match (load(&args.base), load(&args.other)) {
+        (Ok(base), Ok(other)) => (base, other),
+        (Err(err), _) => {
+            error!(gen!("Failed to load {}: {}"), args.base.display(), err);
+            return;
+        }
+        (_, Err(err)) => {
+            error!(gen!("Failed to load {}: {}"), args.other.display(), err);
+            return;
+        }
+    };
+
+    let offset = [args.offset[0], args.offset[1]];
+    let conflicts = base.blit(&other, offset, args.policy.into());
+
+    if conflicts > 0 {
+        warn!(
+            gen!("{} tiles of both maps overlap, resolved with {:?}"),
+            conflicts, args.policy
+        );
+    }
+
+    let (width, height) = (base.width(), base.height());
+    let mut map = base.into_raw();
+
+    match save_map(&mut map, &args.output) {
+        Ok(()) => info!(
+            gen!("Composed {}x{} map saved to {}"),
+            width,
+            height,
+            args.output.display()
+        ),
+        Err(err) => error!(gen!("Failed to save {}: {}"), args.output.display(), err),
+    }
+}
+
+fn convert_configs(args: ConvertArgs) {
+    for input in args.inputs.iter() {
+        match convert_config(&args, input) {
+            Ok(output) => info!(
+                gen!("Converted {} to {}"),
+                input.display(),
+                output.display()
+            ),
+            Err(err) => error!(gen!("Failed to convert {}: {}"), input.display(), err),
+        }
+    }
+}
+
+/// branding of the start arguments, panics when the logo can't be read
+fn load_branding(args: &BridgeArgs) -> Option<Branding> {
+    let pixels = if let Some(path) = &args.brand_logo {
+        let image = image::open(path)
+            .unwrap_or_else(|err| panic!("Failed to load {}: {}", path.display(), err))
+            .to_luma_alpha8();
+        let luma_alpha = Array2::from_shape_fn(
+            (image.width() as usize, image.height() as usize),
+            |(x, y)| image.get_pixel(x as u32, y as u32).0,
+        );
+
+        logo_pixels(luma_alpha.view())
+    } else {
+        rasterize_text(args.brand_text.as_ref()?, args.brand_scale)
+    };
+
+    let block = if args.brand_unhookable {
+        BlockType::Unhookable
+    } else {
+        BlockType::Hookable
+    };
+
+    Some(Branding::new(
+        pixels,
+        [args.brand_at[0], args.brand_at[1]],
+        block,
+    ))
+}
+
+/// converts one preset file, returns the path it was written to
+fn convert_config(args: &ConvertArgs, input: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    let mut value = read_config(input)?;
+    let schema = migration::schema_of(&value);
+
+    for description in migration::migrate(&mut value)? {
+        info!(gen!("{}: {}"), input.display(), description);
+    }
+
+    let mut preset = migration::load_preset(value.clone())?;
+    let name = input.file_stem().unwrap().to_string_lossy();
+    let mut errors = 0;
+
+    let fixed = args.fix_waypoints && preset.fix_waypoint_spacing() > 0;
+
+    if fixed {
+        info!(gen!("Preset {}: waypoint spacing fixed"), name);
+    }
+
+    for issue in preset.validate() {
+        match issue.severity {
+            Severity::Warning => warn!(gen!("Preset {}: {}"), name, issue),
+            Severity::Error => {
+                error!(gen!("Preset {}: {}"), name, issue);
+                errors += 1;
+            }
+        }
+    }
+
+    if errors > 0 && !args.force {
+        return Err(format!("{} validation errors, --force writes it anyway", errors).into());
+    }
+
+    if args.upgrade || fixed {
+        value = migration::save_preset(&preset);
+    }
+
+    let data = match args.to {
+        ConfigFormat::Json => serde_json::to_string_pretty(&value)?,
+        // toml has no null, an absent field deserializes to the same default
+        ConfigFormat::Toml => toml::to_string_pretty(&without_nulls(value))?,
+    };
+
+    let output = args
+        .output
+        .as_deref()
+        .unwrap_or_else(|| input.parent().unwrap_or(Path::new("")))
+        .join(format!("{}.{}", name, args.to.extension()));
+
+    fs::write(&output, data)?;
+
+    if schema < PRESET_SCHEMA {
+        info!(
+            gen!("{}: schema {} upgraded to {}"),
+            input.display(),
+            schema,
+            PRESET_SCHEMA
+        );
+    }
+
+    Ok(output)
+}
+
+/// json or toml preset by the file extension
+fn read_config(path: &Path) -> Result<serde_json::Value, Box<dyn Error>> {
+    let data = fs::read_to_string(path)?;
+
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("toml") => Ok(toml::from_str(&data)?),
+        _ => Ok(serde_json::from_str(&data)?),
+    }
+}
+
+fn without_nulls(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(fields) => fields
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| (key, without_nulls(value)))
+            .collect(),
+        serde_json::Value::Array(values) => values.into_iter().map(without_nulls).collect(),
+        value => value,
+    }
+}
+
+fn snapshot_map_path(args: &SnapshotArgs, snapshot: &Snapshot) -> PathBuf {
+    args.snapshots
+        .join(format!("{}_{}.map", snapshot.preset, snapshot.seed))
+}
+
+fn load_presets(path: &Path) -> HashMap<String, GenerationPreset> {
+    let mut presets: HashMap<String, GenerationPreset> =
+        load_configs_from_dir::<serde_json::Value, _>(path)
             .unwrap()
-            .keys()
             .into_iter()
-            .join(",")
+            .filter_map(|(name, value)| match migration::load_preset(value) {
+                Ok(preset) => Some((name, preset)),
+                Err(err) => {
+                    error!(gen!("Preset {}: {}"), name, err);
+                    None
+                }
+            })
+            .collect();
+
+    // file names identify presets in votes, map metadata and snapshots
+    for (name, preset) in presets.iter_mut() {
+        preset.name = name.clone();
+
+        for issue in preset.validate() {
+            match issue.severity {
+                Severity::Warning => warn!(gen!("Preset {}: {}"), name, issue),
+                Severity::Error => error!(gen!("Preset {}: {}"), name, issue),
+            }
+        }
+    }
+
+    presets
+}
+
+fn record_snapshots(args: SnapshotArgs) {
+    let presets = load_presets(args.presets.as_path());
+    let mut snapshots = Vec::new();
+
+    fs::create_dir_all(&args.snapshots).unwrap();
+
+    for name in presets.keys().sorted() {
+        for seed in 0..args.seeds {
+            let (snapshot, mut map) = match Snapshot::record(&presets[name], seed) {
+                Ok(recorded) => recorded,
+                Err(err) => {
+                    error!(gen!("Failed to record {} seed {}: {}"), name, seed, err);
+                    continue;
+                }
+            };
+            let map_path = snapshot_map_path(&args, &snapshot);
+
+            if let Err(err) = save_map(&mut map, &map_path) {
+                error!(gen!("Failed to save {}: {}"), map_path.display(), err);
+            }
+
+            info!(gen!("Recorded {} seed {}"), name, seed);
+
+            snapshots.push(snapshot);
+        }
+    }
+
+    let path = args.snapshots.join("snapshots.json");
+
+    fs::write(&path, serde_json::to_string_pretty(&snapshots).unwrap()).unwrap();
+
+    info!(
+        gen!("Saved {} snapshots to {}"),
+        snapshots.len(),
+        path.display()
     );
 }
 
-fn load_base_maps_paths<P: AsRef<Path>>(path: P) -> Vec<PathBuf> {
-    let mut paths = Vec::new();
+fn verify_snapshots(args: SnapshotArgs) {
+    let presets = load_presets(args.presets.as_path());
+
+    let path = args.snapshots.join("snapshots.json");
+    let snapshots: Vec<Snapshot> =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+    let mut failures = 0;
+
+    for snapshot in snapshots.iter() {
+        let Some(preset) = presets.get(&snapshot.preset) else {
+            error!(gen!("Unknown preset: {}"), snapshot.preset);
+            failures += 1;
+            continue;
+        };
+
+        let map = match snapshot.verify(preset) {
+            Ok(()) => {
+                info!(gen!("{} seed {} matches"), snapshot.preset, snapshot.seed);
+                continue;
+            }
+            Err(Mismatch::Changed(map)) => map,
+            Err(Mismatch::Failed(err)) => {
+                error!(
+                    gen!("{} seed {} failed: {}"),
+                    snapshot.preset, snapshot.seed, err
+                );
+                failures += 1;
+                continue;
+            }
+        };
+
+        failures += 1;
 
-    for file_path in std::fs::read_dir(path).unwrap() {
-        let file_path = file_path.unwrap().path();
+        let diff_path = args
+            .snapshots
+            .join(format!("{}_{}_diff.png", snapshot.preset, snapshot.seed));
 
-        paths.push(file_path);
+        match save_diff(&snapshot_map_path(&args, snapshot), &map, &diff_path) {
+            Ok(()) => error!(
+                gen!("{} seed {} changed, see {}"),
+                snapshot.preset,
+                snapshot.seed,
+                diff_path.display()
+            ),
+            Err(err) => error!(
+                gen!("{} seed {} changed, no diff: {}"),
+                snapshot.preset, snapshot.seed, err
+            ),
+        }
+    }
+
+    if failures > 0 {
+        error!(gen!("{}/{} snapshots changed"), failures, snapshots.len());
+        std::process::exit(1);
     }
 
-    paths
+    info!(gen!("All {} snapshots match"), snapshots.len());
+}
+
+/// paints the snapshot map's game layer in the dimmed default palette with the changed tiles
+/// in red
+fn save_diff(snapshot_path: &Path, map: &TwMap, path: &Path) -> Result<(), Box<dyn Error>> {
+    let mut snapshot = TwMap::parse_path(snapshot_path)?;
+    snapshot.load()?;
+
+    let old: &GameLayer = snapshot.find_physics_layer().unwrap();
+    let new: &GameLayer = map.find_physics_layer().unwrap();
+    let (old, new) = (old.tiles.unwrap_ref(), new.tiles.unwrap_ref());
+
+    let diff = diff_tiles(old, new);
+    let (width, height) = diff.dim();
+
+    let palette = Palette::default();
+
+    let image = image::RgbImage::from_fn(width as u32, height as u32, |x, y| {
+        let index = [x as usize, y as usize];
+
+        if diff[index] {
+            image::Rgb([255, 0, 0])
+        } else {
+            let [r, g, b, _] = palette.tile_color(&old[index]).to_array();
+            image::Rgb([r / 3, g / 3, b / 3])
+        }
+    });
+
+    image.save(path)?;
+
+    Ok(())
+}
+
+fn save_map(map: &mut TwMap, path: &Path) -> Result<(), Box<dyn Error>> {
+    let mut file = fs::File::create(path)?;
+
+    map.save(&mut file)?;
+
+    Ok(())
 }
 
 pub fn load_configs_from_dir<C, P>(path: P) -> Result<HashMap<String, C>, Box<dyn Error>>
@@ -494,11 +1230,12 @@ where
         let file_name = osstr_file_name
             .to_str()
             .unwrap() // believe to user that it will be valid utf8, what an asshole will use utf16 for fucking generator config name?
-            .replace(".json", "");
+            .replace(".json", "")
+            .replace(".toml", "");
 
-        let data = fs::read_to_string(&file_path).unwrap();
+        let data = read_config(&file_path)?;
 
-        configs.insert(file_name.to_string(), serde_json::from_str::<C>(&data)?);
+        configs.insert(file_name.to_string(), serde_json::from_value::<C>(data)?);
     }
 
     Ok(configs)
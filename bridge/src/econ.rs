@@ -57,6 +57,11 @@ impl Econ {
         self.authed
     }
 
+    /// makes `read` fail with `WouldBlock` or `TimedOut` when nothing arrives in time
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        self.connection.set_read_timeout(timeout)
+    }
+
     pub fn read(&mut self) -> Result<(), Error> {
         let written = self.connection.read(&mut self.buffer)?;
 
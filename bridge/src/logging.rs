@@ -0,0 +1,24 @@
+use tracing::Level;
+use tracing_subscriber::{filter::LevelFilter, EnvFilter};
+
+/// installs the global subscriber, `RUST_LOG` overrides the level picked by `verbose`, records
+/// of the `log` crate are forwarded to it
+pub fn init(verbose: u8, json: bool) {
+    let level = match verbose {
+        0 => Level::INFO,
+        1 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+
+    let filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::from_level(level).into())
+        .from_env_lossy();
+
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+
+    if json {
+        builder.json().init();
+    } else {
+        builder.init();
+    }
+}
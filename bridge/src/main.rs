@@ -1,13 +1,10 @@
-use simple_logger::SimpleLogger;
-
 #[macro_use]
 mod color_macros;
 
 mod app;
 mod econ;
+mod logging;
 
 fn main() {
-    SimpleLogger::new().init().unwrap();
-    
     app::ServerBridge::run();
 }
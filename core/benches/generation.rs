@@ -0,0 +1,112 @@
+use std::{fs, path::Path, time::Duration};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use mapgen_core::{config::GenerationPreset, generator::Generator, migration};
+
+const PRESETS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/configs/presets");
+/// map sizes the full generation is measured at
+const SCALE_FACTORS: [f32; 3] = [100.0, 200.0, 400.0];
+
+/// presets of the presets directory, sorted by name
+fn presets() -> Vec<GenerationPreset> {
+    let mut presets: Vec<GenerationPreset> = fs::read_dir(PRESETS)
+        .expect("presets directory")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .map(|path| load_preset(&path))
+        .collect();
+
+    presets.sort_by(|a, b| a.name.cmp(&b.name));
+    presets
+}
+
+fn load_preset(path: &Path) -> GenerationPreset {
+    let data = fs::read_to_string(path).unwrap();
+    let mut preset = migration::load_preset(serde_json::from_str(&data).unwrap()).unwrap();
+
+    preset.name = path.file_stem().unwrap().to_string_lossy().into_owned();
+    preset
+}
+
+fn generator(preset: &GenerationPreset) -> Generator {
+    let mut generator = Generator::from_preset(preset.clone());
+
+    generator.set_seed(0);
+    generator
+}
+
+/// time `phase` took summed over `iterations` generations
+fn phase_time(generator: &mut Generator, phase: &str, iterations: u64) -> Duration {
+    (0..iterations)
+        .map(|_| {
+            generator.generate();
+            generator.timing().phase(phase)
+        })
+        .sum()
+}
+
+fn full_generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generation");
+    group.sample_size(10);
+
+    for preset in presets() {
+        for scale_factor in SCALE_FACTORS {
+            let mut generator = generator(&preset);
+            generator.set_scale_factor(scale_factor);
+
+            let id = BenchmarkId::new(&preset.name, scale_factor);
+            group.bench_function(id, |b| b.iter(|| generator.generate()));
+        }
+    }
+
+    group.finish();
+}
+
+fn walker_steps(c: &mut Criterion) {
+    let mut group = c.benchmark_group("walker");
+    group.sample_size(10);
+
+    for preset in presets() {
+        let mut generator = generator(&preset);
+        generator.generate();
+
+        group.throughput(Throughput::Elements(generator.timing().steps as u64));
+        group.bench_function(&preset.name, |b| {
+            b.iter_custom(|iterations| phase_time(&mut generator, "walking", iterations))
+        });
+    }
+
+    group.finish();
+}
+
+fn post_processing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("phases");
+    group.sample_size(10);
+
+    for preset in presets() {
+        let mut generator = generator(&preset);
+        generator.generate();
+
+        let phases: Vec<&'static str> = generator
+            .timing()
+            .phases()
+            .iter()
+            .map(|&(phase, _)| phase)
+            .filter(|&phase| phase != "walking")
+            .collect();
+
+        for phase in phases {
+            group.bench_function(BenchmarkId::new(phase, &preset.name), |b| {
+                b.iter_custom(|iterations| phase_time(&mut generator, phase, iterations))
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, full_generation, walker_steps, post_processing);
+criterion_main!(benches);
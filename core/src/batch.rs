@@ -0,0 +1,154 @@
+use std::{
+    error::Error,
+    fmt,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use tracing::warn;
+
+use crate::{
+    config::GenerationPreset,
+    generator::{GenerationError, Generator},
+    handle::{panic_message, GenerationHandle},
+    map::Map,
+    random::Seed,
+};
+
+/// how often running jobs are checked against their timeout
+const WATCH_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchError {
+    /// the job ran longer than the timeout and was cancelled
+    TimedOut,
+    /// the generation panicked, holds the panic message
+    Panicked(String),
+    /// the generation ended without a map
+    Failed(GenerationError),
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TimedOut => write!(f, "generation timed out"),
+            Self::Panicked(message) => write!(f, "generation panicked: {}", message),
+            Self::Failed(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for BatchError {}
+
+/// generates a map per preset and seed on `threads` worker threads, all available cores for 0,
+/// results are in the order of the jobs, jobs running longer than `timeout` are cancelled and a
+/// panicking job only fails itself
+pub fn generate_maps_parallel(
+    jobs: &[(GenerationPreset, Seed)],
+    threads: usize,
+    timeout: Option<Duration>,
+) -> Vec<Result<Map, BatchError>> {
+    let threads = match threads {
+        0 => thread::available_parallelism().map_or(1, |threads| threads.get()),
+        threads => threads,
+    };
+    let threads = threads.min(jobs.len()).max(1);
+
+    let next = AtomicUsize::new(0);
+    let finished = AtomicUsize::new(0);
+    // deadline and handle of the job each worker is running
+    let running: Mutex<Vec<Option<(Instant, GenerationHandle)>>> = Mutex::new(vec![None; threads]);
+
+    let mut results: Vec<Option<Result<Map, BatchError>>> = jobs.iter().map(|_| None).collect();
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|worker| {
+                let (next, finished, running) = (&next, &finished, &running);
+
+                scope.spawn(move || {
+                    let mut done = Vec::new();
+
+                    loop {
+                        let job = next.fetch_add(1, Ordering::Relaxed);
+
+                        let Some((preset, seed)) = jobs.get(job) else {
+                            break;
+                        };
+
+                        let handle = GenerationHandle::new();
+
+                        if let Some(timeout) = timeout {
+                            running.lock().unwrap()[worker] =
+                                Some((Instant::now() + timeout, handle.clone()));
+                        }
+
+                        done.push((job, run_job(preset, *seed, &handle)));
+                        running.lock().unwrap()[worker] = None;
+                    }
+
+                    finished.fetch_add(1, Ordering::Relaxed);
+
+                    done
+                })
+            })
+            .collect();
+
+        if timeout.is_some() {
+            while finished.load(Ordering::Relaxed) < threads {
+                thread::sleep(WATCH_INTERVAL);
+
+                let now = Instant::now();
+
+                for (deadline, handle) in running.lock().unwrap().iter().flatten() {
+                    if now >= *deadline {
+                        handle.cancel();
+                    }
+                }
+            }
+        }
+
+        for worker in workers {
+            // jobs catch their panics, so the workers themselves don't panic
+            for (job, result) in worker.join().unwrap() {
+                results[job] = Some(result);
+            }
+        }
+    });
+
+    results.into_iter().map(Option::unwrap).collect()
+}
+
+fn run_job(
+    preset: &GenerationPreset,
+    seed: Seed,
+    handle: &GenerationHandle,
+) -> Result<Map, BatchError> {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let mut generator = Generator::from_preset(preset.clone());
+        generator.set_seed(seed);
+
+        generator.generate_with_handle(handle)
+    }));
+
+    match result {
+        Ok(Ok(map)) => Ok(Map::from_raw(map)),
+        Ok(Err(GenerationError::Cancelled)) => {
+            warn!(preset = %preset.name, seed, "generation timed out");
+
+            Err(BatchError::TimedOut)
+        }
+        Ok(Err(err)) => Err(BatchError::Failed(err)),
+        Err(panic) => {
+            let message = panic_message(panic.as_ref());
+            warn!(preset = %preset.name, seed, %message, "generation panicked");
+
+            Err(BatchError::Panicked(message))
+        }
+    }
+}
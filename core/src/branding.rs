@@ -0,0 +1,202 @@
+use ndarray::{Array2, ArrayView2};
+
+use crate::{
+    map::{BlockType, Map},
+    position::TileIndex,
+};
+
+/// glyph rows top to bottom, bit 4 is the leftmost column
+type Glyph = [u8; 7];
+
+const GLYPH_WIDTH: usize = 5;
+
+const GLYPH_HEIGHT: usize = 7;
+
+/// free columns between two glyphs
+const GLYPH_SPACING: usize = 1;
+
+/// free tiles between the stamp and the walls around it, taken by the freeze outline
+const STAMP_PADDING: usize = 2;
+
+/// letters are uppercased, characters without a glyph are drawn as `?`
+#[rustfmt::skip]
+const FONT: &[(char, Glyph)] = &[
+    (' ', [0, 0, 0, 0, 0, 0, 0]),
+    ('A', [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('B', [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110]),
+    ('C', [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110]),
+    ('D', [0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110]),
+    ('E', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111]),
+    ('F', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('G', [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111]),
+    ('H', [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('I', [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('J', [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100]),
+    ('K', [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001]),
+    ('L', [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111]),
+    ('M', [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001]),
+    ('N', [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001]),
+    ('O', [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('P', [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('Q', [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101]),
+    ('R', [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001]),
+    ('S', [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110]),
+    ('T', [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('U', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('V', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100]),
+    ('W', [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010]),
+    ('X', [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001]),
+    ('Y', [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100]),
+    ('Z', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111]),
+    ('0', [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110]),
+    ('1', [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('2', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111]),
+    ('3', [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110]),
+    ('4', [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010]),
+    ('5', [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110]),
+    ('6', [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110]),
+    ('7', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000]),
+    ('8', [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110]),
+    ('9', [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]),
+    ('.', [0, 0, 0, 0, 0, 0b01100, 0b01100]),
+    (',', [0, 0, 0, 0, 0b01100, 0b00100, 0b01000]),
+    (':', [0, 0b01100, 0b01100, 0, 0b01100, 0b01100, 0]),
+    ('-', [0, 0, 0, 0b11111, 0, 0, 0]),
+    ('_', [0, 0, 0, 0, 0, 0, 0b11111]),
+    ('!', [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0, 0b00100]),
+    ('?', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0, 0b00100]),
+    ('/', [0b00001, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b10000]),
+];
+
+fn glyph(character: char) -> Glyph {
+    let find = |wanted: char| {
+        FONT.iter()
+            .find(|(known, _)| *known == wanted)
+            .map(|&(_, glyph)| glyph)
+    };
+
+    find(character.to_ascii_uppercase())
+        .or_else(|| find('?'))
+        .unwrap()
+}
+
+/// pixels of a single line of text indexed by `[x, y]`, every font pixel becomes a
+/// `scale` wide square
+pub fn rasterize_text(text: &str, scale: usize) -> Array2<bool> {
+    let scale = scale.max(1);
+    let characters: Vec<char> = text.chars().collect();
+    let advance = GLYPH_WIDTH + GLYPH_SPACING;
+    let width = (characters.len() * advance).saturating_sub(GLYPH_SPACING);
+
+    let mut pixels = Array2::from_elem((width * scale, GLYPH_HEIGHT * scale), false);
+
+    for (i, &character) in characters.iter().enumerate() {
+        let rows = glyph(character);
+
+        for (y, row) in rows.iter().enumerate() {
+            for x in 0..GLYPH_WIDTH {
+                if row & (1 << (GLYPH_WIDTH - 1 - x)) == 0 {
+                    continue;
+                }
+
+                let left = (i * advance + x) * scale;
+                let top = y * scale;
+
+                for dx in 0..scale {
+                    for dy in 0..scale {
+                        pixels[[left + dx, top + dy]] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    pixels
+}
+
+/// pixels of a monochrome logo from its luma and alpha values indexed by `[x, y]`, opaque dark
+/// pixels are set
+pub fn logo_pixels(luma_alpha: ArrayView2<[u8; 2]>) -> Array2<bool> {
+    luma_alpha.map(|&[luma, alpha]| alpha >= 128 && luma < 128)
+}
+
+/// text or logo stamped into a generated map, for server branding
+#[derive(Debug, Clone, PartialEq)]
+pub struct Branding {
+    /// set pixels become blocks, indexed by `[x, y]`
+    pub pixels: Array2<bool>,
+    /// map tile the top left pixel is stamped at
+    pub at: [usize; 2],
+    pub block: BlockType,
+}
+
+impl Branding {
+    pub fn new(pixels: Array2<bool>, at: [usize; 2], block: BlockType) -> Self {
+        Self { pixels, at, block }
+    }
+
+    /// clears a padded box around the stamp, places the blocks and lines every empty tile
+    /// touching a wall in the box with freeze, the outermost ring of map tiles stays untouched
+    /// so the map stays sealed
+    pub fn stamp(&self, map: &mut Map) {
+        let tiles = map.game_layer().tiles.unwrap_mut();
+        let (map_width, map_height) = tiles.dim();
+        let (width, height) = self.pixels.dim();
+
+        if map_width < 3 || map_height < 3 {
+            return;
+        }
+
+        let inner =
+            |x: usize, y: usize| x >= 1 && y >= 1 && x < map_width - 1 && y < map_height - 1;
+
+        let left = self.at[0].saturating_sub(STAMP_PADDING);
+        let top = self.at[1].saturating_sub(STAMP_PADDING);
+        let right = (self.at[0] + width + STAMP_PADDING).min(map_width);
+        let bottom = (self.at[1] + height + STAMP_PADDING).min(map_height);
+
+        for x in left..right {
+            for y in top..bottom {
+                if !inner(x, y) {
+                    continue;
+                }
+
+                let pixel = x
+                    .checked_sub(self.at[0])
+                    .zip(y.checked_sub(self.at[1]))
+                    .and_then(|(px, py)| self.pixels.get([px, py]).copied())
+                    .unwrap_or(false);
+
+                tiles[[x, y]] = if pixel {
+                    self.block.tile()
+                } else {
+                    BlockType::Empty.tile()
+                };
+            }
+        }
+
+        let is_wall = |id: u8| {
+            [
+                BlockType::Hookable,
+                BlockType::Unhookable,
+                BlockType::Hookthrough,
+            ]
+            .iter()
+            .any(|block| block.game_id() == id)
+        };
+
+        let outline: Vec<[usize; 2]> = (left..right)
+            .flat_map(|x| (top..bottom).map(move |y| [x, y]))
+            .filter(|&[x, y]| inner(x, y) && tiles[[x, y]].id == BlockType::Empty.game_id())
+            .filter(|&index| {
+                index
+                    .neighbors(tiles.dim(), true)
+                    .any(|next| is_wall(tiles[next].id))
+            })
+            .collect();
+
+        for index in outline {
+            tiles[index] = BlockType::Freeze.tile();
+        }
+    }
+}
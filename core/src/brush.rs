@@ -3,6 +3,13 @@ use twmap::AnyTile;
 
 use crate::position::{as_index, Vector2};
 
+/// why an inner brush can't be carved inside an outer one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KernelError {
+    /// tile the outer brush has to cover, as an offset from the brush center
+    Uncovered([isize; 2]),
+}
+
 #[derive(Clone)]
 pub struct Brush {
     texture: Array2<bool>,
@@ -71,20 +78,70 @@ impl Brush {
         self.scaled_texture = None;
     }
 
-    pub fn apply<T: AnyTile>(&self, tiles: &mut Array2<T>, pos: Vector2, tile: T) {
-        let used_texture = if let Some(t) = &self.scaled_texture {
+    /// size of the texture in use, scaled if a scale is applied
+    pub fn dim(&self) -> (usize, usize) {
+        self.used_texture().dim()
+    }
+
+    /// checks the invariant of a brush pair carving the inner one on top of the outer one, the
+    /// outer brush must cover the inner one dilated by 1, diagonals included, so the inner tiles
+    /// never touch what's beyond the outer brush and no edge bugs are left behind
+    pub fn check_compatibility(inner: &Brush, outer: &Brush) -> Result<(), KernelError> {
+        let center = |texture: &Array2<bool>| {
+            let (width, height) = texture.dim();
+            [(width / 2) as isize, (height / 2) as isize]
+        };
+
+        let (inner_texture, outer_texture) = (inner.used_texture(), outer.used_texture());
+        let ([inner_x, inner_y], [outer_x, outer_y]) =
+            (center(inner_texture), center(outer_texture));
+
+        let covered = |[x, y]: [isize; 2]| {
+            x >= 0
+                && y >= 0
+                && outer_texture
+                    .get((x as usize, y as usize))
+                    .copied()
+                    .unwrap_or(false)
+        };
+
+        for ((x, y), _) in inner_texture.indexed_iter().filter(|(_, &carved)| carved) {
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    let offset = [x as isize - inner_x + dx, y as isize - inner_y + dy];
+
+                    if !covered([offset[0] + outer_x, offset[1] + outer_y]) {
+                        return Err(KernelError::Uncovered(offset));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn used_texture(&self) -> &Array2<bool> {
+        if let Some(t) = &self.scaled_texture {
             t
         } else {
             &self.texture
-        };
+        }
+    }
 
-        let (width, height) = used_texture.dim();
+    fn top_left(&self, pos: Vector2) -> Vector2 {
+        let (width, height) = self.used_texture().dim();
         let (offx, offy) = (
             (width as f32 / 2.0) as usize,
             (height as f32 / 2.0) as usize,
         );
 
-        let top_left = pos - Vector2::from(vec![offx as f32, offy as f32]);
+        pos - Vector2::from(vec![offx as f32, offy as f32])
+    }
+
+    pub fn apply<T: AnyTile>(&self, tiles: &mut Array2<T>, pos: Vector2, tile: T) {
+        let used_texture = self.used_texture();
+
+        let top_left = self.top_left(pos);
         for ((x, y), &not_empty) in used_texture.indexed_iter() {
             let real_pos = top_left.clone() + Vector2::from(vec![x as f32, y as f32]);
             if not_empty {
@@ -92,4 +149,50 @@ impl Brush {
             }
         }
     }
+
+    /// adds 1 to every count the brush covers at `pos`
+    pub fn accumulate(&self, counts: &mut Array2<u32>, pos: Vector2) {
+        let used_texture = self.used_texture();
+
+        let top_left = self.top_left(pos);
+        for ((x, y), &not_empty) in used_texture.indexed_iter() {
+            let real_pos = top_left.clone() + Vector2::from(vec![x as f32, y as f32]);
+            if not_empty {
+                counts[as_index(real_pos.view())] += 1;
+            }
+        }
+    }
+
+    /// positions just outside the brush shape which share a side with it
+    pub fn outline(&self, pos: Vector2) -> Vec<Vector2> {
+        let used_texture = self.used_texture();
+        let (width, height) = used_texture.dim();
+
+        let covered = |x: isize, y: isize| {
+            x >= 0
+                && y >= 0
+                && used_texture
+                    .get((x as usize, y as usize))
+                    .copied()
+                    .unwrap_or(false)
+        };
+
+        let top_left = self.top_left(pos);
+        let mut outline = Vec::new();
+
+        for x in -1..=width as isize {
+            for y in -1..=height as isize {
+                if covered(x, y) {
+                    continue;
+                }
+
+                if covered(x - 1, y) || covered(x + 1, y) || covered(x, y - 1) || covered(x, y + 1)
+                {
+                    outline.push(top_left.clone() + Vector2::from(vec![x as f32, y as f32]));
+                }
+            }
+        }
+
+        outline
+    }
 }
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use ndarray::{Array2, ArrayView2};
+use twmap::{GameLayer, GameTile};
+
+use crate::map::{BlockType, Map};
+
+/// side length of the chunks tiles are stored in
+pub const CHUNK_SIZE: usize = 32;
+
+/// chunk coordinates, tile position divided by the chunk size rounding down
+pub type ChunkPos = [i64; 2];
+
+type Chunk<T> = [[T; CHUNK_SIZE]; CHUNK_SIZE];
+
+/// sparse tiles without fixed bounds, chunks are allocated on their first tile differing from
+/// the fill, so huge mostly solid maps only pay for their route
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkedTiles<T: Copy> {
+    fill: T,
+    chunks: HashMap<ChunkPos, Box<Chunk<T>>>,
+}
+
+impl<T: Copy + PartialEq> ChunkedTiles<T> {
+    /// empty storage reading `fill` everywhere
+    pub fn new(fill: T) -> Self {
+        Self {
+            fill,
+            chunks: HashMap::new(),
+        }
+    }
+
+    pub fn from_tiles(tiles: ArrayView2<T>, offset: [i64; 2], fill: T) -> Self {
+        let mut chunked = Self::new(fill);
+        chunked.insert_tiles(tiles, offset);
+
+        chunked
+    }
+
+    pub fn fill(&self) -> T {
+        self.fill
+    }
+
+    pub fn get(&self, position: [i64; 2]) -> T {
+        let (chunk, [x, y]) = split(position);
+
+        self.chunks
+            .get(&chunk)
+            .map_or(self.fill, |tiles| tiles[x][y])
+    }
+
+    pub fn set(&mut self, position: [i64; 2], tile: T) {
+        let (chunk, [x, y]) = split(position);
+
+        match self.chunks.get_mut(&chunk) {
+            Some(tiles) => tiles[x][y] = tile,
+            None if tile == self.fill => {}
+            None => {
+                let mut tiles = Box::new([[self.fill; CHUNK_SIZE]; CHUNK_SIZE]);
+                tiles[x][y] = tile;
+
+                self.chunks.insert(chunk, tiles);
+            }
+        }
+    }
+
+    /// writes `tiles` with their top left tile at `offset`
+    pub fn insert_tiles(&mut self, tiles: ArrayView2<T>, offset: [i64; 2]) {
+        for ((x, y), &tile) in tiles.indexed_iter() {
+            self.set([offset[0] + x as i64, offset[1] + y as i64], tile);
+        }
+    }
+
+    /// dense copy of the `dim` area at `min`, only touches the chunks overlapping it
+    pub fn extract(&self, min: [i64; 2], dim: (usize, usize)) -> Array2<T> {
+        Array2::from_shape_fn(dim, |(x, y)| {
+            self.get([min[0] + x as i64, min[1] + y as i64])
+        })
+    }
+
+    /// allocated chunks
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = ChunkPos> + '_ {
+        self.chunks.keys().copied()
+    }
+
+    /// smallest area holding every allocated chunk as min and max tile, both inclusive
+    pub fn bounds(&self) -> Option<([i64; 2], [i64; 2])> {
+        let size = CHUNK_SIZE as i64;
+
+        self.chunks.keys().fold(None, |bounds, &[x, y]| {
+            let (min, max) = bounds.unwrap_or(([i64::MAX; 2], [i64::MIN; 2]));
+            let (x, y) = (x * size, y * size);
+
+            Some((
+                [min[0].min(x), min[1].min(y)],
+                [max[0].max(x + size - 1), max[1].max(y + size - 1)],
+            ))
+        })
+    }
+
+    /// drops the chunks outside the area from `min` to `max`, both inclusive, they read as fill
+    /// afterwards
+    pub fn retain_area(&mut self, min: [i64; 2], max: [i64; 2]) {
+        let ([min_x, min_y], _) = split(min);
+        let ([max_x, max_y], _) = split(max);
+
+        self.chunks
+            .retain(|&[x, y], _| (min_x..=max_x).contains(&x) && (min_y..=max_y).contains(&y));
+    }
+
+    /// frees the chunks holding only fill
+    pub fn compact(&mut self) {
+        let fill = self.fill;
+
+        self.chunks
+            .retain(|_, tiles| tiles.iter().flatten().any(|&tile| tile != fill));
+    }
+}
+
+impl ChunkedTiles<GameTile> {
+    /// game tiles of `map` with its top left tile at `offset`, solid tiles of the generation
+    /// `fill` stay unallocated
+    pub fn from_map(map: &Map, offset: [i64; 2], fill: BlockType) -> Self {
+        if !map.is_sparse() {
+            let game: &GameLayer = map.raw_map().find_physics_layer().unwrap();
+
+            return Self::from_tiles(game.tiles.unwrap_ref().view(), offset, fill.tile());
+        }
+
+        // a chunk at a time, a sparse map is never dense as a whole
+        let mut chunked = Self::new(fill.tile());
+        let (width, height) = map.dim();
+
+        for x in (0..width).step_by(CHUNK_SIZE) {
+            for y in (0..height).step_by(CHUNK_SIZE) {
+                let tiles = map.copy_area([x, y], [x + CHUNK_SIZE - 1, y + CHUNK_SIZE - 1]);
+                let at = [offset[0] + x as i64, offset[1] + y as i64];
+
+                chunked.insert_tiles(tiles.view(), at);
+            }
+        }
+
+        chunked
+    }
+
+    /// map of the `dim` area at `min`, exports a part without building the whole map
+    pub fn to_map(&self, min: [i64; 2], dim: (usize, usize)) -> Map {
+        let mut map = Map::new();
+        map.reshape(dim.0, dim.1);
+        *map.game_layer().tiles.unwrap_mut() = self.extract(min, dim);
+
+        map
+    }
+}
+
+/// chunk of a tile and the tile's position inside it
+fn split([x, y]: [i64; 2]) -> (ChunkPos, [usize; 2]) {
+    let size = CHUNK_SIZE as i64;
+
+    (
+        [x.div_euclid(size), y.div_euclid(size)],
+        [x.rem_euclid(size) as usize, y.rem_euclid(size) as usize],
+    )
+}
@@ -0,0 +1,1199 @@
+use std::fmt;
+
+use crate::{
+    doors::DoorConfig,
+    freeze::FreezeConfig,
+    generator::{roomgrid::RoomGridConfig, tower::TowerConfig, GenerationMode},
+    guidance::GuidanceMask,
+    map::{BlockType, TargetVersion, TileMapping},
+    maze::MazeConfig,
+    modifiers::{ModifierEffect, ScheduledModifier},
+    patterns::RewriteRule,
+    prefabs::PrefabPlacement,
+    random::{Random, Seed},
+    regions::SealedRegionConfig,
+    rooms::RoomConfig,
+    skips::SkipConfig,
+    spawn::SpawnConfig,
+    spikes::SpikeConfig,
+    wfc::WfcConfig,
+};
+
+/// how the default step policy picks its base direction
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StepPolicy {
+    /// heads straight to the next waypoint
+    #[default]
+    Greedy,
+    /// follows an A* path to the next waypoint which avoids recently visited tiles
+    Guided,
+    /// picks any direction with a softmax over its score, made of the distance it gains on the
+    /// waypoint, keeping the momentum and the openness bias
+    Softmax,
+}
+
+/// how the walker gets away once it's stuck
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StuckPolicy {
+    /// only takes the preferred direction for `steps` steps, the default step policy only
+    Greedy { steps: usize },
+    /// scales the brush by `scale` for `steps` steps
+    Enlarge { scale: f32, steps: usize },
+    /// carves straight to the waypoint
+    Tunnel,
+}
+
+impl Default for StuckPolicy {
+    fn default() -> Self {
+        Self::Greedy { steps: 20 }
+    }
+}
+
+/// walker behaviour
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WalkerConfig {
+    /// distance at which the walker considers a waypoint as reached
+    pub reach_distance: f32,
+    /// probability of a random step instead of the preferred one, only used when no step
+    /// callback drives the walker
+    pub randomness: f32,
+    /// probability of turning a hookable tile along the carved edge into unhookable, rolled
+    /// for every edge tile on every step
+    pub unhookable_probability: f32,
+    /// same as `unhookable_probability`, but for hookthrough tiles
+    pub hookthrough_probability: f32,
+    /// gores mode only, probability of leaving a hookable island at a carved edge tile
+    pub island_probability: f32,
+    /// steps the walker keeps its direction after turning, relaxed close to waypoints
+    pub min_straight_run: usize,
+    /// highest share of turning steps among the recent steps, 1.0 doesn't limit turns
+    pub max_turn_frequency: f32,
+    /// probability of avoiding a tile the walker recently stood on, scaled by how recent the
+    /// visit is, 1.0 avoids them whenever there is a less recent alternative
+    pub backtrack_penalty: f32,
+    /// steps after which a visit no longer counts as recent
+    pub backtrack_decay: usize,
+    pub step_policy: StepPolicy,
+    /// guided policy only, steps after which the path is planned again
+    pub guide_interval: usize,
+    /// guided policy only, probability of following the planned path instead of the usual step
+    pub guide_bias: f32,
+    /// softmax policy only, 0.0 always takes the best scored direction and higher temperatures
+    /// get closer to fully random steps
+    pub temperature: f32,
+    /// from -1.0 to 1.0, probability of steering toward open space when positive, hugging
+    /// existing caverns, or away from it when negative, 0.0 turns it off
+    pub openness_bias: f32,
+    /// lets the walker step diagonally, so slanted parts of the route become straight 45 degree
+    /// tunnels instead of staircases
+    pub allow_diagonal: bool,
+    /// consecutive upward steps before the walker is forced sideways, 0 doesn't limit climbs
+    pub max_climb: usize,
+    /// steps of a climb between hookable ledges to rest on, placed alternately at both walls,
+    /// 0 places none
+    pub climb_ledge: usize,
+    /// brush and route modifiers active for a part of the walk, scaling ones take over the brush
+    /// scale from the brush mutations
+    pub modifiers: Vec<ScheduledModifier>,
+    /// brush scale over the share of the route already walked, as (progress, scale) points from
+    /// 0.0 at the spawn to 1.0 at the finish, linear in between and flat past the outer points,
+    /// empty keeps the brush as it is, branches keep the scale at their branch point
+    pub kernel_profile: Vec<(f32, f32)>,
+    /// steps without getting closer to the waypoint after which the walker is stuck, 0 turns the
+    /// detection off
+    pub stuck_steps: usize,
+    pub stuck_policy: StuckPolicy,
+}
+
+impl WalkerConfig {
+    /// brush scale of the kernel profile at `progress`
+    pub fn kernel_scale(&self, progress: f32) -> f32 {
+        let profile = &self.kernel_profile;
+
+        let (Some(first), Some(last)) = (profile.first(), profile.last()) else {
+            return 1.0;
+        };
+
+        if progress <= first.0 {
+            return first.1;
+        }
+
+        for pair in profile.windows(2) {
+            let ((from, from_scale), (to, to_scale)) = (pair[0], pair[1]);
+
+            if progress <= to {
+                let span = to - from;
+                let t = if span > 0.0 {
+                    (progress - from) / span
+                } else {
+                    1.0
+                };
+
+                return from_scale + (to_scale - from_scale) * t;
+            }
+        }
+
+        last.1
+    }
+
+    fn validate(&self, field: &str, issues: &mut Vec<ConfigIssue>) {
+        if self.reach_distance <= 0.0 {
+            issues.push(
+                ConfigIssue::error(format!("{}.reach_distance", field), "must be positive")
+                    .fix("set it to 2.0"),
+            );
+        }
+
+        let probabilities = [
+            ("randomness", self.randomness),
+            ("unhookable_probability", self.unhookable_probability),
+            ("hookthrough_probability", self.hookthrough_probability),
+            ("island_probability", self.island_probability),
+            ("max_turn_frequency", self.max_turn_frequency),
+            ("backtrack_penalty", self.backtrack_penalty),
+            ("guide_bias", self.guide_bias),
+        ];
+
+        for (name, value) in probabilities {
+            if !(0.0..=1.0).contains(&value) {
+                issues.push(
+                    ConfigIssue::warning(format!("{}.{}", field, name), "outside 0.0 to 1.0")
+                        .fix(format!("set it to {}", value.clamp(0.0, 1.0))),
+                );
+            }
+        }
+
+        if self.unhookable_probability + self.hookthrough_probability > 1.0 {
+            issues.push(ConfigIssue::warning(
+                format!("{}.hookthrough_probability", field),
+                "unhookable and hookthrough probabilities add up to more than 1.0",
+            ));
+        }
+
+        if self.max_turn_frequency == 0.0 {
+            issues.push(
+                ConfigIssue::warning(
+                    format!("{}.max_turn_frequency", field),
+                    "the walker can only turn close to waypoints",
+                )
+                .fix("set it above 0.0"),
+            );
+        }
+
+        if !(-1.0..=1.0).contains(&self.openness_bias) {
+            issues.push(
+                ConfigIssue::warning(format!("{}.openness_bias", field), "outside -1.0 to 1.0")
+                    .fix(format!("set it to {}", self.openness_bias.clamp(-1.0, 1.0))),
+            );
+        }
+
+        if self.max_climb > 0 && self.climb_ledge >= self.max_climb {
+            issues.push(
+                ConfigIssue::warning(
+                    format!("{}.climb_ledge", field),
+                    "climbs end before the first ledge",
+                )
+                .fix(format!("use less than {}", self.max_climb)),
+            );
+        }
+
+        for (i, modifier) in self.modifiers.iter().enumerate() {
+            let field = format!("{}.modifiers[{}]", field, i);
+
+            match modifier.effect {
+                ModifierEffect::Pulse { scale, .. } | ModifierEffect::Fade { scale }
+                    if scale <= 0.0 =>
+                {
+                    issues.push(
+                        ConfigIssue::error(format!("{}.effect", field), "scale must be positive")
+                            .fix("set it to 0.5"),
+                    );
+                }
+                ModifierEffect::Pulse { peak, .. } if !(0.0..=1.0).contains(&peak) => {
+                    issues.push(
+                        ConfigIssue::warning(
+                            format!("{}.effect", field),
+                            "peak outside 0.0 to 1.0",
+                        )
+                        .fix(format!("set it to {}", peak.clamp(0.0, 1.0))),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        let mut previous = 0.0;
+
+        for (i, &(progress, scale)) in self.kernel_profile.iter().enumerate() {
+            let field = format!("{}.kernel_profile[{}]", field, i);
+
+            if !(0.0..=1.0).contains(&progress) {
+                issues.push(
+                    ConfigIssue::warning(field.clone(), "progress outside 0.0 to 1.0")
+                        .fix(format!("set it to {}", progress.clamp(0.0, 1.0))),
+                );
+            } else if progress < previous {
+                issues.push(
+                    ConfigIssue::error(field.clone(), "progress goes back")
+                        .fix("sort the points by progress"),
+                );
+            }
+
+            if scale.is_nan() || scale <= 0.0 {
+                let issue = ConfigIssue::error(field, "scale must be positive");
+                issues.push(issue.fix("set it to 1.0"));
+            }
+
+            previous = progress;
+        }
+
+        if let StuckPolicy::Enlarge { scale, .. } = self.stuck_policy {
+            if scale <= 0.0 {
+                issues.push(
+                    ConfigIssue::error(format!("{}.stuck_policy", field), "scale must be positive")
+                        .fix("set it to 2.0"),
+                );
+            }
+        }
+
+        if self.temperature < 0.0 {
+            issues.push(
+                ConfigIssue::error(format!("{}.temperature", field), "must not be negative")
+                    .fix("set it to 0.5"),
+            );
+        }
+
+        if self.step_policy == StepPolicy::Guided && self.guide_interval == 0 {
+            issues.push(
+                ConfigIssue::error(
+                    format!("{}.guide_interval", field),
+                    "the guided policy never plans a path",
+                )
+                .fix("set it to 20"),
+            );
+        }
+    }
+}
+
+impl Default for WalkerConfig {
+    fn default() -> Self {
+        Self {
+            reach_distance: 2.0,
+            randomness: 0.25,
+            unhookable_probability: 0.0,
+            hookthrough_probability: 0.0,
+            island_probability: 0.02,
+            min_straight_run: 0,
+            max_turn_frequency: 1.0,
+            backtrack_penalty: 0.0,
+            backtrack_decay: 100,
+            step_policy: StepPolicy::Greedy,
+            guide_interval: 20,
+            guide_bias: 0.75,
+            temperature: 0.5,
+            openness_bias: 0.0,
+            allow_diagonal: false,
+            max_climb: 0,
+            climb_ledge: 0,
+            modifiers: Vec::new(),
+            kernel_profile: Vec::new(),
+            stuck_steps: 0,
+            stuck_policy: StuckPolicy::default(),
+        }
+    }
+}
+
+/// initial brush, mutations may rescale it during generation
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct KernelConfig {
+    /// brush width and height in tiles
+    pub size: usize,
+    /// 0.0 is a square, 1.0 is a circle
+    pub circularity: f32,
+}
+
+impl KernelConfig {
+    fn validate(&self, field: &str, issues: &mut Vec<ConfigIssue>) {
+        if self.size == 0 {
+            issues.push(
+                ConfigIssue::error(format!("{}.size", field), "the brush carves nothing")
+                    .fix("set it to 1"),
+            );
+        }
+
+        if !(0.0..=1.0).contains(&self.circularity) {
+            issues.push(
+                ConfigIssue::warning(format!("{}.circularity", field), "outside 0.0 to 1.0")
+                    .fix(format!("set it to {}", self.circularity.clamp(0.0, 1.0))),
+            );
+        }
+    }
+}
+
+impl Default for KernelConfig {
+    fn default() -> Self {
+        Self {
+            size: 1,
+            circularity: 0.0,
+        }
+    }
+}
+
+/// route layout, keeps the format of the old waypoints configs
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WaypointConfig {
+    /// waypoints in normalized coordinates
+    pub waypoints: Vec<(f32, f32)>,
+    /// factor the normalized waypoints are scaled with
+    pub scale_factor: f32,
+    /// free tiles around the route bounds, lets the walker leave the straight route
+    pub margin: usize,
+    /// alternative route endings, walked in order after the main route
+    pub branches: Vec<BranchConfig>,
+    /// leads the route back to the spawn and places start and finish lines for lap timing
+    pub looped: bool,
+    /// parameters of single waypoints, the first override of a waypoint wins
+    pub overrides: Vec<WaypointOverride>,
+    /// moves the main route waypoints by a seeded random offset, so one layout gives varied maps
+    pub jitter: Option<WaypointJitter>,
+}
+
+/// consecutive main route waypoints closer than this many tiles are merged by the fixer
+pub const MIN_WAYPOINT_SPACING: f32 = 8.0;
+
+/// cosine of the turn at a waypoint below which the route doubles back on itself
+const REVERSAL_COS: f32 = -0.94;
+
+/// main route layout problem making the walker take a degenerate path
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpacingProblem {
+    /// waypoints `index` and `index + 1` are less than `MIN_WAYPOINT_SPACING` tiles apart
+    TooClose { index: usize, distance: f32 },
+    /// route turns back on itself at waypoint `index`, the three waypoints lie about on a line
+    /// with this one at its far end, straight runs through a waypoint are fine
+    Reversal { index: usize },
+}
+
+impl WaypointConfig {
+    /// spacing and angle problems of the main route, in route order, none without a positive
+    /// scale factor
+    pub fn spacing_problems(&self) -> Vec<SpacingProblem> {
+        if self.scale_factor.is_nan() || self.scale_factor <= 0.0 {
+            return Vec::new();
+        }
+
+        let tiles: Vec<(f32, f32)> = self
+            .waypoints
+            .iter()
+            .map(|&(x, y)| (x * self.scale_factor, y * self.scale_factor))
+            .collect();
+        let mut problems = Vec::new();
+
+        for (index, pair) in tiles.windows(2).enumerate() {
+            let distance = (pair[1].0 - pair[0].0).hypot(pair[1].1 - pair[0].1);
+
+            if distance < MIN_WAYPOINT_SPACING {
+                problems.push(SpacingProblem::TooClose { index, distance });
+            }
+        }
+
+        for (i, triple) in tiles.windows(3).enumerate() {
+            let (a, b, c) = (triple[0], triple[1], triple[2]);
+            let incoming = (b.0 - a.0, b.1 - a.1);
+            let outgoing = (c.0 - b.0, c.1 - b.1);
+            let lengths = incoming.0.hypot(incoming.1) * outgoing.0.hypot(outgoing.1);
+
+            // too close already, the angle means nothing
+            if lengths == 0.0 {
+                continue;
+            }
+
+            let cos = (incoming.0 * outgoing.0 + incoming.1 * outgoing.1) / lengths;
+
+            if cos < REVERSAL_COS {
+                problems.push(SpacingProblem::Reversal { index: i + 1 });
+            }
+        }
+
+        problems.sort_by_key(|problem| match *problem {
+            SpacingProblem::TooClose { index, .. } | SpacingProblem::Reversal { index } => index,
+        });
+
+        problems
+    }
+
+    /// override active while the walker heads to main route `waypoint`
+    pub fn override_at(&self, waypoint: usize) -> Option<&WaypointOverride> {
+        self.overrides
+            .iter()
+            .find(|local| local.waypoint == waypoint)
+    }
+
+    /// normalized top left and bottom right corners of the main route and the branches, the
+    /// map spans them scaled plus the margin on each side
+    pub fn bounds(&self) -> ((f32, f32), (f32, f32)) {
+        self.waypoints
+            .iter()
+            .chain(
+                self.branches
+                    .iter()
+                    .flat_map(|branch| branch.waypoints.iter()),
+            )
+            .fold(
+                ((f32::MAX, f32::MAX), (f32::MIN, f32::MIN)),
+                |(min, max), &(x, y)| ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y))),
+            )
+    }
+}
+
+impl Default for WaypointConfig {
+    fn default() -> Self {
+        Self {
+            waypoints: Vec::new(),
+            scale_factor: 1.0,
+            margin: 200,
+            branches: Vec::new(),
+            looped: false,
+            overrides: Vec::new(),
+            jitter: None,
+        }
+    }
+}
+
+/// walker and kernel parameters changed while the walker heads to one waypoint, applied on top
+/// of the zone ones for pacing without setting up zones
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WaypointOverride {
+    /// main route waypoint index
+    pub waypoint: usize,
+    /// inclusive bounds the kernel size is clamped to
+    pub kernel_size: Option<(usize, usize)>,
+    pub min_straight_run: Option<usize>,
+    pub max_turn_frequency: Option<f32>,
+    pub climb_ledge: Option<usize>,
+}
+
+impl WaypointOverride {
+    pub fn apply(&self, walker: &mut WalkerConfig, kernel: &mut KernelConfig) {
+        if let Some((min, max)) = self.kernel_size {
+            kernel.size = kernel.size.max(min).min(max);
+        }
+
+        if let Some(min_straight_run) = self.min_straight_run {
+            walker.min_straight_run = min_straight_run;
+        }
+
+        if let Some(max_turn_frequency) = self.max_turn_frequency {
+            walker.max_turn_frequency = max_turn_frequency;
+        }
+
+        if let Some(climb_ledge) = self.climb_ledge {
+            walker.climb_ledge = climb_ledge;
+        }
+    }
+}
+
+/// random offset of the waypoints drawn per generation
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WaypointJitter {
+    /// farthest a waypoint is moved in tiles
+    pub radius: f32,
+    /// leaves the first and last waypoint in place
+    pub keep_endpoints: bool,
+}
+
+impl Default for WaypointJitter {
+    fn default() -> Self {
+        Self {
+            radius: 20.0,
+            keep_endpoints: true,
+        }
+    }
+}
+
+impl WaypointJitter {
+    /// moves every waypoint to a uniformly picked point within `radius` tiles of it
+    pub fn apply(&self, waypoints: &mut [(f32, f32)], scale_factor: f32, prng: &mut Random) {
+        let radius = self.radius.max(0.0) / scale_factor;
+        let last = waypoints.len().saturating_sub(1);
+
+        for (i, waypoint) in waypoints.iter_mut().enumerate() {
+            if self.keep_endpoints && (i == 0 || i == last) {
+                continue;
+            }
+
+            let angle: f32 = prng.in_range(0.0..std::f32::consts::TAU);
+            let distance = radius * prng.in_range(0.0..=1.0f32).sqrt();
+
+            waypoint.0 += angle.cos() * distance;
+            waypoint.1 += angle.sin() * distance;
+        }
+    }
+}
+
+/// route leaving the main route at one of its waypoints, each branch ends in its own finish
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BranchConfig {
+    /// main route waypoint the branch starts at
+    pub from: usize,
+    /// waypoints following the branch point, normalized like the main route
+    pub waypoints: Vec<(f32, f32)>,
+}
+
+/// passes run after the walker finished
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PostProcessConfig {
+    pub spawn: SpawnConfig,
+    /// corner cutting passes over the main route, carving it again along a smoother path
+    /// before the rooms, 0 turns it off
+    pub smoothing_iterations: usize,
+    /// room carved at every reached waypoint
+    pub rooms: Option<RoomConfig>,
+    /// shortcuts through thin walls, carved after the rooms
+    pub skips: Option<SkipConfig>,
+    /// timed doors closing some of the skips, switch tiles exported in the switch layer
+    pub doors: Option<DoorConfig>,
+    /// re-textures the walls with learned patterns, after the skips
+    pub wfc: Option<WfcConfig>,
+    /// maze walls in large open caverns, after the wall patterns
+    pub maze: Option<MazeConfig>,
+    /// freeze lining along the route, after the skips so they get lined as well
+    pub freeze: Option<FreezeConfig>,
+    /// stamped in order, before the spawn room is placed
+    pub prefabs: Vec<PrefabPlacement>,
+    /// applied in order after the prefabs
+    pub rewrites: Vec<RewriteRule>,
+    /// empty pockets unreachable from the spawn, checked after the spawn room is placed
+    pub sealed_regions: Option<SealedRegionConfig>,
+    /// kill tiles on hookable ceilings and floors, after the sealed regions so the safe path
+    /// between spawn and finish is known
+    pub spikes: Option<SpikeConfig>,
+    /// freezes empty tiles next to hookable ones
+    pub fix_edge_bugs: bool,
+    /// width of the empty corridor kept free of freeze along the whole route, brushes are never
+    /// smaller and freeze intruding into it is cleared after the edge bug fixes, 0 turns it off
+    pub min_clear_width: usize,
+    /// thickness of the hookable border forced around the exported map, at least 1
+    pub border: usize,
+    /// stamps finish tiles at the end of the main route and every branch
+    pub finish: bool,
+}
+
+/// map info written on export
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ExportConfig {
+    /// map file name without extension, derived from preset name and seed when empty
+    pub name: String,
+    pub author: String,
+    pub version: String,
+    pub credits: String,
+    pub license: String,
+    /// appends the generation metadata to the credits
+    pub stamp_metadata: bool,
+    /// trims the unused margins around the playable area
+    pub crop: bool,
+    /// solid border kept around the playable area when cropping
+    pub crop_padding: usize,
+    /// moves the freeze into the front layer, teeworlds 0.7 maps have no freeze either way
+    pub front_freeze: bool,
+    pub target_version: TargetVersion,
+    /// game layer ids the blocks are written with, after the conversion for the target version
+    pub tile_mapping: TileMapping,
+    /// smallest exported map width, narrower maps are padded with solid tiles on the right so
+    /// the camera doesn't show beyond the map edges
+    pub min_width: usize,
+    /// smallest exported map height, shorter maps are padded with solid tiles at the bottom
+    pub min_height: usize,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            author: "mapgen".to_string(),
+            version: "1.0beta".to_string(),
+            credits: String::new(),
+            license: "CC0".to_string(),
+            stamp_metadata: true,
+            crop: true,
+            crop_padding: 32,
+            front_freeze: false,
+            target_version: TargetVersion::DDNet06,
+            tile_mapping: TileMapping::default(),
+            min_width: 0,
+            min_height: 0,
+        }
+    }
+}
+
+impl ExportConfig {
+    pub fn map_name(&self, metadata: &GenerationMetadata) -> String {
+        if self.name.is_empty() {
+            format!("{}_{}", metadata.preset, metadata.seed)
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+/// what happens once a generation runs out of its budget
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BudgetPolicy {
+    /// carves straight to the last waypoint and runs the passes as usual
+    #[default]
+    Truncate,
+    /// stops the generation with [`GenerationError::BudgetExceeded`]
+    ///
+    /// [`GenerationError::BudgetExceeded`]: crate::generator::GenerationError::BudgetExceeded
+    Fail,
+}
+
+/// limits of a single generation, checked on every walker step
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BudgetConfig {
+    /// seconds, 0.0 doesn't limit the time, never checked on wasm where there is no clock
+    pub max_generation_time: f32,
+    /// walker steps of the main route and all branches, 0 doesn't limit them
+    pub max_total_steps: usize,
+    pub policy: BudgetPolicy,
+}
+
+/// traces a generated map back to the seed and preset that produced it
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GenerationMetadata {
+    pub seed: Seed,
+    pub preset: String,
+    /// version of the generator crate
+    pub generator_version: String,
+}
+
+impl GenerationMetadata {
+    pub fn new(seed: Seed, preset: &GenerationPreset) -> Self {
+        Self {
+            seed,
+            preset: if preset.name.is_empty() {
+                "custom".to_string()
+            } else {
+                preset.name.clone()
+            },
+            generator_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+impl fmt::Display for GenerationMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "mapgen {}, preset {}, seed {}",
+            self.generator_version, self.preset, self.seed
+        )
+    }
+}
+
+/// walker and kernel overrides for a part of the route
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ZoneConfig {
+    /// first and last waypoint index the walker heads to while the zone is active, inclusive
+    pub waypoints: (usize, usize),
+    pub walker: Option<WalkerConfig>,
+    pub kernel: Option<KernelConfig>,
+    /// freeze lining of the route segment, also applied without a preset wide lining
+    pub freeze: Option<FreezeConfig>,
+}
+
+impl ZoneConfig {
+    pub fn contains(&self, waypoint: usize) -> bool {
+        (self.waypoints.0..=self.waypoints.1).contains(&waypoint)
+    }
+}
+
+/// everything needed to generate a map
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GenerationPreset {
+    /// stamped into exported maps, usually the preset file name
+    pub name: String,
+    pub mode: GenerationMode,
+    pub walker: WalkerConfig,
+    pub kernel: KernelConfig,
+    pub waypoints: WaypointConfig,
+    /// replaces the waypoints with a walk through a grid of rooms and corridors
+    pub room_grid: Option<RoomGridConfig>,
+    /// lays the waypoints out along a tall shaft, replaces the zones too if it has bands
+    pub tower: Option<TowerConfig>,
+    /// route segments with their own walker and kernel, the first matching zone wins
+    pub zones: Vec<ZoneConfig>,
+    /// forbidden and preferred regions painted over the route
+    pub guidance_mask: Option<GuidanceMask>,
+    pub post_process: PostProcessConfig,
+    pub export: ExportConfig,
+    pub budget: BudgetConfig,
+}
+
+impl GenerationPreset {
+    /// merges too close waypoints and drops the ones the route turns back at until the main route
+    /// has no spacing problems left, a route of only two waypoints gets them pushed apart instead,
+    /// overrides, branches and zones follow the waypoints they refer to, returns the number of
+    /// changes
+    pub fn fix_waypoint_spacing(&mut self) -> usize {
+        let mut changes = 0;
+
+        while let Some(&problem) = self.waypoints.spacing_problems().first() {
+            let waypoints = &mut self.waypoints.waypoints;
+            let last = waypoints.len() - 1;
+
+            match problem {
+                SpacingProblem::TooClose { .. } if last == 1 => {
+                    let scale_factor = self.waypoints.scale_factor;
+                    let (from, to) = (waypoints[0], waypoints[1]);
+                    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+                    let length = dx.hypot(dy);
+                    let (dx, dy) = if length > 0.0 {
+                        (dx / length, dy / length)
+                    } else {
+                        (1.0, 0.0)
+                    };
+                    // a tile of slack, so rounding doesn't leave them too close again
+                    let spacing = (MIN_WAYPOINT_SPACING + 1.0) / scale_factor;
+
+                    waypoints[1] = (from.0 + dx * spacing, from.1 + dy * spacing);
+                }
+                // endpoints stay where they are, the waypoint next to them goes
+                SpacingProblem::TooClose { index, .. } if index + 1 == last => {
+                    self.remove_waypoint(index, index + 1)
+                }
+                SpacingProblem::TooClose { index: 0, .. } => self.remove_waypoint(1, 0),
+                SpacingProblem::TooClose { index, .. } => {
+                    let (a, b) = (waypoints[index], waypoints[index + 1]);
+                    waypoints[index] = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+
+                    self.remove_waypoint(index + 1, index);
+                }
+                SpacingProblem::Reversal { index } => self.remove_waypoint(index, index + 1),
+            }
+
+            changes += 1;
+        }
+
+        changes
+    }
+
+    /// removes main route waypoint `removed`, whatever referred to it refers to `into` instead
+    fn remove_waypoint(&mut self, removed: usize, into: usize) {
+        self.waypoints.waypoints.remove(removed);
+
+        let remap = |waypoint: usize| {
+            let waypoint = if waypoint == removed { into } else { waypoint };
+
+            if waypoint > removed {
+                waypoint - 1
+            } else {
+                waypoint
+            }
+        };
+
+        for local in self.waypoints.overrides.iter_mut() {
+            local.waypoint = remap(local.waypoint);
+        }
+
+        for branch in self.waypoints.branches.iter_mut() {
+            branch.from = remap(branch.from);
+        }
+
+        for zone in self.zones.iter_mut() {
+            zone.waypoints = (remap(zone.waypoints.0), remap(zone.waypoints.1));
+        }
+    }
+
+    /// index of the zone active while the walker heads to `waypoint`
+    pub fn zone_at(&self, waypoint: usize) -> Option<usize> {
+        self.zones.iter().position(|zone| zone.contains(waypoint))
+    }
+
+    /// every problem found in the preset, run once when a preset is loaded or edited
+    pub fn validate(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+
+        self.walker.validate("walker", &mut issues);
+
+        if self.budget.max_generation_time < 0.0 {
+            issues.push(
+                ConfigIssue::warning("budget.max_generation_time", "negative, doesn't limit")
+                    .fix("set it to 0.0"),
+            );
+        }
+
+        self.kernel.validate("kernel", &mut issues);
+
+        for (i, zone) in self.zones.iter().enumerate() {
+            let field = format!("zones[{}]", i);
+
+            if let Some(walker) = &zone.walker {
+                walker.validate(&format!("{}.walker", field), &mut issues);
+            }
+
+            if let Some(kernel) = &zone.kernel {
+                kernel.validate(&format!("{}.kernel", field), &mut issues);
+            }
+
+            if zone.waypoints.0 > zone.waypoints.1 {
+                issues.push(
+                    ConfigIssue::warning(
+                        format!("{}.waypoints", field),
+                        "first waypoint is after the last one, the zone is never active",
+                    )
+                    .fix("swap the waypoint indices"),
+                );
+            }
+        }
+
+        let waypoints = &self.waypoints;
+
+        if self.room_grid.is_none() && self.tower.is_none() && waypoints.waypoints.len() < 2 {
+            issues.push(
+                ConfigIssue::error(
+                    "waypoints.waypoints",
+                    "at least two waypoints are needed for a route",
+                )
+                .fix("add waypoints or enable the room grid"),
+            );
+        }
+
+        let replaced = self.room_grid.is_some() || self.tower.is_some();
+        let problems = if replaced {
+            Vec::new()
+        } else {
+            waypoints.spacing_problems()
+        };
+
+        for problem in problems {
+            let issue = match problem {
+                SpacingProblem::TooClose { index, distance } => ConfigIssue::warning(
+                    "waypoints.waypoints",
+                    format!(
+                        "waypoints {} and {} are {:.1} tiles apart",
+                        index,
+                        index + 1,
+                        distance
+                    ),
+                )
+                .fix(format!(
+                    "keep them at least {} tiles apart or merge them",
+                    MIN_WAYPOINT_SPACING
+                )),
+                SpacingProblem::Reversal { index } => ConfigIssue::warning(
+                    "waypoints.waypoints",
+                    format!("the route turns back on itself at waypoint {}", index),
+                )
+                .fix("remove it or move it off the line"),
+            };
+
+            issues.push(issue);
+        }
+
+        let outside = |&(x, y): &(f32, f32)| !(0.0..=1.0).contains(&x) || !(0.0..=1.0).contains(&y);
+
+        if waypoints.waypoints.iter().any(outside) {
+            issues.push(ConfigIssue::warning(
+                "waypoints.waypoints",
+                "waypoints outside 0.0 to 1.0 stretch the map beyond the scale factor",
+            ));
+        }
+
+        if let Some(jitter) = &waypoints.jitter {
+            if jitter.radius < 0.0 {
+                issues.push(
+                    ConfigIssue::warning(
+                        "waypoints.jitter.radius",
+                        "negative, doesn't move anything",
+                    )
+                    .fix("set it to 0.0"),
+                );
+            }
+        }
+
+        if waypoints.scale_factor <= 0.0 {
+            issues.push(
+                ConfigIssue::error("waypoints.scale_factor", "must be positive")
+                    .fix("set it to 200"),
+            );
+        }
+
+        for (i, local) in waypoints.overrides.iter().enumerate() {
+            let field = format!("waypoints.overrides[{}]", i);
+
+            if local.waypoint >= waypoints.waypoints.len() {
+                issues.push(
+                    ConfigIssue::warning(
+                        format!("{}.waypoint", field),
+                        "the waypoint doesn't exist, the override is never active",
+                    )
+                    .fix(format!(
+                        "use a waypoint below {}",
+                        waypoints.waypoints.len()
+                    )),
+                );
+            }
+
+            if local.kernel_size.is_some_and(|(min, max)| min > max) {
+                issues.push(
+                    ConfigIssue::warning(
+                        format!("{}.kernel_size", field),
+                        "lower bound is above the upper one, the kernel is always the upper one",
+                    )
+                    .fix("swap the bounds"),
+                );
+            }
+        }
+
+        for (i, branch) in waypoints.branches.iter().enumerate() {
+            if branch.from >= waypoints.waypoints.len() {
+                issues.push(
+                    ConfigIssue::error(
+                        format!("waypoints.branches[{}].from", i),
+                        "leaves the route at a waypoint that doesn't exist",
+                    )
+                    .fix(format!(
+                        "use a waypoint below {}",
+                        waypoints.waypoints.len()
+                    )),
+                );
+            }
+        }
+
+        if let Some(tower) = &self.tower {
+            if tower.width == 0 || tower.height == 0 || tower.spacing == 0 {
+                issues.push(
+                    ConfigIssue::error("tower", "width, height and spacing must be positive")
+                        .fix("use the default tower"),
+                );
+            }
+
+            if !(0.0..=1.0).contains(&tower.sway) {
+                issues.push(
+                    ConfigIssue::warning("tower.sway", "outside 0.0 to 1.0")
+                        .fix(format!("set it to {}", tower.sway.clamp(0.0, 1.0))),
+                );
+            }
+
+            if self.room_grid.is_some() {
+                issues.push(
+                    ConfigIssue::warning("tower", "the room grid replaces the tower waypoints")
+                        .fix("disable the room grid"),
+                );
+            }
+
+            for (i, band) in tower.bands.iter().enumerate() {
+                let field = format!("tower.bands[{}]", i);
+
+                if let Some(walker) = &band.walker {
+                    walker.validate(&format!("{}.walker", field), &mut issues);
+                }
+
+                if let Some(kernel) = &band.kernel {
+                    kernel.validate(&format!("{}.kernel", field), &mut issues);
+                }
+            }
+        }
+
+        if let Some(mask) = &self.guidance_mask {
+            if mask.cell_size == 0 {
+                issues.push(
+                    ConfigIssue::error("guidance_mask.cell_size", "cells have no size")
+                        .fix("set it to 4"),
+                );
+            }
+
+            if !(0.0..=1.0).contains(&mask.attraction) {
+                issues.push(
+                    ConfigIssue::warning("guidance_mask.attraction", "outside 0.0 to 1.0")
+                        .fix(format!("set it to {}", mask.attraction.clamp(0.0, 1.0))),
+                );
+            }
+        }
+
+        if self.post_process.border > waypoints.margin {
+            issues.push(
+                ConfigIssue::warning(
+                    "post_process.border",
+                    "thicker than the margin, the border may cut into the route",
+                )
+                .fix(format!("use at most {}", waypoints.margin)),
+            );
+        }
+
+        if let Some(freeze) = &self.post_process.freeze {
+            if freeze.thickness == 0 {
+                issues.push(
+                    ConfigIssue::warning("post_process.freeze.thickness", "lines nothing")
+                        .fix("set it to 1 or disable the freeze lining"),
+                );
+            }
+        }
+
+        let mapping = &self.export.tile_mapping;
+        let mut ids: Vec<u8> = BlockType::ALL
+            .iter()
+            .map(|&block| mapping.id(block))
+            .chain(mapping.spawns)
+            .collect();
+        ids.sort_unstable();
+
+        if ids.windows(2).any(|pair| pair[0] == pair[1]) {
+            issues.push(
+                ConfigIssue::warning(
+                    "export.tile_mapping",
+                    "several tiles share an id, they can't be told apart in the exported map",
+                )
+                .fix("give every tile its own id"),
+            );
+        }
+
+        if let Some(maze) = &self.post_process.maze {
+            if maze.cell_size == 0 {
+                issues.push(
+                    ConfigIssue::error("post_process.maze.cell_size", "corridors have no width")
+                        .fix("set it to 5"),
+                );
+            }
+
+            if maze.wall_thickness == 0 {
+                issues.push(
+                    ConfigIssue::warning("post_process.maze.wall_thickness", "draws no walls")
+                        .fix("set it to 1 or disable the maze"),
+                );
+            }
+
+            if maze.min_clearance < 2 {
+                issues.push(
+                    ConfigIssue::warning(
+                        "post_process.maze.min_clearance",
+                        "below 2, raised to 2 so the maze entrances stay free",
+                    )
+                    .fix("set it to 2"),
+                );
+            }
+        }
+
+        if let Some(spikes) = &self.post_process.spikes {
+            if !(0.0..=1.0).contains(&spikes.chance) {
+                issues.push(
+                    ConfigIssue::warning("post_process.spikes.chance", "outside 0.0 to 1.0")
+                        .fix(format!("set it to {}", spikes.chance.clamp(0.0, 1.0))),
+                );
+            }
+
+            if spikes.min_length > spikes.max_length {
+                issues.push(
+                    ConfigIssue::warning(
+                        "post_process.spikes.min_length",
+                        "above the max length, no strip is ever placed",
+                    )
+                    .fix("swap the lengths"),
+                );
+            }
+
+            if !self.post_process.finish {
+                issues.push(
+                    ConfigIssue::warning(
+                        "post_process.spikes",
+                        "no finish to keep a safe path to, no kill tiles are placed",
+                    )
+                    .fix("enable the finish"),
+                );
+            }
+        }
+
+        if self.post_process.doors.is_some() {
+            let skips = &self.post_process.skips;
+
+            if skips.as_ref().is_none_or(|skips| skips.freeze) {
+                issues.push(
+                    ConfigIssue::warning("post_process.doors", "no carved skips to close")
+                        .fix("enable the skips without freeze or disable the doors"),
+                );
+            }
+        }
+
+        issues
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// generation works, but likely not as intended
+    Warning,
+    /// generation fails or panics
+    Error,
+}
+
+/// problem found by the preset validation
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigIssue {
+    /// path of the field, like `walker.reach_distance`
+    pub field: String,
+    pub severity: Severity,
+    pub message: String,
+    pub suggested_fix: Option<String>,
+}
+
+impl ConfigIssue {
+    pub fn warning(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            severity: Severity::Warning,
+            message: message.into(),
+            suggested_fix: None,
+        }
+    }
+
+    pub fn error(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            ..Self::warning(field, message)
+        }
+    }
+
+    pub fn fix(mut self, suggested_fix: impl Into<String>) -> Self {
+        self.suggested_fix = Some(suggested_fix.into());
+        self
+    }
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} in {}: {}", self.severity, self.field, self.message)?;
+
+        if let Some(fix) = &self.suggested_fix {
+            write!(f, " ({})", fix)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ndarray::Array2;
+
+/// color of a debug layer, kept free of any graphics crate so headless users don't need one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DebugColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl DebugColor {
+    pub const WHITE: Self = Self::rgb(255, 255, 255);
+    pub const GRAY: Self = Self::rgb(128, 128, 128);
+    pub const RED: Self = Self::rgb(230, 41, 55);
+    pub const ORANGE: Self = Self::rgb(255, 161, 0);
+    pub const YELLOW: Self = Self::rgb(253, 249, 0);
+    pub const GREEN: Self = Self::rgb(0, 228, 48);
+    pub const BLUE: Self = Self::rgb(0, 121, 241);
+    pub const PURPLE: Self = Self::rgb(200, 122, 255);
+
+    /// distinct colors for class layers
+    pub const PALETTE: [Self; 6] = [
+        Self::RED,
+        Self::ORANGE,
+        Self::YELLOW,
+        Self::GREEN,
+        Self::BLUE,
+        Self::PURPLE,
+    ];
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+
+    pub fn to_array(self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+}
+
+/// tiles of a debug layer
+#[derive(Debug, Clone, PartialEq)]
+pub enum DebugLayerData {
+    Mask(Array2<bool>),
+    /// values from 0.0 to 1.0
+    Heatmap(Array2<f32>),
+    /// 0 for none, other values pick the color at `value - 1`, wrapping around
+    Classes(Array2<u8>, Vec<DebugColor>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugLayer {
+    /// tint of masks and heatmaps
+    pub color: DebugColor,
+    pub data: DebugLayerData,
+}
+
+/// debug layers the passes produced during a generation, a layer is allocated on its first
+/// write and never while it's disabled, disabled layers stay listed and disabled across
+/// generations
+#[derive(Debug, Clone, Default)]
+pub struct DebugLayers {
+    dim: (usize, usize),
+    layers: BTreeMap<String, DebugLayer>,
+    produced: BTreeSet<String>,
+    disabled: BTreeSet<String>,
+}
+
+impl DebugLayers {
+    pub fn new(dim: (usize, usize)) -> Self {
+        Self {
+            dim,
+            ..Default::default()
+        }
+    }
+
+    /// drops the layers of the last generation, keeps which ones are disabled
+    pub fn reset(&mut self, dim: (usize, usize)) {
+        self.dim = dim;
+        self.layers.clear();
+        self.produced.clear();
+    }
+
+    pub fn dim(&self) -> (usize, usize) {
+        self.dim
+    }
+
+    /// mask layer `name`, none while it's disabled or already of another kind
+    pub fn mask(&mut self, name: &str, color: DebugColor) -> Option<&mut Array2<bool>> {
+        let dim = self.dim;
+        let make = || DebugLayerData::Mask(Array2::from_elem(dim, false));
+
+        match &mut self.layer(name, color, make)?.data {
+            DebugLayerData::Mask(tiles) => Some(tiles),
+            _ => None,
+        }
+    }
+
+    /// heatmap layer `name`, none while it's disabled or already of another kind
+    pub fn heatmap(&mut self, name: &str, color: DebugColor) -> Option<&mut Array2<f32>> {
+        let dim = self.dim;
+        let make = || DebugLayerData::Heatmap(Array2::zeros(dim));
+
+        match &mut self.layer(name, color, make)?.data {
+            DebugLayerData::Heatmap(tiles) => Some(tiles),
+            _ => None,
+        }
+    }
+
+    /// class layer `name` colored with `colors`, none while it's disabled or already of another
+    /// kind
+    pub fn classes(&mut self, name: &str, colors: &[DebugColor]) -> Option<&mut Array2<u8>> {
+        let dim = self.dim;
+        let make = || DebugLayerData::Classes(Array2::zeros(dim), colors.to_vec());
+
+        match &mut self.layer(name, DebugColor::WHITE, make)?.data {
+            DebugLayerData::Classes(tiles, _) => Some(tiles),
+            _ => None,
+        }
+    }
+
+    fn layer(
+        &mut self,
+        name: &str,
+        color: DebugColor,
+        make: impl FnOnce() -> DebugLayerData,
+    ) -> Option<&mut DebugLayer> {
+        self.produced.insert(name.to_string());
+
+        if self.disabled.contains(name) {
+            return None;
+        }
+
+        let layer = self
+            .layers
+            .entry(name.to_string())
+            .or_insert_with(|| DebugLayer {
+                color,
+                data: make(),
+            });
+
+        Some(layer)
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        !self.disabled.contains(name)
+    }
+
+    /// takes effect with the next generation, disabling drops the layer right away
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) {
+        if enabled {
+            self.disabled.remove(name);
+        } else {
+            self.disabled.insert(name.to_string());
+            self.layers.remove(name);
+        }
+    }
+
+    /// names of every layer produced by the last generation, disabled ones included
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.produced.iter().map(String::as_str)
+    }
+
+    /// layers written by the last generation
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &DebugLayer)> {
+        self.layers
+            .iter()
+            .map(|(name, layer)| (name.as_str(), layer))
+    }
+}
@@ -0,0 +1,211 @@
+use std::{error::Error, fmt, ops::Range};
+
+use ndarray::{s, Array2};
+use twmap::{GameLayer, GameTile, TileFlags};
+
+use crate::{distance::CHUNK_SIZE, map::Map};
+
+/// tiles of one chunk of the target map, clipped at its border
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkPatch {
+    pub chunk: [usize; 2],
+    pub tiles: Array2<GameTile>,
+}
+
+impl ChunkPatch {
+    /// top left tile of the chunk
+    pub fn origin(&self) -> [usize; 2] {
+        [self.chunk[0] * CHUNK_SIZE, self.chunk[1] * CHUNK_SIZE]
+    }
+}
+
+/// game layer changes turning one map into another, made of the chunks that differ
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapDiff {
+    /// size of the target map
+    dim: (usize, usize),
+    patches: Vec<ChunkPatch>,
+}
+
+impl MapDiff {
+    pub fn between(from: &Map, to: &Map) -> Self {
+        Self::between_tiles(game_tiles(from), game_tiles(to))
+    }
+
+    /// chunks of `to` whose tiles differ from `from`, every chunk when their sizes differ
+    pub fn between_tiles(from: &Array2<GameTile>, to: &Array2<GameTile>) -> Self {
+        let dim = to.dim();
+        let resized = from.dim() != dim;
+
+        let mut patches = Vec::new();
+
+        for x in 0..dim.0.div_ceil(CHUNK_SIZE) {
+            for y in 0..dim.1.div_ceil(CHUNK_SIZE) {
+                let [xs, ys] = chunk_area([x, y], dim);
+                let tiles = to.slice(s![xs.clone(), ys.clone()]);
+
+                if resized || from.slice(s![xs, ys]) != tiles {
+                    patches.push(ChunkPatch {
+                        chunk: [x, y],
+                        tiles: tiles.to_owned(),
+                    });
+                }
+            }
+        }
+
+        Self { dim, patches }
+    }
+
+    pub fn dim(&self) -> (usize, usize) {
+        self.dim
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patches.is_empty()
+    }
+
+    pub fn patches(&self) -> &[ChunkPatch] {
+        &self.patches
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = [usize; 2]> + '_ {
+        self.patches.iter().map(|patch| patch.chunk)
+    }
+
+    /// writes the patches into `tiles`, which are replaced by empty ones first when their size
+    /// isn't the target size
+    pub fn apply_to(&self, tiles: &mut Array2<GameTile>) {
+        if tiles.dim() != self.dim {
+            *tiles = Array2::from_elem(self.dim, GameTile::default());
+        }
+
+        for patch in &self.patches {
+            let [xs, ys] = chunk_area(patch.chunk, self.dim);
+
+            tiles.slice_mut(s![xs, ys]).assign(&patch.tiles);
+        }
+    }
+
+    /// compact encoding to hand a diff to another process, the tiles of each patch are run-length
+    /// encoded
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        for value in [self.dim.0, self.dim.1, self.patches.len()] {
+            bytes.extend((value as u32).to_le_bytes());
+        }
+
+        for patch in &self.patches {
+            bytes.extend((patch.chunk[0] as u32).to_le_bytes());
+            bytes.extend((patch.chunk[1] as u32).to_le_bytes());
+
+            let mut tiles = patch.tiles.iter().peekable();
+
+            while let Some(tile) = tiles.next() {
+                let mut run: u16 = 1;
+
+                while tiles.next_if_eq(&tile).is_some() {
+                    run += 1;
+                }
+
+                bytes.extend(run.to_le_bytes());
+                bytes.extend([tile.id, tile.flags.bits()]);
+            }
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DiffError> {
+        let mut reader = Reader { bytes };
+
+        let dim = (reader.u32()? as usize, reader.u32()? as usize);
+        let count = reader.u32()? as usize;
+
+        let mut patches = Vec::new();
+
+        for _ in 0..count {
+            let chunk = [reader.u32()? as usize, reader.u32()? as usize];
+
+            if chunk[0] * CHUNK_SIZE >= dim.0 || chunk[1] * CHUNK_SIZE >= dim.1 {
+                return Err(DiffError::ChunkOutside(chunk));
+            }
+
+            let [xs, ys] = chunk_area(chunk, dim);
+            let shape = (xs.len(), ys.len());
+            let mut tiles = Vec::with_capacity(shape.0 * shape.1);
+
+            while tiles.len() < shape.0 * shape.1 {
+                let run = u16::from_le_bytes(reader.take()?) as usize;
+                let [id, flags] = reader.take()?;
+                let tile = GameTile::new(id, TileFlags::from_bits_truncate(flags));
+
+                tiles.extend(std::iter::repeat_n(tile, run));
+            }
+
+            let tiles =
+                Array2::from_shape_vec(shape, tiles).map_err(|_| DiffError::BadRuns(chunk))?;
+
+            patches.push(ChunkPatch { chunk, tiles });
+        }
+
+        Ok(Self { dim, patches })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffError {
+    /// the bytes end in the middle of the diff
+    Truncated,
+    /// a patch lies outside the target map
+    ChunkOutside([usize; 2]),
+    /// the runs of a patch don't add up to its tiles
+    BadRuns([usize; 2]),
+}
+
+impl fmt::Display for DiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "diff is truncated"),
+            Self::ChunkOutside([x, y]) => write!(f, "chunk {}, {} is outside the map", x, y),
+            Self::BadRuns([x, y]) => write!(f, "runs of chunk {}, {} overflow it", x, y),
+        }
+    }
+}
+
+impl Error for DiffError {}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], DiffError> {
+        let (head, rest) = self
+            .bytes
+            .split_first_chunk::<N>()
+            .ok_or(DiffError::Truncated)?;
+
+        self.bytes = rest;
+
+        Ok(*head)
+    }
+
+    fn u32(&mut self) -> Result<u32, DiffError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+}
+
+/// tile ranges of a chunk, clipped at the map border
+fn chunk_area(chunk: [usize; 2], dim: (usize, usize)) -> [Range<usize>; 2] {
+    [
+        chunk[0] * CHUNK_SIZE..((chunk[0] + 1) * CHUNK_SIZE).min(dim.0),
+        chunk[1] * CHUNK_SIZE..((chunk[1] + 1) * CHUNK_SIZE).min(dim.1),
+    ]
+}
+
+fn game_tiles(map: &Map) -> &Array2<GameTile> {
+    let game: &GameLayer = map.raw_map().find_physics_layer().unwrap();
+
+    game.tiles.unwrap_ref()
+}
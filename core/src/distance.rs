@@ -0,0 +1,163 @@
+use std::collections::VecDeque;
+
+use ndarray::{s, Array2};
+use twmap::GameTile;
+
+use crate::{map::BlockType, position::TileIndex};
+
+/// side length of the chunks tracked for changes
+pub const CHUNK_SIZE: usize = 32;
+
+/// distances are capped at this value, so an update only has to look this far around a chunk
+pub const MAX_DISTANCE: u32 = 32;
+
+/// manhattan distance of every tile to the nearest non-empty tile or the map border, kept up
+/// to date incrementally for the chunks modified since the last update
+#[derive(Debug, Clone)]
+pub struct DistanceField {
+    distances: Array2<u32>,
+    dirty: Array2<bool>,
+}
+
+impl DistanceField {
+    /// field of a `(width, height)` map, everything is dirty until the first update
+    pub fn new(dim: (usize, usize)) -> Self {
+        let chunks = (dim.0.div_ceil(CHUNK_SIZE), dim.1.div_ceil(CHUNK_SIZE));
+
+        Self {
+            distances: Array2::zeros(dim),
+            dirty: Array2::from_elem(chunks, true),
+        }
+    }
+
+    pub fn dim(&self) -> (usize, usize) {
+        self.distances.dim()
+    }
+
+    /// capped distance at `index`, 0 outside the map
+    pub fn get(&self, index: [usize; 2]) -> u32 {
+        self.distances.get(index).copied().unwrap_or(0)
+    }
+
+    /// all distances, only valid right after an update
+    pub fn distances(&self) -> &Array2<u32> {
+        &self.distances
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.iter().any(|&dirty| dirty)
+    }
+
+    /// marks the chunks overlapping the inclusive tile area from `min` to `max`
+    pub fn mark_dirty(&mut self, min: [usize; 2], max: [usize; 2]) {
+        let (width, height) = self.dirty.dim();
+
+        for x in min[0] / CHUNK_SIZE..=(max[0] / CHUNK_SIZE).min(width.saturating_sub(1)) {
+            for y in min[1] / CHUNK_SIZE..=(max[1] / CHUNK_SIZE).min(height.saturating_sub(1)) {
+                self.dirty[[x, y]] = true;
+            }
+        }
+    }
+
+    pub fn mark_all_dirty(&mut self) {
+        self.dirty.fill(true);
+    }
+
+    /// recomputes the distances around dirty chunks, `tiles` must have the field's dimensions
+    pub fn update(&mut self, tiles: &Array2<GameTile>) {
+        if !self.is_dirty() {
+            return;
+        }
+
+        if self.dirty.iter().all(|&dirty| dirty) {
+            let (width, height) = self.dim();
+
+            self.recompute(tiles, [0, 0], [width, height]);
+        } else {
+            let dirty: Vec<[usize; 2]> = self
+                .dirty
+                .indexed_iter()
+                .filter(|(_, &dirty)| dirty)
+                .map(|((x, y), _)| [x, y])
+                .collect();
+
+            for [x, y] in dirty {
+                let min = [x * CHUNK_SIZE, y * CHUNK_SIZE];
+                let max = [min[0] + CHUNK_SIZE, min[1] + CHUNK_SIZE];
+
+                self.recompute(tiles, min, max);
+            }
+        }
+
+        self.dirty.fill(false);
+    }
+
+    /// updates the tiles within `MAX_DISTANCE` of the area from `min` to the exclusive `max`
+    fn recompute(&mut self, tiles: &Array2<GameTile>, min: [usize; 2], max: [usize; 2]) {
+        let (width, height) = self.dim();
+        let reach = MAX_DISTANCE as usize;
+
+        // tiles whose value can change
+        let inner_min = [min[0].saturating_sub(reach), min[1].saturating_sub(reach)];
+        let inner_max = [(max[0] + reach).min(width), (max[1] + reach).min(height)];
+
+        // the nearest source of an inner tile is at most `MAX_DISTANCE` further out
+        let outer_min = [
+            inner_min[0].saturating_sub(reach),
+            inner_min[1].saturating_sub(reach),
+        ];
+        let outer_max = [
+            (inner_max[0] + reach).min(width),
+            (inner_max[1] + reach).min(height),
+        ];
+
+        let window = tiles.slice(s![outer_min[0]..outer_max[0], outer_min[1]..outer_max[1]]);
+        let mut local = Array2::from_elem(window.dim(), MAX_DISTANCE);
+        let mut queue = VecDeque::new();
+
+        // sources at 0 first, then the border tiles at 1 keep the queue ordered by distance
+        for ((x, y), tile) in window.indexed_iter() {
+            if tile.id != BlockType::Empty.game_id() {
+                local[[x, y]] = 0;
+                queue.push_back([x, y]);
+            }
+        }
+
+        for ((x, y), tile) in window.indexed_iter() {
+            let (map_x, map_y) = (outer_min[0] + x, outer_min[1] + y);
+            let border = map_x == 0 || map_y == 0 || map_x + 1 == width || map_y + 1 == height;
+
+            if border && tile.id == BlockType::Empty.game_id() {
+                local[[x, y]] = 1;
+                queue.push_back([x, y]);
+            }
+        }
+
+        while let Some(current) = queue.pop_front() {
+            let next_distance = local[current] + 1;
+
+            if next_distance >= MAX_DISTANCE {
+                continue;
+            }
+
+            for next in current.neighbors(local.dim(), false) {
+                if let Some(distance) = local.get_mut(next) {
+                    if next_distance < *distance {
+                        *distance = next_distance;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        let offset = [inner_min[0] - outer_min[0], inner_min[1] - outer_min[1]];
+        let size = [inner_max[0] - inner_min[0], inner_max[1] - inner_min[1]];
+
+        self.distances
+            .slice_mut(s![inner_min[0]..inner_max[0], inner_min[1]..inner_max[1]])
+            .assign(&local.slice(s![
+                offset[0]..offset[0] + size[0],
+                offset[1]..offset[1] + size[1]
+            ]));
+    }
+}
@@ -0,0 +1,128 @@
+use twmap::GameLayer;
+
+use crate::{
+    map::{BlockType, Map},
+    random::Random,
+    skips::Skip,
+};
+
+// switch layer ids as ddnet names them
+const SWITCH_FREEZE: u8 = 9;
+const SWITCH_TIMED_CLOSE: u8 = 23;
+
+/// timed doors closing some of the carved skips, the door is switchable freeze which a button
+/// on the route before the skip turns off for a while
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DoorConfig {
+    /// chance of a skip getting a door
+    pub probability: f32,
+    /// route steps between the button and the skip
+    pub button_distance: usize,
+    /// tiles the button reaches around its route position, so it can't be walked around
+    pub button_radius: usize,
+    /// seconds the door stays open once the button was touched
+    pub delay: u8,
+}
+
+impl Default for DoorConfig {
+    fn default() -> Self {
+        Self {
+            probability: 0.5,
+            button_distance: 30,
+            button_radius: 2,
+            delay: 5,
+        }
+    }
+}
+
+/// timed door placed in a skip
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Door {
+    /// switch number shared by the button and the door
+    pub number: u8,
+    /// route tile the button is centered at
+    pub button: [usize; 2],
+    pub tiles: Vec<[usize; 2]>,
+}
+
+/// closes some of the carved `skips` with timed doors, `route` is the walker's path in order,
+/// a button lands `button_distance` steps before the end of the skip the route reaches first
+pub fn place_doors(
+    map: &mut Map,
+    skips: &[Skip],
+    route: &[[usize; 2]],
+    config: &DoorConfig,
+    prng: &mut Random,
+) -> Vec<Door> {
+    let game: &GameLayer = map.raw_map().find_physics_layer().unwrap();
+    let empty = game
+        .tiles
+        .unwrap_ref()
+        .map(|tile| tile.id == BlockType::Empty.game_id());
+
+    let mut doors = Vec::new();
+
+    for skip in skips {
+        if !prng.gen_bool(config.probability) {
+            continue;
+        }
+
+        // switch numbers are a byte, 0 is reserved for tiles without a switch
+        let Ok(number) = u8::try_from(doors.len() + 1) else {
+            break;
+        };
+
+        // freeze skips already block the way
+        let tiles: Vec<[usize; 2]> = skip
+            .tiles()
+            .into_iter()
+            .filter(|&tile| empty.get(tile).copied().unwrap_or(false))
+            .collect();
+
+        let (Some(start), Some(end)) = (closest(route, skip.start), closest(route, skip.end))
+        else {
+            continue;
+        };
+
+        if tiles.is_empty() {
+            continue;
+        }
+
+        let [x, y] = route[start.min(end).saturating_sub(config.button_distance)];
+        let radius = config.button_radius;
+
+        for bx in x.saturating_sub(radius)..=x + radius {
+            for by in y.saturating_sub(radius)..=y + radius {
+                // the button must not reach into the door
+                if empty.get([bx, by]) == Some(&true) && !tiles.contains(&[bx, by]) {
+                    map.set_switch([bx, by], SWITCH_TIMED_CLOSE, number, config.delay);
+                }
+            }
+        }
+
+        for &tile in &tiles {
+            map.set_switch(tile, SWITCH_FREEZE, number, 0);
+        }
+
+        doors.push(Door {
+            number,
+            button: [x, y],
+            tiles,
+        });
+    }
+
+    doors
+}
+
+/// index of the route tile closest to `tile`
+fn closest(route: &[[usize; 2]], tile: [usize; 2]) -> Option<usize> {
+    route
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, position)| {
+            position[0].abs_diff(tile[0]).pow(2) + position[1].abs_diff(tile[1]).pow(2)
+        })
+        .map(|(i, _)| i)
+}
@@ -0,0 +1,58 @@
+use std::ops::Range;
+
+use ndarray::{s, Array2, Zip};
+use twmap::GameTile;
+
+use crate::map::BlockType;
+
+/// turns empty tiles touching a hookable tile, diagonals included, into freeze so players
+/// can't stand on hookable edges without being frozen, returns the changed tiles
+///
+/// meant for gores mode, in solid mode the whole route is lined with hookable tiles
+pub fn fix_edge_bugs(tiles: &mut Array2<GameTile>) -> Array2<bool> {
+    let (width, height) = tiles.dim();
+
+    let hookable = tiles.map(|tile| tile.id == BlockType::Hookable.game_id());
+    let mut near_hookable = Array2::from_elem((width, height), false);
+
+    // or together the hookable mask shifted to each of the eight neighbors
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            if dx == 0 && dy == 0 {
+                continue;
+            }
+
+            let (target_x, source_x) = shifted(dx, width);
+            let (target_y, source_y) = shifted(dy, height);
+
+            Zip::from(near_hookable.slice_mut(s![target_x, target_y]))
+                .and(hookable.slice(s![source_x, source_y]))
+                .for_each(|near, &hookable| *near |= hookable);
+        }
+    }
+
+    let edge_bugs = Zip::from(tiles.view())
+        .and(&near_hookable)
+        .map_collect(|tile, &near| near && tile.id == BlockType::Empty.game_id());
+
+    Zip::from(tiles)
+        .and(&edge_bugs)
+        .for_each(|tile, &edge_bug| {
+            if edge_bug {
+                *tile = BlockType::Freeze.tile();
+            }
+        });
+
+    edge_bugs
+}
+
+/// ranges of target and source indices so `target + offset = source` stays inside `len`
+fn shifted(offset: isize, len: usize) -> (Range<usize>, Range<usize>) {
+    let shift = offset.unsigned_abs().min(len);
+
+    if offset < 0 {
+        (shift..len, 0..len - shift)
+    } else {
+        (0..len - shift, shift..len)
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::VecDeque;
+
+use ndarray::Array2;
+use twmap::GameTile;
+
+use crate::{map::BlockType, position::TileIndex};
+
+/// freeze lining between the carved route and the walls around it
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct FreezeConfig {
+    /// wall tiles next to the route turned into freeze, diagonals included
+    pub thickness: usize,
+}
+
+impl Default for FreezeConfig {
+    fn default() -> Self {
+        Self { thickness: 1 }
+    }
+}
+
+/// dilates the carved area into the walls around it and fills the dilation with freeze,
+/// `thickness` holds the lining thickness at the route tiles and 0 elsewhere, the carved tiles
+/// around the route take the thickness of the closest route tile
+pub fn pad_freeze(tiles: &mut Array2<GameTile>, thickness: &Array2<usize>) {
+    let is_empty = |tile: &GameTile| tile.id == BlockType::Empty.game_id();
+    let is_wall = |tile: &GameTile| {
+        [
+            BlockType::Hookable,
+            BlockType::Unhookable,
+            BlockType::Hookthrough,
+            BlockType::Freeze,
+        ]
+        .iter()
+        .any(|block| tile.id == block.game_id())
+    };
+
+    // spread the route thickness over the carved tiles
+    let mut lining = thickness.clone();
+    let mut queue: VecDeque<[usize; 2]> = lining
+        .indexed_iter()
+        .filter(|(_, &thickness)| thickness != 0)
+        .map(|((x, y), _)| [x, y])
+        .collect();
+    let mut carved = Vec::new();
+
+    while let Some(current) = queue.pop_front() {
+        if tiles.get(current).is_some_and(is_empty) {
+            carved.push(current);
+        }
+
+        for next in current.neighbors(tiles.dim(), false) {
+            let open = tiles.get(next).is_some_and(is_empty);
+
+            if open && lining[next] == 0 {
+                lining[next] = lining[current];
+                queue.push_back(next);
+            }
+        }
+    }
+
+    // grow into the walls, `reach` holds the wall layers still laid beyond a tile
+    let mut reach = lining.map(|&lining| (lining != 0).then_some(lining));
+    let mut queue = VecDeque::from(carved);
+
+    while let Some(current) = queue.pop_front() {
+        let Some(current_reach) = reach[current].filter(|&reach| reach > 0) else {
+            continue;
+        };
+
+        for next in current.neighbors(tiles.dim(), true) {
+            let wall = tiles.get(next).is_some_and(is_wall);
+
+            // keeps the thickest lining where two segments meet
+            if !wall || reach[next].is_some_and(|reach| reach >= current_reach - 1) {
+                continue;
+            }
+
+            tiles[next] = BlockType::Freeze.tile();
+            reach[next] = Some(current_reach - 1);
+            queue.push_back(next);
+        }
+    }
+}
+
+/// turns the freeze tiles in the `width` wide square around every route position back into
+/// empty ones, returns how many were cleared
+pub fn clear_route(
+    tiles: &mut Array2<GameTile>,
+    route: impl IntoIterator<Item = [usize; 2]>,
+    width: usize,
+) -> usize {
+    let (map_width, map_height) = tiles.dim();
+    let mut cleared = 0;
+
+    for [x, y] in route {
+        let (left, top) = (x.saturating_sub(width / 2), y.saturating_sub(width / 2));
+
+        for x in left..(left + width).min(map_width) {
+            for y in top..(top + width).min(map_height) {
+                if tiles[[x, y]].id == BlockType::Freeze.game_id() {
+                    tiles[[x, y]] = BlockType::Empty.tile();
+                    cleared += 1;
+                }
+            }
+        }
+    }
+
+    cleared
+}
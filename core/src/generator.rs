@@ -1,48 +1,547 @@
-use twmap::{GameTile, TileFlags, TwMap};
+pub mod placement;
+pub mod roomgrid;
+pub mod tower;
+
+use std::{collections::VecDeque, error::Error, fmt};
+
+use ndarray::{s, Array2};
+use tracing::{debug, info, trace_span, warn};
+use twmap::{GameLayer, GameTile, TwMap};
 
 use crate::{
     brush::Brush,
-    map::Map,
-    position::{from_raw, shift_by_direction},
-    walker::Walker,
+    config::{
+        BudgetPolicy, GenerationMetadata, GenerationPreset, KernelConfig, StepPolicy, StuckPolicy,
+        WalkerConfig,
+    },
+    debug::{DebugColor, DebugLayers},
+    distance::{DistanceField, MAX_DISTANCE},
+    doors::place_doors,
+    edges::fix_edge_bugs,
+    freeze::{clear_route, pad_freeze},
+    generator::placement::{check_placement, Endpoint, PlacementIssue},
+    guidance::Guidance,
+    handle::GenerationHandle,
+    map::{BlockType, Map, ENTITY_OFFSET},
+    modifiers::ModifierEffect,
+    pathfinding::astar,
+    position::{
+        as_index, dominant_direction, euclidian, from_raw, shift_by_direction, Direction,
+        TileIndex, Vector2, VectorView2,
+    },
+    random::{Random, RandomAudit, Seed},
+    skips::generate_all_skips,
+    smoothing::smooth_route,
+    timing::{PhaseClock, Timing},
+    walker::{Walker, WalkerState},
 };
 
+/// hand-made tiles a generation starts from, the walker extends them
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    pub tiles: Array2<GameTile>,
+    /// tile the first waypoint is placed at
+    pub start: [usize; 2],
+}
+
+impl Canvas {
+    pub fn new(map: &Map, start: [usize; 2]) -> Self {
+        let game: &GameLayer = map.raw_map().find_physics_layer().unwrap();
+
+        Self {
+            tiles: game.tiles.unwrap_ref().clone(),
+            start,
+        }
+    }
+}
+
 pub struct Generator {
     walker: Walker,
     brush: Brush,
+    preset: GenerationPreset,
+    prng: Random,
+    momentum: Momentum,
+    stuck: Stuck,
+    /// step each scheduled modifier of the walker config started at
+    modifier_starts: Vec<Option<usize>>,
+    /// step of the last visit per tile, 0 for never visited tiles
+    visits: Array2<usize>,
+    /// waypoint the walker headed to on the last visit per tile, branches count as the main
+    /// route waypoint they start at
+    segments: Array2<usize>,
+    /// steps walked by the walkers before the current one
+    step_offset: usize,
+    /// distances to non-empty tiles, updated before the passes using them
+    distances: DistanceField,
+    /// tiles changed by the edge bug fix
+    edge_bugs: Array2<bool>,
+    /// layers the passes leave for debugging, in uncropped coordinates
+    debug: DebugLayers,
+    /// times the walker brush covered each tile, only counted while its debug layer is enabled
+    touches: Array2<u32>,
+    /// top left corner of the exported area
+    crop_offset: [usize; 2],
+    /// space around the waypoints, larger than the preset one when a canvas didn't fit
+    margin: usize,
+    /// positions the walker reached the main route waypoints at
+    route_positions: Vec<Vector2>,
+    /// segments of the main route, the lap back to the spawn counts as one when looped
+    route_segments: usize,
+    /// segments regenerated since the last generation, each gets a fresh random stream
+    rerolls: u64,
+    guide: Guide,
+    canvas: Option<Canvas>,
+    /// uncropped map of the last generation, its locked areas are kept by the next one
+    previous: Option<Map>,
+    /// phase timings of the last generation
+    timing: Timing,
     before_step: Option<Box<dyn FnMut(&mut Walker, &mut Map, &mut Brush)>>,
 }
 
 impl Generator {
     pub fn new() -> Self {
+        Self::from_preset(GenerationPreset::default())
+    }
+
+    pub fn from_preset(preset: GenerationPreset) -> Self {
+        let preset = Self::laid_out(preset);
+
         Self {
-            walker: Walker::new(1.0),
-            brush: Brush::new(),
+            walker: Walker::new(preset.waypoints.scale_factor),
+            brush: Self::initial_brush(&preset.kernel, preset.post_process.min_clear_width),
+            preset,
+            prng: Random::default(),
+            momentum: Momentum::default(),
+            stuck: Stuck::default(),
+            modifier_starts: Vec::new(),
+            visits: Array2::zeros((0, 0)),
+            segments: Array2::zeros((0, 0)),
+            step_offset: 0,
+            distances: DistanceField::new((0, 0)),
+            edge_bugs: Array2::from_elem((0, 0), false),
+            debug: DebugLayers::default(),
+            touches: Array2::zeros((0, 0)),
+            crop_offset: [0, 0],
+            margin: 0,
+            route_positions: Vec::new(),
+            route_segments: 0,
+            rerolls: 0,
+            guide: Guide::default(),
+            canvas: None,
+            previous: None,
+            timing: Timing::default(),
             before_step: None,
         }
     }
 
+    /// brush of the kernel, at least `min_size` wide so the route keeps its clear width
+    fn initial_brush(kernel: &KernelConfig, min_size: usize) -> Brush {
+        Brush::circular(kernel.size.max(min_size).max(1), kernel.circularity)
+    }
+
+    /// `preset` with the waypoints of its tower, if it has one
+    fn laid_out(mut preset: GenerationPreset) -> GenerationPreset {
+        if let Some(tower) = preset.tower.clone() {
+            tower.apply(&mut preset);
+        }
+
+        preset
+    }
+
+    pub fn set_preset(&mut self, preset: GenerationPreset) {
+        self.preset = Self::laid_out(preset);
+    }
+
+    pub fn preset(&self) -> &GenerationPreset {
+        &self.preset
+    }
+
+    /// distances to the nearest non-empty tile of the last generated map
+    pub fn distance_field(&self) -> &DistanceField {
+        &self.distances
+    }
+
+    /// step of the walker's last visit per tile of the last generated map, 0 for tiles off the
+    /// walker path
+    pub fn visits(&self) -> &Array2<usize> {
+        &self.visits
+    }
+
+    /// positions the walker reached the main route waypoints at during the last generation,
+    /// uncropped
+    pub fn route_positions(&self) -> &[Vector2] {
+        &self.route_positions
+    }
+
+    /// time the last generation spent in each phase
+    pub fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
+    /// tiles of the last generated map turned into freeze by the edge bug fix
+    pub fn edge_bugs(&self) -> &Array2<bool> {
+        &self.edge_bugs
+    }
+
+    pub fn debug_layers(&self) -> &DebugLayers {
+        &self.debug
+    }
+
+    pub fn debug_layers_mut(&mut self) -> &mut DebugLayers {
+        &mut self.debug
+    }
+
+    /// position of the exported map's top left tile, positions reported during generation
+    /// are shifted by it when the map is cropped
+    pub fn crop_offset(&self) -> [usize; 2] {
+        self.crop_offset
+    }
+
+    /// tile of the exported last generated map a normalized waypoint lies on
+    pub fn waypoint_tile(&self, waypoint: (f32, f32)) -> [f32; 2] {
+        let scale_factor = self.preset.waypoints.scale_factor;
+        let shift = |offset: usize| self.margin as f32 - offset as f32;
+
+        [
+            waypoint.0 * scale_factor + shift(self.crop_offset[0]),
+            waypoint.1 * scale_factor + shift(self.crop_offset[1]),
+        ]
+    }
+
+    /// normalized waypoint at a tile of the exported last generated map, the inverse of
+    /// `waypoint_tile`
+    pub fn tile_waypoint(&self, tile: [f32; 2]) -> (f32, f32) {
+        let scale_factor = self.preset.waypoints.scale_factor;
+        let shift = |offset: usize| self.margin as f32 - offset as f32;
+
+        (
+            (tile[0] - shift(self.crop_offset[0])) / scale_factor,
+            (tile[1] - shift(self.crop_offset[1])) / scale_factor,
+        )
+    }
+
+    /// what speaks against placing `endpoint` at a tile of the exported last generated map
+    pub fn placement_issues(&self, endpoint: Endpoint, tile: [f32; 2]) -> Vec<PlacementIssue> {
+        check_placement(
+            &self.preset,
+            self.margin.max(self.preset.waypoints.margin),
+            endpoint,
+            self.tile_waypoint(tile),
+            self.previous.as_ref(),
+        )
+    }
+
+    /// tiles stamped under the first waypoint before walking, the map grows to fit them
+    pub fn set_canvas(&mut self, canvas: Option<Canvas>) {
+        self.canvas = canvas;
+    }
+
+    pub fn canvas(&self) -> Option<&Canvas> {
+        self.canvas.as_ref()
+    }
+
+    /// uncropped map of the last generation, add the crop offset to exported positions
+    pub fn previous_map(&self) -> Option<&Map> {
+        self.previous.as_ref()
+    }
+
+    /// locks an area of the last generated map so the next generations keep its game tiles
+    /// as long as the map size stays the same, positions are uncropped
+    pub fn lock_area(&mut self, min: [usize; 2], max: [usize; 2]) {
+        if let Some(previous) = &mut self.previous {
+            previous.lock_area(min, max);
+        }
+    }
+
+    pub fn unlock_area(&mut self, min: [usize; 2], max: [usize; 2]) {
+        if let Some(previous) = &mut self.previous {
+            previous.unlock_area(min, max);
+        }
+    }
+
+    /// walks the main route segment between waypoints `segment` and `segment + 1` again with a
+    /// fresh random stream, keeping the rest of the last generated map, `None` before the first
+    /// generation or for segments the main route doesn't have
+    pub fn regenerate_segment(&mut self, segment: usize) -> Option<TwMap> {
+        let start = self.route_positions.get(segment)?.clone();
+        let end = self.route_positions.get(segment + 1)?.clone();
+        let previous = self.previous.clone()?;
+
+        let mut map = previous.clone();
+        self.erase_segment(&mut map, segment + 1);
+
+        let scale_factor = self.preset.waypoints.scale_factor;
+        let margin = self.margin as f32;
+        let normalize = |position: &Vector2| {
+            (
+                (position[[0]] - margin) / scale_factor,
+                (position[[1]] - margin) / scale_factor,
+            )
+        };
+
+        // the walker halts once it heads to the last waypoint, so the end goes in twice
+        let waypoints = vec![normalize(&start), normalize(&end), normalize(&end)];
+
+        let seed = self.prng.seed();
+        self.rerolls += 1;
+        self.prng = self.prng.fork(((segment as u64) << 32) | self.rerolls);
+
+        self.step_offset = self.visits.iter().copied().max().unwrap_or(0);
+        self.walker.reset();
+        self.walker.set_waypoints(waypoints);
+        self.momentum = Momentum::default();
+        self.stuck = Stuck::default();
+        self.guide = Guide::default();
+
+        let zone = self.preset.zone_at(segment + 1);
+        self.enter_zone(zone, Some(segment + 1));
+        self.before_step(&mut map, start.view());
+
+        let mut state = GenerationState {
+            map: Some(map),
+            failed: None,
+            truncated: false,
+            current_pos: start,
+            spawn_pos: self.route_positions[0].clone(),
+            zone,
+            branch: None,
+            finishes: Vec::new(),
+            route: Vec::new(),
+            trunk: Vec::new(),
+            previous: Some(previous),
+            restored: GenerationPhase::Walking,
+            rejoin: Some(end),
+            waypoint_offset: segment,
+            grid_rooms: Vec::new(),
+            room_sites: Vec::new(),
+            phase: GenerationPhase::Walking,
+            pending: VecDeque::new(),
+            kernel: self.brush.dim(),
+            clock: PhaseClock::default(),
+        };
+
+        let mut result = None;
+
+        self.timing = Timing::default();
+
+        while let Some(step) = self.advance_timed(&mut state) {
+            if let StepResult::Finished(map) = step {
+                result = Some(map);
+            }
+        }
+
+        self.prng.reseed(seed);
+
+        result
+    }
+
+    pub fn preset_mut(&mut self) -> &mut GenerationPreset {
+        &mut self.preset
+    }
+
     pub fn set_scale_factor(&mut self, scale_factor: f32) {
-        self.walker.set_scale_factor(scale_factor);
+        self.preset.waypoints.scale_factor = scale_factor;
     }
 
     pub fn get_scale_factor(&self) -> f32 {
-        self.walker.get_scale_factor()
+        self.preset.waypoints.scale_factor
+    }
+
+    /// seeds the default step policy used when no step callback is set
+    pub fn set_seed(&mut self, seed: Seed) {
+        self.prng.reseed(seed);
+    }
+
+    /// records the random draws of the next generations per call site, to find where two runs
+    /// that should match diverge
+    pub fn set_rng_audit(&mut self, enabled: bool) {
+        self.prng.set_audit(enabled);
+    }
+
+    /// draws of the last generation, segments regenerated since are audited separately
+    pub fn rng_audit(&self) -> Option<&RandomAudit> {
+        self.prng.audit()
+    }
+
+    /// metadata stamped into the next generated map
+    pub fn metadata(&self) -> GenerationMetadata {
+        GenerationMetadata::new(self.prng.seed(), &self.preset)
     }
 
     pub fn on_step(&mut self, func: impl FnMut(&mut Walker, &mut Map, &mut Brush) + 'static) {
         self.before_step = Some(Box::new(func));
     }
 
-    pub fn generate(&mut self, waypoints: Vec<(f32, f32)>) -> TwMap {
+    /// panics when the generation fails, for presets known to generate like the bundled ones,
+    /// see [`Generator::try_generate_observed`] for everything else
+    pub fn generate(&mut self) -> TwMap {
+        self.generate_observed(|_| {})
+    }
+
+    /// generates while passing every event except the finished map to `observer`, panics when
+    /// the generation fails, see [`Generator::try_generate_observed`]
+    pub fn generate_observed(&mut self, observer: impl FnMut(&StepResult)) -> TwMap {
+        self.try_generate_observed(observer)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// same as [`Generator::generate_observed`], but returns the error instead of panicking
+    pub fn try_generate_observed(
+        &mut self,
+        observer: impl FnMut(&StepResult),
+    ) -> Result<TwMap, GenerationError> {
+        self.run(&GenerationHandle::new(), observer)
+    }
+
+    /// generates while reporting progress to `handle` and checking it for cancellation every step
+    pub fn generate_with_handle(
+        &mut self,
+        handle: &GenerationHandle,
+    ) -> Result<TwMap, GenerationError> {
+        self.run(handle, |_| {})
+    }
+
+    fn run(
+        &mut self,
+        handle: &GenerationHandle,
+        mut observer: impl FnMut(&StepResult),
+    ) -> Result<TwMap, GenerationError> {
+        let mut state = self.begin()?;
+
+        let route_length = self
+            .preset
+            .waypoints
+            .branches
+            .iter()
+            .map(|branch| branch.waypoints.len())
+            .sum::<usize>()
+            + state.trunk.len().saturating_sub(1);
+        let route_length = route_length.max(1);
+        let mut reached = 0;
+
+        handle.report(0.0, state.phase.name());
+
+        while let Some(result) = self.advance_timed(&mut state) {
+            if handle.is_cancelled() {
+                self.walker.reset();
+                self.previous = state.previous.take();
+
+                return Err(GenerationError::Cancelled);
+            }
+
+            match result {
+                StepResult::WaypointReached { .. } => reached += 1,
+                StepResult::Finished(map) => {
+                    handle.report(1.0, state.phase.name());
+
+                    return Ok(map);
+                }
+                _ => {}
+            }
+
+            observer(&result);
+
+            handle.report(reached as f32 / route_length as f32, state.phase.name());
+        }
+
+        self.walker.reset();
+        self.previous = state.previous.take();
+
+        Err(state
+            .failed
+            .expect("generation finishes with a map unless it fails"))
+    }
+
+    /// turns the generator into an iterator over single generation steps, a generation failing
+    /// to start has none, see [`GenerationSteps::error`]
+    pub fn into_steps(mut self, preset: GenerationPreset) -> GenerationSteps {
+        self.preset = Self::laid_out(preset);
+
+        let state = self.begin().unwrap_or_else(GenerationState::failed);
+
+        GenerationSteps {
+            generator: self,
+            state,
+        }
+    }
+
+    /// fails without touching the last generation when the route is too short to walk
+    fn begin(&mut self) -> Result<GenerationState, GenerationError> {
         // prepare canvas
         let mut map = Map::new();
+        map.set_info(&self.preset.export);
+
+        if self.preset.export.stamp_metadata {
+            map.stamp_metadata(&self.metadata());
+        }
+
+        self.prng.reset();
+        self.timing = Timing::default();
+
+        info!(preset = %self.preset.name, seed = self.prng.seed(), "generation started");
+
+        let grid = self
+            .preset
+            .room_grid
+            .as_ref()
+            .map(|config| config.generate(&mut self.prng));
+
+        let mut waypoints = match &grid {
+            Some(grid) => grid.waypoints(),
+            None => self.preset.waypoints.waypoints.clone(),
+        };
+
+        if let Some(jitter) = &self.preset.waypoints.jitter {
+            let scale_factor = self.preset.waypoints.scale_factor;
+
+            jitter.apply(&mut waypoints, scale_factor, &mut self.prng);
+        }
+
+        let count = waypoints.len();
+
+        if count < 2 {
+            warn!(count, "route too short, generation failed");
+
+            return Err(GenerationError::RouteTooShort { waypoints: count });
+        }
+
+        self.route_segments = match self.preset.waypoints.looped {
+            true => waypoints.len(),
+            false => waypoints.len().saturating_sub(1),
+        };
+
+        if self.preset.waypoints.looped && !waypoints.is_empty() {
+            // the walker halts once it heads to the last waypoint, so the spawn goes in twice
+            waypoints.extend([waypoints[0], waypoints[0]]);
+        }
+        let scale_factor = self.preset.waypoints.scale_factor;
+        let first = from_raw(waypoints[0], scale_factor);
+
+        // the margin grows until the canvas fits left of and above the first waypoint
+        let margin = self.canvas.as_ref().map_or(0, |canvas| {
+            let x = canvas.start[0].saturating_sub(first[[0]] as usize);
+            let y = canvas.start[1].saturating_sub(first[[1]] as usize);
+
+            x.max(y)
+        });
+        let margin = margin.max(self.preset.waypoints.margin);
+
+        // tools might be dirty from a previously cancelled generation
+        self.walker.reset();
+        self.walker
+            .set_scale_factor(scale_factor)
+            .set_margin(margin as f32);
 
-        let scale_factor = self.walker.get_scale_factor();
+        let zone = self.preset.zone_at(0);
+        self.enter_zone(zone, Some(0));
 
         // 1. calculate bounds and enlarge them to let walker freely... walk
         let mut freaky_waypoints = waypoints.clone();
 
+        for branch in self.preset.waypoints.branches.iter() {
+            freaky_waypoints.extend(branch.waypoints.iter().copied());
+        }
+
         freaky_waypoints.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
 
         let normal_width = freaky_waypoints.last().unwrap().0 - freaky_waypoints.first().unwrap().0;
@@ -56,40 +555,2019 @@ impl Generator {
         let approx_height = normal_height * scale_factor;
 
         // 2. create map with enlarged bounds
-        map.reshape(approx_width as usize + 400, approx_height as usize + 400);
-        map.fill_game(GameTile::new(1, TileFlags::empty()));
+        let mut width = approx_width as usize + 2 * margin;
+        let mut height = approx_height as usize + 2 * margin;
+
+        let canvas_origin = self.canvas.as_ref().map(|canvas| {
+            let origin = [
+                first[[0]] as usize + margin - canvas.start[0],
+                first[[1]] as usize + margin - canvas.start[1],
+            ];
+            let (canvas_width, canvas_height) = canvas.tiles.dim();
+
+            width = width.max(origin[0] + canvas_width);
+            height = height.max(origin[1] + canvas_height);
+
+            origin
+        });
+
+        map.reshape(width, height);
+        self.visits = Array2::zeros((width, height));
+        self.segments = Array2::zeros((width, height));
+        self.step_offset = 0;
+        self.distances = DistanceField::new((width, height));
+        self.edge_bugs = Array2::from_elem((width, height), false);
+        self.debug.reset((width, height));
+        self.touches = Array2::zeros((width, height));
+        self.crop_offset = [0, 0];
+        self.margin = margin;
+        self.route_positions.clear();
+        self.rerolls = 0;
+        map.fill_game(self.preset.mode.fill_block().tile());
+
+        if let (Some(canvas), Some([x, y])) = (&self.canvas, canvas_origin) {
+            let (canvas_width, canvas_height) = canvas.tiles.dim();
+
+            map.game_layer()
+                .tiles
+                .unwrap_mut()
+                .slice_mut(s![x..x + canvas_width, y..y + canvas_height])
+                .assign(&canvas.tiles);
+        }
+
+        let previous = self.previous.take();
+
+        if let Some(previous) = &previous {
+            map.restore_locked(previous);
+        }
 
         // 3. setup initial position
-        let mut current_pos = from_raw(waypoints[0], scale_factor);
-        current_pos[[0]] += 200.0;
-        current_pos[[1]] += 200.0;
+        let mut current_pos = first;
+        current_pos[[0]] += margin as f32;
+        current_pos[[1]] += margin as f32;
+
+        let spawn_pos = current_pos.clone();
+
+        let grid_rooms = grid
+            .map(|grid| {
+                grid.rooms
+                    .iter()
+                    .map(|&cell| {
+                        let mut position = from_raw(grid.cell_center(cell), scale_factor);
+                        position[[0]] += margin as f32;
+                        position[[1]] += margin as f32;
+
+                        as_index(position.view())
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        self.walker.set_waypoints(waypoints.clone());
+
+        self.momentum = Momentum::default();
+        self.stuck = Stuck::default();
+        self.guide = Guide::default();
+        self.before_step(&mut map, current_pos.view());
+
+        let mut pending = VecDeque::new();
+
+        if zone.is_some() {
+            pending.push_back(StepResult::ZoneEntered {
+                zone,
+                position: current_pos.clone(),
+            });
+        }
+
+        Ok(GenerationState {
+            map: Some(map),
+            failed: None,
+            truncated: false,
+            current_pos,
+            spawn_pos,
+            zone,
+            branch: None,
+            finishes: Vec::new(),
+            route: Vec::new(),
+            trunk: waypoints,
+            previous,
+            restored: GenerationPhase::Walking,
+            rejoin: None,
+            waypoint_offset: 0,
+            grid_rooms,
+            room_sites: Vec::new(),
+            phase: GenerationPhase::Walking,
+            pending,
+            kernel: self.brush.dim(),
+            clock: PhaseClock::default(),
+        })
+    }
+
+    /// drags the brush in unit steps from `from` to `to`
+    fn carve_line(&self, map: &mut Map, from: Vector2, to: Vector2) {
+        let tiles = map.game_layer().tiles.unwrap_mut();
+        let steps = euclidian(from.view(), to.view()).ceil() as usize;
+
+        for step in 0..=steps {
+            let t = step as f32 / steps.max(1) as f32;
+            let position = &from + &((&to - &from) * t);
 
+            self.brush
+                .apply(tiles, position.mapv(f32::round), BlockType::Empty.tile());
+        }
+    }
+
+    /// moves the walker to the route tile closest to the branch point and lets it walk the
+    /// waypoints of `branch`
+    fn start_branch(&mut self, state: &mut GenerationState, branch: usize) -> Option<StepResult> {
+        let config = self.preset.waypoints.branches[branch].clone();
+        let scale_factor = self.preset.waypoints.scale_factor;
+        let margin = self.margin as f32;
+
+        let trunk = &state.trunk;
+        let from = config.from.min(trunk.len().saturating_sub(1));
+
+        let mut anchor = from_raw(trunk[from], scale_factor);
+        anchor[[0]] += margin;
+        anchor[[1]] += margin;
+
+        let start = self
+            .visits
+            .indexed_iter()
+            .filter(|(_, &visit)| visit != 0)
+            .map(|((x, y), _)| Vector2::from(vec![x as f32, y as f32]))
+            .min_by(|a, b| {
+                let a = euclidian(a.view(), anchor.view());
+                let b = euclidian(b.view(), anchor.view());
+
+                a.partial_cmp(&b).unwrap()
+            })
+            .unwrap_or(anchor);
+
+        let mut waypoints = vec![(
+            (start[[0]] - margin) / scale_factor,
+            (start[[1]] - margin) / scale_factor,
+        )];
+        waypoints.extend(config.waypoints.iter().copied());
+
+        self.step_offset = self.current_step();
+        self.walker.reset();
         self.walker.set_waypoints(waypoints);
+        self.momentum = Momentum::default();
+        self.stuck = Stuck::default();
+        self.guide = Guide::default();
+
+        let zone = self.preset.zone_at(from);
+
+        // drops the overrides of the last main route waypoint
+        if zone != state.zone || !self.preset.waypoints.overrides.is_empty() {
+            self.enter_zone(zone, None);
+            state.zone = zone;
+        }
+
+        state.branch = Some(branch);
+        state.current_pos = start;
+
+        let map = state.map.as_mut().unwrap();
+        self.before_step(map, state.current_pos.view());
+
+        Some(StepResult::BranchStarted {
+            branch,
+            position: state.current_pos.clone(),
+        })
+    }
+
+    /// fills the tiles closest to the visits of the walker heading to `waypoint`, which are the
+    /// carved tiles of the segment before it and whatever the passes placed around them, spawn,
+    /// start and finish tiles are kept
+    fn erase_segment(&mut self, map: &mut Map, waypoint: usize) {
+        let fill = self.preset.mode.fill_block();
+        let tiles = map.game_layer().tiles.unwrap_mut();
+
+        // every placed tile belongs to the segment of the closest visit
+        let mut owners: Array2<Option<usize>> = Array2::from_elem(tiles.dim(), None);
+        let mut queue = VecDeque::new();
+
+        for ((x, y), &visit) in self.visits.indexed_iter() {
+            if visit != 0 {
+                owners[[x, y]] = Some(self.segments[[x, y]]);
+                queue.push_back([x, y]);
+            }
+        }
+
+        while let Some(current) = queue.pop_front() {
+            let owner = owners[current];
+
+            for next in current.neighbors(tiles.dim(), false) {
+                let placed = tiles
+                    .get(next)
+                    .is_some_and(|tile| tile.id != fill.game_id());
+
+                if placed && owners[next].is_none() {
+                    owners[next] = owner;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let kept = |tile: &GameTile| {
+            tile.id >= ENTITY_OFFSET
+                || tile.id == BlockType::Start.game_id()
+                || tile.id == BlockType::Finish.game_id()
+        };
+
+        for ((index, tile), &owner) in tiles.indexed_iter_mut().zip(owners.iter()) {
+            if owner != Some(waypoint) || kept(tile) {
+                continue;
+            }
+
+            *tile = fill.tile();
+            self.visits[index] = 0;
+            self.segments[index] = 0;
+        }
 
+        self.distances.mark_all_dirty();
+    }
+
+    /// swaps walker config and brush for the ones of `zone`, or the preset ones outside zones,
+    /// with the overrides of the main route `waypoint` on top
+    fn enter_zone(&mut self, zone: Option<usize>, waypoint: Option<usize>) {
+        let zone = zone.map(|zone| &self.preset.zones[zone]);
+
+        let mut walker = zone
+            .and_then(|zone| zone.walker.clone())
+            .unwrap_or_else(|| self.preset.walker.clone());
+        let mut kernel = zone
+            .and_then(|zone| zone.kernel.clone())
+            .unwrap_or_else(|| self.preset.kernel.clone());
+
+        let waypoints = &self.preset.waypoints;
+
+        if let Some(local) = waypoint.and_then(|waypoint| waypoints.override_at(waypoint)) {
+            local.apply(&mut walker, &mut kernel);
+        }
+
+        self.walker.set_config(walker);
+        self.brush = Self::initial_brush(&kernel, self.preset.post_process.min_clear_width);
+        self.modifier_starts.clear();
+    }
+
+    /// effects of the scheduled modifiers active at this step and their strength, starts the
+    /// ones whose trigger fired
+    fn active_modifiers(
+        &mut self,
+        pos: VectorView2,
+        spawn: VectorView2,
+        waypoint: usize,
+    ) -> Vec<(ModifierEffect, f32)> {
+        let step = self.current_step();
+        let distance = euclidian(pos, spawn);
+        let modifiers = &self.walker.get_config().modifiers;
+
+        self.modifier_starts.resize(modifiers.len(), None);
+
+        modifiers
+            .iter()
+            .zip(self.modifier_starts.iter_mut())
+            .filter_map(|(modifier, start)| {
+                if start.is_none() && modifier.trigger.fired(step, distance, waypoint) {
+                    *start = Some(step);
+                }
+
+                Some((modifier.effect, modifier.strength((*start)?, step)?))
+            })
+            .collect()
+    }
+
+    /// share of the main route already walked, the part of the current segment counts by how
+    /// much closer the walker got to its waypoint, branches stay at the progress of their branch
+    /// point, `offset` is the main route index of the walker's first waypoint
+    fn route_progress(&self, pos: VectorView2, branch: Option<usize>, offset: usize) -> f32 {
+        let segments = self.route_segments.max(1) as f32;
+
+        if let Some(branch) = branch {
+            let from = self.preset.waypoints.branches[branch].from;
+
+            return (from as f32 / segments).min(1.0);
+        }
+
+        let heading = self.walker.preferred_state().waypoint;
+
+        let (Some(from), Some(to)) = (
+            heading
+                .checked_sub(1)
+                .and_then(|i| self.walker.waypoint_position(i)),
+            self.walker.waypoint_position(heading),
+        ) else {
+            return (offset as f32 / segments).min(1.0);
+        };
+
+        // a waypoint given twice only halts the walker
+        let length = euclidian(from.view(), to.view());
+        let walked = if length > 0.0 {
+            (1.0 - euclidian(pos, to.view()) / length).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        ((heading - 1 + offset) as f32 + walked) / segments
+    }
+
+    /// scales the brush by the product of the active scaling modifiers and the kernel profile at
+    /// `progress`, left alone when the walker config has neither so brush mutations keep working
+    fn scale_brush(&mut self, active: &[(ModifierEffect, f32)], progress: f32) {
+        let config = self.walker.get_config();
+        let escape = match config.stuck_policy {
+            StuckPolicy::Enlarge { scale, .. } if self.stuck.escape > 0 => Some(scale),
+            _ => None,
+        };
+
+        // an ended escape still has to reset the brush
+        if !config.modifiers.iter().any(|m| m.effect.scales_brush())
+            && config.kernel_profile.is_empty()
+            && escape.is_none()
+            && !self.stuck.scaled
+        {
+            return;
+        }
+
+        self.stuck.scaled = escape.is_some();
+
+        let scale: f32 = active
+            .iter()
+            .map(|(effect, strength)| effect.scale(*strength))
+            .product::<f32>()
+            * config.kernel_scale(progress)
+            * escape.unwrap_or(1.0);
+
+        self.brush.reset_scale();
+
+        if scale != 1.0 {
+            let (width, height) = self.brush.dim();
+
+            // never below a single tile, rounding down mustn't leave an empty brush either
+            let scale = scale.max(1.0 / width.min(height) as f32) + f32::EPSILON;
+            self.brush.apply_scale(scale);
+        }
+    }
+
+    /// jitter and freeze bursts of the active modifiers around the brush at `pos`
+    fn apply_modifiers(
+        &mut self,
+        map: &mut Map,
+        pos: VectorView2,
+        active: &[(ModifierEffect, f32)],
+    ) {
+        let tiles = map.game_layer().tiles.unwrap_mut();
+        let (map_width, map_height) = tiles.dim();
+        let (width, height) = self.brush.dim();
+        let [x, y] = as_index(pos);
+
+        for &(effect, _) in active {
+            match effect {
+                // a brush larger than the map has nowhere to be moved to
+                ModifierEffect::Jitter { amount }
+                    if amount > 0 && width <= map_width && height <= map_height =>
+                {
+                    let amount = amount as isize;
+                    let offset = [
+                        self.prng.in_range(-amount..=amount),
+                        self.prng.in_range(-amount..=amount),
+                    ];
+
+                    // keeps the whole brush inside the map
+                    let [x, y] = [x, y].saturating_offset(offset);
+                    let x = x.clamp(width / 2, map_width - (width - width / 2));
+                    let y = y.clamp(height / 2, map_height - (height - height / 2));
+
+                    let jittered = Vector2::from(vec![x as f32, y as f32]);
+                    self.brush
+                        .apply(tiles, jittered.clone(), BlockType::Empty.tile());
+
+                    if self.debug.is_enabled(TOUCHES_LAYER) {
+                        self.brush.accumulate(&mut self.touches, jittered);
+                    }
+
+                    let reach = width.max(height) / 2 + 1;
+                    self.distances.mark_dirty(
+                        [x.saturating_sub(reach), y.saturating_sub(reach)],
+                        [x + reach, y + reach],
+                    );
+                }
+                ModifierEffect::FreezeBurst { thickness } => {
+                    let (left, top) = (
+                        x.saturating_sub(width / 2 + thickness),
+                        y.saturating_sub(height / 2 + thickness),
+                    );
+                    let (right, bottom) = (
+                        (x + width - width / 2 + thickness).min(map_width),
+                        (y + height - height / 2 + thickness).min(map_height),
+                    );
+
+                    for tile in tiles.slice_mut(s![left..right, top..bottom]) {
+                        let wall = [
+                            BlockType::Hookable,
+                            BlockType::Unhookable,
+                            BlockType::Hookthrough,
+                        ]
+                        .iter()
+                        .any(|block| tile.id == block.game_id());
+
+                        if wall {
+                            *tile = BlockType::Freeze.tile();
+                        }
+                    }
+
+                    self.distances.mark_dirty([left, top], [right, bottom]);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn before_step(&mut self, map: &mut Map, pos: VectorView2) {
         if let Some(ref mut on_step) = &mut self.before_step {
-            on_step(&mut self.walker, &mut map, &mut self.brush);
+            on_step(&mut self.walker, map, &mut self.brush);
+        } else {
+            if self.walker.get_config().openness_bias != 0.0 {
+                self.distances.update(map.game_layer().tiles.unwrap_mut());
+            }
+
+            self.default_step(pos);
+        }
+    }
+
+    /// follows the preferred direction, randomly deviating from it as far as the momentum
+    /// constraints allow
+    fn default_step(&mut self, pos: VectorView2) {
+        let preferred = *self.walker.preferred_state();
+        let config = self.walker.get_config().clone();
+
+        if self.stuck.escape > 0 && matches!(config.stuck_policy, StuckPolicy::Greedy { .. }) {
+            self.momentum.record(preferred.direction);
+            self.walker
+                .set_next_direction(preferred.direction)
+                .set_next_waypoint(preferred.waypoint);
+
+            return;
+        }
+
+        let softmax = config.step_policy == StepPolicy::Softmax;
+
+        let guided = match config.step_policy {
+            StepPolicy::Greedy | StepPolicy::Softmax => None,
+            StepPolicy::Guided => self.guided_direction(pos, preferred.waypoint, &config),
+        };
+
+        let mut direction = match guided {
+            Some(guided) if self.prng.gen_bool(config.guide_bias) => guided,
+            _ if softmax => self.softmax_direction(pos, preferred, &config),
+            _ if self.prng.gen_bool(config.randomness) => {
+                Direction::from(self.prng.in_range(0..directions(&config)))
+            }
+            _ => preferred.direction,
+        };
+
+        // the softmax scores already weigh the openness
+        if !softmax && config.openness_bias != 0.0 && self.prng.gen_bool(config.openness_bias.abs())
+        {
+            direction = self.openness_direction(pos, preferred.direction, config.openness_bias);
         }
 
-        // loop thru generation
-        while self.walker.step(current_pos.view()) != 0 {
-            if let Some(ref mut on_step) = &mut self.before_step {
-                on_step(&mut self.walker, &mut map, &mut self.brush);
+        if let Some(current) = self.momentum.direction {
+            // a long straight run could overshoot the waypoint forever
+            let near_waypoint = self
+                .walker
+                .waypoint_position(preferred.waypoint)
+                .is_some_and(|waypoint| {
+                    euclidian(waypoint.view(), pos)
+                        <= config.min_straight_run as f32 + config.reach_distance
+                });
+
+            if direction != current && !near_waypoint && !self.momentum.can_turn(&config) {
+                direction = current;
             }
+        }
 
-            shift_by_direction(&mut current_pos, 1.0, self.walker.current_state().direction);
+        if config.backtrack_penalty > 0.0 {
+            direction = self.avoid_backtracking(direction, preferred.direction, pos, &config);
+        }
+
+        direction = self.mask_direction(pos, direction);
 
-            self.brush.apply(
-                map.game_layer().tiles.unwrap_mut(),
-                current_pos.clone(),
-                GameTile::new(0, TileFlags::empty()),
-            );
+        // long shafts can't be climbed without something to hook or stand on
+        if config.max_climb > 0
+            && direction == Direction::Up
+            && self.momentum.climb() >= config.max_climb
+        {
+            direction = match preferred.direction {
+                Direction::Left | Direction::Right => preferred.direction,
+                _ if self.prng.gen_bool(0.5) => Direction::Left,
+                _ => Direction::Right,
+            };
         }
 
-        // reset our tools
-        self.walker.reset();
-        self.brush = Brush::new();
+        self.momentum.record(direction);
+
+        self.walker
+            .set_next_direction(direction)
+            .set_next_waypoint(preferred.waypoint);
+    }
+
+    /// keeps the walker out of the forbidden cells of the guidance mask and turns it towards
+    /// preferred cells it would pass by, walks through forbidden cells when boxed in by them
+    fn mask_direction(&mut self, pos: VectorView2, direction: Direction) -> Direction {
+        let Some(mask) = &self.preset.guidance_mask else {
+            return direction;
+        };
+
+        if mask.is_empty() {
+            return direction;
+        }
+
+        // looks at the cell the brush reaches into, not only the one the walker steps on
+        let (width, height) = self.brush.dim();
+        let probe = (width.max(height) / 2 + 1) as f32;
+        let margin = self.margin as f32;
+
+        let guidance = |direction: Direction| {
+            let mut ahead = pos.to_owned();
+            shift_by_direction(&mut ahead, probe, direction);
+
+            mask.at([ahead[[0]] - margin, ahead[[1]] - margin])
+        };
+
+        let back = direction.next().next();
+        let allowed: Vec<Direction> = [direction, direction.next(), direction.prev(), back]
+            .into_iter()
+            .filter(|&direction| guidance(direction) != Some(Guidance::Forbidden))
+            .collect();
+
+        let Some(&first) = allowed.first() else {
+            return direction;
+        };
+
+        if guidance(first) == Some(Guidance::Preferred) {
+            return first;
+        }
+
+        // turning around for a preferred cell would undo the walk
+        let preferred = allowed.iter().copied().find(|&direction| {
+            direction != back && guidance(direction) == Some(Guidance::Preferred)
+        });
+
+        match preferred {
+            Some(preferred) if self.prng.gen_bool(mask.attraction) => preferred,
+            _ => first,
+        }
+    }
+
+    /// direction along the planned path, plans again when the path is outdated or was left
+    fn guided_direction(
+        &mut self,
+        pos: VectorView2,
+        waypoint: usize,
+        config: &WalkerConfig,
+    ) -> Option<Direction> {
+        let current = as_index(pos);
+
+        let outdated = self.guide.waypoint != waypoint
+            || self.guide.age >= config.guide_interval.max(1)
+            || self.guide.next_after(current).is_none();
+
+        if outdated {
+            let goal = as_index(self.walker.waypoint_position(waypoint)?.view());
+            let step = self.current_step();
+            let decay = config.backtrack_decay.max(1);
+            let visits = &self.visits;
+
+            let path = astar(visits.dim(), current, goal, |index| {
+                let visit = visits[index];
+
+                if visit != 0 && step.saturating_sub(visit) < decay {
+                    Some(VISITED_COST)
+                } else {
+                    Some(1)
+                }
+            });
+
+            self.guide = Guide {
+                path: path.unwrap_or_default(),
+                waypoint,
+                age: 0,
+            };
+        }
+
+        self.guide.age += 1;
+
+        let [x, y] = current;
+        let next = self.guide.next_after(current)?;
+
+        Some(if next[0] > x {
+            Direction::Right
+        } else if next[0] < x {
+            Direction::Left
+        } else if next[1] > y {
+            Direction::Down
+        } else {
+            Direction::Up
+        })
+    }
+
+    /// samples a direction from the softmax of its scores at `config.temperature`
+    fn softmax_direction(
+        &mut self,
+        pos: VectorView2,
+        preferred: WalkerState,
+        config: &WalkerConfig,
+    ) -> Direction {
+        let Some(waypoint) = self.walker.waypoint_position(preferred.waypoint) else {
+            return preferred.direction;
+        };
+
+        let (width, height) = self.brush.dim();
+        let probe = (width.max(height) / 2 + 2) as f32;
+        let distance = euclidian(waypoint.view(), pos);
+
+        let scores: Vec<f32> = (0..directions(config))
+            .map(|i| {
+                let direction = Direction::from(i);
+
+                let mut next = pos.to_owned();
+                shift_by_direction(&mut next, 1.0, direction);
+                let gain = distance - euclidian(waypoint.view(), next.view());
+
+                let mut ahead = pos.to_owned();
+                shift_by_direction(&mut ahead, probe, direction);
+                let openness =
+                    self.distances.get(as_index(ahead.view())) as f32 / MAX_DISTANCE as f32;
+
+                let momentum = if self.momentum.direction == Some(direction) {
+                    SOFTMAX_MOMENTUM
+                } else {
+                    0.0
+                };
+
+                gain + momentum + config.openness_bias * openness
+            })
+            .collect();
+
+        let best = (0..scores.len())
+            .max_by(|&a, &b| scores[a].total_cmp(&scores[b]))
+            .unwrap();
+
+        if config.temperature <= 0.0 {
+            return Direction::from(best);
+        }
+
+        // shifted by the best score so the exponentials can't overflow
+        let weights: Vec<f32> = scores
+            .iter()
+            .map(|score| ((score - scores[best]) / config.temperature).exp())
+            .collect();
+        let mut roll = self.prng.in_range(0.0..weights.iter().sum::<f32>());
+
+        for (i, weight) in weights.into_iter().enumerate() {
+            if roll < weight {
+                return Direction::from(i);
+            }
+
+            roll -= weight;
+        }
+
+        Direction::from(best)
+    }
+
+    /// picks the preferred direction or a turn off it, weighted by the distance to walls a bit
+    /// beyond the brush, open directions are favored for a positive bias and closed ones for a
+    /// negative bias
+    fn openness_direction(
+        &mut self,
+        pos: VectorView2,
+        preferred: Direction,
+        bias: f32,
+    ) -> Direction {
+        let (width, height) = self.brush.dim();
+        let probe = (width.max(height) / 2 + 2) as f32;
+
+        let candidates = [preferred, preferred.next(), preferred.prev()];
+        let weights = candidates.map(|direction| {
+            let mut ahead = pos.to_owned();
+            shift_by_direction(&mut ahead, probe, direction);
+
+            let openness = self.distances.get(as_index(ahead.view())) as f32 / MAX_DISTANCE as f32;
+
+            // keeps a direction possible when all of them are fully open or closed
+            let weight = if bias > 0.0 { openness } else { 1.0 - openness };
+            weight + f32::EPSILON
+        });
+
+        let mut roll = self.prng.in_range(0.0..weights.iter().sum::<f32>());
+
+        for (direction, weight) in candidates.into_iter().zip(weights) {
+            if roll < weight {
+                return direction;
+            }
+
+            roll -= weight;
+        }
+
+        preferred
+    }
+
+    /// tracks the walker's progress towards `waypoint` and starts an escape once it stalled for
+    /// too long, returns the policy of a started escape
+    fn detect_stuck(&mut self, pos: VectorView2, waypoint: usize) -> Option<StuckPolicy> {
+        let config = self.walker.get_config();
+        let stuck = &mut self.stuck;
+
+        stuck.escape = stuck.escape.saturating_sub(1);
+
+        if config.stuck_steps == 0 {
+            return None;
+        }
+
+        let distance = euclidian(self.walker.waypoint_position(waypoint)?.view(), pos);
+
+        if stuck.waypoint != Some(waypoint) || distance < stuck.closest {
+            stuck.waypoint = Some(waypoint);
+            stuck.closest = distance;
+            stuck.stalled = 0;
+
+            return None;
+        }
+
+        stuck.stalled += 1;
+
+        if stuck.stalled < config.stuck_steps || stuck.escape > 0 {
+            return None;
+        }
+
+        stuck.stalled = 0;
+        stuck.escape = match config.stuck_policy {
+            StuckPolicy::Greedy { steps } | StuckPolicy::Enlarge { steps, .. } => steps,
+            StuckPolicy::Tunnel => 0,
+        };
+
+        Some(config.stuck_policy)
+    }
+
+    /// whether the walker ran past the step or time budget of the preset
+    fn over_budget(&self) -> bool {
+        let budget = &self.preset.budget;
+
+        let steps = budget.max_total_steps > 0 && self.current_step() >= budget.max_total_steps;
+        let time = budget.max_generation_time > 0.0
+            && self.timing.total().as_secs_f32() >= budget.max_generation_time;
+
+        steps || time
+    }
+
+    /// steps walked since the generation started, branches included
+    fn current_step(&self) -> usize {
+        self.step_offset + self.walker.get_current_step()
+    }
+
+    /// how recently the walker stood on the tile in `direction`, from 0.0 for never or long ago
+    /// to 1.0 for the last step
+    fn recency(&self, pos: VectorView2, direction: Direction, decay: usize) -> f32 {
+        let mut next = pos.to_owned();
+        shift_by_direction(&mut next, 1.0, direction);
+
+        let visit = self.visits.get(as_index(next.view())).copied().unwrap_or(0);
+
+        if visit == 0 {
+            return 0.0;
+        }
+
+        let age = self.current_step().saturating_sub(visit);
+
+        1.0 - (age as f32 / decay.max(1) as f32).min(1.0)
+    }
+
+    /// swaps `direction` for the least recently visited alternative, preferring `preferred`
+    fn avoid_backtracking(
+        &mut self,
+        direction: Direction,
+        preferred: Direction,
+        pos: VectorView2,
+        config: &WalkerConfig,
+    ) -> Direction {
+        let decay = config.backtrack_decay;
+        let recency = self.recency(pos, direction, decay);
+
+        if recency <= 0.0 || !self.prng.gen_bool(config.backtrack_penalty * recency) {
+            return direction;
+        }
+
+        let alternative = [
+            preferred,
+            preferred.next(),
+            preferred.prev(),
+            preferred.backwards(),
+        ]
+        .into_iter()
+        .filter(|&alternative| alternative != direction)
+        .map(|alternative| (alternative, self.recency(pos, alternative, decay)))
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap());
+
+        match alternative {
+            Some((alternative, alternative_recency)) if alternative_recency < recency => {
+                alternative
+            }
+            _ => direction,
+        }
+    }
+
+    /// replaces hookable tiles around the last carved area by unhookable and hookthrough ones,
+    /// leaves hookable islands in the freeze when generating gores
+    fn decorate_edges(&mut self, map: &mut Map, pos: Vector2) {
+        let unhookable = self.walker.get_config().unhookable_probability;
+        let hookthrough = self.walker.get_config().hookthrough_probability;
+        let island = match self.preset.mode {
+            GenerationMode::Solid => 0.0,
+            GenerationMode::Gores => self.walker.get_config().island_probability,
+        };
+
+        if unhookable <= 0.0 && hookthrough <= 0.0 && island <= 0.0 {
+            return;
+        }
+
+        let tiles = map.game_layer().tiles.unwrap_mut();
+
+        for edge in self.brush.outline(pos) {
+            let index = as_index(edge.view());
+
+            if island > 0.0 && self.prng.gen_bool(island) {
+                place_island(tiles, index);
+            }
+
+            let Some(tile) = tiles.get_mut(index) else {
+                continue;
+            };
+
+            if tile.id != BlockType::Hookable.game_id() {
+                continue;
+            }
+
+            if self.prng.gen_bool(unhookable) {
+                *tile = BlockType::Unhookable.tile();
+            } else if self.prng.gen_bool(hookthrough) {
+                *tile = BlockType::Hookthrough.tile();
+            }
+        }
+    }
+
+    /// hookable ledge across a third of the carved row at the bottom of the brush every
+    /// `climb_ledge` steps of a climb, alternating between the left and right wall
+    fn place_ledge(&mut self, map: &mut Map, pos: VectorView2) {
+        let interval = self.walker.get_config().climb_ledge;
+        let climb = self.momentum.climb();
+
+        if interval == 0 || climb == 0 || !climb.is_multiple_of(interval) {
+            return;
+        }
+
+        let (width, height) = self.brush.dim();
+        let ledge = width / 3;
+
+        if ledge == 0 {
+            return;
+        }
+
+        let [x, y] = as_index(pos);
+        let (left, bottom) = (
+            x.saturating_sub(width / 2),
+            y.saturating_sub(height / 2) + height - 1,
+        );
+
+        let start = if (climb / interval).is_multiple_of(2) {
+            left
+        } else {
+            left + width - ledge
+        };
+
+        let tiles = map.game_layer().tiles.unwrap_mut();
+
+        for x in start..start + ledge {
+            if let Some(tile) = tiles.get_mut([x, bottom]) {
+                if tile.id == BlockType::Empty.game_id() {
+                    *tile = BlockType::Hookable.tile();
+                }
+            }
+        }
+    }
+
+    /// layers describing the finished map, before it's cropped
+    fn write_debug_layers(&mut self, map: &Map) {
+        if let Some(heatmap) = self.debug.heatmap("distance", DebugColor::BLUE) {
+            let distances = self.distances.distances();
+
+            heatmap.zip_mut_with(distances, |value, &distance| {
+                *value = distance as f32 / MAX_DISTANCE as f32;
+            });
+        }
+
+        let last_visit = self.visits.iter().copied().max().unwrap_or(0).max(1);
+
+        if let Some(heatmap) = self.debug.heatmap("visits", DebugColor::ORANGE) {
+            heatmap.zip_mut_with(&self.visits, |value, &visit| {
+                *value = visit as f32 / last_visit as f32;
+            });
+        }
+
+        // oscillating walkers show up as hot spots
+        let most_touches = self.touches.iter().copied().max().unwrap_or(0).max(1);
+
+        if let Some(heatmap) = self.debug.heatmap(TOUCHES_LAYER, DebugColor::RED) {
+            heatmap.zip_mut_with(&self.touches, |value, &touches| {
+                *value = touches as f32 / most_touches as f32;
+            });
+        }
+
+        if let Some(classes) = self.debug.classes("segments", &DebugColor::PALETTE) {
+            for ((value, &visit), &segment) in classes
+                .iter_mut()
+                .zip(self.visits.iter())
+                .zip(self.segments.iter())
+            {
+                *value = if visit == 0 {
+                    0
+                } else {
+                    (segment % 255) as u8 + 1
+                };
+            }
+        }
+
+        if map.locked().iter().any(|&locked| locked) {
+            if let Some(mask) = self.debug.mask("locked", DebugColor::GRAY) {
+                mask.assign(map.locked());
+            }
+        }
+    }
+
+    /// advances while timing the phases, the time between calls isn't counted
+    fn advance_timed(&mut self, state: &mut GenerationState) -> Option<StepResult> {
+        let _span = trace_span!("step", phase = state.phase.name()).entered();
+
+        state.clock.start(state.phase.name());
+
+        let result = self.advance(state);
+
+        state.clock.stop(&mut self.timing);
+
+        match result {
+            Some(StepResult::WalkerMoved { .. }) => self.timing.steps += 1,
+            Some(StepResult::Finished(_)) => info!(
+                steps = self.timing.steps,
+                millis = self.timing.total().as_secs_f64() * 1000.0,
+                "generation finished"
+            ),
+            _ => {}
+        }
+
+        result
+    }
+
+    fn advance(&mut self, state: &mut GenerationState) -> Option<StepResult> {
+        // phases switch right before advancing again
+        state.clock.tick(state.phase.name(), &mut self.timing);
+
+        if let Some(result) = state.pending.pop_front() {
+            return Some(result);
+        }
+
+        // a regenerated segment only gets the passes local to its tiles
+        if state.rejoin.is_some() {
+            state.phase = match state.phase {
+                GenerationPhase::Skips | GenerationPhase::Detail => GenerationPhase::Freeze,
+                GenerationPhase::Prefabs => GenerationPhase::Spawn,
+                GenerationPhase::Regions => GenerationPhase::EdgeBugs,
+                phase => phase,
+            };
+        }
+
+        if state.phase != state.restored {
+            debug!(phase = state.phase.name(), "phase started");
+            state.restored = state.phase;
+
+            if let (Some(map), Some(previous)) = (state.map.as_mut(), &state.previous) {
+                map.restore_locked(previous);
+                self.distances.mark_all_dirty();
+            }
+        }
+
+        match state.phase {
+            GenerationPhase::Walking => {
+                let map = state.map.as_mut().unwrap();
+                let waypoint = self.walker.preferred_state().waypoint;
+                let over_budget = self.over_budget();
+
+                if over_budget {
+                    let steps = self.current_step();
+
+                    if self.preset.budget.policy == BudgetPolicy::Fail {
+                        warn!(steps, "budget exceeded, generation failed");
+                        state.failed = Some(GenerationError::BudgetExceeded { steps });
+
+                        return None;
+                    }
+
+                    if !state.truncated {
+                        warn!(steps, "budget exceeded, heading straight to the end");
+                        state.truncated = true;
+                    }
+
+                    let last = self.walker.get_waypoints().len().saturating_sub(1);
+
+                    if let Some(end) = self.walker.waypoint_position(last) {
+                        self.carve_line(map, state.current_pos.clone(), end.clone());
+                        state.current_pos = end;
+                    }
+                }
+
+                if over_budget || self.walker.step(state.current_pos.view()) == 0 {
+                    if let Some(rejoin) = state.rejoin.clone() {
+                        self.carve_line(map, state.current_pos.clone(), rejoin);
+                        state.phase = GenerationPhase::Rooms;
+
+                        return self.advance(state);
+                    }
+
+                    if self.preset.waypoints.looped && state.branch.is_none() {
+                        // the walker stops within reach distance of the spawn, close the gap
+                        self.carve_line(map, state.current_pos.clone(), state.spawn_pos.clone());
+                    } else {
+                        state.finishes.push(as_index(state.current_pos.view()));
+                    }
+
+                    let next_branch = state.branch.map_or(0, |branch| branch + 1);
+
+                    if next_branch < self.preset.waypoints.branches.len() {
+                        return self.start_branch(state, next_branch);
+                    }
+
+                    state.phase = GenerationPhase::Smoothing;
+
+                    return self.advance(state);
+                }
+
+                let step = self.current_step();
+                let heading = self.walker.preferred_state().waypoint;
+
+                if let Some(policy) = self.detect_stuck(state.current_pos.view(), heading) {
+                    debug!(?policy, waypoint = heading, "walker stuck");
+
+                    state.pending.push_back(StepResult::WalkerStuck {
+                        waypoint: heading + state.waypoint_offset,
+                        position: state.current_pos.clone(),
+                    });
+
+                    if let (StuckPolicy::Tunnel, Some(target)) =
+                        (policy, self.walker.waypoint_position(heading))
+                    {
+                        self.carve_line(map, state.current_pos.clone(), target.clone());
+                        self.distances.mark_all_dirty();
+                        state.current_pos = target;
+                    }
+                }
+
+                self.before_step(map, state.current_pos.view());
+
+                let direction = self.walker.current_state().direction;
+
+                // thin brushes only touch at their corners on diagonal steps
+                if direction.is_diagonal() && self.brush.dim().0.min(self.brush.dim().1) < 3 {
+                    let mut corner = state.current_pos.clone();
+                    shift_by_direction(&mut corner, 1.0, direction.horizontal());
+
+                    self.brush.apply(
+                        map.game_layer().tiles.unwrap_mut(),
+                        corner,
+                        BlockType::Empty.tile(),
+                    );
+                }
+
+                shift_by_direction(&mut state.current_pos, 1.0, direction);
+
+                let active = self.active_modifiers(
+                    state.current_pos.view(),
+                    state.spawn_pos.view(),
+                    waypoint + state.waypoint_offset,
+                );
+                let progress = self.route_progress(
+                    state.current_pos.view(),
+                    state.branch,
+                    state.waypoint_offset,
+                );
+                self.scale_brush(&active, progress.min(1.0));
+
+                self.brush.apply(
+                    map.game_layer().tiles.unwrap_mut(),
+                    state.current_pos.clone(),
+                    BlockType::Empty.tile(),
+                );
+
+                if self.brush.dim() != state.kernel {
+                    state.kernel = self.brush.dim();
+
+                    state.pending.push_back(StepResult::KernelResized {
+                        size: state.kernel,
+                        position: state.current_pos.clone(),
+                    });
+                }
+
+                if self.debug.is_enabled(TOUCHES_LAYER) {
+                    self.brush
+                        .accumulate(&mut self.touches, state.current_pos.clone());
+                }
+
+                self.decorate_edges(map, state.current_pos.clone());
+                self.place_ledge(map, state.current_pos.view());
+                self.apply_modifiers(map, state.current_pos.view(), &active);
+
+                // brush and edge decorations around it
+                let [x, y] = as_index(state.current_pos.view());
+                let (width, height) = self.brush.dim();
+                let reach = width.max(height) / 2 + 2;
+
+                self.distances.mark_dirty(
+                    [x.saturating_sub(reach), y.saturating_sub(reach)],
+                    [x + reach, y + reach],
+                );
+
+                if state.branch.is_none() {
+                    state.route.push((
+                        as_index(state.current_pos.view()),
+                        self.walker.current_state().direction,
+                    ));
+                }
+
+                if let Some(visit) = self.visits.get_mut(as_index(state.current_pos.view())) {
+                    *visit = step;
+                    self.segments[as_index(state.current_pos.view())] = match state.branch {
+                        Some(branch) => self.preset.waypoints.branches[branch].from,
+                        None => waypoint + state.waypoint_offset,
+                    };
+                }
+
+                let next_waypoint = self.walker.preferred_state().waypoint;
+
+                if next_waypoint != waypoint {
+                    state.pending.push_back(StepResult::WaypointReached {
+                        waypoint: waypoint + state.waypoint_offset,
+                    });
+
+                    if state.branch.is_none() && state.rejoin.is_none() {
+                        self.route_positions.push(state.current_pos.clone());
+                    }
+
+                    let exit = self
+                        .walker
+                        .waypoint_position(next_waypoint)
+                        .map(|next| dominant_direction((next - &state.current_pos).view()));
+
+                    state.room_sites.push(RoomSite {
+                        position: as_index(state.current_pos.view()),
+                        entry: self.walker.current_state().direction,
+                        exit,
+                    });
+
+                    // branches stay in the zone of their branch point
+                    let zone = match state.branch {
+                        Some(_) => state.zone,
+                        None => self.preset.zone_at(next_waypoint + state.waypoint_offset),
+                    };
+
+                    // overrides of the waypoint left behind end here as well
+                    let target = state
+                        .branch
+                        .is_none()
+                        .then_some(next_waypoint + state.waypoint_offset);
+                    let overridden = target.is_some_and(|target| {
+                        let waypoints = &self.preset.waypoints;
+
+                        waypoints.override_at(target).is_some()
+                            || waypoints
+                                .override_at(waypoint + state.waypoint_offset)
+                                .is_some()
+                    });
+
+                    if zone != state.zone || overridden {
+                        self.enter_zone(zone, target);
+                    }
+
+                    if zone != state.zone {
+                        state.zone = zone;
+
+                        state.pending.push_back(StepResult::ZoneEntered {
+                            zone,
+                            position: state.current_pos.clone(),
+                        });
+                    }
+                }
+
+                Some(StepResult::WalkerMoved {
+                    step,
+                    position: state.current_pos.clone(),
+                })
+            }
+            GenerationPhase::Smoothing => {
+                let iterations = self.preset.post_process.smoothing_iterations;
+
+                if iterations > 0 {
+                    let map = state.map.as_mut().unwrap();
+                    let route: Vec<[usize; 2]> =
+                        state.route.iter().map(|&(position, _)| position).collect();
+
+                    // small enough to only fill the inner corners of the staircases
+                    let kernel = Brush::circular((self.preset.kernel.size / 2).max(1), 1.0);
+
+                    let carved = smooth_route(
+                        map.game_layer().tiles.unwrap_mut(),
+                        &route,
+                        &kernel,
+                        iterations,
+                    );
+
+                    debug!(carved = carved.len(), "route smoothed");
+                    self.distances.mark_all_dirty();
+                }
+
+                state.phase = GenerationPhase::Rooms;
+
+                self.advance(state)
+            }
+            GenerationPhase::Rooms => {
+                let map = state.map.as_mut().unwrap();
+
+                if let Some(grid) = &self.preset.room_grid {
+                    let tiles = map.game_layer().tiles.unwrap_mut();
+
+                    // corridors are carved already, doors would only widen them
+                    for &position in state.grid_rooms.iter() {
+                        grid.room.apply(tiles, position, None, None, &mut self.prng);
+
+                        state.pending.push_back(StepResult::RoomPlaced {
+                            position: Vector2::from(vec![position[0] as f32, position[1] as f32]),
+                        });
+                    }
+                }
+
+                if let Some(rooms) = &self.preset.post_process.rooms {
+                    let tiles = map.game_layer().tiles.unwrap_mut();
+
+                    for site in state.room_sites.iter() {
+                        rooms.apply(
+                            tiles,
+                            site.position,
+                            Some(site.entry),
+                            site.exit,
+                            &mut self.prng,
+                        );
+
+                        state.pending.push_back(StepResult::RoomPlaced {
+                            position: Vector2::from(vec![
+                                site.position[0] as f32,
+                                site.position[1] as f32,
+                            ]),
+                        });
+                    }
+                }
+
+                self.distances.mark_all_dirty();
+                state.phase = GenerationPhase::Skips;
+
+                self.advance(state)
+            }
+            GenerationPhase::Skips => {
+                let map = state.map.as_mut().unwrap();
+
+                let mut carved = Vec::new();
+
+                if let Some(skips) = &self.preset.post_process.skips {
+                    let tiles = map.game_layer().tiles.unwrap_mut();
+                    carved = generate_all_skips(tiles, &self.visits, skips);
+
+                    for skip in carved.iter() {
+                        if let Some(mask) = self.debug.mask("skips", DebugColor::GREEN) {
+                            for index in skip.tiles() {
+                                mask[index] = true;
+                            }
+                        }
+
+                        state.pending.push_back(StepResult::SkipCarved {
+                            start: Vector2::from(vec![skip.start[0] as f32, skip.start[1] as f32]),
+                            end: Vector2::from(vec![skip.end[0] as f32, skip.end[1] as f32]),
+                        });
+                    }
+                }
+
+                if let Some(doors) = &self.preset.post_process.doors {
+                    let route: Vec<[usize; 2]> =
+                        state.route.iter().map(|&(position, _)| position).collect();
+
+                    for door in place_doors(map, &carved, &route, doors, &mut self.prng) {
+                        if let Some(mask) = self.debug.mask("doors", DebugColor::ORANGE) {
+                            for &index in door.tiles.iter() {
+                                mask[index] = true;
+                            }
+
+                            mask[door.button] = true;
+                        }
+
+                        let [x, y] = door.button;
+                        let [dx, dy] = door.tiles[0];
+
+                        state.pending.push_back(StepResult::DoorPlaced {
+                            number: door.number,
+                            button: Vector2::from(vec![x as f32, y as f32]),
+                            door: Vector2::from(vec![dx as f32, dy as f32]),
+                        });
+                    }
+                }
+
+                self.distances.mark_all_dirty();
+                state.phase = GenerationPhase::Detail;
+
+                self.advance(state)
+            }
+            GenerationPhase::Detail => {
+                let map = state.map.as_mut().unwrap();
+
+                if let Some(wfc) = &self.preset.post_process.wfc {
+                    let route = self.visits.map(|&visit| visit != 0);
+
+                    wfc.apply(
+                        map.game_layer().tiles.unwrap_mut(),
+                        &route,
+                        self.preset.mode.fill_block(),
+                        &mut self.prng,
+                    );
+                    self.distances.mark_all_dirty();
+                }
+
+                if let Some(maze) = &self.preset.post_process.maze {
+                    let tiles = map.game_layer().tiles.unwrap_mut();
+                    self.distances.update(tiles);
+
+                    let mazes = maze.apply(
+                        tiles,
+                        self.distances.distances(),
+                        self.preset.mode.fill_block(),
+                        &mut self.prng,
+                    );
+
+                    for built in mazes.iter() {
+                        if let Some(mask) = self.debug.mask("maze", DebugColor::BLUE) {
+                            for &index in built.walls.iter() {
+                                mask[index] = true;
+                            }
+                        }
+
+                        let [x, y] = built.origin;
+
+                        state.pending.push_back(StepResult::MazeBuilt {
+                            position: Vector2::from(vec![x as f32, y as f32]),
+                            cells: built.cells,
+                        });
+                    }
+
+                    if !mazes.is_empty() {
+                        self.distances.mark_all_dirty();
+                    }
+                }
+
+                state.phase = GenerationPhase::Freeze;
+
+                self.advance(state)
+            }
+            GenerationPhase::Freeze => {
+                let map = state.map.as_mut().unwrap();
+                let global = self.preset.post_process.freeze.as_ref();
+
+                let thickness = Array2::from_shape_fn(self.visits.dim(), |index| {
+                    if self.visits[index] == 0 {
+                        return 0;
+                    }
+
+                    self.preset
+                        .zone_at(self.segments[index])
+                        .and_then(|zone| self.preset.zones[zone].freeze.as_ref())
+                        .or(global)
+                        .map_or(0, |freeze| freeze.thickness)
+                });
+
+                if thickness.iter().any(|&thickness| thickness != 0) {
+                    pad_freeze(map.game_layer().tiles.unwrap_mut(), &thickness);
+                    self.distances.mark_all_dirty();
+                }
+
+                state.phase = GenerationPhase::Prefabs;
+
+                self.advance(state)
+            }
+            GenerationPhase::Prefabs => {
+                let map = state.map.as_mut().unwrap();
+                let tiles = map.game_layer().tiles.unwrap_mut();
+
+                let route: Vec<[usize; 2]> = self
+                    .visits
+                    .indexed_iter()
+                    .filter(|(_, &visit)| visit != 0)
+                    .map(|((x, y), _)| [x, y])
+                    .collect();
+
+                for placement in self.preset.post_process.prefabs.iter() {
+                    self.distances.update(tiles);
+
+                    let placed = placement.apply(tiles, &route, &self.distances, &mut self.prng);
+
+                    if !placed.is_empty() {
+                        self.distances.mark_all_dirty();
+                    }
+
+                    for [x, y] in placed {
+                        state.pending.push_back(StepResult::PrefabPlaced {
+                            name: placement.prefab.name.clone(),
+                            position: Vector2::from(vec![x as f32, y as f32]),
+                        });
+                    }
+                }
+
+                for rule in self.preset.post_process.rewrites.iter() {
+                    for [x, y] in rule.apply(tiles) {
+                        state.pending.push_back(StepResult::PrefabPlaced {
+                            name: rule.replacement.name.clone(),
+                            position: Vector2::from(vec![x as f32, y as f32]),
+                        });
+                    }
+                }
+
+                self.distances.mark_all_dirty();
+                state.phase = GenerationPhase::Spawn;
+
+                self.advance(state)
+            }
+            GenerationPhase::Spawn => {
+                // 4. place spawn room at the start of the route
+                let map = state.map.as_mut().unwrap();
+
+                self.preset
+                    .post_process
+                    .spawn
+                    .apply(map.game_layer().tiles.unwrap_mut(), state.spawn_pos.view());
+
+                if self.preset.waypoints.looped && state.route.len() > 2 * LAP_LINE_OFFSET {
+                    let tiles = map.game_layer().tiles.unwrap_mut();
+                    let (start, start_direction) = state.route[LAP_LINE_OFFSET];
+                    let (finish, finish_direction) =
+                        state.route[state.route.len() - LAP_LINE_OFFSET];
+
+                    place_line(tiles, start, start_direction, BlockType::Start);
+                    place_line(tiles, finish, finish_direction, BlockType::Finish);
+                }
+
+                if self.preset.post_process.finish {
+                    let brush = Self::initial_brush(&self.preset.kernel, 0);
+                    let tiles = map.game_layer().tiles.unwrap_mut();
+
+                    for &[x, y] in state.finishes.iter() {
+                        let position = Vector2::from(vec![x as f32, y as f32]);
+
+                        brush.apply(tiles, position.clone(), BlockType::Finish.tile());
+                        state
+                            .pending
+                            .push_back(StepResult::FinishPlaced { position });
+                    }
+                }
+
+                self.distances.mark_all_dirty();
+                state.phase = GenerationPhase::Regions;
+
+                let sign = self
+                    .preset
+                    .post_process
+                    .spawn
+                    .sign_position(state.spawn_pos.view())
+                    .map(|[x, y]| Vector2::from(vec![x as f32, y as f32]));
+
+                Some(StepResult::SpawnPlaced {
+                    position: state.spawn_pos.clone(),
+                    sign,
+                })
+            }
+            GenerationPhase::Regions => {
+                let map = state.map.as_mut().unwrap();
+                let tiles = map.game_layer().tiles.unwrap_mut();
+
+                if let Some(config) = &self.preset.post_process.sealed_regions {
+                    let start = as_index(state.spawn_pos.view());
+
+                    for region in config.apply(tiles, start, self.preset.mode.fill_block()) {
+                        let [x, y] = region.tiles[0];
+
+                        state.pending.push_back(StepResult::RegionSealed {
+                            position: Vector2::from(vec![x as f32, y as f32]),
+                            size: region.tiles.len(),
+                            tunneled: region.tunnel.is_some(),
+                        });
+                    }
+                }
+
+                if let Some(spikes) = &self.preset.post_process.spikes {
+                    for strip in spikes.apply(tiles, &mut self.prng) {
+                        if let Some(mask) = self.debug.mask("spikes", DebugColor::RED) {
+                            for &index in strip.tiles.iter() {
+                                mask[index] = true;
+                            }
+                        }
+
+                        let [x, y] = strip.tiles[0];
+
+                        state.pending.push_back(StepResult::SpikesPlaced {
+                            position: Vector2::from(vec![x as f32, y as f32]),
+                            length: strip.tiles.len(),
+                        });
+                    }
+                }
+
+                self.distances.mark_all_dirty();
+                state.phase = GenerationPhase::EdgeBugs;
+
+                self.advance(state)
+            }
+            GenerationPhase::EdgeBugs => {
+                if self.preset.post_process.fix_edge_bugs {
+                    let map = state.map.as_mut().unwrap();
+
+                    self.edge_bugs = fix_edge_bugs(map.game_layer().tiles.unwrap_mut());
+                    self.distances.mark_all_dirty();
+
+                    if let Some(mask) = self.debug.mask("edge bugs", DebugColor::RED) {
+                        mask.assign(&self.edge_bugs);
+                    }
+                }
+
+                // repairs the corridor wherever freeze got into it after the walk
+                let width = self.preset.post_process.min_clear_width;
+
+                if width > 0 {
+                    let map = state.map.as_mut().unwrap();
+                    let route = state.route.iter().map(|&(position, _)| position);
+
+                    if clear_route(map.game_layer().tiles.unwrap_mut(), route, width) > 0 {
+                        self.distances.mark_all_dirty();
+                    }
+                }
+
+                state.phase = GenerationPhase::Finalize;
+
+                self.advance(state)
+            }
+            GenerationPhase::Finalize => {
+                let map = state.map.as_mut().unwrap();
+                self.distances.update(map.game_layer().tiles.unwrap_mut());
+
+                self.write_debug_layers(map);
+                self.previous = Some(map.clone());
+
+                if self.preset.export.crop {
+                    self.crop_offset = map.crop_to_content(self.preset.export.crop_padding);
+                }
+
+                let export = &self.preset.export;
+
+                if map.width() < export.min_width || map.height() < export.min_height {
+                    let (width, height) = (map.width(), map.height());
+                    map.grow(export.min_width, export.min_height);
+
+                    let fill = self.preset.mode.fill_block().tile();
+
+                    for ((x, y), tile) in map.game_layer().tiles.unwrap_mut().indexed_iter_mut() {
+                        if x >= width || y >= height {
+                            *tile = fill;
+                        }
+                    }
+                }
+
+                // kernel mutations near the bounds may punch holes into the map edge
+                map.seal_border(self.preset.post_process.border.max(1));
+                debug_assert!(map.is_sealed());
+
+                if self.preset.export.front_freeze {
+                    map.move_to_front(BlockType::Freeze);
+                }
+
+                map.split_hookthrough();
+
+                map.convert_to(self.preset.export.target_version);
+                map.remap_tiles(&self.preset.export.tile_mapping);
+
+                // reset our tools
+                self.walker.reset();
+                self.brush = Self::initial_brush(
+                    &self.preset.kernel,
+                    self.preset.post_process.min_clear_width,
+                );
+
+                state.phase = GenerationPhase::Done;
+
+                // shrink map
+                Some(StepResult::Finished(state.map.take().unwrap().finalize()))
+            }
+            GenerationPhase::Done => None,
+        }
+    }
+}
+
+/// debug layer counting the brush applications per tile
+const TOUCHES_LAYER: &str = "brush touches";
+
+/// steps looked back at for the turn frequency
+const TURN_WINDOW: usize = 20;
+
+/// progress of the walker towards its waypoint, used to detect it being stuck
+#[derive(Debug, Default)]
+struct Stuck {
+    /// waypoint the progress is tracked for
+    waypoint: Option<usize>,
+    /// closest the walker got to it
+    closest: f32,
+    /// steps since the walker last got closer
+    stalled: usize,
+    /// steps left of the running escape
+    escape: usize,
+    /// the escape scaled the brush
+    scaled: bool,
+}
+
+/// recent route shape, used by the default step policy
+#[derive(Debug, Default)]
+struct Momentum {
+    direction: Option<Direction>,
+    straight_run: usize,
+    recent_turns: VecDeque<bool>,
+}
+
+impl Momentum {
+    /// consecutive upward steps up to the last one
+    fn climb(&self) -> usize {
+        match self.direction {
+            Some(Direction::Up) => self.straight_run,
+            _ => 0,
+        }
+    }
+
+    fn can_turn(&self, config: &WalkerConfig) -> bool {
+        if self.straight_run < config.min_straight_run {
+            return false;
+        }
+
+        if config.max_turn_frequency >= 1.0 {
+            return true;
+        }
+
+        let turns = self.recent_turns.iter().filter(|&&turned| turned).count();
+
+        (turns + 1) as f32 <= config.max_turn_frequency * TURN_WINDOW as f32
+    }
+
+    fn record(&mut self, direction: Direction) {
+        let turned = self.direction.is_some_and(|current| current != direction);
+
+        if turned {
+            self.straight_run = 0;
+        }
+
+        self.straight_run += 1;
+        self.direction = Some(direction);
+
+        self.recent_turns.push_back(turned);
+
+        if self.recent_turns.len() > TURN_WINDOW {
+            self.recent_turns.pop_front();
+        }
+    }
+}
+
+/// directions the walker may step in
+fn directions(config: &WalkerConfig) -> usize {
+    if config.allow_diagonal {
+        8
+    } else {
+        4
+    }
+}
+
+/// score of keeping the current direction for the softmax step policy, a step straight to the
+/// waypoint scores 1.0
+const SOFTMAX_MOMENTUM: f32 = 0.5;
+
+/// cost of entering a recently visited tile for the guided step policy
+const VISITED_COST: u32 = 8;
+
+/// path planned by the guided step policy
+#[derive(Debug, Default)]
+struct Guide {
+    path: Vec<[usize; 2]>,
+    waypoint: usize,
+    age: usize,
+}
+
+impl Guide {
+    fn next_after(&self, current: [usize; 2]) -> Option<[usize; 2]> {
+        let index = self.path.iter().position(|&tile| tile == current)?;
+
+        self.path.get(index + 1).copied()
+    }
+}
+
+/// route steps between the spawn and the lap start line, and between the finish line and the
+/// end of a looped route
+const LAP_LINE_OFFSET: usize = 30;
+
+/// fills the empty tiles across the route at `index`, perpendicular to `direction`
+fn place_line(
+    tiles: &mut Array2<GameTile>,
+    index: [usize; 2],
+    direction: Direction,
+    block: BlockType,
+) {
+    for side in [direction.prev(), direction.next()] {
+        let mut position = Vector2::from(vec![index[0] as f32, index[1] as f32]);
+
+        while let Some(tile) = tiles.get_mut(as_index(position.view())) {
+            if tile.id != BlockType::Empty.game_id() && tile.id != block.game_id() {
+                break;
+            }
+
+            *tile = block.tile();
+            shift_by_direction(&mut position, 1.0, side);
+
+            if position.iter().any(|&coordinate| coordinate < 0.0) {
+                break;
+            }
+        }
+    }
+}
+
+/// turns the freeze tiles at and next to `index` into hookable ones
+fn place_island(tiles: &mut Array2<GameTile>, index: [usize; 2]) {
+    let neighbors = std::iter::once(index).chain(index.neighbors(tiles.dim(), false));
+
+    for neighbor in neighbors {
+        if let Some(tile) = tiles.get_mut(neighbor) {
+            if tile.id == BlockType::Freeze.game_id() {
+                *tile = BlockType::Hookable.tile();
+            }
+        }
+    }
+}
+
+/// how the canvas is filled before the walker carves its route into it
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GenerationMode {
+    /// solid hookable canvas
+    #[default]
+    Solid,
+    /// freeze canvas, hookable only appears as islands along the route
+    Gores,
+}
+
+impl GenerationMode {
+    pub fn fill_block(&self) -> BlockType {
+        match self {
+            Self::Solid => BlockType::Hookable,
+            Self::Gores => BlockType::Freeze,
+        }
+    }
+}
+
+/// notable events of a generation, in the order they happen
+#[derive(Debug)]
+pub enum StepResult {
+    /// walker moved one tile and carved at its new position
+    WalkerMoved { step: usize, position: Vector2 },
+    /// walker reached a waypoint and heads to the next one, indices of branches start at their
+    /// branch point
+    WaypointReached { waypoint: usize },
+    /// walker finished the previous route and starts walking `branch` at `position`
+    BranchStarted { branch: usize, position: Vector2 },
+    /// walker crossed a zone boundary, `None` when it left all zones
+    ZoneEntered {
+        zone: Option<usize>,
+        position: Vector2,
+    },
+    /// brush the walker carves with changed its width and height, by a zone, an override, a
+    /// modifier or a mutation
+    KernelResized {
+        size: (usize, usize),
+        position: Vector2,
+    },
+    /// walker stopped getting closer to `waypoint` and started its stuck policy at `position`
+    WalkerStuck { waypoint: usize, position: Vector2 },
+    /// room was carved centered at `position`
+    RoomPlaced { position: Vector2 },
+    /// shortcut was tunneled between two parts of the route
+    SkipCarved { start: Vector2, end: Vector2 },
+    /// timed door of switch `number` was put into a skip, its button is centered at `button`
+    DoorPlaced {
+        number: u8,
+        button: Vector2,
+        door: Vector2,
+    },
+    /// prefab was stamped centered at `position`
+    PrefabPlaced { name: String, position: Vector2 },
+    /// spawn room was placed at the start of the route, `sign` is where a sign fits in it
+    SpawnPlaced {
+        position: Vector2,
+        sign: Option<Vector2>,
+    },
+    /// maze walls were built in an open cavern, `position` is the top left tile of its grid
+    MazeBuilt { position: Vector2, cells: usize },
+    /// finish tiles were placed at the end of the main route or a branch
+    FinishPlaced { position: Vector2 },
+    /// empty region unreachable from the spawn was tunneled to or filled, `position` is its
+    /// leftmost tile
+    RegionSealed {
+        position: Vector2,
+        size: usize,
+        tunneled: bool,
+    },
+    /// strip of kill tiles was placed on a ceiling or floor, `position` is its leftmost tile
+    SpikesPlaced { position: Vector2, length: usize },
+    /// generation is done, always the last event
+    Finished(TwMap),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GenerationPhase {
+    Walking,
+    Smoothing,
+    Rooms,
+    Skips,
+    Detail,
+    Freeze,
+    Prefabs,
+    Spawn,
+    Regions,
+    EdgeBugs,
+    Finalize,
+    Done,
+}
+
+impl GenerationPhase {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Walking => "walking",
+            Self::Smoothing => "smoothing",
+            Self::Rooms => "rooms",
+            Self::Skips => "skips",
+            Self::Detail => "detail",
+            Self::Freeze => "freeze",
+            Self::Prefabs => "prefabs",
+            Self::Spawn => "spawn",
+            Self::Regions => "regions",
+            Self::EdgeBugs => "edge bugs",
+            Self::Finalize => "finalize",
+            Self::Done => "done",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenerationError {
+    /// generation was cancelled through its handle
+    Cancelled,
+    /// walker ran past the step or time budget of a preset with the failing budget policy
+    BudgetExceeded { steps: usize },
+    /// the route has less than the two waypoints a walk needs
+    RouteTooShort { waypoints: usize },
+}
+
+impl fmt::Display for GenerationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cancelled => write!(f, "generation was cancelled"),
+            Self::BudgetExceeded { steps } => {
+                write!(f, "generation exceeded its budget after {} steps", steps)
+            }
+            Self::RouteTooShort { waypoints } => {
+                write!(f, "route has only {} waypoints", waypoints)
+            }
+        }
+    }
+}
+
+impl Error for GenerationError {}
+
+struct GenerationState {
+    map: Option<Map>,
+    /// set once the generation failed, it ends without a map
+    failed: Option<GenerationError>,
+    /// the budget ran out and the walker was routed straight to its last waypoint
+    truncated: bool,
+    current_pos: Vector2,
+    spawn_pos: Vector2,
+    zone: Option<usize>,
+    /// branch walked right now, `None` on the main route
+    branch: Option<usize>,
+    /// last positions of the main route and every branch, the main route has none when looped
+    finishes: Vec<[usize; 2]>,
+    /// positions of the main route and the directions the walker entered them
+    route: Vec<([usize; 2], Direction)>,
+    /// waypoints of the main route as given to the walker
+    trunk: Vec<(f32, f32)>,
+    /// last generation's map, its locked tiles are restored whenever a phase ends
+    previous: Option<Map>,
+    /// phase the locked tiles were restored for last
+    restored: GenerationPhase,
+    /// route position a regenerated segment ends at, only the passes local to the segment run
+    rejoin: Option<Vector2>,
+    /// main route index of the walker's first waypoint
+    waypoint_offset: usize,
+    /// centers of the room grid rooms
+    grid_rooms: Vec<[usize; 2]>,
+    room_sites: Vec<RoomSite>,
+    phase: GenerationPhase,
+    pending: VecDeque<StepResult>,
+    /// brush size the walker carved with last
+    kernel: (usize, usize),
+    clock: PhaseClock,
+}
+
+impl GenerationState {
+    /// state of a generation that failed before its first step
+    fn failed(error: GenerationError) -> Self {
+        Self {
+            map: None,
+            failed: Some(error),
+            truncated: false,
+            current_pos: Vector2::zeros(2),
+            spawn_pos: Vector2::zeros(2),
+            zone: None,
+            branch: None,
+            finishes: Vec::new(),
+            route: Vec::new(),
+            trunk: Vec::new(),
+            previous: None,
+            restored: GenerationPhase::Done,
+            rejoin: None,
+            waypoint_offset: 0,
+            grid_rooms: Vec::new(),
+            room_sites: Vec::new(),
+            phase: GenerationPhase::Done,
+            pending: VecDeque::new(),
+            kernel: (0, 0),
+            clock: PhaseClock::default(),
+        }
+    }
+}
+
+/// reached waypoint and the directions the walker entered and left it
+struct RoomSite {
+    position: [usize; 2],
+    entry: Direction,
+    exit: Option<Direction>,
+}
+
+/// generation driven step by step, dropping it cancels the generation
+pub struct GenerationSteps {
+    generator: Generator,
+    state: GenerationState,
+}
+
+impl GenerationSteps {
+    /// map in its current, unfinished state
+    pub fn current_map(&self) -> Option<&Map> {
+        self.state.map.as_ref()
+    }
+
+    pub fn generator(&self) -> &Generator {
+        &self.generator
+    }
+
+    /// generator running the steps, preset changes apply to the phases not run yet
+    pub fn generator_mut(&mut self) -> &mut Generator {
+        &mut self.generator
+    }
+
+    pub fn into_generator(self) -> Generator {
+        self.generator
+    }
+
+    /// error the generation stopped with, if it ended without a map
+    pub fn error(&self) -> Option<GenerationError> {
+        self.state.failed
+    }
+}
+
+impl Iterator for GenerationSteps {
+    type Item = StepResult;
 
-        // shrink map
-        map.finalize()
+    fn next(&mut self) -> Option<Self::Item> {
+        self.generator.advance_timed(&mut self.state)
     }
 }
@@ -0,0 +1,136 @@
+use std::fmt;
+
+use crate::{config::GenerationPreset, map::Map};
+
+/// end of the main route, the spawn room is carved at the first waypoint and the finish is
+/// stamped at the last one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    Spawn,
+    Finish,
+}
+
+impl Endpoint {
+    /// waypoint of a route with `len` waypoints the endpoint is placed at
+    pub fn waypoint(self, len: usize) -> usize {
+        match self {
+            Self::Spawn => 0,
+            Self::Finish => len.saturating_sub(1),
+        }
+    }
+}
+
+/// reason a spawn or finish doesn't work where it was placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlacementIssue {
+    /// inside the border sealed around the map
+    NearBorder,
+    /// overlaps tiles the next generation keeps from the last one
+    Locked,
+    /// the spawn room reaches past the map or into its border
+    NoRoom,
+}
+
+impl fmt::Display for PlacementIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NearBorder => write!(f, "too close to the map border"),
+            Self::Locked => write!(f, "inside a locked area"),
+            Self::NoRoom => write!(f, "not enough room for the spawn room"),
+        }
+    }
+}
+
+/// checks moving the `endpoint` of the preset's route to the normalized `waypoint` against the
+/// map the generator would create for it, `previous` is the uncropped last generated map whose
+/// locked tiles are kept, routes of a room grid or a tower aren't checked
+pub fn check_placement(
+    preset: &GenerationPreset,
+    margin: usize,
+    endpoint: Endpoint,
+    waypoint: (f32, f32),
+    previous: Option<&Map>,
+) -> Vec<PlacementIssue> {
+    let replaced = preset.room_grid.is_some() || preset.tower.is_some();
+
+    if replaced || preset.waypoints.waypoints.is_empty() {
+        return Vec::new();
+    }
+
+    let mut config = preset.waypoints.clone();
+    let index = endpoint.waypoint(config.waypoints.len());
+    config.waypoints[index] = waypoint;
+
+    // same bounds as the generator
+    let (min, max) = config.bounds();
+
+    let scale_factor = config.scale_factor;
+    let width = ((max.0 - min.0) * scale_factor) as usize + 2 * margin;
+    let height = ((max.1 - min.1) * scale_factor) as usize + 2 * margin;
+
+    let position = [
+        (waypoint.0 * scale_factor) as isize + margin as isize,
+        (waypoint.1 * scale_factor) as isize + margin as isize,
+    ];
+    let [top_left, bottom_right] = footprint(preset, endpoint, position);
+
+    let border = preset.post_process.border.max(1) as isize;
+    let inside = |[x, y]: [isize; 2]| {
+        x >= border && y >= border && x < width as isize - border && y < height as isize - border
+    };
+
+    let mut issues = Vec::new();
+
+    if !inside(position) {
+        issues.push(PlacementIssue::NearBorder);
+    }
+
+    if endpoint == Endpoint::Spawn && !(inside(top_left) && inside(bottom_right)) {
+        issues.push(PlacementIssue::NoRoom);
+    }
+
+    // locked tiles only carry over while the map keeps its size
+    let previous = previous.filter(|map| map.width() == width && map.height() == height);
+
+    if let Some(map) = previous {
+        let locked = (top_left[0]..=bottom_right[0])
+            .flat_map(|x| (top_left[1]..=bottom_right[1]).map(move |y| [x, y]))
+            .filter(|&[x, y]| x >= 0 && y >= 0)
+            .any(|[x, y]| map.is_locked([x as usize, y as usize]));
+
+        if locked {
+            issues.push(PlacementIssue::Locked);
+        }
+    }
+
+    issues
+}
+
+/// inclusive corners of the tiles the spawn room or the finish at `position` covers, they may
+/// lie outside the map
+fn footprint(
+    preset: &GenerationPreset,
+    endpoint: Endpoint,
+    position: [isize; 2],
+) -> [[isize; 2]; 2] {
+    let [x, y] = position;
+
+    match endpoint {
+        Endpoint::Spawn => {
+            // carved above the spawn position, see `SpawnConfig::apply`
+            let (width, height) = preset.post_process.spawn.room_size();
+            let left = x - width as isize / 2;
+
+            [
+                [left, y - height as isize + 1],
+                [left + width as isize - 1, y],
+            ]
+        }
+        Endpoint::Finish => {
+            // stamped with the initial brush
+            let radius = preset.kernel.size.max(1) as isize / 2;
+
+            [[x - radius, y - radius], [x + radius, y + radius]]
+        }
+    }
+}
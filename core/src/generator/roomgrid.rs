@@ -0,0 +1,186 @@
+use crate::{random::Random, rooms::RoomConfig};
+
+/// coarse grid of cells connected by a random spanning tree, the walker walks the tree's
+/// corridors and rooms are carved in some of the cells
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RoomGridConfig {
+    pub columns: usize,
+    pub rows: usize,
+    /// probability of a cell getting a room, the first and last cell of the route never do
+    pub room_probability: f32,
+    pub room: RoomConfig,
+}
+
+impl Default for RoomGridConfig {
+    fn default() -> Self {
+        Self {
+            columns: 4,
+            rows: 4,
+            room_probability: 0.4,
+            room: RoomConfig::default(),
+        }
+    }
+}
+
+/// generated layout, cells are `[column, row]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoomGrid {
+    pub columns: usize,
+    pub rows: usize,
+    /// corridors of the spanning tree
+    pub corridors: Vec<([usize; 2], [usize; 2])>,
+    pub rooms: Vec<[usize; 2]>,
+    /// cells in walking order, every corridor is walked, corridors off the way to the most
+    /// distant cell are walked twice
+    pub route: Vec<[usize; 2]>,
+}
+
+impl RoomGridConfig {
+    pub fn generate(&self, prng: &mut Random) -> RoomGrid {
+        let (columns, rows) = (self.columns.max(1), self.rows.max(1));
+
+        // randomized depth first search builds the spanning tree
+        let mut visited = vec![vec![false; rows]; columns];
+        let mut children: Vec<Vec<Vec<[usize; 2]>>> = vec![vec![Vec::new(); rows]; columns];
+        let mut corridors = Vec::new();
+        let mut stack = vec![[0, 0]];
+
+        visited[0][0] = true;
+
+        while let Some(&current) = stack.last() {
+            let unvisited: Vec<[usize; 2]> = neighbors(current, columns, rows)
+                .into_iter()
+                .filter(|&[x, y]| !visited[x][y])
+                .collect();
+
+            if unvisited.is_empty() {
+                stack.pop();
+                continue;
+            }
+
+            let next = *prng.pick(&unvisited);
+
+            visited[next[0]][next[1]] = true;
+            children[current[0]][current[1]].push(next);
+            corridors.push((current, next));
+            stack.push(next);
+        }
+
+        let route = open_tour([0, 0], &children);
+
+        let last = route.last().copied();
+        let rooms = (0..columns)
+            .flat_map(|x| (0..rows).map(move |y| [x, y]))
+            .filter(|&cell| cell != [0, 0] && Some(cell) != last)
+            .filter(|_| prng.gen_bool(self.room_probability))
+            .collect();
+
+        RoomGrid {
+            columns,
+            rows,
+            corridors,
+            rooms,
+            route,
+        }
+    }
+}
+
+impl RoomGrid {
+    /// cell center in normalized coordinates, the grid spans from 0.0 to 1.0
+    pub fn cell_center(&self, [x, y]: [usize; 2]) -> (f32, f32) {
+        let span = |index: usize, count: usize| {
+            if count > 1 {
+                index as f32 / (count - 1) as f32
+            } else {
+                0.5
+            }
+        };
+
+        (span(x, self.columns), span(y, self.rows))
+    }
+
+    /// route cell centers, the last one repeated since the walker halts once it heads to the
+    /// last waypoint
+    pub fn waypoints(&self) -> Vec<(f32, f32)> {
+        let mut waypoints: Vec<(f32, f32)> = self
+            .route
+            .iter()
+            .map(|&cell| self.cell_center(cell))
+            .collect();
+
+        if let Some(&last) = waypoints.last() {
+            waypoints.push(last);
+        }
+
+        waypoints
+    }
+}
+
+type Children = [Vec<Vec<[usize; 2]>>];
+
+/// walks every subtree of `cell`, returning from all of them but the highest, which is walked
+/// last
+fn open_tour(cell: [usize; 2], children: &Children) -> Vec<[usize; 2]> {
+    let mut route = vec![cell];
+    let subtrees = &children[cell[0]][cell[1]];
+
+    let highest = subtrees
+        .iter()
+        .copied()
+        .max_by_key(|&child| height(child, children));
+
+    for &child in subtrees.iter().filter(|&&child| Some(child) != highest) {
+        route.extend(closed_tour(child, children));
+        route.push(cell);
+    }
+
+    if let Some(highest) = highest {
+        route.extend(open_tour(highest, children));
+    }
+
+    route
+}
+
+/// walks every subtree of `cell` and returns to it
+fn closed_tour(cell: [usize; 2], children: &Children) -> Vec<[usize; 2]> {
+    let mut route = vec![cell];
+
+    for &child in children[cell[0]][cell[1]].iter() {
+        route.extend(closed_tour(child, children));
+        route.push(cell);
+    }
+
+    route
+}
+
+fn height(cell: [usize; 2], children: &Children) -> usize {
+    children[cell[0]][cell[1]]
+        .iter()
+        .map(|&child| height(child, children) + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+fn neighbors([x, y]: [usize; 2], columns: usize, rows: usize) -> Vec<[usize; 2]> {
+    let mut neighbors = Vec::with_capacity(4);
+
+    if x > 0 {
+        neighbors.push([x - 1, y]);
+    }
+
+    if x + 1 < columns {
+        neighbors.push([x + 1, y]);
+    }
+
+    if y > 0 {
+        neighbors.push([x, y - 1]);
+    }
+
+    if y + 1 < rows {
+        neighbors.push([x, y + 1]);
+    }
+
+    neighbors
+}
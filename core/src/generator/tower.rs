@@ -0,0 +1,137 @@
+use crate::config::{GenerationPreset, KernelConfig, WalkerConfig, ZoneConfig};
+
+/// tall narrow map, the route zigzags between the walls of a shaft climbing up or falling down
+/// it, the waypoints are placed every `spacing` tiles
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TowerConfig {
+    /// room for the route across the shaft in tiles
+    pub width: usize,
+    /// route length up or down the shaft in tiles
+    pub height: usize,
+    /// tiles climbed between two waypoints
+    pub spacing: usize,
+    /// share of the width the route swings across, the rest is left to the margin
+    pub sway: f32,
+    /// spawns at the bottom and finishes at the top, otherwise the other way around
+    pub upwards: bool,
+    /// pacing along the height, stacked from the spawn on
+    pub bands: Vec<TowerBand>,
+}
+
+impl Default for TowerConfig {
+    fn default() -> Self {
+        Self {
+            width: 50,
+            height: 2000,
+            spacing: 80,
+            sway: 0.6,
+            upwards: true,
+            bands: Vec::new(),
+        }
+    }
+}
+
+/// walker and kernel of a part of the tower, turned into a zone
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TowerBand {
+    /// share of the height, the last band covers whatever is left
+    pub share: f32,
+    pub walker: Option<WalkerConfig>,
+    pub kernel: Option<KernelConfig>,
+}
+
+impl Default for TowerBand {
+    fn default() -> Self {
+        Self {
+            share: 0.25,
+            walker: None,
+            kernel: None,
+        }
+    }
+}
+
+impl TowerConfig {
+    fn waypoint_count(&self) -> usize {
+        (self.height / self.spacing.max(1)).max(1) + 1
+    }
+
+    /// normalized waypoints alternating between both sides of the shaft, scaled by the height,
+    /// the first and last one are centered
+    pub fn waypoints(&self) -> Vec<(f32, f32)> {
+        let height = self.height.max(1) as f32;
+        let width = self.width as f32 / height;
+        let sway = self.sway.clamp(0.0, 1.0);
+        let count = self.waypoint_count();
+
+        (0..count)
+            .map(|i| {
+                let climbed = ((i * self.spacing) as f32 / height).min(1.0);
+                let y = if self.upwards { 1.0 - climbed } else { climbed };
+
+                let side = if i == 0 || i == count - 1 {
+                    0.0
+                } else if i % 2 == 0 {
+                    -1.0
+                } else {
+                    1.0
+                };
+
+                ((0.5 + side * sway / 2.0) * width, y)
+            })
+            .collect()
+    }
+
+    /// one zone per band covering the waypoints inside it, bands without waypoints are left out
+    pub fn zones(&self) -> Vec<ZoneConfig> {
+        let last = self.waypoint_count() - 1;
+        let mut zones = Vec::new();
+        let mut first = 0;
+        let mut end = 0.0;
+
+        for (i, band) in self.bands.iter().enumerate() {
+            end += band.share.max(0.0);
+
+            let until = if i + 1 == self.bands.len() {
+                last
+            } else {
+                ((end * last as f32) as usize).min(last)
+            };
+
+            if until < first {
+                continue;
+            }
+
+            zones.push(ZoneConfig {
+                waypoints: (first, until),
+                walker: band.walker.clone(),
+                kernel: band.kernel.clone(),
+                freeze: None,
+            });
+
+            first = until + 1;
+        }
+
+        zones
+    }
+
+    /// replaces the waypoints of `preset` with the tower and its zones with the bands if there
+    /// are any, the branches are dropped and the margin fills the width the route doesn't swing
+    /// across
+    pub fn apply(&self, preset: &mut GenerationPreset) {
+        let unused = (1.0 - self.sway.clamp(0.0, 1.0)) * self.width as f32;
+        let waypoints = &mut preset.waypoints;
+
+        waypoints.waypoints = self.waypoints();
+        waypoints.scale_factor = self.height.max(1) as f32;
+        waypoints.margin = ((unused / 2.0) as usize).max(preset.post_process.border + 1);
+        waypoints.branches.clear();
+
+        if !self.bands.is_empty() {
+            preset.zones = self.zones();
+        }
+    }
+}
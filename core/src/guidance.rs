@@ -0,0 +1,140 @@
+use std::collections::BTreeSet;
+
+use ndarray::ArrayView2;
+
+use crate::config::WaypointConfig;
+
+/// imported pixels darker than this are forbidden
+const FORBIDDEN_BELOW: u8 = 64;
+
+/// imported pixels lighter than this are preferred
+const PREFERRED_ABOVE: u8 = 192;
+
+/// what a painted cell of a guidance mask asks of the walker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Guidance {
+    /// stepped into only when every other direction is forbidden as well
+    Forbidden,
+    /// pulls the walker towards it
+    Preferred,
+}
+
+/// regions painted over the route steering the walker, cells are squares of `cell_size` tiles
+/// counted from the waypoint origin, so they stay in place when the margin or the cropping
+/// changes but move along with the scale factor
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GuidanceMask {
+    pub cell_size: usize,
+    pub forbidden: BTreeSet<[isize; 2]>,
+    pub preferred: BTreeSet<[isize; 2]>,
+    /// chance of turning towards a preferred cell the walker would miss otherwise
+    pub attraction: f32,
+}
+
+impl Default for GuidanceMask {
+    fn default() -> Self {
+        Self {
+            cell_size: 4,
+            forbidden: BTreeSet::new(),
+            preferred: BTreeSet::new(),
+            attraction: 0.5,
+        }
+    }
+}
+
+impl GuidanceMask {
+    pub fn is_empty(&self) -> bool {
+        self.forbidden.is_empty() && self.preferred.is_empty()
+    }
+
+    /// cell containing a tile relative to the waypoint origin
+    pub fn cell(&self, tile: [f32; 2]) -> [isize; 2] {
+        let size = self.cell_size.max(1) as f32;
+
+        [
+            (tile[0] / size).floor() as isize,
+            (tile[1] / size).floor() as isize,
+        ]
+    }
+
+    pub fn at(&self, tile: [f32; 2]) -> Option<Guidance> {
+        let cell = self.cell(tile);
+
+        if self.forbidden.contains(&cell) {
+            Some(Guidance::Forbidden)
+        } else if self.preferred.contains(&cell) {
+            Some(Guidance::Preferred)
+        } else {
+            None
+        }
+    }
+
+    /// paints the cell of `tile`, `None` erases it
+    pub fn paint(&mut self, tile: [f32; 2], guidance: Option<Guidance>) {
+        let cell = self.cell(tile);
+
+        self.forbidden.remove(&cell);
+        self.preferred.remove(&cell);
+
+        match guidance {
+            Some(Guidance::Forbidden) => self.forbidden.insert(cell),
+            Some(Guidance::Preferred) => self.preferred.insert(cell),
+            None => false,
+        };
+    }
+
+    pub fn clear(&mut self) {
+        self.forbidden.clear();
+        self.preferred.clear();
+    }
+
+    /// replaces the cells with a grayscale image indexed by `[x, y]` stretched over the whole
+    /// map of `waypoints`, margin included, light pixels are preferred, dark ones forbidden and
+    /// the grays in between left neutral
+    pub fn import_luma(&mut self, luma: ArrayView2<u8>, waypoints: &WaypointConfig) {
+        self.clear();
+
+        let (width, height) = luma.dim();
+
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let ((min_x, min_y), (max_x, max_y)) = waypoints.bounds();
+        let scale_factor = waypoints.scale_factor;
+        let margin = waypoints.margin as f32;
+
+        let origin = [min_x * scale_factor - margin, min_y * scale_factor - margin];
+        let size = [
+            (max_x - min_x) * scale_factor + 2.0 * margin,
+            (max_y - min_y) * scale_factor + 2.0 * margin,
+        ];
+
+        let [first_x, first_y] = self.cell(origin);
+        let [last_x, last_y] = self.cell([origin[0] + size[0], origin[1] + size[1]]);
+        let cell_size = self.cell_size.max(1) as f32;
+
+        // pixel under the center of a cell
+        let pixel = |cell: isize, axis: usize, pixels: usize| {
+            let center = (cell as f32 + 0.5) * cell_size;
+            let relative = (center - origin[axis]) / size[axis];
+
+            ((relative * pixels as f32) as usize).min(pixels - 1)
+        };
+
+        for x in first_x..=last_x {
+            for y in first_y..=last_y {
+                let value = luma[[pixel(x, 0, width), pixel(y, 1, height)]];
+
+                if value < FORBIDDEN_BELOW {
+                    self.forbidden.insert([x, y]);
+                } else if value > PREFERRED_ABOVE {
+                    self.preferred.insert([x, y]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// snapshot of a running generation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    /// share of reached waypoints, from 0.0 to 1.0
+    pub waypoints: f32,
+    /// name of the current generation phase
+    pub phase: &'static str,
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self {
+            waypoints: 0.0,
+            phase: "idle",
+        }
+    }
+}
+
+/// shared between the generating thread and its observers, clones refer to the same generation
+#[derive(Debug, Clone, Default)]
+pub struct GenerationHandle {
+    cancelled: Arc<AtomicBool>,
+    progress: Arc<Mutex<Progress>>,
+}
+
+impl GenerationHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// asks the generation to stop, it returns with an error on its next step
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn progress(&self) -> Progress {
+        self.progress.lock().unwrap().clone()
+    }
+
+    pub(crate) fn report(&self, waypoints: f32, phase: &'static str) {
+        let mut progress = self.progress.lock().unwrap();
+
+        progress.waypoints = waypoints.clamp(0.0, 1.0);
+        progress.phase = phase;
+    }
+}
+
+/// message of a caught generation panic
+pub(crate) fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
@@ -1,7 +1,45 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batch;
+pub mod branding;
 pub mod brush;
+pub mod chunks;
+pub mod config;
+pub mod debug;
+pub mod diff;
+pub mod distance;
+pub mod doors;
+pub mod edges;
+pub mod freeze;
 pub mod generator;
+pub mod guidance;
+pub mod handle;
 pub mod map;
+pub mod maze;
+#[cfg(feature = "serde")]
+pub mod migration;
+pub mod modifiers;
 pub mod mutations;
+pub mod palette;
+pub mod pathfinding;
+pub mod patterns;
+pub mod pipeline;
 pub mod position;
+pub mod prefabs;
 pub mod random;
+pub mod regions;
+pub mod rooms;
+pub mod skips;
+pub mod smoothing;
+pub mod snapshot;
+pub mod spawn;
+pub mod spikes;
+pub mod stream;
+pub mod testing;
+pub mod timing;
+pub mod transform;
+pub mod tuning;
 pub mod walker;
+pub mod wfc;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
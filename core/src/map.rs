@@ -1,28 +1,226 @@
-use crate::position::{as_index, VectorView2};
-use ndarray::Array2;
+use crate::{
+    chunks::ChunkedTiles,
+    config::{ExportConfig, GenerationMetadata},
+    diff::MapDiff,
+    position::{as_index, VectorView2},
+    spawn::SpawnTeam,
+    transform::Transform,
+};
+use ndarray::{s, Array2, Zip};
 use twmap::{
-    AnyTile, CompressedData, GameLayer, GameTile, Group, Layer, Speedup, Switch, Tele, TileFlags,
-    Tune, TwMap, Version,
+    AnyTile, CompressedData, FrontLayer, GameLayer, GameTile, Group, Layer, Speedup, SpeedupLayer,
+    Switch, SwitchLayer, Tele, TeleLayer, TileFlags, Tune, TuneLayer, TwMap, Version,
 };
 
-// TileTag::Empty | TileTag::EmptyReserved => 0,
-// TileTag::Hookable | TileTag::Platform => 1,
-// TileTag::Freeze => 9,
-// TileTag::Spawn => 192,
-// TileTag::Start => 33,
-// TileTag::Finish => 34,
+/// first game layer id of the entities, spawns and pickups
+pub const ENTITY_OFFSET: u8 = 192;
+
+/// game layer blocks placed by the generator
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlockType {
+    #[default]
+    Empty,
+    Hookable,
+    Unhookable,
+    /// hookable wall the hook passes through, only the generator has it in the game layer, maps
+    /// get a hookable tile under a through tile in the front layer, see `Map::split_hookthrough`
+    Hookthrough,
+    Freeze,
+    Start,
+    Finish,
+    /// kill tile, touching it kills the player
+    Death,
+}
+
+impl BlockType {
+    pub const ALL: [Self; 8] = [
+        Self::Empty,
+        Self::Hookable,
+        Self::Unhookable,
+        Self::Hookthrough,
+        Self::Freeze,
+        Self::Start,
+        Self::Finish,
+        Self::Death,
+    ];
+
+    pub fn game_id(&self) -> u8 {
+        match self {
+            Self::Empty => 0,
+            Self::Hookable => 1,
+            Self::Unhookable => 3,
+            Self::Hookthrough => 66,
+            Self::Freeze => 9,
+            Self::Start => 33,
+            Self::Finish => 34,
+            Self::Death => 2,
+        }
+    }
+
+    pub fn tile(&self) -> GameTile {
+        GameTile::new(self.game_id(), TileFlags::empty())
+    }
+
+    pub fn from_game_id(id: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|block| block.game_id() == id)
+    }
+}
+
+/// game version an exported map is made for
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TargetVersion {
+    #[default]
+    DDNet06,
+    /// vanilla teeworlds 0.7, only the game layer is kept and ddnet tiles are remapped, so
+    /// hookthrough walls are plain hookable ones
+    Teeworlds07,
+}
+
+impl TargetVersion {
+    pub fn version(&self) -> Version {
+        match self {
+            Self::DDNet06 => Version::DDNet06,
+            Self::Teeworlds07 => Version::Teeworlds07,
+        }
+    }
+}
+
+/// game layer ids the blocks and spawns are exported with, for modded servers with their own
+/// entities, the generator itself always works with the ddnet ids of `BlockType::game_id`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TileMapping {
+    pub empty: u8,
+    pub hookable: u8,
+    pub unhookable: u8,
+    /// front layer tile over the hookable walls the hook passes through
+    pub hookthrough: u8,
+    pub freeze: u8,
+    pub start: u8,
+    pub finish: u8,
+    pub death: u8,
+    /// neutral, red and blue spawn
+    pub spawns: [u8; 3],
+}
+
+impl Default for TileMapping {
+    fn default() -> Self {
+        Self {
+            empty: BlockType::Empty.game_id(),
+            hookable: BlockType::Hookable.game_id(),
+            unhookable: BlockType::Unhookable.game_id(),
+            hookthrough: BlockType::Hookthrough.game_id(),
+            freeze: BlockType::Freeze.game_id(),
+            start: BlockType::Start.game_id(),
+            finish: BlockType::Finish.game_id(),
+            death: BlockType::Death.game_id(),
+            spawns: SPAWN_TEAMS.map(|team| team.game_id()),
+        }
+    }
+}
+
+impl TileMapping {
+    pub fn id(&self, block: BlockType) -> u8 {
+        match block {
+            BlockType::Empty => self.empty,
+            BlockType::Hookable => self.hookable,
+            BlockType::Unhookable => self.unhookable,
+            BlockType::Hookthrough => self.hookthrough,
+            BlockType::Freeze => self.freeze,
+            BlockType::Start => self.start,
+            BlockType::Finish => self.finish,
+            BlockType::Death => self.death,
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// exported id of a ddnet game layer id, other tiles keep theirs
+    pub fn map_id(&self, id: u8) -> u8 {
+        if let Some(block) = BlockType::from_game_id(id) {
+            return self.id(block);
+        }
+
+        SPAWN_TEAMS
+            .iter()
+            .position(|team| team.game_id() == id)
+            .map_or(id, |team| self.spawns[team])
+    }
+}
+
+const SPAWN_TEAMS: [SpawnTeam; 3] = [SpawnTeam::Neutral, SpawnTeam::Red, SpawnTeam::Blue];
+
+/// how pasted tiles combine with the game tiles under them
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PastePolicy {
+    /// every pasted tile replaces the one under it
+    #[default]
+    Overwrite,
+    /// empty pasted tiles keep the ones under them
+    SkipEmpty,
+    /// only empty tiles are replaced
+    FillEmpty,
+}
+
+impl PastePolicy {
+    /// like `merge` for tiles of any physics layer, default tiles count as empty
+    pub fn merge_any<T: AnyTile>(&self, under: T, pasted: T) -> T {
+        let empty = T::default();
+
+        match self {
+            Self::Overwrite => pasted,
+            Self::SkipEmpty if pasted == empty => under,
+            Self::FillEmpty if under != empty => under,
+            Self::SkipEmpty | Self::FillEmpty => pasted,
+        }
+    }
+
+    pub fn merge(&self, under: GameTile, pasted: GameTile) -> GameTile {
+        let empty = BlockType::Empty.game_id();
 
+        match self {
+            Self::Overwrite => pasted,
+            Self::SkipEmpty if pasted.id == empty => under,
+            Self::FillEmpty if under.id != empty => under,
+            Self::SkipEmpty | Self::FillEmpty => pasted,
+        }
+    }
+}
+
+/// closest vanilla tile of a game layer tile, ddnet only tiles without a counterpart become air
+fn vanilla_id(id: u8) -> u8 {
+    match id {
+        // air, solid, death, unhookable and the entities
+        0..=3 | 192..=202 => id,
+        _ => BlockType::Empty.game_id(),
+    }
+}
+
+#[derive(Clone)]
 pub struct Map {
     raw: TwMap,
+    /// game tiles kept when generating over this map
+    locked: Array2<bool>,
+    /// game tiles of a sparse map, its raw game layer is only a placeholder until `make_dense`
+    sparse: Option<SparseTiles>,
+}
+
+/// game tiles of a sparse map and its size, tiles past the size are ignored
+#[derive(Clone)]
+struct SparseTiles {
+    tiles: ChunkedTiles<GameTile>,
+    dim: (usize, usize),
 }
 
 impl Map {
     pub fn new() -> Self {
         let mut map = TwMap::empty(Version::DDNet06);
-        
-        map.info.author = "mapgen".to_string();
-        map.info.version = "1.0beta".to_string();
-        map.info.license = "CC0".to_string();
 
         map.groups.push(Group::physics());
         map.groups[0].layers.push(Layer::Game(GameLayer {
@@ -32,39 +230,490 @@ impl Map {
             )),
         }));
 
-        Self { raw: map }
+        let mut map = Self {
+            raw: map,
+            locked: Array2::from_elem((1, 1), false),
+            sparse: None,
+        };
+        map.set_info(&ExportConfig::default());
+
+        map
+    }
+
+    /// wraps a map with loaded physics layers as it is, nothing is locked
+    pub fn from_raw(raw: TwMap) -> Self {
+        let game: &GameLayer = raw.find_physics_layer().unwrap();
+        let locked = Array2::from_elem(game.tiles.unwrap_ref().dim(), false);
+
+        Self {
+            raw,
+            locked,
+            sparse: None,
+        }
+    }
+
+    /// map of `width` by `height` game tiles of `fill`, only chunks with other tiles are
+    /// allocated until the map is made dense, see `from_chunks`
+    pub fn sparse(width: usize, height: usize, fill: BlockType) -> Self {
+        Self::from_chunks(ChunkedTiles::new(fill.tile()), (width, height))
+    }
+
+    /// sparse map of the `dim` area of `tiles` at their origin, game tiles are read and written
+    /// through the chunks, anything working on the raw layers makes the map dense first
+    pub fn from_chunks(mut tiles: ChunkedTiles<GameTile>, dim: (usize, usize)) -> Self {
+        tiles.retain_area([0, 0], [dim.0 as i64 - 1, dim.1 as i64 - 1]);
+
+        let mut map = Self::new();
+        map.locked = Array2::from_elem((0, 0), false);
+        map.sparse = Some(SparseTiles { tiles, dim });
+
+        map
+    }
+
+    pub fn is_sparse(&self) -> bool {
+        self.sparse.is_some()
+    }
+
+    /// game tiles of a sparse map
+    pub fn chunked_tiles(&self) -> Option<&ChunkedTiles<GameTile>> {
+        self.sparse.as_ref().map(|sparse| &sparse.tiles)
+    }
+
+    /// moves the game tiles of a sparse map into its game layer, nothing is locked afterwards,
+    /// does nothing for dense maps
+    pub fn make_dense(&mut self) {
+        let Some(SparseTiles { tiles, dim }) = self.sparse.take() else {
+            return;
+        };
+
+        let game: &mut GameLayer = self.raw.find_physics_layer_mut().unwrap();
+        *game.tiles.unwrap_mut() = tiles.extract([0, 0], dim);
+        self.locked = Array2::from_elem(dim, false);
+    }
+
+    /// the wrapped map, unlike `finalize` the tiles layers are left as they are
+    pub fn into_raw(mut self) -> TwMap {
+        self.make_dense();
+        self.raw
+    }
+
+    /// game layer of a hand-made map converted to the generator's blocks, tiles without a
+    /// matching block become empty, the map's layers have to be loaded
+    pub fn from_twmap(map: &TwMap) -> Self {
+        let mut result = Self::new();
+
+        if let Some(game) = map.find_physics_layer::<GameLayer>() {
+            let tiles = game.tiles.unwrap_ref();
+            let (width, height) = tiles.dim();
+
+            result.reshape(width, height);
+            *result.game_layer().tiles.unwrap_mut() =
+                tiles.map(|tile| BlockType::from_game_id(tile.id).unwrap_or_default().tile());
+        }
+
+        result
+    }
+
+    pub fn set_info(&mut self, export: &ExportConfig) {
+        self.raw.info.author = export.author.clone();
+        self.raw.info.version = export.version.clone();
+        self.raw.info.credits = export.credits.clone();
+        self.raw.info.license = export.license.clone();
+    }
+
+    /// appends the metadata to the credits, keeping the credits set before
+    pub fn stamp_metadata(&mut self, metadata: &GenerationMetadata) {
+        let credits = &mut self.raw.info.credits;
+
+        if !credits.is_empty() {
+            credits.push_str(" | ");
+        }
+
+        credits.push_str(&metadata.to_string());
     }
 
     pub fn width(&self) -> usize {
+        // like twmap's shape, the width counts the columns
+        if let Some(sparse) = &self.sparse {
+            return sparse.dim.1;
+        }
+
         let game: &GameLayer = self.raw.find_physics_layer::<GameLayer>().unwrap();
 
         game.tiles.shape().w
     }
 
     pub fn height(&self) -> usize {
+        if let Some(sparse) = &self.sparse {
+            return sparse.dim.0;
+        }
+
         let game: &GameLayer = self.raw.find_physics_layer::<GameLayer>().unwrap();
 
         game.tiles.shape().h
     }
 
+    /// size of the game tiles in the order they are indexed in
+    pub fn dim(&self) -> (usize, usize) {
+        if let Some(sparse) = &self.sparse {
+            return sparse.dim;
+        }
+
+        let game: &GameLayer = self.raw.find_physics_layer().unwrap();
+
+        game.tiles.unwrap_ref().dim()
+    }
+
+    /// game tile at `index`, none outside the map
+    pub fn game_tile(&self, index: [usize; 2]) -> Option<GameTile> {
+        match &self.sparse {
+            Some(sparse) if index[0] < sparse.dim.0 && index[1] < sparse.dim.1 => {
+                Some(sparse.tiles.get([index[0] as i64, index[1] as i64]))
+            }
+            Some(_) => None,
+            None => {
+                let game: &GameLayer = self.raw.find_physics_layer().unwrap();
+
+                game.tiles.unwrap_ref().get(index).copied()
+            }
+        }
+    }
+
+    /// sets the game tile at `index`, tiles outside the map are dropped
+    pub fn set_game_tile(&mut self, index: [usize; 2], tile: GameTile) {
+        match &mut self.sparse {
+            Some(sparse) if index[0] < sparse.dim.0 && index[1] < sparse.dim.1 => {
+                sparse.tiles.set([index[0] as i64, index[1] as i64], tile);
+            }
+            Some(_) => {}
+            None => {
+                if let Some(under) = self.game_layer().tiles.unwrap_mut().get_mut(index) {
+                    *under = tile;
+                }
+            }
+        }
+    }
+
+    /// game layer with the map's tiles, makes a sparse map dense
     pub fn game_layer(&mut self) -> &mut GameLayer {
+        self.make_dense();
         self.raw.find_physics_layer_mut().unwrap()
     }
 
+    /// the wrapped map, the game layer of a sparse map is only a placeholder
+    pub fn raw_map(&self) -> &TwMap {
+        &self.raw
+    }
+
+    /// the wrapped map, makes a sparse map dense
     pub fn raw_map_mut(&mut self) -> &mut TwMap {
+        self.make_dense();
         &mut self.raw
     }
 
-    pub fn finalize(self) -> TwMap {
+    /// the wrapped map with its tiles layers shrunk, a sparse map is made dense for it
+    pub fn finalize(mut self) -> TwMap {
+        self.make_dense();
         self.raw.lossless_shrink_tiles_layers().unwrap()
     }
 
+    /// converts the map for `target`, dropping the physics layers and tiles it doesn't know
+    pub fn convert_to(&mut self, target: TargetVersion) {
+        if target == TargetVersion::Teeworlds07 {
+            self.raw
+                .physics_group_mut()
+                .layers
+                .retain(|layer| matches!(layer, Layer::Game(_)));
+
+            for tile in self.game_layer().tiles.unwrap_mut().iter_mut() {
+                let id = vanilla_id(tile.id);
+
+                if id != tile.id {
+                    *tile = GameTile::new(id, TileFlags::empty());
+                }
+            }
+        }
+
+        self.raw.version = target.version();
+    }
+
+    /// rewrites the ids of the game and front layer tiles with `mapping`, the last step of an
+    /// export since nothing recognizes the blocks afterwards
+    pub fn remap_tiles(&mut self, mapping: &TileMapping) {
+        if mapping.is_default() {
+            return;
+        }
+
+        let remap = |tile: &mut GameTile| tile.id = mapping.map_id(tile.id);
+
+        self.game_layer()
+            .tiles
+            .unwrap_mut()
+            .iter_mut()
+            .for_each(remap);
+
+        if let Some(front) = self.raw.find_physics_layer_mut::<FrontLayer>() {
+            front.tiles.unwrap_mut().iter_mut().for_each(remap);
+        }
+    }
+
+    /// locks the inclusive area from `min` to `max`, clamped to the map
+    pub fn lock_area(&mut self, min: [usize; 2], max: [usize; 2]) {
+        self.set_locked(min, max, true);
+    }
+
+    pub fn unlock_area(&mut self, min: [usize; 2], max: [usize; 2]) {
+        self.set_locked(min, max, false);
+    }
+
+    fn set_locked(&mut self, min: [usize; 2], max: [usize; 2], locked: bool) {
+        self.make_dense();
+
+        let (width, height) = self.locked.dim();
+        let max = [(max[0] + 1).min(width), (max[1] + 1).min(height)];
+        let min = [min[0].min(max[0]), min[1].min(max[1])];
+
+        self.locked
+            .slice_mut(s![min[0]..max[0], min[1]..max[1]])
+            .fill(locked);
+    }
+
+    pub fn is_locked(&self, index: [usize; 2]) -> bool {
+        self.locked.get(index).copied().unwrap_or(false)
+    }
+
+    pub fn locked(&self) -> &Array2<bool> {
+        &self.locked
+    }
+
+    /// copies the game tiles locked in `from` and locks them here as well, does nothing if the
+    /// maps differ in size
+    pub fn restore_locked(&mut self, from: &Map) {
+        self.make_dense();
+
+        if self.locked.dim() != from.locked.dim() {
+            return;
+        }
+
+        let source: &GameLayer = from.raw.find_physics_layer().unwrap();
+
+        Zip::from(self.game_layer().tiles.unwrap_mut())
+            .and(source.tiles.unwrap_ref())
+            .and(&from.locked)
+            .for_each(|tile, &source, &locked| {
+                if locked {
+                    *tile = source;
+                }
+            });
+
+        Zip::from(&mut self.locked)
+            .and(&from.locked)
+            .for_each(|locked, &from| *locked |= from);
+    }
+
+    /// applies a diff made against this map's game layer, all layers are cleared when the diff
+    /// changes the size
+    pub fn apply_diff(&mut self, diff: &MapDiff) {
+        let (width, height) = diff.dim();
+
+        self.reshape(width, height);
+        diff.apply_to(self.game_layer().tiles.unwrap_mut());
+    }
+
+    /// game tiles of the inclusive area from `min` to `max`, clamped to the map
+    pub fn copy_area(&self, min: [usize; 2], max: [usize; 2]) -> Array2<GameTile> {
+        let (width, height) = self.dim();
+        let max = [(max[0] + 1).min(width), (max[1] + 1).min(height)];
+        let min = [min[0].min(max[0]), min[1].min(max[1])];
+
+        if let Some(sparse) = &self.sparse {
+            let dim = (max[0] - min[0], max[1] - min[1]);
+
+            return sparse.tiles.extract([min[0] as i64, min[1] as i64], dim);
+        }
+
+        let game: &GameLayer = self.raw.find_physics_layer().unwrap();
+
+        game.tiles
+            .unwrap_ref()
+            .slice(s![min[0]..max[0], min[1]..max[1]])
+            .to_owned()
+    }
+
+    /// writes `tiles` with their top left corner at `at`, tiles past the map border are dropped
+    pub fn paste_area(&mut self, tiles: &Array2<GameTile>, at: [usize; 2], policy: PastePolicy) {
+        if self.is_sparse() {
+            for ((x, y), &pasted) in tiles.indexed_iter() {
+                let index = [at[0] + x, at[1] + y];
+
+                if let Some(under) = self.game_tile(index) {
+                    self.set_game_tile(index, policy.merge(under, pasted));
+                }
+            }
+
+            return;
+        }
+
+        let target = self.game_layer().tiles.unwrap_mut();
+        let (width, height) = target.dim();
+        let end = [
+            (at[0] + tiles.dim().0).min(width),
+            (at[1] + tiles.dim().1).min(height),
+        ];
+
+        if at[0] >= end[0] || at[1] >= end[1] {
+            return;
+        }
+
+        Zip::from(target.slice_mut(s![at[0]..end[0], at[1]..end[1]]))
+            .and(tiles.slice(s![..end[0] - at[0], ..end[1] - at[1]]))
+            .for_each(|under, &pasted| *under = policy.merge(*under, pasted));
+    }
+
+    /// copies every physics layer of `other` with its top left corner at `offset`, the map grows
+    /// to fit it and physics layers only `other` has are added, design groups of `other` are
+    /// left out, returns the number of tiles where both game layers had different blocks
+    pub fn blit(&mut self, other: &Map, offset: [usize; 2], policy: PastePolicy) -> usize {
+        fn blit_layer<T: AnyTile>(
+            target: &mut Array2<T>,
+            source: &Array2<T>,
+            offset: [usize; 2],
+            policy: PastePolicy,
+        ) {
+            let (width, height) = source.dim();
+            let area = s![offset[0]..offset[0] + width, offset[1]..offset[1] + height];
+
+            Zip::from(target.slice_mut(area))
+                .and(source)
+                .for_each(|under, &pasted| *under = policy.merge_any(*under, pasted));
+        }
+
+        if other.is_sparse() {
+            let mut other = other.clone();
+            other.make_dense();
+
+            return self.blit(&other, offset, policy);
+        }
+
+        self.make_dense();
+
+        let (width, height) = (other.width(), other.height());
+        self.grow(offset[0] + width, offset[1] + height);
+
+        let empty = BlockType::Empty.game_id();
+        let game: &GameLayer = other.raw.find_physics_layer().unwrap();
+        let area = s![offset[0]..offset[0] + width, offset[1]..offset[1] + height];
+
+        let conflicts = Zip::from(self.game_layer().tiles.unwrap_ref().slice(area))
+            .and(game.tiles.unwrap_ref())
+            .fold(0, |count, under, pasted| {
+                let conflict = under.id != empty && pasted.id != empty && under.id != pasted.id;
+
+                count + conflict as usize
+            });
+
+        for layer in other.raw.physics_group().layers.iter() {
+            match layer {
+                Layer::Game(l) => {
+                    let target = self.game_layer().tiles.unwrap_mut();
+                    blit_layer(target, l.tiles.unwrap_ref(), offset, policy);
+                }
+                Layer::Front(l) => {
+                    let target = self.front_layer().tiles.unwrap_mut();
+                    blit_layer(target, l.tiles.unwrap_ref(), offset, policy);
+                }
+                Layer::Tele(l) => {
+                    let target = self.tele_layer().tiles.unwrap_mut();
+                    blit_layer(target, l.tiles.unwrap_ref(), offset, policy);
+                }
+                Layer::Speedup(l) => {
+                    let target = self.speedup_layer().tiles.unwrap_mut();
+                    blit_layer(target, l.tiles.unwrap_ref(), offset, policy);
+                }
+                Layer::Switch(l) => {
+                    let target = self.switch_layer().tiles.unwrap_mut();
+                    blit_layer(target, l.tiles.unwrap_ref(), offset, policy);
+                }
+                Layer::Tune(l) => {
+                    let target = self.tune_layer().tiles.unwrap_mut();
+                    blit_layer(target, l.tiles.unwrap_ref(), offset, policy);
+                }
+                _ => {}
+            }
+        }
+
+        Zip::from(self.locked.slice_mut(area))
+            .and(&other.locked)
+            .for_each(|locked, &other| *locked |= other);
+
+        conflicts
+    }
+
+    /// turns the map a quarter clockwise
+    pub fn rotate90(&mut self) {
+        self.transform(Transform::default().rotated());
+    }
+
+    /// flips the map's columns
+    pub fn mirror_x(&mut self) {
+        self.transform(Transform::default().mirrored_x());
+    }
+
+    /// flips the map's rows
+    pub fn mirror_y(&mut self) {
+        self.transform(Transform::default().mirrored_y());
+    }
+
+    /// transforms every physics layer and the locked tiles, spawns and finishes move along with
+    /// the game layer, game and front tiles keep facing the same way relative to the map,
+    /// speedup angles are left alone
+    pub fn transform(&mut self, transform: Transform) {
+        if transform.is_identity() {
+            return;
+        }
+
+        fn transform_layer<T: Clone>(tiles: &mut Array2<T>, transform: Transform) {
+            *tiles = transform.apply(tiles.view());
+        }
+
+        fn orient(tiles: &mut Array2<GameTile>, transform: Transform) {
+            transform_layer(tiles, transform);
+            tiles.map_inplace(|tile| tile.flags = transform.apply_flags(tile.flags));
+        }
+
+        self.make_dense();
+        self.locked = transform.apply(self.locked.view());
+
+        for layer in self.raw.physics_group_mut().layers.iter_mut() {
+            match layer {
+                Layer::Game(l) => orient(l.tiles.unwrap_mut(), transform),
+                Layer::Front(l) => orient(l.tiles.unwrap_mut(), transform),
+                Layer::Tele(l) => transform_layer(l.tiles.unwrap_mut(), transform),
+                Layer::Speedup(l) => transform_layer(l.tiles.unwrap_mut(), transform),
+                Layer::Switch(l) => transform_layer(l.tiles.unwrap_mut(), transform),
+                Layer::Tune(l) => transform_layer(l.tiles.unwrap_mut(), transform),
+                _ => {}
+            }
+        }
+    }
+
     /// clears all the placed tiles
     pub fn reshape(&mut self, width: usize, height: usize) {
         if self.width() == width && self.height() == height {
             return;
         }
 
+        if let Some(sparse) = &mut self.sparse {
+            *sparse = SparseTiles {
+                tiles: ChunkedTiles::new(BlockType::Empty.tile()),
+                dim: (width, height),
+            };
+
+            return;
+        }
+
+        self.locked = Array2::from_elem((width, height), false);
+
         fn reshape_layer<T: AnyTile>(tiles: &mut Array2<T>, width: usize, height: usize) {
             *tiles = Array2::from_elem((width, height), Default::default());
         }
@@ -75,23 +724,158 @@ impl Map {
                 Layer::Front(l) => reshape_layer(l.tiles.unwrap_mut(), width, height),
                 Layer::Tele(l) => reshape_layer(l.tiles.unwrap_mut(), width, height),
                 Layer::Speedup(l) => reshape_layer(l.tiles.unwrap_mut(), width, height),
+                Layer::Switch(l) => reshape_layer(l.tiles.unwrap_mut(), width, height),
                 Layer::Tune(l) => reshape_layer(l.tiles.unwrap_mut(), width, height),
                 _ => {}
             }
         }
     }
 
+    /// enlarges every physics layer to `width` by `height` keeping the tiles at the top left,
+    /// unlike `reshape`
+    pub fn grow(&mut self, width: usize, height: usize) {
+        if self.width() >= width && self.height() >= height {
+            return;
+        }
+
+        fn grow_layer<T: Clone + Default>(tiles: &mut Array2<T>, width: usize, height: usize) {
+            let (old_width, old_height) = tiles.dim();
+            let mut grown = Array2::from_elem((width, height), Default::default());
+
+            grown.slice_mut(s![..old_width, ..old_height]).assign(tiles);
+            *tiles = grown;
+        }
+
+        let width = width.max(self.width());
+        let height = height.max(self.height());
+
+        self.make_dense();
+        grow_layer(&mut self.locked, width, height);
+
+        for layer in self.raw.physics_group_mut().layers.iter_mut() {
+            match layer {
+                Layer::Game(l) => grow_layer(l.tiles.unwrap_mut(), width, height),
+                Layer::Front(l) => grow_layer(l.tiles.unwrap_mut(), width, height),
+                Layer::Tele(l) => grow_layer(l.tiles.unwrap_mut(), width, height),
+                Layer::Speedup(l) => grow_layer(l.tiles.unwrap_mut(), width, height),
+                Layer::Switch(l) => grow_layer(l.tiles.unwrap_mut(), width, height),
+                Layer::Tune(l) => grow_layer(l.tiles.unwrap_mut(), width, height),
+                _ => {}
+            }
+        }
+    }
+
+    /// shrinks all physics layers to the bounding box of the tiles players can enter, plus
+    /// `padding` tiles around it, returns the top left corner of the kept area
+    pub fn crop_to_content(&mut self, padding: usize) -> [usize; 2] {
+        let solid = [
+            BlockType::Hookable,
+            BlockType::Unhookable,
+            BlockType::Hookthrough,
+            BlockType::Freeze,
+        ];
+
+        let tiles = self.game_layer().tiles.unwrap_ref();
+        let (width, height) = tiles.dim();
+
+        let content = tiles
+            .indexed_iter()
+            .filter(|(_, tile)| !solid.iter().any(|block| tile.id == block.game_id()))
+            .map(|((x, y), _)| [x, y]);
+
+        let (min, max) = content.fold(([usize::MAX, usize::MAX], [0, 0]), |(min, max), [x, y]| {
+            (
+                [min[0].min(x), min[1].min(y)],
+                [max[0].max(x), max[1].max(y)],
+            )
+        });
+
+        if min[0] > max[0] {
+            return [0, 0];
+        }
+
+        let min = [
+            min[0].saturating_sub(padding),
+            min[1].saturating_sub(padding),
+        ];
+        let max = [
+            (max[0] + padding + 1).min(width),
+            (max[1] + padding + 1).min(height),
+        ];
+
+        fn crop_layer<T: AnyTile>(tiles: &mut Array2<T>, min: [usize; 2], max: [usize; 2]) {
+            *tiles = tiles.slice(s![min[0]..max[0], min[1]..max[1]]).to_owned();
+        }
+
+        self.locked = self
+            .locked
+            .slice(s![min[0]..max[0], min[1]..max[1]])
+            .to_owned();
+
+        for layer in self.raw.physics_group_mut().layers.iter_mut() {
+            match layer {
+                Layer::Game(l) => crop_layer(l.tiles.unwrap_mut(), min, max),
+                Layer::Front(l) => crop_layer(l.tiles.unwrap_mut(), min, max),
+                Layer::Tele(l) => crop_layer(l.tiles.unwrap_mut(), min, max),
+                Layer::Speedup(l) => crop_layer(l.tiles.unwrap_mut(), min, max),
+                Layer::Switch(l) => crop_layer(l.tiles.unwrap_mut(), min, max),
+                Layer::Tune(l) => crop_layer(l.tiles.unwrap_mut(), min, max),
+                _ => {}
+            }
+        }
+
+        min
+    }
+
+    /// turns every game tile within `thickness` tiles of the map edge into hookable
+    pub fn seal_border(&mut self, thickness: usize) {
+        let tiles = self.game_layer().tiles.unwrap_mut();
+        let (width, height) = tiles.dim();
+
+        for ((x, y), tile) in tiles.indexed_iter_mut() {
+            let edge_distance = x.min(y).min(width - 1 - x).min(height - 1 - y);
+
+            if edge_distance < thickness {
+                *tile = BlockType::Hookable.tile();
+            }
+        }
+    }
+
+    /// whether no tile on the map edge lets players leave the map
+    pub fn is_sealed(&self) -> bool {
+        let solid = [
+            BlockType::Hookable,
+            BlockType::Unhookable,
+            BlockType::Hookthrough,
+        ];
+
+        let (width, height) = self.dim();
+        let (right, bottom) = (width.saturating_sub(1), height.saturating_sub(1));
+
+        let edge = (0..width)
+            .flat_map(|x| [[x, 0], [x, bottom]])
+            .chain((0..height).flat_map(|y| [[0, y], [right, y]]));
+
+        edge.filter_map(|index| self.game_tile(index))
+            .all(|tile| solid.iter().any(|block| tile.id == block.game_id()))
+    }
+
     pub fn clear(&mut self) {
         fn clear_layer<T: AnyTile>(tiles: &mut Array2<T>) {
             tiles.fill(Default::default());
         }
 
+        if let Some(sparse) = &mut self.sparse {
+            sparse.tiles = ChunkedTiles::new(GameTile::default());
+        }
+
         for layer in self.raw.physics_group_mut().layers.iter_mut() {
             match layer {
                 Layer::Game(l) => clear_layer(l.tiles.unwrap_mut()),
                 Layer::Front(l) => clear_layer(l.tiles.unwrap_mut()),
                 Layer::Tele(l) => clear_layer(l.tiles.unwrap_mut()),
                 Layer::Speedup(l) => clear_layer(l.tiles.unwrap_mut()),
+                Layer::Switch(l) => clear_layer(l.tiles.unwrap_mut()),
                 Layer::Tune(l) => clear_layer(l.tiles.unwrap_mut()),
                 _ => {}
             }
@@ -99,88 +883,186 @@ impl Map {
     }
 
     pub fn fill_game(&mut self, tile: GameTile) {
+        if let Some(sparse) = &mut self.sparse {
+            sparse.tiles = ChunkedTiles::new(tile);
+            return;
+        }
+
         if let Some(layer) = self.raw.find_physics_layer_mut::<GameLayer>() {
             layer.tiles.unwrap_mut().fill(tile);
         }
     }
 
     pub fn fill_front(&mut self, tile: GameTile) {
-        let _ = self.raw.physics_group_mut().layers.iter_mut().map(|layer| {
-            if let Layer::Front(layer) = layer {
-                layer.tiles.unwrap_mut().fill(tile);
-            }
-        });
+        if let Some(layer) = self.raw.find_physics_layer_mut::<FrontLayer>() {
+            layer.tiles.unwrap_mut().fill(tile);
+        }
     }
 
     pub fn fill_switch(&mut self, tile: Switch) {
-        let _ = self.raw.physics_group_mut().layers.iter_mut().map(|layer| {
-            if let Layer::Switch(layer) = layer {
-                layer.tiles.unwrap_mut().fill(tile);
-            }
-        });
+        if let Some(layer) = self.raw.find_physics_layer_mut::<SwitchLayer>() {
+            layer.tiles.unwrap_mut().fill(tile);
+        }
     }
 
     pub fn fill_tele(&mut self, tile: Tele) {
-        let _ = self.raw.physics_group_mut().layers.iter_mut().map(|layer| {
-            if let Layer::Tele(layer) = layer {
-                layer.tiles.unwrap_mut().fill(tile);
-            }
-        });
+        if let Some(layer) = self.raw.find_physics_layer_mut::<TeleLayer>() {
+            layer.tiles.unwrap_mut().fill(tile);
+        }
     }
 
     pub fn fill_speedup(&mut self, tile: Speedup) {
-        let _ = self.raw.physics_group_mut().layers.iter_mut().map(|layer| {
-            if let Layer::Speedup(layer) = layer {
-                layer.tiles.unwrap_mut().fill(tile);
-            }
-        });
+        if let Some(layer) = self.raw.find_physics_layer_mut::<SpeedupLayer>() {
+            layer.tiles.unwrap_mut().fill(tile);
+        }
     }
 
     pub fn fill_tune(&mut self, tile: Tune) {
-        let _ = self.raw.physics_group_mut().layers.iter_mut().map(|layer| {
-            if let Layer::Tune(layer) = layer {
-                layer.tiles.unwrap_mut().fill(tile);
-            }
-        });
+        if let Some(layer) = self.raw.find_physics_layer_mut::<TuneLayer>() {
+            layer.tiles.unwrap_mut().fill(tile);
+        }
     }
 
     pub fn set_tile_game(&mut self, pos: VectorView2, tile: GameTile) {
-        let _ = self.raw.physics_group_mut().layers.iter_mut().map(|layer| {
-            if let Layer::Game(layer) = layer {
-                layer.tiles.unwrap_mut()[as_index(pos)] = tile;
-            }
-        });
+        self.set_game_tile(as_index(pos), tile);
     }
 
     pub fn set_tile_front(&mut self, pos: VectorView2, tile: GameTile) {
-        let _ = self.raw.physics_group_mut().layers.iter_mut().map(|layer| {
-            if let Layer::Front(layer) = layer {
-                layer.tiles.unwrap_mut()[as_index(pos)] = tile;
-            }
-        });
+        self.front_layer().tiles.unwrap_mut()[as_index(pos)] = tile;
     }
 
     pub fn set_tile_tele(&mut self, pos: VectorView2, tile: Tele) {
-        let _ = self.raw.physics_group_mut().layers.iter_mut().map(|layer| {
-            if let Layer::Tele(layer) = layer {
-                layer.tiles.unwrap_mut()[as_index(pos)] = tile;
-            }
-        });
+        self.tele_layer().tiles.unwrap_mut()[as_index(pos)] = tile;
     }
 
     pub fn set_tile_switch(&mut self, pos: VectorView2, tile: Switch) {
-        let _ = self.raw.physics_group_mut().layers.iter_mut().map(|layer| {
-            if let Layer::Switch(layer) = layer {
-                layer.tiles.unwrap_mut()[as_index(pos)] = tile;
-            }
-        });
+        self.switch_layer().tiles.unwrap_mut()[as_index(pos)] = tile;
     }
 
     pub fn set_tile_tune(&mut self, pos: VectorView2, tile: Tune) {
-        let _ = self.raw.physics_group_mut().layers.iter_mut().map(|layer| {
-            if let Layer::Tune(layer) = layer {
-                layer.tiles.unwrap_mut()[as_index(pos)] = tile;
-            }
-        });
+        if let Some(layer) = self.raw.find_physics_layer_mut::<TuneLayer>() {
+            layer.tiles.unwrap_mut()[as_index(pos)] = tile;
+        }
+    }
+
+    /// places a teleporter tile of channel `number` at `index`
+    pub fn set_tele(&mut self, index: [usize; 2], id: u8, number: u8) {
+        self.tele_layer().tiles.unwrap_mut()[index] = Tele { number, id };
+    }
+
+    /// places a switch tile of channel `number` at `index`, `delay` is in seconds for the
+    /// timed switches
+    pub fn set_switch(&mut self, index: [usize; 2], id: u8, number: u8, delay: u8) {
+        self.switch_layer().tiles.unwrap_mut()[index] = Switch {
+            number,
+            id,
+            flags: TileFlags::empty(),
+            delay,
+        };
+    }
+
+    /// front layer, added on first use, its tiles act together with the game tiles under them
+    pub fn front_layer(&mut self) -> &mut FrontLayer {
+        if self.raw.find_physics_layer::<FrontLayer>().is_none() {
+            let tiles = CompressedData::Loaded(self.empty_tiles());
+            self.push_physics_layer(Layer::Front(FrontLayer { tiles }));
+        }
+
+        self.raw.find_physics_layer_mut().unwrap()
+    }
+
+    /// tele layer, added on first use
+    pub fn tele_layer(&mut self) -> &mut TeleLayer {
+        if self.raw.find_physics_layer::<TeleLayer>().is_none() {
+            let tiles = CompressedData::Loaded(self.empty_tiles());
+            self.push_physics_layer(Layer::Tele(TeleLayer { tiles }));
+        }
+
+        self.raw.find_physics_layer_mut().unwrap()
+    }
+
+    /// speedup layer, added on first use
+    pub fn speedup_layer(&mut self) -> &mut SpeedupLayer {
+        if self.raw.find_physics_layer::<SpeedupLayer>().is_none() {
+            let tiles = CompressedData::Loaded(self.empty_tiles());
+            self.push_physics_layer(Layer::Speedup(SpeedupLayer { tiles }));
+        }
+
+        self.raw.find_physics_layer_mut().unwrap()
+    }
+
+    /// tune layer, added on first use
+    pub fn tune_layer(&mut self) -> &mut TuneLayer {
+        if self.raw.find_physics_layer::<TuneLayer>().is_none() {
+            let tiles = CompressedData::Loaded(self.empty_tiles());
+            self.push_physics_layer(Layer::Tune(TuneLayer { tiles }));
+        }
+
+        self.raw.find_physics_layer_mut().unwrap()
+    }
+
+    /// switch layer, added on first use
+    pub fn switch_layer(&mut self) -> &mut SwitchLayer {
+        if self.raw.find_physics_layer::<SwitchLayer>().is_none() {
+            let tiles = CompressedData::Loaded(self.empty_tiles());
+            self.push_physics_layer(Layer::Switch(SwitchLayer { tiles }));
+        }
+
+        self.raw.find_physics_layer_mut().unwrap()
+    }
+
+    fn empty_tiles<T: AnyTile>(&self) -> Array2<T> {
+        Array2::from_elem((self.width(), self.height()), Default::default())
+    }
+
+    fn push_physics_layer(&mut self, layer: Layer) {
+        self.make_dense();
+        self.raw.physics_group_mut().layers.push(layer);
+    }
+
+    /// moves the game tiles of `block` into the front layer, leaving air behind, they work the
+    /// same there but designs can draw them apart from the walls
+    pub fn move_to_front(&mut self, block: BlockType) {
+        let id = block.game_id();
+        let tiles = self.game_layer().tiles.unwrap_mut();
+        let moved = tiles.map(|tile| tile.id == id);
+
+        tiles
+            .iter_mut()
+            .filter(|tile| tile.id == id)
+            .for_each(|tile| *tile = BlockType::Empty.tile());
+
+        Zip::from(self.front_layer().tiles.unwrap_mut())
+            .and(&moved)
+            .for_each(|tile, &moved| {
+                if moved {
+                    *tile = block.tile();
+                }
+            });
+    }
+
+    /// turns the hookthrough game tiles into hookable ones under a through tile in the front
+    /// layer, a through tile alone doesn't collide, the front layer is only added if needed
+    pub fn split_hookthrough(&mut self) {
+        let through = BlockType::Hookthrough;
+        let tiles = self.game_layer().tiles.unwrap_mut();
+        let split = tiles.map(|tile| tile.id == through.game_id());
+
+        if !split.iter().any(|&split| split) {
+            return;
+        }
+
+        tiles
+            .iter_mut()
+            .filter(|tile| tile.id == through.game_id())
+            .for_each(|tile| *tile = BlockType::Hookable.tile());
+
+        Zip::from(self.front_layer().tiles.unwrap_mut())
+            .and(&split)
+            .for_each(|tile, &split| {
+                if split {
+                    *tile = through.tile();
+                }
+            });
     }
 }
@@ -0,0 +1,305 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use ndarray::Array2;
+use twmap::GameTile;
+
+use crate::{map::BlockType, position::TileIndex, random::Random};
+
+/// neighbor offsets of a maze cell
+const CELL_OFFSETS: [[isize; 2]; 4] = [[1, 0], [-1, 0], [0, 1], [0, -1]];
+
+/// mazes with fewer cells aren't worth their walls
+const MIN_CELLS: usize = 4;
+
+/// cell with the offset to the cell it's connected to
+type Passage = ([usize; 2], [isize; 2]);
+
+/// fills large open caverns with maze walls, the cells are laid out on a coarse grid inside the
+/// tiles far enough from any block and connected with a recursive backtracker
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MazeConfig {
+    /// distance to the nearest block an empty tile needs to be open, the band closer to the
+    /// blocks stays free around the maze, at least 2
+    pub min_clearance: u32,
+    /// open tiles a cavern needs to get a maze
+    pub min_area: usize,
+    /// corridor width of the maze in tiles
+    pub cell_size: usize,
+    pub wall_thickness: usize,
+    /// gaps opened in the outer walls of every maze
+    pub entrances: usize,
+}
+
+impl Default for MazeConfig {
+    fn default() -> Self {
+        Self {
+            min_clearance: 6,
+            min_area: 400,
+            cell_size: 5,
+            wall_thickness: 1,
+            entrances: 2,
+        }
+    }
+}
+
+/// maze built in one cavern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Maze {
+    /// top left tile of the maze grid
+    pub origin: [usize; 2],
+    pub cells: usize,
+    /// tiles turned into walls
+    pub walls: Vec<[usize; 2]>,
+}
+
+impl MazeConfig {
+    fn pitch(&self) -> usize {
+        self.cell_size.max(1) + self.wall_thickness
+    }
+
+    /// turns every cavern of at least `min_area` open tiles into mazes of `wall` blocks,
+    /// `distances` holds the distance of every tile to the nearest block
+    pub fn apply(
+        &self,
+        tiles: &mut Array2<GameTile>,
+        distances: &Array2<u32>,
+        wall: BlockType,
+        prng: &mut Random,
+    ) -> Vec<Maze> {
+        let clearance = self.min_clearance.max(2);
+        let mut labels = Array2::from_elem(tiles.dim(), 0);
+        let mut mazes = Vec::new();
+        let mut next_label = 0;
+
+        for ((x, y), &distance) in distances.indexed_iter() {
+            if distance < clearance || labels[[x, y]] != 0 {
+                continue;
+            }
+
+            next_label += 1;
+
+            let cavern = label_cavern(distances, clearance, &mut labels, [x, y], next_label);
+
+            if cavern.len() < self.min_area {
+                continue;
+            }
+
+            for maze in self.build(&labels, &cavern, next_label, prng) {
+                for &index in maze.walls.iter() {
+                    tiles[index] = wall.tile();
+                }
+
+                mazes.push(maze);
+            }
+        }
+
+        mazes
+    }
+
+    /// mazes of one cavern, a cavern can be split into several by its shape
+    fn build(
+        &self,
+        labels: &Array2<usize>,
+        cavern: &[[usize; 2]],
+        label: usize,
+        prng: &mut Random,
+    ) -> Vec<Maze> {
+        let pitch = self.pitch();
+        let origin = [
+            cavern.iter().map(|index| index[0]).min().unwrap(),
+            cavern.iter().map(|index| index[1]).min().unwrap(),
+        ];
+        let extent = [
+            cavern.iter().map(|index| index[0]).max().unwrap() + 1 - origin[0],
+            cavern.iter().map(|index| index[1]).max().unwrap() + 1 - origin[1],
+        ];
+        let grid = (
+            extent[0].saturating_sub(self.wall_thickness) / pitch,
+            extent[1].saturating_sub(self.wall_thickness) / pitch,
+        );
+
+        // cells whose corridor and surrounding walls lie inside the cavern
+        let usable = Array2::from_shape_fn(grid, |(cx, cy)| {
+            let [x, y] = self.block_origin(origin, [cx, cy]);
+            let size = pitch + self.wall_thickness;
+
+            (x..x + size).all(|x| (y..y + size).all(|y| labels[[x, y]] == label))
+        });
+
+        let mut visited = Array2::from_elem(grid, false);
+        let mut mazes = Vec::new();
+
+        for ((cx, cy), &usable_cell) in usable.indexed_iter() {
+            if !usable_cell || visited[[cx, cy]] {
+                continue;
+            }
+
+            let (cells, passages) = backtrack(&usable, &mut visited, [cx, cy], prng);
+
+            if cells.len() < MIN_CELLS {
+                continue;
+            }
+
+            mazes.push(self.draw(origin, &usable, &cells, &passages, prng));
+        }
+
+        mazes
+    }
+
+    /// walls of a maze, every cell is framed and the walls between connected cells as well as
+    /// a few outer ones are carved again
+    fn draw(
+        &self,
+        origin: [usize; 2],
+        usable: &Array2<bool>,
+        cells: &[[usize; 2]],
+        passages: &[Passage],
+        prng: &mut Random,
+    ) -> Maze {
+        let wall = self.wall_thickness;
+        let size = self.pitch() + wall;
+        let mut walls = BTreeSet::new();
+
+        for &cell in cells {
+            let [x, y] = self.block_origin(origin, cell);
+
+            for bx in x..x + size {
+                for by in y..y + size {
+                    let inside = (x + wall..x + size - wall).contains(&bx)
+                        && (y + wall..y + size - wall).contains(&by);
+
+                    if !inside {
+                        walls.insert([bx, by]);
+                    }
+                }
+            }
+        }
+
+        for &(cell, offset) in passages {
+            for index in self.wall_segment(origin, cell, offset) {
+                walls.remove(&index);
+            }
+        }
+
+        // every usable cell next to the maze belongs to it, so the outer walls face free tiles
+        let mut outer: Vec<Passage> = cells
+            .iter()
+            .flat_map(|&cell| CELL_OFFSETS.iter().map(move |&offset| (cell, offset)))
+            .filter(|&(cell, offset)| {
+                cell.checked_offset(offset)
+                    .is_none_or(|next| usable.get(next) != Some(&true))
+            })
+            .collect();
+
+        for _ in 0..self.entrances.min(outer.len()) {
+            let (cell, offset) = outer.swap_remove(prng.in_range(0..outer.len()));
+
+            for index in self.wall_segment(origin, cell, offset) {
+                walls.remove(&index);
+            }
+        }
+
+        Maze {
+            origin,
+            cells: cells.len(),
+            walls: walls.into_iter().collect(),
+        }
+    }
+
+    /// top left tile of the walls around a cell
+    fn block_origin(&self, origin: [usize; 2], cell: [usize; 2]) -> [usize; 2] {
+        let pitch = self.pitch();
+
+        [origin[0] + cell[0] * pitch, origin[1] + cell[1] * pitch]
+    }
+
+    /// wall tiles on the `offset` side of a cell, without the corners
+    fn wall_segment(
+        &self,
+        origin: [usize; 2],
+        cell: [usize; 2],
+        offset: [isize; 2],
+    ) -> Vec<[usize; 2]> {
+        let [x, y] = self.block_origin(origin, cell);
+        let wall = self.wall_thickness;
+        let pitch = self.pitch();
+
+        let across = |start: usize, offset: isize| match offset {
+            1 => start + pitch..start + pitch + wall,
+            -1 => start..start + wall,
+            _ => start + wall..start + pitch,
+        };
+
+        let xs = across(x, offset[0]);
+        let ys = across(y, offset[1]);
+
+        xs.flat_map(|x| ys.clone().map(move |y| [x, y])).collect()
+    }
+}
+
+/// labels the open tiles connected to `start` and returns them
+fn label_cavern(
+    distances: &Array2<u32>,
+    clearance: u32,
+    labels: &mut Array2<usize>,
+    start: [usize; 2],
+    label: usize,
+) -> Vec<[usize; 2]> {
+    let mut cavern = vec![start];
+    let mut queue = VecDeque::from([start]);
+    labels[start] = label;
+
+    while let Some(current) = queue.pop_front() {
+        for next in current.neighbors(distances.dim(), false) {
+            if distances[next] >= clearance && labels[next] == 0 {
+                labels[next] = label;
+                cavern.push(next);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    cavern
+}
+
+/// recursive backtracker over the usable cells connected to `start`, returns the reached cells
+/// and the passages as a cell with the offset to the next one
+fn backtrack(
+    usable: &Array2<bool>,
+    visited: &mut Array2<bool>,
+    start: [usize; 2],
+    prng: &mut Random,
+) -> (Vec<[usize; 2]>, Vec<Passage>) {
+    let mut cells = vec![start];
+    let mut passages = Vec::new();
+    let mut stack = vec![start];
+    visited[start] = true;
+
+    while let Some(&current) = stack.last() {
+        let options: Vec<[isize; 2]> = CELL_OFFSETS
+            .into_iter()
+            .filter(|&offset| {
+                current
+                    .checked_offset(offset)
+                    .is_some_and(|next| usable.get(next) == Some(&true) && !visited[next])
+            })
+            .collect();
+
+        if options.is_empty() {
+            stack.pop();
+            continue;
+        }
+
+        let offset = *prng.pick(&options);
+        let next = current.checked_offset(offset).unwrap();
+
+        visited[next] = true;
+        cells.push(next);
+        passages.push((current, offset));
+        stack.push(next);
+    }
+
+    (cells, passages)
+}
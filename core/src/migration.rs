@@ -0,0 +1,99 @@
+use std::{error::Error, fmt};
+
+use serde_json::{Map, Value};
+
+use crate::config::GenerationPreset;
+
+/// schema of the presets written by this version
+pub const PRESET_SCHEMA: u64 = 1;
+
+/// preset field holding the schema, presets from before the versioning have none and count as
+/// schema 0
+pub const SCHEMA_FIELD: &str = "schema";
+
+/// upgrade of a raw preset from `schema - 1` to `schema`
+struct Migration {
+    schema: u64,
+    description: &'static str,
+    apply: fn(&mut Map<String, Value>),
+}
+
+const MIGRATIONS: &[Migration] = &[Migration {
+    schema: 1,
+    description: "stamps the schema version",
+    apply: |_| {},
+}];
+
+#[derive(Debug)]
+pub enum MigrationError {
+    /// the preset isn't a json object
+    NotAnObject,
+    /// the preset was written by a newer version
+    Newer(u64),
+    /// the migrated preset doesn't deserialize
+    Invalid(serde_json::Error),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAnObject => write!(f, "preset isn't an object"),
+            Self::Newer(schema) => write!(
+                f,
+                "preset schema {} is newer than the supported {}",
+                schema, PRESET_SCHEMA
+            ),
+            Self::Invalid(err) => write!(f, "invalid preset: {}", err),
+        }
+    }
+}
+
+impl Error for MigrationError {}
+
+/// schema a raw preset was written with
+pub fn schema_of(value: &Value) -> u64 {
+    value.get(SCHEMA_FIELD).and_then(Value::as_u64).unwrap_or(0)
+}
+
+/// runs the migrations a raw preset is missing and stamps the current schema, returns the
+/// descriptions of the applied ones
+pub fn migrate(value: &mut Value) -> Result<Vec<&'static str>, MigrationError> {
+    let schema = schema_of(value);
+
+    if schema > PRESET_SCHEMA {
+        return Err(MigrationError::Newer(schema));
+    }
+
+    let fields = value.as_object_mut().ok_or(MigrationError::NotAnObject)?;
+    let mut applied = Vec::new();
+
+    for migration in MIGRATIONS
+        .iter()
+        .filter(|migration| migration.schema > schema)
+    {
+        (migration.apply)(fields);
+        applied.push(migration.description);
+    }
+
+    fields.insert(SCHEMA_FIELD.to_owned(), PRESET_SCHEMA.into());
+
+    Ok(applied)
+}
+
+/// migrates and deserializes a raw preset
+pub fn load_preset(mut value: Value) -> Result<GenerationPreset, MigrationError> {
+    migrate(&mut value)?;
+
+    serde_json::from_value(value).map_err(MigrationError::Invalid)
+}
+
+/// raw preset of the current schema, fields left at their defaults are written out too
+pub fn save_preset(preset: &GenerationPreset) -> Value {
+    let mut value = serde_json::to_value(preset).expect("presets always serialize");
+
+    if let Some(fields) = value.as_object_mut() {
+        fields.insert(SCHEMA_FIELD.to_owned(), PRESET_SCHEMA.into());
+    }
+
+    value
+}
@@ -0,0 +1,101 @@
+/// when a scheduled modifier starts, each modifier starts once per walker config
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ModifierTrigger {
+    /// steps walked since the generation started, branches included
+    Step(usize),
+    /// straight line distance between the walker and the spawn in tiles
+    Distance(f32),
+    /// main route waypoint the walker heads to
+    Waypoint(usize),
+}
+
+impl Default for ModifierTrigger {
+    fn default() -> Self {
+        Self::Step(0)
+    }
+}
+
+impl ModifierTrigger {
+    pub fn fired(&self, step: usize, distance: f32, waypoint: usize) -> bool {
+        match *self {
+            Self::Step(start) => step >= start,
+            Self::Distance(start) => distance >= start,
+            Self::Waypoint(start) => waypoint >= start,
+        }
+    }
+}
+
+/// what a scheduled modifier does while it's active
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ModifierEffect {
+    /// scales the brush towards `scale` and back, reaching it at `peak` of the duration
+    Pulse { scale: f32, peak: f32 },
+    /// scales the brush from its size to `scale` over the duration
+    Fade { scale: f32 },
+    /// carves the brush a second time up to `amount` tiles away in a random direction
+    Jitter { amount: usize },
+    /// turns the walls up to `thickness` tiles around the brush into freeze
+    FreezeBurst { thickness: usize },
+}
+
+impl Default for ModifierEffect {
+    fn default() -> Self {
+        Self::Pulse {
+            scale: 0.5,
+            peak: 0.5,
+        }
+    }
+}
+
+impl ModifierEffect {
+    /// brush scale at `strength`, 1.0 for effects leaving the brush size alone
+    pub fn scale(&self, strength: f32) -> f32 {
+        match *self {
+            Self::Pulse { scale, .. } | Self::Fade { scale } => 1.0 + (scale - 1.0) * strength,
+            Self::Jitter { .. } | Self::FreezeBurst { .. } => 1.0,
+        }
+    }
+
+    pub fn scales_brush(&self) -> bool {
+        matches!(self, Self::Pulse { .. } | Self::Fade { .. })
+    }
+}
+
+/// walker modifier active for a number of steps once its trigger fires
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ScheduledModifier {
+    pub trigger: ModifierTrigger,
+    /// steps the modifier stays active for, 0 keeps it active at full strength until the walker
+    /// config changes
+    pub duration: usize,
+    pub effect: ModifierEffect,
+}
+
+impl ScheduledModifier {
+    /// strength of the effect `step` for a modifier started at `start`, from 0.0 to 1.0, none
+    /// once the modifier ran out
+    pub fn strength(&self, start: usize, step: usize) -> Option<f32> {
+        if self.duration == 0 {
+            return Some(1.0);
+        }
+
+        let elapsed = step.checked_sub(start)?;
+
+        if elapsed >= self.duration {
+            return None;
+        }
+
+        let progress = elapsed as f32 / self.duration as f32;
+
+        Some(match self.effect {
+            ModifierEffect::Pulse { peak, .. } if progress < peak => progress / peak,
+            ModifierEffect::Pulse { peak, .. } => (1.0 - progress) / (1.0 - peak).max(f32::EPSILON),
+            ModifierEffect::Fade { .. } => progress,
+            ModifierEffect::Jitter { .. } | ModifierEffect::FreezeBurst { .. } => 1.0,
+        })
+    }
+}
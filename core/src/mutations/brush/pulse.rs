@@ -1,3 +1,5 @@
+use tracing::{debug, trace};
+
 use crate::{
     brush::Brush,
     mutations::{MutationState, Mutator},
@@ -14,7 +16,7 @@ pub struct PulseBrushMutation {
 
 impl PulseBrushMutation {
     pub fn new(value_min: usize, value_max: usize, overall_steps: usize, normal_peak: f32) -> Self {
-        println!("pulse: steps: {}", overall_steps);
+        debug!(overall_steps, "pulse brush mutation");
         Self {
             value_border: value_min,
             value_climax: value_max,
@@ -44,7 +46,7 @@ impl Mutator<Brush> for PulseBrushMutation {
                 + self.value_border as f32
         };
 
-        println!("[pulse]\tslope\t{}", slope);
+        trace!(slope, "pulse brush mutation");
         mutant.apply_scale(slope);
 
         self.steps -= 1;
@@ -1,3 +1,5 @@
+use tracing::trace;
+
 use crate::{
     brush::Brush,
     mutations::{MutationState, Mutator},
@@ -32,7 +34,7 @@ impl Mutator<Brush> for TransitionBrushMutation {
         let current_step = self.overall_steps - self.steps;
         let slope = current_step as f32 / self.overall_steps as f32 * diff + self.value_from as f32;
 
-        println!("[trans]\tslope\t{}", slope);
+        trace!(slope, "transition brush mutation");
         mutant.apply_scale(slope);
 
         self.steps -= 1;
@@ -1,7 +1,7 @@
 use crate::{
     mutations::{MutationState, Mutator},
     position::Direction,
-    random::{Random, Seed},
+    random::{Random, RandomDist, RandomDistConfig, Seed},
     walker::Walker,
 };
 
@@ -9,6 +9,8 @@ use crate::{
 pub struct RandomWalkerMutation {
     pub seed: Seed,
     pub overall_steps: usize,
+    /// weights of the up, right, down and left directions, uniform when empty
+    pub directions: RandomDistConfig<usize>,
 
     prng: Random,
     steps: usize
@@ -19,6 +21,7 @@ impl RandomWalkerMutation {
         Self {
             seed,
             overall_steps,
+            directions: RandomDistConfig::new(),
             prng: Random::new(seed),
             steps: overall_steps
         }
@@ -31,7 +34,12 @@ impl Mutator<Walker> for RandomWalkerMutation {
             return MutationState::Finished;
         }
 
-        let random_direction = self.prng.gen_u64() as usize % 4;
+        let random_direction = if self.directions.values.is_empty() {
+            self.prng.gen_u64() as usize % 4
+        } else {
+            self.prng
+                .sample_value(&RandomDist::from_config(self.directions.clone()))
+        };
         let random_waypoint = self.prng.gen_u64() as usize % mutant.get_waypoints().len();
 
         let random_direction = Direction::from(random_direction);
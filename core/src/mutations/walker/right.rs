@@ -1,3 +1,5 @@
+use tracing::debug;
+
 use crate::{
     mutations::{MutationState, Mutator},
     walker::Walker,
@@ -11,7 +13,7 @@ pub struct RightWalkerMutation {
 
 impl RightWalkerMutation {
     pub fn new(overall_steps: usize) -> Self {
-        println!("right: steps: {}", overall_steps);
+        debug!(overall_steps, "right walker mutation");
         Self {
             overall_steps,
             steps: overall_steps,
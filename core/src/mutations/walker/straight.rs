@@ -1,3 +1,5 @@
+use tracing::trace;
+
 use crate::{
     walker::Walker,
     mutations::{MutationState, Mutator},
@@ -20,7 +22,11 @@ impl StraightWalkerMutation {
 
 impl Mutator<Walker> for StraightWalkerMutation {
     fn mutate(&mut self, mutant: &mut Walker) -> MutationState {
-        println!("straight: steps: {} {}", self.steps, self.overall_steps);
+        trace!(
+            steps = self.steps,
+            overall_steps = self.overall_steps,
+            "straight walker mutation"
+        );
         if self.steps == 0 {
             return MutationState::Finished;
         }
@@ -0,0 +1,109 @@
+use ndarray::Array2;
+use twmap::GameTile;
+
+use crate::{debug::DebugColor, map::BlockType};
+
+/// colors blocks are drawn with, shared by every frontend that renders a map so previews,
+/// thumbnails and exported images look the same
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Palette {
+    pub empty: DebugColor,
+    pub hookable: DebugColor,
+    pub unhookable: DebugColor,
+    pub hookthrough: DebugColor,
+    pub freeze: DebugColor,
+    pub start: DebugColor,
+    pub finish: DebugColor,
+    pub death: DebugColor,
+    /// tiles that aren't a block the generator places
+    pub unknown: DebugColor,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            empty: DebugColor::WHITE,
+            hookable: DebugColor::rgb(120, 80, 40),
+            unhookable: DebugColor::rgb(96, 96, 96),
+            hookthrough: DebugColor::rgb(0, 0, 255),
+            freeze: DebugColor::rgb(0, 200, 220),
+            start: DebugColor::rgb(0, 255, 0),
+            finish: DebugColor::rgb(255, 0, 0),
+            death: DebugColor::rgb(128, 0, 0),
+            unknown: DebugColor::rgb(0, 0, 0),
+        }
+    }
+}
+
+impl Palette {
+    /// okabe-ito colors, freeze and hookable stay apart for all common color vision deficiencies
+    pub fn colorblind() -> Self {
+        Self {
+            empty: DebugColor::WHITE,
+            hookable: DebugColor::rgb(230, 159, 0),
+            unhookable: DebugColor::rgb(64, 64, 64),
+            hookthrough: DebugColor::rgb(0, 114, 178),
+            freeze: DebugColor::rgb(204, 121, 167),
+            start: DebugColor::rgb(0, 158, 115),
+            finish: DebugColor::rgb(213, 94, 0),
+            death: DebugColor::rgb(86, 180, 233),
+            unknown: DebugColor::rgb(0, 0, 0),
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            empty: DebugColor::WHITE,
+            hookable: DebugColor::rgb(0, 0, 0),
+            unhookable: DebugColor::rgb(128, 128, 128),
+            hookthrough: DebugColor::rgb(0, 0, 255),
+            freeze: DebugColor::rgb(255, 0, 255),
+            start: DebugColor::rgb(0, 255, 0),
+            finish: DebugColor::rgb(255, 0, 0),
+            death: DebugColor::rgb(255, 128, 0),
+            unknown: DebugColor::rgb(255, 255, 0),
+        }
+    }
+
+    pub fn color(&self, block: BlockType) -> DebugColor {
+        match block {
+            BlockType::Empty => self.empty,
+            BlockType::Hookable => self.hookable,
+            BlockType::Unhookable => self.unhookable,
+            BlockType::Hookthrough => self.hookthrough,
+            BlockType::Freeze => self.freeze,
+            BlockType::Start => self.start,
+            BlockType::Finish => self.finish,
+            BlockType::Death => self.death,
+        }
+    }
+
+    pub fn color_mut(&mut self, block: BlockType) -> &mut DebugColor {
+        match block {
+            BlockType::Empty => &mut self.empty,
+            BlockType::Hookable => &mut self.hookable,
+            BlockType::Unhookable => &mut self.unhookable,
+            BlockType::Hookthrough => &mut self.hookthrough,
+            BlockType::Freeze => &mut self.freeze,
+            BlockType::Start => &mut self.start,
+            BlockType::Finish => &mut self.finish,
+            BlockType::Death => &mut self.death,
+        }
+    }
+
+    pub fn tile_color(&self, tile: &GameTile) -> DebugColor {
+        BlockType::from_game_id(tile.id).map_or(self.unknown, |block| self.color(block))
+    }
+
+    /// rgba pixels of the tiles in row-major order, one per tile
+    pub fn render(&self, tiles: &Array2<GameTile>) -> Vec<[u8; 4]> {
+        let (width, height) = tiles.dim();
+
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| [x, y]))
+            .map(|index| self.tile_color(&tiles[index]).to_array())
+            .collect()
+    }
+}
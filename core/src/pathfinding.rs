@@ -0,0 +1,86 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+/// gives up on searches expanding more tiles than this, the route might be unreachable
+const MAX_EXPANDED: usize = 100_000;
+
+/// shortest straight-step path from `start` to `goal`, both included. `cost` returns the cost of
+/// entering a tile, `None` for impassable ones
+pub fn astar(
+    dim: (usize, usize),
+    start: [usize; 2],
+    goal: [usize; 2],
+    cost: impl Fn([usize; 2]) -> Option<u32>,
+) -> Option<Vec<[usize; 2]>> {
+    let (width, height) = dim;
+
+    if start[0] >= width || start[1] >= height || goal[0] >= width || goal[1] >= height {
+        return None;
+    }
+
+    let heuristic = |[x, y]: [usize; 2]| (x.abs_diff(goal[0]) + y.abs_diff(goal[1])) as u32;
+
+    let mut open = BinaryHeap::new();
+    let mut scores = HashMap::new();
+    let mut came_from = HashMap::new();
+
+    open.push(Reverse((heuristic(start), start)));
+    scores.insert(start, 0);
+
+    let mut expanded = 0;
+
+    while let Some(Reverse((_, current))) = open.pop() {
+        if current == goal {
+            let mut path = vec![current];
+            let mut current = current;
+
+            while let Some(&previous) = came_from.get(&current) {
+                path.push(previous);
+                current = previous;
+            }
+
+            path.reverse();
+
+            return Some(path);
+        }
+
+        expanded += 1;
+
+        if expanded > MAX_EXPANDED {
+            return None;
+        }
+
+        let score = scores[&current];
+        let [x, y] = current;
+
+        let neighbors = [
+            (x + 1 < width).then(|| [x + 1, y]),
+            x.checked_sub(1).map(|x| [x, y]),
+            (y + 1 < height).then(|| [x, y + 1]),
+            y.checked_sub(1).map(|y| [x, y]),
+        ];
+
+        for neighbor in neighbors.into_iter().flatten() {
+            let Some(step_cost) = cost(neighbor) else {
+                continue;
+            };
+
+            let neighbor_score = score + step_cost;
+
+            if scores
+                .get(&neighbor)
+                .is_some_and(|&known| known <= neighbor_score)
+            {
+                continue;
+            }
+
+            scores.insert(neighbor, neighbor_score);
+            came_from.insert(neighbor, current);
+            open.push(Reverse((neighbor_score + heuristic(neighbor), neighbor)));
+        }
+    }
+
+    None
+}
@@ -0,0 +1,190 @@
+use ndarray::Array2;
+use twmap::GameTile;
+
+use crate::{map::BlockType, prefabs::Prefab};
+
+/// tile structure declared as a mask, matched centered on a tile
+///
+/// every row is a string of mask characters:
+/// `?` matches anything, `_` empty, `#` hookable, unhookable or hookthrough, `*` freeze and
+/// `!` anything but empty
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Pattern {
+    pub name: String,
+    pub rows: Vec<String>,
+}
+
+impl Pattern {
+    pub fn new(name: &str, rows: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            rows: rows.iter().map(|row| row.to_string()).collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn mask(&self, x: usize, y: usize) -> char {
+        self.rows
+            .get(y)
+            .and_then(|row| row.chars().nth(x))
+            .unwrap_or('?')
+    }
+
+    /// same pattern turned clockwise by 90 degrees
+    pub fn rotated(&self) -> Self {
+        let (width, height) = (self.width(), self.height());
+
+        let rows = (0..width)
+            .map(|x| (0..height).rev().map(|y| self.mask(x, y)).collect())
+            .collect();
+
+        Self {
+            name: self.name.clone(),
+            rows,
+        }
+    }
+
+    /// same pattern mirrored horizontally
+    pub fn mirrored(&self) -> Self {
+        let width = self.width();
+
+        let rows = (0..self.height())
+            .map(|y| (0..width).rev().map(|x| self.mask(x, y)).collect())
+            .collect();
+
+        Self {
+            name: self.name.clone(),
+            rows,
+        }
+    }
+
+    /// the pattern in all four rotations
+    pub fn rotations(&self) -> Vec<Self> {
+        let mut rotations = vec![self.clone()];
+
+        for _ in 0..3 {
+            rotations.push(rotations.last().unwrap().rotated());
+        }
+
+        rotations
+    }
+
+    /// whether the pattern centered at `center` matches, parts outside the map never match
+    pub fn matches_at(&self, tiles: &Array2<GameTile>, center: [usize; 2]) -> bool {
+        let left = center[0] as isize - self.width() as isize / 2;
+        let top = center[1] as isize - self.height() as isize / 2;
+
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let mask = self.mask(x, y);
+
+                if mask == '?' {
+                    continue;
+                }
+
+                let (map_x, map_y) = (left + x as isize, top + y as isize);
+
+                if map_x < 0 || map_y < 0 {
+                    return false;
+                }
+
+                let Some(tile) = tiles.get((map_x as usize, map_y as usize)) else {
+                    return false;
+                };
+
+                if !mask_matches(mask, tile.id) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// centers of all matches
+    pub fn find_all(&self, tiles: &Array2<GameTile>) -> Vec<[usize; 2]> {
+        tiles
+            .indexed_iter()
+            .map(|((x, y), _)| [x, y])
+            .filter(|&center| self.matches_at(tiles, center))
+            .collect()
+    }
+}
+
+fn mask_matches(mask: char, id: u8) -> bool {
+    let is = |block: BlockType| id == block.game_id();
+
+    match mask {
+        '_' => is(BlockType::Empty),
+        '#' => is(BlockType::Hookable) || is(BlockType::Unhookable) || is(BlockType::Hookthrough),
+        '*' => is(BlockType::Freeze),
+        '!' => !is(BlockType::Empty),
+        _ => true,
+    }
+}
+
+/// outer corners of the carved area, in all rotations
+pub fn corners() -> Vec<Pattern> {
+    Pattern::new("corner", &["###", "#__", "#__"]).rotations()
+}
+
+/// solid floor with free space above it
+pub fn ledges() -> Vec<Pattern> {
+    vec![Pattern::new("ledge", &["___", "___", "###"])]
+}
+
+/// solid ceiling with free space below it
+pub fn ceilings() -> Vec<Pattern> {
+    vec![Pattern::new("ceiling", &["###", "___", "___"])]
+}
+
+/// one tile wide holes in the floor
+pub fn pits() -> Vec<Pattern> {
+    vec![Pattern::new("pit", &["___", "#_#", "#_#"])]
+}
+
+/// replaces every match of `pattern` by `replacement`, stamped at the same center
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RewriteRule {
+    pub pattern: Pattern,
+    /// also matches the pattern turned by 90, 180 and 270 degrees
+    pub rotate: bool,
+    pub replacement: Prefab,
+}
+
+impl RewriteRule {
+    /// rewrites all matches found before rewriting, returns their centers
+    pub fn apply(&self, tiles: &mut Array2<GameTile>) -> Vec<[usize; 2]> {
+        let patterns = if self.rotate {
+            self.pattern.rotations()
+        } else {
+            vec![self.pattern.clone()]
+        };
+
+        let matches: Vec<[usize; 2]> = patterns
+            .iter()
+            .flat_map(|pattern| pattern.find_all(tiles))
+            .collect();
+
+        for &center in matches.iter() {
+            self.replacement.stamp(tiles, center);
+        }
+
+        matches
+    }
+}
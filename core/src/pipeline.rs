@@ -0,0 +1,118 @@
+use std::mem;
+
+use twmap::{GameLayer, TwMap};
+
+use crate::{
+    config::GenerationPreset,
+    generator::{GenerationError, Generator, StepResult},
+    map::Map,
+    random::Seed,
+    snapshot::diff_tiles,
+    transform::Transform,
+};
+
+/// how a frontend turns a preset and a seed into a map, the bridge and the editor both drive
+/// their generations through it, so the same seed gives them the same map
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GenerationPipeline {
+    /// applied to the finished map
+    pub transform: Transform,
+    /// drives the generation step by step like the editor's live view instead of in one go
+    pub stepped: bool,
+}
+
+impl GenerationPipeline {
+    /// generates the map of `seed` while passing every event except the finished map to
+    /// `observer`
+    pub fn run(
+        &self,
+        generator: &mut Generator,
+        seed: Seed,
+        mut observer: impl FnMut(&StepResult),
+    ) -> Result<TwMap, GenerationError> {
+        generator.set_seed(seed);
+
+        let map = if self.stepped {
+            run_stepped(generator, &mut observer)?
+        } else {
+            generator.try_generate_observed(observer)?
+        };
+
+        if self.transform.is_identity() {
+            return Ok(map);
+        }
+
+        let mut map = Map::from_raw(map);
+        map.transform(self.transform);
+
+        Ok(map.into_raw())
+    }
+}
+
+fn run_stepped(
+    generator: &mut Generator,
+    observer: &mut impl FnMut(&StepResult),
+) -> Result<TwMap, GenerationError> {
+    let preset = generator.preset().clone();
+    let mut steps = mem::replace(generator, Generator::new()).into_steps(preset);
+
+    let mut map = None;
+
+    for result in steps.by_ref() {
+        match result {
+            StepResult::Finished(finished) => {
+                map = Some(finished);
+                break;
+            }
+            result => observer(&result),
+        }
+    }
+
+    let error = steps.error();
+    *generator = steps.into_generator();
+
+    map.ok_or_else(|| error.expect("generation finishes with a map unless it fails"))
+}
+
+/// how the maps of both ways of driving a generation compare
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parity {
+    Match,
+    /// the game layers differ in size, direct size first
+    Size((usize, usize), (usize, usize)),
+    /// game tiles differing between the maps
+    Tiles(usize),
+}
+
+/// generates the map of `seed` in one go like the bridge and step by step like the editor's
+/// live view, each with a fresh generator, and compares their game layers
+pub fn check_parity(preset: &GenerationPreset, seed: Seed) -> Result<Parity, GenerationError> {
+    let generate = |stepped| {
+        let pipeline = GenerationPipeline {
+            stepped,
+            ..Default::default()
+        };
+
+        pipeline.run(&mut Generator::from_preset(preset.clone()), seed, |_| {})
+    };
+
+    let (direct, stepped) = (generate(false)?, generate(true)?);
+
+    let direct: &GameLayer = direct.find_physics_layer().unwrap();
+    let stepped: &GameLayer = stepped.find_physics_layer().unwrap();
+    let (direct, stepped) = (direct.tiles.unwrap_ref(), stepped.tiles.unwrap_ref());
+
+    if direct.dim() != stepped.dim() {
+        return Ok(Parity::Size(direct.dim(), stepped.dim()));
+    }
+
+    let differing = diff_tiles(direct, stepped)
+        .iter()
+        .filter(|&&diff| diff)
+        .count();
+
+    Ok(match differing {
+        0 => Parity::Match,
+        differing => Parity::Tiles(differing),
+    })
+}
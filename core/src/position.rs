@@ -10,30 +10,72 @@ pub enum Direction {
     Right = 1,
     Down = 2,
     Left = 3,
+    /// diagonals, only taken when the walker config allows them
+    UpRight = 4,
+    DownRight = 5,
+    DownLeft = 6,
+    UpLeft = 7,
 }
 
 impl Direction {
+    /// turns by 90 degrees counterclockwise
     pub fn prev(&self) -> Self {
         match &self {
             Self::Up => Self::Left,
             Self::Right => Self::Up,
             Self::Down => Self::Right,
             Self::Left => Self::Down,
+            Self::UpRight => Self::UpLeft,
+            Self::DownRight => Self::UpRight,
+            Self::DownLeft => Self::DownRight,
+            Self::UpLeft => Self::DownLeft,
         }
     }
 
+    /// turns by 90 degrees clockwise
     pub fn next(&self) -> Self {
         match &self {
             Self::Up => Self::Right,
             Self::Right => Self::Down,
             Self::Down => Self::Left,
             Self::Left => Self::Up,
+            Self::UpRight => Self::DownRight,
+            Self::DownRight => Self::DownLeft,
+            Self::DownLeft => Self::UpLeft,
+            Self::UpLeft => Self::UpRight,
         }
     }
 
     pub fn backwards(&self) -> Self {
         self.next().next()
     }
+
+    pub fn is_diagonal(&self) -> bool {
+        *self as usize >= 4
+    }
+
+    /// tile offset of a step, y grows downwards
+    pub fn offset(&self) -> [isize; 2] {
+        match self {
+            Self::Up => [0, -1],
+            Self::Right => [1, 0],
+            Self::Down => [0, 1],
+            Self::Left => [-1, 0],
+            Self::UpRight => [1, -1],
+            Self::DownRight => [1, 1],
+            Self::DownLeft => [-1, 1],
+            Self::UpLeft => [-1, -1],
+        }
+    }
+
+    /// horizontal part of a diagonal, straight directions stay as they are
+    pub fn horizontal(&self) -> Self {
+        match self {
+            Self::UpRight | Self::DownRight => Self::Right,
+            Self::DownLeft | Self::UpLeft => Self::Left,
+            _ => *self,
+        }
+    }
 }
 
 impl From<usize> for Direction {
@@ -43,6 +85,10 @@ impl From<usize> for Direction {
             1 => Self::Right,
             2 => Self::Down,
             3 => Self::Left,
+            4 => Self::UpRight,
+            5 => Self::DownRight,
+            6 => Self::DownLeft,
+            7 => Self::UpLeft,
             _ => Default::default(),
         }
     }
@@ -66,6 +112,53 @@ pub fn as_index(value: VectorView2) -> [usize; 2] {
     [value[[0]] as usize, value[[1]] as usize]
 }
 
+/// tile index of a map as x and y, moved by signed offsets without wrapping around the map
+pub trait TileIndex: Copy {
+    /// moved by `offset`, none when it leaves the first quadrant
+    fn checked_offset(self, offset: [isize; 2]) -> Option<Self>;
+
+    /// moved by `offset`, stops at 0 and `usize::MAX`
+    fn saturating_offset(self, offset: [isize; 2]) -> Self;
+
+    /// neighbor tile in `direction`
+    fn step(self, direction: Direction) -> Option<Self>;
+
+    /// neighbor tiles inside a map of `dim`, in the order of `Direction`, diagonals included when
+    /// `diagonal` is set
+    fn neighbors(self, dim: (usize, usize), diagonal: bool) -> impl Iterator<Item = Self>;
+}
+
+impl TileIndex for [usize; 2] {
+    #[inline]
+    fn checked_offset(self, offset: [isize; 2]) -> Option<Self> {
+        Some([
+            self[0].checked_add_signed(offset[0])?,
+            self[1].checked_add_signed(offset[1])?,
+        ])
+    }
+
+    #[inline]
+    fn saturating_offset(self, offset: [isize; 2]) -> Self {
+        [
+            self[0].saturating_add_signed(offset[0]),
+            self[1].saturating_add_signed(offset[1]),
+        ]
+    }
+
+    #[inline]
+    fn step(self, direction: Direction) -> Option<Self> {
+        self.checked_offset(direction.offset())
+    }
+
+    fn neighbors(self, dim: (usize, usize), diagonal: bool) -> impl Iterator<Item = Self> {
+        let count = if diagonal { 8 } else { 4 };
+
+        (0..count)
+            .filter_map(move |i| self.step(Direction::from(i)))
+            .filter(move |&[x, y]| x < dim.0 && y < dim.1)
+    }
+}
+
 pub fn from_raw(value: (f32, f32), scale_factor: f32) -> Vector2 {
     Vector2::from(vec![(value.0 * scale_factor), (value.1 * scale_factor)])
 }
@@ -121,15 +214,31 @@ pub fn direction(value: VectorView2) -> Direction {
     angle_direction(angle(value))
 }
 
-pub fn shift_by_direction(value: &mut Vector2, shift: f32, direction: Direction) {
-    match direction {
-        Direction::Up => value[[1]] -= shift,
-        Direction::Right => value[[0]] += shift,
-        Direction::Down => value[[1]] += shift,
-        Direction::Left => value[[0]] -= shift,
+/// direction along the longer axis of `delta`, y grows downwards
+pub fn dominant_direction(delta: VectorView2) -> Direction {
+    let (x, y) = (get_x(delta), get_y(delta));
+
+    if x.abs() >= y.abs() {
+        if x >= 0.0 {
+            Direction::Right
+        } else {
+            Direction::Left
+        }
+    } else if y >= 0.0 {
+        Direction::Down
+    } else {
+        Direction::Up
     }
 }
 
+/// diagonals move `shift` along both axes
+pub fn shift_by_direction(value: &mut Vector2, shift: f32, direction: Direction) {
+    let [x, y] = direction.offset();
+
+    value[[0]] += x as f32 * shift;
+    value[[1]] += y as f32 * shift;
+}
+
 pub fn straight_neighbors(pos: VectorView2) -> Vec<Vector2> {
     let cur = pos.to_vec();
     let mut neighbors: Vec<Vector2> = vec![
@@ -145,6 +254,7 @@ pub fn straight_neighbors(pos: VectorView2) -> Vec<Vector2> {
     neighbors
 }
 
+/// straight neighbors followed by the diagonal ones, in the order of `Direction`
 pub fn all_neighbors(pos: VectorView2) -> Vec<Vector2> {
     let mut neighbors = straight_neighbors(pos);
 
@@ -0,0 +1,246 @@
+use ndarray::Array2;
+use twmap::GameTile;
+
+use crate::{distance::DistanceField, map::BlockType, random::Random};
+
+/// hand-authored structure stamped into the map during post-processing
+///
+/// every row is a string of stencil characters:
+/// `.` keeps the tile, `_` empty, `#` hookable, `x` unhookable, `h` hookthrough, `*` freeze,
+/// `!` death
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Prefab {
+    pub name: String,
+    pub rows: Vec<String>,
+}
+
+impl Prefab {
+    /// builds a prefab from rgba pixels in row-major order, see [`Prefab::block_from_color`]
+    pub fn from_pixels(name: &str, width: usize, pixels: &[[u8; 4]]) -> Self {
+        let rows = pixels
+            .chunks(width.max(1))
+            .map(|row| {
+                row.iter()
+                    .map(|&pixel| Self::block_char(Self::block_from_color(pixel)))
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            name: name.to_string(),
+            rows,
+        }
+    }
+
+    /// transparent pixels keep the tile, everything else maps to the closest block color:
+    /// white empty, black hookable, dark grey unhookable, blue hookthrough and cyan freeze
+    pub fn block_from_color([r, g, b, a]: [u8; 4]) -> Option<BlockType> {
+        if a < 128 {
+            return None;
+        }
+
+        let block = match (r > 127, g > 127, b > 127) {
+            (true, true, true) => BlockType::Empty,
+            (false, false, false) if r > 63 => BlockType::Unhookable,
+            (false, false, false) => BlockType::Hookable,
+            (false, false, true) => BlockType::Hookthrough,
+            (false, true, true) => BlockType::Freeze,
+            _ => BlockType::Hookable,
+        };
+
+        Some(block)
+    }
+
+    fn block_char(block: Option<BlockType>) -> char {
+        match block {
+            None => '.',
+            Some(BlockType::Empty) => '_',
+            Some(BlockType::Hookable) => '#',
+            Some(BlockType::Unhookable) => 'x',
+            Some(BlockType::Hookthrough) => 'h',
+            Some(BlockType::Freeze) => '*',
+            Some(BlockType::Start) => 's',
+            Some(BlockType::Finish) => 'f',
+            Some(BlockType::Death) => '!',
+        }
+    }
+
+    fn char_block(cell: char) -> Option<BlockType> {
+        match cell {
+            '_' => Some(BlockType::Empty),
+            '#' => Some(BlockType::Hookable),
+            'x' => Some(BlockType::Unhookable),
+            'h' => Some(BlockType::Hookthrough),
+            '*' => Some(BlockType::Freeze),
+            's' => Some(BlockType::Start),
+            'f' => Some(BlockType::Finish),
+            '!' => Some(BlockType::Death),
+            _ => None,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// block placed at the stencil position, `None` keeps the tile
+    pub fn cell(&self, x: usize, y: usize) -> Option<BlockType> {
+        self.rows
+            .get(y)
+            .and_then(|row| row.chars().nth(x))
+            .and_then(Self::char_block)
+    }
+
+    /// stamps the prefab centered at `center`, parts outside the map are cut off
+    pub fn stamp(&self, tiles: &mut Array2<GameTile>, center: [usize; 2]) {
+        for (x, y, index) in self.covered(center) {
+            if let (Some(block), Some(tile)) = (self.cell(x, y), tiles.get_mut(index)) {
+                *tile = block.tile();
+            }
+        }
+    }
+
+    /// whether every map tile under the prefab is empty
+    pub fn fits_open_area(&self, tiles: &Array2<GameTile>, center: [usize; 2]) -> bool {
+        let (width, height) = tiles.dim();
+
+        let left = center[0] as isize - self.width() as isize / 2;
+        let top = center[1] as isize - self.height() as isize / 2;
+
+        if left < 0
+            || top < 0
+            || left as usize + self.width() > width
+            || top as usize + self.height() > height
+        {
+            return false;
+        }
+
+        self.covered(center)
+            .all(|(_, _, index)| tiles[index].id == BlockType::Empty.game_id())
+    }
+
+    /// stencil positions with their map index
+    fn covered(&self, center: [usize; 2]) -> impl Iterator<Item = (usize, usize, [usize; 2])> {
+        let left = center[0] as isize - self.width() as isize / 2;
+        let top = center[1] as isize - self.height() as isize / 2;
+
+        let (width, height) = (self.width(), self.height());
+
+        (0..height).flat_map(move |y| {
+            (0..width).filter_map(move |x| {
+                let map_x = left + x as isize;
+                let map_y = top + y as isize;
+
+                (map_x >= 0 && map_y >= 0).then_some((x, y, [map_x as usize, map_y as usize]))
+            })
+        })
+    }
+}
+
+/// where a prefab may be stamped
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PlacementRule {
+    /// centered on tiles the walker passed
+    #[default]
+    Route,
+    /// only where the whole prefab covers empty tiles
+    OpenArea,
+}
+
+/// a prefab and the rules for stamping it
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PrefabPlacement {
+    pub prefab: Prefab,
+    pub rule: PlacementRule,
+    /// stamps at most this many copies
+    pub count: usize,
+    /// lowest distance in tiles between two copies of this prefab
+    pub min_spacing: f32,
+}
+
+impl Default for PrefabPlacement {
+    fn default() -> Self {
+        Self {
+            prefab: Prefab::default(),
+            rule: PlacementRule::Route,
+            count: 1,
+            min_spacing: 50.0,
+        }
+    }
+}
+
+impl PrefabPlacement {
+    /// picks positions among `route` following the placement rules and stamps the prefab there,
+    /// `distances` has to be up to date with `tiles`
+    pub fn apply(
+        &self,
+        tiles: &mut Array2<GameTile>,
+        route: &[[usize; 2]],
+        distances: &DistanceField,
+        prng: &mut Random,
+    ) -> Vec<[usize; 2]> {
+        const ATTEMPTS_PER_COPY: usize = 50;
+
+        let mut placed: Vec<[usize; 2]> = Vec::new();
+
+        let candidates: Vec<[usize; 2]> = match self.rule {
+            PlacementRule::Route => route.to_vec(),
+            PlacementRule::OpenArea => {
+                // the prefab can't fit closer than half its shorter side to a non-empty tile
+                let clearance = (self.prefab.width().min(self.prefab.height()) / 2) as u32;
+
+                distances
+                    .distances()
+                    .indexed_iter()
+                    .filter(|(_, &distance)| distance > clearance)
+                    .map(|((x, y), _)| [x, y])
+                    .collect()
+            }
+        };
+
+        if candidates.is_empty() {
+            return placed;
+        }
+
+        for _ in 0..self.count * ATTEMPTS_PER_COPY {
+            if placed.len() >= self.count {
+                break;
+            }
+
+            let center = *prng.pick(&candidates);
+
+            let spaced = placed.iter().all(|other| {
+                let dx = center[0] as f32 - other[0] as f32;
+                let dy = center[1] as f32 - other[1] as f32;
+
+                (dx * dx + dy * dy).sqrt() >= self.min_spacing
+            });
+
+            if !spaced {
+                continue;
+            }
+
+            if self.rule == PlacementRule::OpenArea && !self.prefab.fits_open_area(tiles, center) {
+                continue;
+            }
+
+            self.prefab.stamp(tiles, center);
+            placed.push(center);
+        }
+
+        placed
+    }
+}
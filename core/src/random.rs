@@ -1,3 +1,5 @@
+use std::{collections::BTreeMap, fmt::Write as _, fs, io, panic::Location, path::Path};
+
 use rand::prelude::*;
 use rand::rngs::SmallRng;
 use rand_distr::uniform::{SampleRange, SampleUniform};
@@ -55,6 +57,58 @@ impl<T: Copy> RandomDistConfig<T> {
     }
 }
 
+impl RandomDistConfig<usize> {
+    /// values from 0 to `n - 1` with probabilities following the shape
+    pub fn from_shape(shape: Shape, n: usize) -> Self {
+        let mut config = Self::from_values((0..n).map(|i| ProbableValue(0.0, i)).collect());
+
+        config.reshape(shape);
+
+        config
+    }
+}
+
+impl<T: Copy> RandomDistConfig<T> {
+    /// replaces the probabilities with the shape, the values stay in order
+    pub fn reshape(&mut self, shape: Shape) {
+        let n = self.values.len();
+
+        for (i, ProbableValue(p, _)) in self.values.iter_mut().enumerate() {
+            *p = shape.weight(i, n);
+        }
+
+        self.normalize_probs();
+    }
+}
+
+/// shape of a distribution over values in order
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Shape {
+    #[default]
+    Uniform,
+    /// the first value is the most probable, the last one the least
+    LinearFalloff,
+    /// bell curve around the middle value
+    Gaussian,
+}
+
+impl Shape {
+    /// unnormalized weight of value `index` out of `n`
+    pub fn weight(&self, index: usize, n: usize) -> f32 {
+        match self {
+            Shape::Uniform => 1.0,
+            Shape::LinearFalloff => (n - index) as f32,
+            Shape::Gaussian => {
+                let center = (n as f32 - 1.0) / 2.0;
+                let sigma = (n as f32 / 4.0).max(0.5);
+
+                (-0.5 * ((index as f32 - center) / sigma).powi(2)).exp()
+            }
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, PartialOrd)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RandomDist<T: Copy> {
@@ -96,10 +150,58 @@ pub fn random_seed() -> Seed {
     SmallRng::from_entropy().next_u64()
 }
 
+/// draw of an audited generator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Draw {
+    /// source file and line the value was drawn at
+    pub site: String,
+    /// next raw value of the generator before the draw, tells apart draws of diverged states
+    pub state: u64,
+}
+
+/// draws of an audited generator in order, counted per call site
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RandomAudit {
+    pub counts: BTreeMap<String, usize>,
+    pub draws: Vec<Draw>,
+}
+
+impl RandomAudit {
+    fn record(&mut self, site: &Location, state: u64) {
+        let site = format!("{}:{}", site.file(), site.line());
+
+        *self.counts.entry(site.clone()).or_default() += 1;
+        self.draws.push(Draw { site, state });
+    }
+
+    /// index of the first draw that differs from `other`, none when both drew the same values
+    /// at the same sites
+    pub fn first_divergence(&self, other: &RandomAudit) -> Option<usize> {
+        let common = self.draws.len().min(other.draws.len());
+
+        (0..common)
+            .find(|&i| self.draws[i] != other.draws[i])
+            .or((self.draws.len() != other.draws.len()).then_some(common))
+    }
+
+    /// one draw per line as index, site and state, so two dumps can be diffed
+    pub fn write_sequence(&self, path: &Path) -> io::Result<()> {
+        let mut sequence = String::new();
+
+        for (i, draw) in self.draws.iter().enumerate() {
+            writeln!(sequence, "{} {} {:016x}", i, draw.site, draw.state).unwrap();
+        }
+
+        fs::write(path, sequence)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Random {
     seed: Seed,
     prng: SmallRng,
+    /// only recorded once enabled, boxed to keep plain generators small
+    audit: Option<Box<RandomAudit>>,
 }
 
 impl Random {
@@ -107,51 +209,109 @@ impl Random {
         Random {
             seed,
             prng: SmallRng::seed_from_u64(seed),
+            audit: None,
         }
     }
 
+    pub fn seed(&self) -> Seed {
+        self.seed
+    }
+
+    /// starts over from the seed, an enabled audit starts over as well
     pub fn reset(&mut self) {
         self.prng = SmallRng::seed_from_u64(self.seed);
+
+        if let Some(audit) = &mut self.audit {
+            **audit = RandomAudit::default();
+        }
+    }
+
+    /// starts over from `seed`, keeps the audit enabled
+    pub fn reseed(&mut self, seed: Seed) {
+        self.seed = seed;
+        self.reset();
+    }
+
+    /// independent generator for `stream`, derived from the seed, audited when this one is
+    pub fn fork(&self, stream: u64) -> Self {
+        let mut bytes = self.seed.to_le_bytes().to_vec();
+        bytes.extend(stream.to_le_bytes());
+
+        let mut fork = Random::new(hash(&bytes));
+        fork.set_audit(self.audit.is_some());
+
+        fork
+    }
+
+    /// counts the draws per call site and records their order, clears the recorded draws
+    pub fn set_audit(&mut self, enabled: bool) {
+        self.audit = enabled.then(Box::default);
     }
 
+    pub fn audit(&self) -> Option<&RandomAudit> {
+        self.audit.as_deref()
+    }
+
+    #[track_caller]
+    fn record(&mut self) {
+        if let Some(audit) = &mut self.audit {
+            audit.record(Location::caller(), self.prng.clone().next_u64());
+        }
+    }
+
+    #[track_caller]
     pub fn sample_value<T: Copy>(&mut self, dist: &RandomDist<T>) -> T {
         dist.config.get(self.sample_index(dist)).1
     }
 
+    #[track_caller]
     pub fn sample_index<T: Copy>(&mut self, dist: &RandomDist<T>) -> usize {
+        self.record();
         dist.weights().sample(&mut self.prng)
     }
 
+    #[track_caller]
     pub fn in_range<T, R>(&mut self, range: R) -> T
     where
         T: SampleUniform,
         R: SampleRange<T>,
     {
+        self.record();
         self.prng.gen_range(range)
     }
 
+    #[track_caller]
     pub fn gen_u64(&mut self) -> u64 {
+        self.record();
         self.prng.next_u64()
     }
 
+    #[track_caller]
     pub fn gen_bool(&mut self, probability: f32) -> bool {
+        self.record();
         self.prng.gen_bool(probability.clamp(0.0, 1.0).into())
     }
 
+    #[track_caller]
     pub fn gen_normal(&mut self) -> f32 {
+        self.record();
         self.prng.next_u32() as f32 / f32::MAX
     }
 
+    #[track_caller]
     pub fn pick<'a, T>(&'a mut self, values: &'a [T]) -> &T {
         &values[self.in_range(0..values.len())]
     }
 
     /// skip one gen step to ensure that a value is consumed in any case
+    #[track_caller]
     pub fn skip(&mut self) {
+        self.record();
         self.prng.next_u64();
     }
 
     /// skip n gen steps to ensure that n values are consumed in any case
+    #[track_caller]
     pub fn skip_n(&mut self, n: usize) {
         for _ in 0..n {
             self.skip();
@@ -161,6 +321,10 @@ impl Random {
 
 impl Default for Random {
     fn default() -> Self {
-        Self { seed: 0, prng: SmallRng::seed_from_u64(0) }
+        Self {
+            seed: 0,
+            prng: SmallRng::seed_from_u64(0),
+            audit: None,
+        }
     }
 }
@@ -0,0 +1,219 @@
+use std::collections::VecDeque;
+
+use ndarray::Array2;
+use twmap::GameTile;
+
+use crate::map::BlockType;
+
+/// what happens to empty regions the player can't reach from the spawn
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SealedRegionMode {
+    /// fills them with the canvas block
+    #[default]
+    Fill,
+    /// connects them to the playable area with the shortest tunnel, too distant ones are filled
+    Tunnel,
+}
+
+/// detection of empty pockets sealed off from the playable area
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SealedRegionConfig {
+    pub mode: SealedRegionMode,
+    /// longest tunnel in tiles, regions further away are filled
+    pub max_tunnel_length: usize,
+}
+
+impl Default for SealedRegionConfig {
+    fn default() -> Self {
+        Self {
+            mode: SealedRegionMode::Fill,
+            max_tunnel_length: 20,
+        }
+    }
+}
+
+/// connected empty tiles the player can't reach
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SealedRegion {
+    pub tiles: Vec<[usize; 2]>,
+    /// tiles carved to connect the region, `None` when it was filled
+    pub tunnel: Option<Vec<[usize; 2]>>,
+}
+
+impl SealedRegionConfig {
+    /// fills or tunnels every empty region not connected to `start`, `fill` is the block filled
+    /// regions are turned into
+    pub fn apply(
+        &self,
+        tiles: &mut Array2<GameTile>,
+        start: [usize; 2],
+        fill: BlockType,
+    ) -> Vec<SealedRegion> {
+        let mut reached = flood_fill(tiles, start, passable);
+        let mut regions = Vec::new();
+
+        for region in sealed_regions(tiles, &reached) {
+            let tunnel = match self.mode {
+                SealedRegionMode::Fill => None,
+                SealedRegionMode::Tunnel => {
+                    find_tunnel(tiles, &reached, &region, self.max_tunnel_length)
+                }
+            };
+
+            match &tunnel {
+                Some(tunnel) => {
+                    for &tile in tunnel.iter().chain(region.iter()) {
+                        tiles[tile] = BlockType::Empty.tile();
+                        reached[tile] = true;
+                    }
+                }
+                None => {
+                    for &tile in region.iter() {
+                        tiles[tile] = fill.tile();
+                    }
+                }
+            }
+
+            regions.push(SealedRegion {
+                tiles: region,
+                tunnel,
+            });
+        }
+
+        regions
+    }
+}
+
+/// empty regions none of whose tiles were reached, each starting with its leftmost tile
+pub fn sealed_regions(tiles: &Array2<GameTile>, reached: &Array2<bool>) -> Vec<Vec<[usize; 2]>> {
+    let mut visited = reached.clone();
+    let mut regions = Vec::new();
+
+    for ((x, y), tile) in tiles.indexed_iter() {
+        if visited[[x, y]] || tile.id != BlockType::Empty.game_id() {
+            continue;
+        }
+
+        let mut region = vec![[x, y]];
+        let mut queue = VecDeque::from([[x, y]]);
+        visited[[x, y]] = true;
+
+        while let Some(current) = queue.pop_front() {
+            for next in neighbors(current) {
+                let empty = tiles
+                    .get(next)
+                    .is_some_and(|tile| tile.id == BlockType::Empty.game_id());
+
+                if empty && !visited[next] {
+                    visited[next] = true;
+                    region.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        regions.push(region);
+    }
+
+    regions
+}
+
+/// tiles connected to `start` through tiles accepted by `open`
+pub fn flood_fill(
+    tiles: &Array2<GameTile>,
+    start: [usize; 2],
+    open: impl Fn(&GameTile) -> bool,
+) -> Array2<bool> {
+    let mut reached = Array2::from_elem(tiles.dim(), false);
+
+    if !tiles.get(start).is_some_and(&open) {
+        return reached;
+    }
+
+    let mut queue = VecDeque::from([start]);
+    reached[start] = true;
+
+    while let Some(current) = queue.pop_front() {
+        for next in neighbors(current) {
+            let accepted = tiles.get(next).is_some_and(&open);
+
+            if accepted && !reached[next] {
+                reached[next] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    reached
+}
+
+/// everything but solid blocks can be passed by the player
+fn passable(tile: &GameTile) -> bool {
+    ![
+        BlockType::Hookable,
+        BlockType::Unhookable,
+        BlockType::Hookthrough,
+    ]
+    .iter()
+    .any(|block| tile.id == block.game_id())
+}
+
+/// shortest path from the region to a reached tile, without both ends
+fn find_tunnel(
+    tiles: &Array2<GameTile>,
+    reached: &Array2<bool>,
+    region: &[[usize; 2]],
+    max_length: usize,
+) -> Option<Vec<[usize; 2]>> {
+    let mut previous: Array2<Option<[usize; 2]>> = Array2::from_elem(tiles.dim(), None);
+    let mut distance = Array2::from_elem(tiles.dim(), usize::MAX);
+    let mut queue = VecDeque::new();
+
+    for &tile in region {
+        distance[tile] = 0;
+        queue.push_back(tile);
+    }
+
+    while let Some(current) = queue.pop_front() {
+        for next in neighbors(current) {
+            if tiles.get(next).is_none() || distance[next] != usize::MAX {
+                continue;
+            }
+
+            previous[next] = Some(current);
+            distance[next] = distance[current] + 1;
+
+            if reached[next] {
+                let mut tunnel = Vec::new();
+                let mut tile = current;
+
+                while distance[tile] > 0 {
+                    tunnel.push(tile);
+                    tile = previous[tile].unwrap();
+                }
+
+                return Some(tunnel);
+            }
+
+            if distance[next] <= max_length {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+fn neighbors([x, y]: [usize; 2]) -> impl Iterator<Item = [usize; 2]> {
+    [
+        [x.checked_sub(1), Some(y)],
+        [x.checked_add(1), Some(y)],
+        [Some(x), y.checked_sub(1)],
+        [Some(x), y.checked_add(1)],
+    ]
+    .into_iter()
+    .filter_map(|[x, y]| Some([x?, y?]))
+}
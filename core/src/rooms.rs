@@ -0,0 +1,164 @@
+use std::f32::consts::PI;
+
+use ndarray::Array2;
+use twmap::GameTile;
+
+use crate::{
+    map::BlockType,
+    position::{Direction, TileIndex},
+    random::Random,
+};
+
+/// samples around the room outline for the irregular shape
+const IRREGULAR_SAMPLES: usize = 8;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RoomShape {
+    #[default]
+    Rectangle,
+    /// ellipse fitting the room bounds
+    Circle,
+    /// ellipse with a noise-perturbed outline
+    Irregular,
+}
+
+/// room carved around a position of the route
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RoomConfig {
+    pub shape: RoomShape,
+    pub width: usize,
+    pub height: usize,
+    /// thickness of the hookable wall around the room, only replaces tiles that aren't empty
+    pub padding: usize,
+    /// irregular shape only, how far the outline may deviate from the ellipse, relative to it
+    pub roughness: f32,
+    /// places a hookable platform in the lower half of the room
+    pub platform: bool,
+    /// width of the openings towards the entry and exit directions
+    pub door_width: usize,
+}
+
+impl Default for RoomConfig {
+    fn default() -> Self {
+        Self {
+            shape: RoomShape::Rectangle,
+            width: 12,
+            height: 8,
+            padding: 1,
+            roughness: 0.3,
+            platform: false,
+            door_width: 3,
+        }
+    }
+}
+
+impl RoomConfig {
+    /// carves the room centered at `center`, with doors on the side the walker came from and
+    /// the side it leaves to
+    pub fn apply(
+        &self,
+        tiles: &mut Array2<GameTile>,
+        center: [usize; 2],
+        entry: Option<Direction>,
+        exit: Option<Direction>,
+        prng: &mut Random,
+    ) {
+        let noise: Vec<f32> = (0..IRREGULAR_SAMPLES)
+            .map(|_| prng.in_range(-1.0..=1.0))
+            .collect();
+
+        let half_width = self.width as f32 / 2.0;
+        let half_height = self.height as f32 / 2.0;
+        let padding = self.padding as f32;
+
+        let reach_x = (half_width + padding).ceil() as isize;
+        let reach_y = (half_height + padding).ceil() as isize;
+
+        for dy in -reach_y..=reach_y {
+            for dx in -reach_x..=reach_x {
+                let (x, y) = (dx as f32, dy as f32);
+
+                if self.contains(x, y, half_width, half_height, &noise) {
+                    set_block(tiles, center, dx, dy, BlockType::Empty, true);
+                } else if self.contains(x, y, half_width + padding, half_height + padding, &noise) {
+                    set_block(tiles, center, dx, dy, BlockType::Hookable, false);
+                }
+            }
+        }
+
+        if self.platform {
+            let platform_y = (half_height / 2.0) as isize;
+            let platform_half = (half_width / 2.0) as isize;
+
+            for dx in -platform_half..=platform_half {
+                set_block(tiles, center, dx, platform_y, BlockType::Hookable, true);
+            }
+        }
+
+        // the walker moves towards `entry`, so it came from the opposite side
+        let doors = [entry.map(|entry| entry.backwards()), exit];
+
+        for door in doors.into_iter().flatten() {
+            self.carve_door(tiles, center, door);
+        }
+    }
+
+    fn contains(&self, x: f32, y: f32, half_width: f32, half_height: f32, noise: &[f32]) -> bool {
+        let nx = x / half_width.max(0.5);
+        let ny = y / half_height.max(0.5);
+
+        match self.shape {
+            RoomShape::Rectangle => nx.abs() <= 1.0 && ny.abs() <= 1.0,
+            RoomShape::Circle => nx * nx + ny * ny <= 1.0,
+            RoomShape::Irregular => {
+                // interpolate between the noise samples around the outline
+                let angle = (ny.atan2(nx) + 2.0 * PI) % (2.0 * PI);
+                let sample = angle / (2.0 * PI) * IRREGULAR_SAMPLES as f32;
+                let index = sample as usize % IRREGULAR_SAMPLES;
+                let next = (index + 1) % IRREGULAR_SAMPLES;
+                let t = sample.fract();
+
+                let offset = noise[index] * (1.0 - t) + noise[next] * t;
+
+                (nx * nx + ny * ny).sqrt() <= 1.0 + self.roughness * offset
+            }
+        }
+    }
+
+    fn carve_door(&self, tiles: &mut Array2<GameTile>, center: [usize; 2], direction: Direction) {
+        let half_door = (self.door_width.max(1) / 2) as isize;
+        let length = (self.width.max(self.height) / 2 + self.padding + 1) as isize;
+
+        for along in 0..=length {
+            for across in -half_door..=half_door {
+                let [x, y] = direction.offset();
+                let (dx, dy) = (x * along - y * across, y * along + x * across);
+
+                set_block(tiles, center, dx, dy, BlockType::Empty, true);
+            }
+        }
+    }
+}
+
+/// sets the tile at `center` + offset, `overwrite_empty` false keeps empty tiles
+fn set_block(
+    tiles: &mut Array2<GameTile>,
+    center: [usize; 2],
+    dx: isize,
+    dy: isize,
+    block: BlockType,
+    overwrite_empty: bool,
+) {
+    let Some(position) = center.checked_offset([dx, dy]) else {
+        return;
+    };
+
+    if let Some(tile) = tiles.get_mut(position) {
+        if overwrite_empty || tile.id != BlockType::Empty.game_id() {
+            *tile = block.tile();
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::VecDeque;
+
+use ndarray::Array2;
+use twmap::GameTile;
+
+use crate::{map::BlockType, position::TileIndex};
+
+/// straight skip directions, the opposite ones find the same skips from the other end
+const STRAIGHT: [[isize; 2]; 2] = [[1, 0], [0, 1]];
+const DIAGONAL: [[isize; 2]; 2] = [[1, 1], [-1, 1]];
+
+/// shortcuts tunneled through thin walls between distant parts of the route
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SkipConfig {
+    pub max_skips: usize,
+    /// shortest wall a skip tunnels through
+    pub min_length: usize,
+    /// longest wall a skip tunnels through
+    pub max_length: usize,
+    /// walker steps a skip has to save at least
+    pub min_shortcut: usize,
+    /// tunnel width in tiles, from 1 to 3
+    pub width: usize,
+    /// fills skips with freeze instead of carving them, players have to pass the freeze
+    pub freeze: bool,
+    /// also looks for diagonal skips
+    pub diagonal: bool,
+}
+
+impl Default for SkipConfig {
+    fn default() -> Self {
+        Self {
+            max_skips: 5,
+            min_length: 2,
+            max_length: 6,
+            min_shortcut: 200,
+            width: 1,
+            freeze: false,
+            diagonal: false,
+        }
+    }
+}
+
+/// tunnel between two empty tiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Skip {
+    /// last empty tile before the wall
+    pub start: [usize; 2],
+    /// first empty tile behind the wall
+    pub end: [usize; 2],
+    /// tile offset of one step along the skip
+    pub offset: [isize; 2],
+    /// walls tunneled through
+    pub length: usize,
+    pub width: usize,
+    /// walker steps saved by taking the skip
+    pub shortcut: usize,
+}
+
+impl Skip {
+    pub fn is_diagonal(&self) -> bool {
+        self.offset[0] != 0 && self.offset[1] != 0
+    }
+
+    /// tiles carved by the skip
+    pub fn tiles(&self) -> Vec<[usize; 2]> {
+        let [dx, dy] = self.offset;
+        let across = [-dy, dx];
+
+        let width = self.width.clamp(1, 3) as isize;
+        let lanes = -(width - 1) / 2..=width / 2;
+
+        let mut tiles = Vec::new();
+
+        for along in 1..=self.length as isize {
+            for lane in lanes.clone() {
+                let offset = [along * dx + lane * across[0], along * dy + lane * across[1]];
+
+                tiles.extend(self.start.checked_offset(offset));
+
+                // diagonal tiles only touch at their corners, connect them into stairs
+                if self.is_diagonal() {
+                    tiles.extend(self.start.checked_offset([offset[0] + dx, offset[1]]));
+                }
+            }
+        }
+
+        tiles
+    }
+
+    /// tiles of the skip and the walls around it, skips must not share any of them
+    fn footprint(&self) -> Vec<[usize; 2]> {
+        let mut footprint = Vec::new();
+
+        for tile in self.tiles() {
+            for offset in [[0, 0], [1, 0], [-1, 0], [0, 1], [0, -1]] {
+                footprint.extend(tile.checked_offset(offset));
+            }
+        }
+
+        footprint
+    }
+}
+
+/// finds skips saving at least `min_shortcut` steps and carves the best ones which don't cross
+/// each other, `visits` holds the walker step of every visited tile and 0 elsewhere
+pub fn generate_all_skips(
+    tiles: &mut Array2<GameTile>,
+    visits: &Array2<usize>,
+    config: &SkipConfig,
+) -> Vec<Skip> {
+    let steps = route_steps(tiles, visits);
+
+    let offsets: Vec<[isize; 2]> = if config.diagonal {
+        STRAIGHT.iter().chain(DIAGONAL.iter()).copied().collect()
+    } else {
+        STRAIGHT.to_vec()
+    };
+
+    let mut candidates = Vec::new();
+
+    for ((x, y), tile) in tiles.indexed_iter() {
+        if tile.id != BlockType::Empty.game_id() {
+            continue;
+        }
+
+        for &offset in offsets.iter() {
+            if let Some(skip) = generate_skip(tiles, &steps, [x, y], offset, config) {
+                candidates.push(skip);
+            }
+        }
+    }
+
+    // the biggest shortcuts first, position breaks ties to stay deterministic
+    candidates.sort_by(|a, b| b.shortcut.cmp(&a.shortcut).then(a.start.cmp(&b.start)));
+
+    let block = if config.freeze {
+        BlockType::Freeze
+    } else {
+        BlockType::Empty
+    };
+
+    let mut occupied = Array2::from_elem(tiles.dim(), false);
+    let mut accepted = Vec::new();
+
+    for skip in candidates {
+        if accepted.len() >= config.max_skips {
+            break;
+        }
+
+        let footprint = skip.footprint();
+
+        // rasterized overlap check, endpoints being far apart doesn't prevent crossing
+        if footprint
+            .iter()
+            .any(|&tile| occupied.get(tile).copied().unwrap_or(true))
+        {
+            continue;
+        }
+
+        for tile in footprint {
+            occupied[tile] = true;
+        }
+
+        for tile in skip.tiles() {
+            if let Some(tile) = tiles.get_mut(tile) {
+                *tile = block.tile();
+            }
+        }
+
+        accepted.push(skip);
+    }
+
+    accepted
+}
+
+/// skip through the wall right after `start` along `offset`, if it's thin enough and worth it
+fn generate_skip(
+    tiles: &Array2<GameTile>,
+    steps: &Array2<usize>,
+    start: [usize; 2],
+    offset: [isize; 2],
+    config: &SkipConfig,
+) -> Option<Skip> {
+    let is_empty = |tile: [usize; 2]| tiles[tile].id == BlockType::Empty.game_id();
+
+    let mut current = start;
+    let mut length = 0;
+
+    loop {
+        current = current
+            .checked_offset(offset)
+            .filter(|&next| tiles.get(next).is_some())?;
+
+        if is_empty(current) {
+            break;
+        }
+
+        length += 1;
+
+        if length > config.max_length {
+            return None;
+        }
+    }
+
+    if length < config.min_length.max(1) {
+        return None;
+    }
+
+    let (from, to) = (steps[start], steps[current]);
+
+    if from == 0 || to == 0 {
+        return None;
+    }
+
+    let shortcut = from.abs_diff(to);
+
+    let skip = Skip {
+        start,
+        end: current,
+        offset,
+        length,
+        width: config.width,
+        shortcut,
+    };
+
+    // wider and diagonal skips must stay inside the map
+    let inside = skip.tiles().iter().all(|&tile| tiles.get(tile).is_some());
+
+    (shortcut >= config.min_shortcut && inside).then_some(skip)
+}
+
+/// spreads the walker steps of visited tiles to the empty tiles carved around them
+fn route_steps(tiles: &Array2<GameTile>, visits: &Array2<usize>) -> Array2<usize> {
+    let mut steps = visits.clone();
+    let mut queue: VecDeque<[usize; 2]> = visits
+        .indexed_iter()
+        .filter(|(_, &visit)| visit != 0)
+        .map(|((x, y), _)| [x, y])
+        .collect();
+
+    while let Some(current) = queue.pop_front() {
+        for offset in [[0, -1], [1, 0], [0, 1], [-1, 0]] {
+            let Some(next) = current.checked_offset(offset) else {
+                continue;
+            };
+
+            let carved = tiles
+                .get(next)
+                .is_some_and(|tile| tile.id == BlockType::Empty.game_id());
+
+            if carved && steps.get(next) == Some(&0) {
+                steps[next] = steps[current];
+                queue.push_back(next);
+            }
+        }
+    }
+
+    steps
+}
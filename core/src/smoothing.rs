@@ -0,0 +1,92 @@
+use ndarray::Array2;
+use twmap::GameTile;
+
+use crate::{brush::Brush, map::BlockType, position::Vector2};
+
+/// route tiles between the points of the polyline that gets smoothed, single steps are too
+/// short to have corners worth cutting
+const SPACING: usize = 8;
+
+/// cuts every corner of `points` `iterations` times, keeps both ends in place
+pub fn chaikin(points: &[[f32; 2]], iterations: usize) -> Vec<[f32; 2]> {
+    let mut points = points.to_vec();
+
+    for _ in 0..iterations {
+        if points.len() < 3 {
+            break;
+        }
+
+        let mut smoothed = Vec::with_capacity(points.len() * 2);
+        smoothed.push(points[0]);
+
+        for pair in points.windows(2) {
+            let ([ax, ay], [bx, by]) = (pair[0], pair[1]);
+
+            smoothed.push([0.75 * ax + 0.25 * bx, 0.75 * ay + 0.25 * by]);
+            smoothed.push([0.25 * ax + 0.75 * bx, 0.25 * ay + 0.75 * by]);
+        }
+
+        smoothed.push(*points.last().unwrap());
+        points = smoothed;
+    }
+
+    points
+}
+
+/// carves `kernel` along the chaikin smoothed polyline of `route`, which removes the staircases
+/// the walker leaves on slanted parts of the route, returns the carved positions
+pub fn smooth_route(
+    tiles: &mut Array2<GameTile>,
+    route: &[[usize; 2]],
+    kernel: &Brush,
+    iterations: usize,
+) -> Vec<[usize; 2]> {
+    let mut points: Vec<[f32; 2]> = route
+        .iter()
+        .step_by(SPACING)
+        .map(|&[x, y]| [x as f32, y as f32])
+        .collect();
+
+    if let Some(&[x, y]) = route.last() {
+        points.push([x as f32, y as f32]);
+    }
+
+    let (width, height) = tiles.dim();
+    let (kernel_width, kernel_height) = kernel.dim();
+    let reach = kernel_width.max(kernel_height);
+
+    let mut carved = Vec::new();
+
+    for pair in chaikin(&points, iterations).windows(2) {
+        let ([ax, ay], [bx, by]) = (pair[0], pair[1]);
+        let steps = (bx - ax).abs().max((by - ay).abs()).ceil().max(1.0) as usize;
+
+        for i in 0..steps {
+            let t = i as f32 / steps as f32;
+            let position = [
+                (ax + (bx - ax) * t).round() as usize,
+                (ay + (by - ay) * t).round() as usize,
+            ];
+
+            // the brush isn't clipped at the map border
+            if position[0] < reach
+                || position[1] < reach
+                || position[0] + reach >= width
+                || position[1] + reach >= height
+                || carved.last() == Some(&position)
+            {
+                continue;
+            }
+
+            kernel.apply(
+                tiles,
+                Vector2::from(vec![position[0] as f32, position[1] as f32]),
+                BlockType::Empty.tile(),
+            );
+
+            carved.push(position);
+        }
+    }
+
+    carved
+}
@@ -0,0 +1,91 @@
+use ndarray::Array2;
+use twmap::{GameLayer, GameTile, TwMap};
+
+use crate::{
+    config::GenerationPreset,
+    generator::{GenerationError, Generator},
+    random::Seed,
+};
+
+/// hash of the map a preset generates with a seed, a different hash after a change means the
+/// generation isn't the same anymore, on purpose or because the random values are drawn in a
+/// different order
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    pub preset: String,
+    pub seed: Seed,
+    pub hash: u64,
+}
+
+impl Snapshot {
+    /// generates the map and snapshots it
+    pub fn record(preset: &GenerationPreset, seed: Seed) -> Result<(Self, TwMap), GenerationError> {
+        let map = generate(preset, seed)?;
+
+        let snapshot = Self {
+            preset: preset.name.clone(),
+            seed,
+            hash: hash_map(&map),
+        };
+
+        Ok((snapshot, map))
+    }
+
+    /// generates the map again and compares its hash
+    pub fn verify(&self, preset: &GenerationPreset) -> Result<(), Mismatch> {
+        let map = generate(preset, self.seed).map_err(Mismatch::Failed)?;
+
+        if hash_map(&map) == self.hash {
+            Ok(())
+        } else {
+            Err(Mismatch::Changed(Box::new(map)))
+        }
+    }
+}
+
+/// why a snapshot doesn't hold anymore
+#[derive(Debug)]
+pub enum Mismatch {
+    /// the map generated again
+    Changed(Box<TwMap>),
+    Failed(GenerationError),
+}
+
+/// hash of the game layer size and tiles
+pub fn hash_map(map: &TwMap) -> u64 {
+    let game: &GameLayer = map.find_physics_layer().unwrap();
+
+    hash_tiles(game.tiles.unwrap_ref())
+}
+
+pub fn hash_tiles(tiles: &Array2<GameTile>) -> u64 {
+    let (width, height) = tiles.dim();
+
+    let mut bytes = Vec::with_capacity(16 + tiles.len() * 2);
+    bytes.extend((width as u64).to_le_bytes());
+    bytes.extend((height as u64).to_le_bytes());
+
+    for tile in tiles.iter() {
+        bytes.extend([tile.id, tile.flags.bits()]);
+    }
+
+    seahash::hash(&bytes)
+}
+
+/// tiles that differ between two game layers, tiles outside one of them differ too
+pub fn diff_tiles(a: &Array2<GameTile>, b: &Array2<GameTile>) -> Array2<bool> {
+    let (a_width, a_height) = a.dim();
+    let (b_width, b_height) = b.dim();
+
+    Array2::from_shape_fn((a_width.max(b_width), a_height.max(b_height)), |(x, y)| {
+        a.get([x, y]) != b.get([x, y])
+    })
+}
+
+fn generate(preset: &GenerationPreset, seed: Seed) -> Result<TwMap, GenerationError> {
+    let mut generator = Generator::from_preset(preset.clone());
+
+    generator.set_seed(seed);
+    generator.try_generate_observed(|_| {})
+}
@@ -0,0 +1,162 @@
+use ndarray::Array2;
+use twmap::{GameTile, TileFlags};
+
+use crate::position::{get_x, get_y, VectorView2};
+
+/// spawn entities and their game layer ids
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpawnTeam {
+    /// regular spawn, there is no dedicated spectator tile so spectators joining the game use it
+    #[default]
+    Neutral,
+    Red,
+    Blue,
+}
+
+impl SpawnTeam {
+    pub fn game_id(&self) -> u8 {
+        match self {
+            Self::Neutral => 192,
+            Self::Red => 193,
+            Self::Blue => 194,
+        }
+    }
+}
+
+/// pickups placed next to the spawns and their game layer ids
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpawnPickup {
+    Armor,
+    Health,
+    Shotgun,
+    Grenade,
+    Ninja,
+    Laser,
+}
+
+impl SpawnPickup {
+    pub fn game_id(&self) -> u8 {
+        match self {
+            Self::Armor => 197,
+            Self::Health => 198,
+            Self::Shotgun => 199,
+            Self::Grenade => 200,
+            Self::Ninja => 201,
+            Self::Laser => 202,
+        }
+    }
+}
+
+/// layout of the spawn room
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SpawnConfig {
+    /// neutral spawns, used by spectators and non-team gametypes
+    pub neutral: usize,
+    /// red team spawns
+    pub red: usize,
+    /// blue team spawns
+    pub blue: usize,
+    /// room width, widened to fit the spawns and pickups with a free tile on each side
+    pub width: usize,
+    /// room height, floor row included
+    pub height: usize,
+    /// placed on the floor row right of the spawns
+    pub pickups: Vec<SpawnPickup>,
+    /// keeps the room's top row free for a sign, see [`SpawnConfig::sign_position`]
+    pub sign: bool,
+}
+
+impl Default for SpawnConfig {
+    fn default() -> Self {
+        Self {
+            neutral: 1,
+            red: 0,
+            blue: 0,
+            width: 3,
+            height: 4,
+            pickups: Vec::new(),
+            sign: false,
+        }
+    }
+}
+
+impl SpawnConfig {
+    pub fn count(&self) -> usize {
+        self.neutral + self.red + self.blue
+    }
+
+    /// spawns from left to right: red team, neutral, blue team
+    pub fn layout(&self) -> Vec<SpawnTeam> {
+        let mut layout = Vec::with_capacity(self.count());
+
+        layout.extend(std::iter::repeat_n(SpawnTeam::Red, self.red));
+        layout.extend(std::iter::repeat_n(SpawnTeam::Neutral, self.neutral));
+        layout.extend(std::iter::repeat_n(SpawnTeam::Blue, self.blue));
+
+        layout
+    }
+
+    /// carved room size, the sign row included
+    pub fn room_size(&self) -> (usize, usize) {
+        let width = self.width.max(self.count().max(1) + self.pickups.len() + 2);
+        let height = self.height.max(2) + self.sign as usize;
+
+        (width, height)
+    }
+
+    /// carves the spawn room above `pos` and places the spawns and pickups on its bottom row
+    pub fn apply(&self, tiles: &mut Array2<GameTile>, pos: VectorView2) {
+        let layout = self.layout();
+        let (width, height) = self.room_size();
+        let (width, height) = (width as isize, height as isize);
+
+        let left = get_x(pos) as isize - width / 2;
+        let floor = get_y(pos) as isize;
+
+        for x in left..left + width {
+            for y in floor - height + 1..=floor {
+                set_tile(tiles, x, y, 0);
+            }
+        }
+
+        for (i, team) in layout.iter().enumerate() {
+            set_tile(tiles, left + 1 + i as isize, floor, team.game_id());
+        }
+
+        let pickups_left = left + 1 + layout.len().max(1) as isize;
+
+        for (i, pickup) in self.pickups.iter().enumerate() {
+            set_tile(tiles, pickups_left + i as isize, floor, pickup.game_id());
+        }
+    }
+
+    /// center of the free top row of the room carved above `pos`, where a mapres sign with
+    /// the map info can be placed
+    pub fn sign_position(&self, pos: VectorView2) -> Option<[usize; 2]> {
+        if !self.sign {
+            return None;
+        }
+
+        let (width, height) = self.room_size();
+
+        let left = get_x(pos) as isize - width as isize / 2;
+        let top = get_y(pos) as isize - height as isize + 1;
+        let center = left + width as isize / 2;
+
+        (center >= 0 && top >= 0).then_some([center as usize, top as usize])
+    }
+}
+
+fn set_tile(tiles: &mut Array2<GameTile>, x: isize, y: isize, id: u8) {
+    if x < 0 || y < 0 {
+        return;
+    }
+
+    if let Some(tile) = tiles.get_mut((x as usize, y as usize)) {
+        *tile = GameTile::new(id, TileFlags::empty());
+    }
+}
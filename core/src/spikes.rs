@@ -0,0 +1,227 @@
+use std::collections::VecDeque;
+
+use ndarray::Array2;
+use twmap::GameTile;
+
+use crate::{map::BlockType, position::TileIndex, random::Random, spawn::SpawnTeam};
+
+/// hookable surfaces kill tiles are placed on
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpikeSurfaces {
+    /// walls with free tiles below them
+    #[default]
+    Ceilings,
+    /// walls with free tiles above them
+    Floors,
+    Both,
+}
+
+/// strips of kill tiles decorating hookable ceilings and floors, a strip is only kept while a
+/// path from a spawn to a finish remains that never touches a kill tile
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SpikeConfig {
+    pub surfaces: SpikeSurfaces,
+    /// chance of a surface strip getting kill tiles
+    pub chance: f32,
+    /// shortest strip, shorter surfaces are skipped
+    pub min_length: usize,
+    /// longest strip, longer surfaces are split into several
+    pub max_length: usize,
+}
+
+impl Default for SpikeConfig {
+    fn default() -> Self {
+        Self {
+            surfaces: SpikeSurfaces::Ceilings,
+            chance: 0.2,
+            min_length: 3,
+            max_length: 8,
+        }
+    }
+}
+
+/// horizontal run of kill tiles
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpikeStrip {
+    pub tiles: Vec<[usize; 2]>,
+}
+
+impl SpikeConfig {
+    /// turns randomly picked surface strips into kill tiles, strips cutting off the last safe
+    /// path are put back, nothing is placed when there's no safe path to begin with
+    pub fn apply(&self, tiles: &mut Array2<GameTile>, prng: &mut Random) -> Vec<SpikeStrip> {
+        let candidates = self.candidates(tiles);
+
+        // the dice are thrown for every strip so the placement doesn't shift other draws
+        let picked: Vec<Vec<[usize; 2]>> = candidates
+            .into_iter()
+            .filter(|_| prng.gen_bool(self.chance))
+            .collect();
+
+        let Some(mut path) = safe_path(tiles) else {
+            return Vec::new();
+        };
+
+        let mut on_path = Array2::from_elem(tiles.dim(), false);
+
+        for &index in path.iter() {
+            on_path[index] = true;
+        }
+
+        let mut placed = Vec::new();
+
+        for strip in picked {
+            let before: Vec<GameTile> = strip.iter().map(|&index| tiles[index]).collect();
+
+            for &index in strip.iter() {
+                tiles[index] = BlockType::Death.tile();
+            }
+
+            let touches_path = strip.iter().any(|&index| {
+                on_path[index]
+                    || index
+                        .neighbors(tiles.dim(), false)
+                        .any(|next| on_path[next])
+            });
+
+            if touches_path {
+                match safe_path(tiles) {
+                    Some(detour) => {
+                        for &index in path.iter() {
+                            on_path[index] = false;
+                        }
+
+                        for &index in detour.iter() {
+                            on_path[index] = true;
+                        }
+
+                        path = detour;
+                    }
+                    None => {
+                        for (&index, &tile) in strip.iter().zip(before.iter()) {
+                            tiles[index] = tile;
+                        }
+
+                        continue;
+                    }
+                }
+            }
+
+            placed.push(SpikeStrip { tiles: strip });
+        }
+
+        placed
+    }
+
+    /// runs of hookable surface tiles at most `max_length` long, left to right and top to bottom
+    fn candidates(&self, tiles: &Array2<GameTile>) -> Vec<Vec<[usize; 2]>> {
+        let (width, height) = tiles.dim();
+        let hookable = BlockType::Hookable.game_id();
+        let free = |x: usize, y: usize| tiles.get([x, y]).is_some_and(|tile| !is_wall(tile));
+        let wall = |x: usize, y: usize| tiles.get([x, y]).is_some_and(is_wall);
+
+        // the wall behind the kill tiles keeps the map sealed
+        let surface = |x: usize, y: usize| {
+            if tiles[[x, y]].id != hookable || y == 0 {
+                return false;
+            }
+
+            let ceiling = free(x, y + 1) && wall(x, y - 1);
+            let floor = free(x, y - 1) && wall(x, y + 1);
+
+            match self.surfaces {
+                SpikeSurfaces::Ceilings => ceiling,
+                SpikeSurfaces::Floors => floor,
+                SpikeSurfaces::Both => ceiling || floor,
+            }
+        };
+
+        let max_length = self.max_length.max(1);
+        let mut candidates = Vec::new();
+
+        for y in 0..height {
+            let mut run = Vec::new();
+
+            for x in 0..=width {
+                if x < width && surface(x, y) {
+                    run.push([x, y]);
+
+                    if run.len() < max_length {
+                        continue;
+                    }
+                }
+
+                if run.len() >= self.min_length.max(1) {
+                    candidates.push(run.clone());
+                }
+
+                run.clear();
+            }
+        }
+
+        candidates
+    }
+}
+
+/// shortest path from a spawn to a finish tile over passable tiles not touching a kill tile,
+/// `None` when there's no spawn, no finish or every path touches a kill tile
+pub fn safe_path(tiles: &Array2<GameTile>) -> Option<Vec<[usize; 2]>> {
+    let dim = tiles.dim();
+    let spawns = [SpawnTeam::Neutral, SpawnTeam::Red, SpawnTeam::Blue].map(|team| team.game_id());
+    let death = BlockType::Death.game_id();
+
+    let safe = |index: [usize; 2]| {
+        !is_wall(&tiles[index])
+            && tiles[index].id != death
+            && index
+                .neighbors(dim, false)
+                .all(|next| tiles[next].id != death)
+    };
+
+    let mut previous: Array2<Option<[usize; 2]>> = Array2::from_elem(dim, None);
+    let mut queue = VecDeque::new();
+
+    for ((x, y), tile) in tiles.indexed_iter() {
+        if spawns.contains(&tile.id) && safe([x, y]) {
+            previous[[x, y]] = Some([x, y]);
+            queue.push_back([x, y]);
+        }
+    }
+
+    while let Some(current) = queue.pop_front() {
+        if tiles[current].id == BlockType::Finish.game_id() {
+            let mut path = vec![current];
+            let mut index = current;
+
+            while let Some(before) = previous[index].filter(|&before| before != index) {
+                path.push(before);
+                index = before;
+            }
+
+            path.reverse();
+            return Some(path);
+        }
+
+        for next in current.neighbors(dim, false) {
+            if previous[next].is_none() && safe(next) {
+                previous[next] = Some(current);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+fn is_wall(tile: &GameTile) -> bool {
+    [
+        BlockType::Hookable,
+        BlockType::Unhookable,
+        BlockType::Hookthrough,
+    ]
+    .iter()
+    .any(|block| tile.id == block.game_id())
+}
@@ -0,0 +1,327 @@
+use std::collections::VecDeque;
+
+use ndarray::s;
+use twmap::{GameLayer, GameTile, TwMap};
+
+use crate::{
+    chunks::ChunkedTiles,
+    config::GenerationPreset,
+    generator::{Canvas, GenerationError, Generator},
+    position::as_index,
+    random::{Random, Seed},
+    spawn::SpawnConfig,
+};
+
+/// side new strips are appended at
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StreamDirection {
+    /// an endless climb
+    #[default]
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl StreamDirection {
+    /// axis the strips follow each other along
+    fn axis(&self) -> usize {
+        match self {
+            Self::Up | Self::Down => 1,
+            Self::Left | Self::Right => 0,
+        }
+    }
+
+    fn forward(&self) -> bool {
+        matches!(self, Self::Down | Self::Right)
+    }
+}
+
+/// endless generation in strips, the walker of each strip starts where the last one stopped
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct StreamConfig {
+    pub direction: StreamDirection,
+    /// route length of a strip along the direction in tiles
+    pub strip_length: usize,
+    /// room for the route across the direction in tiles
+    pub strip_width: usize,
+    /// waypoints between the entry and the exit of a strip
+    pub waypoints: usize,
+    /// tiles of the last strip behind its exit the next strip starts from, so they join
+    pub overlap: usize,
+    /// latest strips whose tiles are kept in the stream's world, 0 keeps none
+    pub world_strips: usize,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            direction: StreamDirection::Up,
+            strip_length: 200,
+            strip_width: 100,
+            waypoints: 3,
+            overlap: 20,
+            world_strips: 0,
+        }
+    }
+}
+
+/// generated strip of an endless map
+#[derive(Debug)]
+pub struct Strip {
+    pub index: usize,
+    /// position of the map's top left tile in the stream, the first strip's uncropped map
+    /// starts at 0, 0
+    pub offset: [i64; 2],
+    pub map: TwMap,
+}
+
+/// generates the strips of an endless map one at a time, only the last strip is kept to
+/// continue from, so older ones can be exported and dropped
+pub struct StripStream {
+    generator: Generator,
+    config: StreamConfig,
+    prng: Random,
+    index: usize,
+    /// uncropped position of the last strip's top left tile
+    origin: [i64; 2],
+    /// normalized waypoint coordinate across the direction the last strip ended at
+    across: Option<f32>,
+    /// uncropped game tiles of the latest strips at their stream positions
+    world: ChunkedTiles<GameTile>,
+    /// areas of the strips in the world, oldest first
+    world_areas: VecDeque<([i64; 2], [i64; 2])>,
+}
+
+impl StripStream {
+    /// the preset's route is replaced by the strips, branches, room grids, towers, jitter,
+    /// loops, finishes and the guidance mask are turned off
+    pub fn new(mut preset: GenerationPreset, config: StreamConfig, seed: Seed) -> Self {
+        preset.waypoints.branches.clear();
+        preset.waypoints.looped = false;
+        preset.waypoints.overrides.clear();
+        preset.waypoints.jitter = None;
+        preset.room_grid = None;
+        preset.tower = None;
+        preset.guidance_mask = None;
+        preset.post_process.finish = false;
+
+        let fill = preset.mode.fill_block().tile();
+
+        Self {
+            generator: Generator::from_preset(preset),
+            config,
+            prng: Random::new(seed),
+            index: 0,
+            origin: [0, 0],
+            across: None,
+            world: ChunkedTiles::new(fill),
+            world_areas: VecDeque::new(),
+        }
+    }
+
+    pub fn config(&self) -> &StreamConfig {
+        &self.config
+    }
+
+    /// strips generated so far
+    pub fn len(&self) -> usize {
+        self.index
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index == 0
+    }
+
+    /// tiles of the latest strips, later strips overwrite the overlap with earlier ones
+    pub fn world(&self) -> &ChunkedTiles<GameTile> {
+        &self.world
+    }
+
+    /// generates the next strip, a failed one is generated again by the next call
+    pub fn next_strip(&mut self) -> Result<Strip, GenerationError> {
+        let scale_factor = self.generator.get_scale_factor();
+        let length = self.config.strip_length as f32 / scale_factor;
+        let width = self.config.strip_width as f32 / scale_factor;
+        let axis = self.config.direction.axis();
+
+        let mut prng = self.prng.fork(self.index as u64);
+        let count = self.config.waypoints + 1;
+
+        let mut waypoints: Vec<(f32, f32)> = (0..=count)
+            .map(|i| {
+                let t = i as f32 / count as f32;
+                let along = if self.config.direction.forward() {
+                    t * length
+                } else {
+                    (1.0 - t) * length
+                };
+                let across = match (i, self.across) {
+                    (0, Some(across)) => across,
+                    (0, None) => width / 2.0,
+                    _ => prng.in_range(0.0..=width),
+                };
+
+                if axis == 1 {
+                    (across, along)
+                } else {
+                    (along, across)
+                }
+            })
+            .collect();
+
+        // the walker halts once it heads to the last waypoint, so the exit goes in twice
+        waypoints.push(*waypoints.last().unwrap());
+
+        let exit = waypoints[count];
+        self.across = Some(if axis == 1 { exit.0 } else { exit.1 });
+
+        let preset = self.generator.preset_mut();
+        preset.waypoints.waypoints = waypoints;
+
+        if self.index > 0 {
+            // only the first strip gets the spawns
+            preset.post_process.spawn = SpawnConfig {
+                neutral: 0,
+                red: 0,
+                blue: 0,
+                pickups: Vec::new(),
+                sign: false,
+                ..preset.post_process.spawn.clone()
+            };
+        }
+
+        self.generator.set_seed(prng.gen_u64());
+        let map = self.generator.try_generate_observed(|_| {})?;
+
+        // the canvas start, the last strip's exit, lies under the route start
+        if let (true, Some([x, y])) = (self.index > 0, self.route_position(0)) {
+            self.origin[0] -= x as i64;
+            self.origin[1] -= y as i64;
+        }
+
+        let crop = self.generator.crop_offset();
+        let offset = [
+            self.origin[0] + crop[0] as i64,
+            self.origin[1] + crop[1] as i64,
+        ];
+
+        let strip = Strip {
+            index: self.index,
+            offset,
+            map,
+        };
+
+        self.update_world();
+        self.continue_from_exit();
+        self.index += 1;
+
+        Ok(strip)
+    }
+
+    /// uncropped position the walker reached route waypoint `i` at
+    fn route_position(&self, i: usize) -> Option<[usize; 2]> {
+        self.generator
+            .route_positions()
+            .get(i)
+            .map(|position| as_index(position.view()))
+    }
+
+    /// adds the last strip to the world and drops the chunks only older strips cover
+    fn update_world(&mut self) {
+        let Some(previous) = self.generator.previous_map() else {
+            return;
+        };
+
+        if self.config.world_strips == 0 {
+            return;
+        }
+
+        let game: &GameLayer = previous.raw_map().find_physics_layer().unwrap();
+        let tiles = game.tiles.unwrap_ref();
+        let (width, height) = tiles.dim();
+
+        self.world.insert_tiles(tiles.view(), self.origin);
+        self.world_areas.push_back((
+            self.origin,
+            [
+                self.origin[0] + width as i64 - 1,
+                self.origin[1] + height as i64 - 1,
+            ],
+        ));
+
+        if self.world_areas.len() > self.config.world_strips {
+            self.world_areas.pop_front();
+
+            let (min, max) = self.world_areas.iter().fold(
+                ([i64::MAX; 2], [i64::MIN; 2]),
+                |(min, max), (from, to)| {
+                    (
+                        [min[0].min(from[0]), min[1].min(from[1])],
+                        [max[0].max(to[0]), max[1].max(to[1])],
+                    )
+                },
+            );
+
+            self.world.retain_area(min, max);
+        }
+    }
+
+    /// cuts the tiles behind the last strip's exit into the canvas of the next strip
+    fn continue_from_exit(&mut self) {
+        let Some(previous) = self.generator.previous_map() else {
+            return;
+        };
+
+        let Some(exit) = self.generator.route_positions().last() else {
+            return;
+        };
+        let exit = as_index(exit.view());
+
+        let game: &GameLayer = previous.raw_map().find_physics_layer().unwrap();
+        let tiles = game.tiles.unwrap_ref();
+
+        let axis = self.config.direction.axis();
+        let size = tiles.dim();
+        let size = if axis == 1 { size.1 } else { size.0 };
+        let overlap = self.config.overlap.max(1);
+
+        // behind the exit is where the strip came from
+        let (from, to) = if self.config.direction.forward() {
+            ((exit[axis] + 1).saturating_sub(overlap), exit[axis] + 1)
+        } else {
+            (exit[axis], (exit[axis] + overlap).min(size))
+        };
+
+        let mut start = exit;
+        start[axis] -= from;
+
+        let tiles = if axis == 1 {
+            tiles.slice(s![.., from..to])
+        } else {
+            tiles.slice(s![from..to, ..])
+        };
+
+        // the next strip is placed relative to its route start, which is this exit
+        self.origin[0] += exit[0] as i64;
+        self.origin[1] += exit[1] as i64;
+
+        let canvas = Canvas {
+            tiles: tiles.to_owned(),
+            start,
+        };
+
+        self.generator.set_canvas(Some(canvas));
+    }
+}
+
+impl Iterator for StripStream {
+    type Item = Result<Strip, GenerationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_strip())
+    }
+}
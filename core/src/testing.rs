@@ -0,0 +1,231 @@
+//! invariant checkers and random seed and preset generators for tests asserting generation
+//! correctness over many seeds
+
+use std::{fmt, panic};
+
+use ndarray::Array2;
+use twmap::{GameLayer, GameTile, TwMap};
+
+use crate::{
+    config::GenerationPreset,
+    generator::{GenerationError, Generator},
+    handle::panic_message,
+    map::BlockType,
+    random::{Random, Seed},
+    spawn::SpawnTeam,
+    spikes::safe_path,
+    tuning::{MapStats, MutationConfig},
+};
+
+/// broken invariant and where it was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// generation panicked, out of bounds writes end up here
+    Panicked(String),
+    /// generation returned an error, like an exceeded budget under the failing policy
+    Failed(GenerationError),
+    /// a physics layer doesn't have the game layer's size, sizes are width and height
+    LayerSize {
+        layer: usize,
+        size: [usize; 2],
+        expected: [usize; 2],
+    },
+    /// empty tile touching a wall without freeze in between
+    UnfrozenEdge([usize; 2]),
+    MissingSpawn,
+    MissingFinish,
+    /// no spawn is connected to a finish tile
+    Disconnected,
+    /// every path from a spawn to a finish touches a kill tile
+    Unsafe,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Panicked(message) => write!(f, "generation panicked: {}", message),
+            Self::Failed(err) => write!(f, "generation failed: {}", err),
+            Self::LayerSize {
+                layer,
+                size,
+                expected,
+            } => write!(
+                f,
+                "layer {} is {}x{} instead of {}x{}",
+                layer, size[0], size[1], expected[0], expected[1]
+            ),
+            Self::UnfrozenEdge([x, y]) => write!(f, "empty tile at {}, {} touches a wall", x, y),
+            Self::MissingSpawn => write!(f, "no spawn"),
+            Self::MissingFinish => write!(f, "no finish"),
+            Self::Disconnected => write!(f, "spawn and finish aren't connected"),
+            Self::Unsafe => write!(f, "every path to the finish touches a kill tile"),
+        }
+    }
+}
+
+/// invariants a generated map is checked for, each can be turned off for presets that don't
+/// hold it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invariants {
+    /// every physics layer has the game layer's size
+    pub layer_sizes: bool,
+    /// every empty tile next to a wall, diagonals included, is separated from it by freeze,
+    /// holds for presets lining the route with freeze and fixing edge bugs
+    pub freeze_lining: bool,
+    /// a spawn and a finish exist and are connected, by a path without kill tiles next to it if
+    /// the map has any
+    pub connected: bool,
+}
+
+impl Default for Invariants {
+    fn default() -> Self {
+        Self {
+            layer_sizes: true,
+            freeze_lining: true,
+            connected: true,
+        }
+    }
+}
+
+impl Invariants {
+    pub fn check(&self, map: &TwMap) -> Result<(), Violation> {
+        let game: &GameLayer = map.find_physics_layer().unwrap();
+        let tiles = game.tiles.unwrap_ref();
+
+        if self.layer_sizes {
+            check_layer_sizes(map)?;
+        }
+
+        if self.freeze_lining {
+            check_freeze_lining(tiles)?;
+        }
+
+        if self.connected {
+            check_connected(tiles)?;
+        }
+
+        Ok(())
+    }
+
+    /// generates `preset` with `seed` and checks the map, panics are reported as violations
+    pub fn check_generation(&self, preset: &GenerationPreset, seed: Seed) -> Result<(), Violation> {
+        let generated = panic::catch_unwind(|| {
+            let mut generator = Generator::from_preset(preset.clone());
+
+            generator.set_seed(seed);
+            generator.try_generate_observed(|_| {})
+        });
+
+        match generated {
+            Ok(Ok(map)) => self.check(&map),
+            Ok(Err(err)) => Err(Violation::Failed(err)),
+            Err(payload) => Err(Violation::Panicked(panic_message(payload.as_ref()))),
+        }
+    }
+
+    /// checks every seed, returns the first one breaking an invariant
+    pub fn check_seeds(
+        &self,
+        preset: &GenerationPreset,
+        seeds: impl IntoIterator<Item = Seed>,
+    ) -> Result<(), (Seed, Violation)> {
+        for seed in seeds {
+            self.check_generation(preset, seed)
+                .map_err(|violation| (seed, violation))?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn check_layer_sizes(map: &TwMap) -> Result<(), Violation> {
+    let game: &GameLayer = map.find_physics_layer().unwrap();
+    let expected = game.tiles.shape();
+
+    let layers = map.physics_group().layers.iter();
+
+    for (layer, shape) in layers
+        .enumerate()
+        .filter_map(|(i, l)| Some((i, l.shape()?)))
+    {
+        if shape != expected {
+            return Err(Violation::LayerSize {
+                layer,
+                size: [shape.w, shape.h],
+                expected: [expected.w, expected.h],
+            });
+        }
+    }
+
+    Ok(())
+}
+
+pub fn check_freeze_lining(tiles: &Array2<GameTile>) -> Result<(), Violation> {
+    let (width, height) = tiles.dim();
+
+    for ((x, y), tile) in tiles.indexed_iter() {
+        if tile.id != BlockType::Empty.game_id() {
+            continue;
+        }
+
+        let touches_wall = (x.saturating_sub(1)..(x + 2).min(width))
+            .flat_map(|x| (y.saturating_sub(1)..(y + 2).min(height)).map(move |y| [x, y]))
+            .any(|index| is_wall(&tiles[index]));
+
+        if touches_wall {
+            return Err(Violation::UnfrozenEdge([x, y]));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn check_connected(tiles: &Array2<GameTile>) -> Result<(), Violation> {
+    let spawns = [SpawnTeam::Neutral, SpawnTeam::Red, SpawnTeam::Blue].map(|team| team.game_id());
+
+    if !tiles.iter().any(|tile| spawns.contains(&tile.id)) {
+        return Err(Violation::MissingSpawn);
+    }
+
+    if !tiles
+        .iter()
+        .any(|tile| tile.id == BlockType::Finish.game_id())
+    {
+        return Err(Violation::MissingFinish);
+    }
+
+    if MapStats::from_tiles(tiles).route_length.is_none() {
+        return Err(Violation::Disconnected);
+    }
+
+    let death = BlockType::Death.game_id();
+
+    if tiles.iter().any(|tile| tile.id == death) && safe_path(tiles).is_none() {
+        return Err(Violation::Unsafe);
+    }
+
+    Ok(())
+}
+
+/// `count` random seeds
+pub fn seeds(prng: &mut Random, count: usize) -> impl Iterator<Item = Seed> + '_ {
+    (0..count).map(|_| prng.gen_u64())
+}
+
+/// `base` with every tunable field moved by a random amount up to its whole bounds
+pub fn arbitrary_preset(base: &GenerationPreset, prng: &mut Random) -> GenerationPreset {
+    let mutation = MutationConfig {
+        probability: 1.0,
+        strength: 1.0,
+        ..Default::default()
+    };
+
+    mutation.mutate(base, prng)
+}
+
+/// exported maps have their hookthrough walls split into a hookable tile and a front layer one
+fn is_wall(tile: &GameTile) -> bool {
+    [BlockType::Hookable, BlockType::Unhookable]
+        .iter()
+        .any(|block| tile.id == block.game_id())
+}
@@ -0,0 +1,105 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// time a generation spent in each phase, stays empty on wasm where there is no clock
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Timing {
+    /// phases in the order they first ran
+    phases: Vec<(&'static str, Duration)>,
+    /// walker steps of the generation
+    pub steps: usize,
+}
+
+impl Timing {
+    pub fn add(&mut self, phase: &'static str, elapsed: Duration) {
+        match self.phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => self.phases.push((phase, elapsed)),
+        }
+    }
+
+    pub fn phases(&self) -> &[(&'static str, Duration)] {
+        &self.phases
+    }
+
+    /// time spent in `phase`, zero if it didn't run
+    pub fn phase(&self, phase: &str) -> Duration {
+        self.phases
+            .iter()
+            .find(|(name, _)| *name == phase)
+            .map_or(Duration::ZERO, |&(_, elapsed)| elapsed)
+    }
+
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|&(_, elapsed)| elapsed).sum()
+    }
+
+    /// walker steps per second of the walking phase
+    pub fn steps_per_second(&self) -> Option<f64> {
+        let walking = self.phase("walking").as_secs_f64();
+
+        (walking > 0.0).then(|| self.steps as f64 / walking)
+    }
+}
+
+impl fmt::Display for Timing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total().as_secs_f64().max(f64::EPSILON);
+
+        for &(name, elapsed) in self.phases.iter() {
+            writeln!(
+                f,
+                "{:<12}{:>10.2} ms{:>7.1}%",
+                name,
+                elapsed.as_secs_f64() * 1000.0,
+                elapsed.as_secs_f64() / total * 100.0
+            )?;
+        }
+
+        write!(f, "{:<12}{:>10.2} ms", "total", total * 1000.0)?;
+
+        if let Some(steps_per_second) = self.steps_per_second() {
+            write!(f, ", {:.0} walker steps/s", steps_per_second)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// hands the time between two ticks to the phase of the first one
+#[derive(Debug, Default)]
+pub(crate) struct PhaseClock {
+    last: Option<(Instant, &'static str)>,
+}
+
+impl PhaseClock {
+    /// starts timing `phase`, the time since the clock stopped isn't counted
+    pub fn start(&mut self, phase: &'static str) {
+        self.last = now().map(|now| (now, phase));
+    }
+
+    /// counts the time since the last tick and goes on with `phase`
+    pub fn tick(&mut self, phase: &'static str, timing: &mut Timing) {
+        self.stop(timing);
+        self.start(phase);
+    }
+
+    pub fn stop(&mut self, timing: &mut Timing) {
+        if let (Some((last, phase)), Some(now)) = (self.last.take(), now()) {
+            timing.add(phase, now - last);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> Option<Instant> {
+    Some(Instant::now())
+}
+
+/// `Instant::now` panics on wasm
+#[cfg(target_arch = "wasm32")]
+fn now() -> Option<Instant> {
+    None
+}
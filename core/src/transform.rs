@@ -0,0 +1,138 @@
+use ndarray::{s, Array2, ArrayView2};
+use twmap::TileFlags;
+
+// tile flag bits as ddnet names them
+const XFLIP: u8 = 1;
+const YFLIP: u8 = 2;
+const ROTATE: u8 = 8;
+
+/// quarter turns and mirroring of a tile area, the mirroring happens before the turns
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Transform {
+    /// clockwise, only the lowest two bits matter
+    pub quarter_turns: u8,
+    /// flips the columns
+    pub mirror_x: bool,
+    /// flips the rows
+    pub mirror_y: bool,
+}
+
+impl Transform {
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// the transform followed by a clockwise quarter turn
+    pub fn rotated(self) -> Self {
+        Self {
+            quarter_turns: (self.quarter_turns + 1) % 4,
+            ..self
+        }
+    }
+
+    /// the transform followed by flipping the columns
+    pub fn mirrored_x(self) -> Self {
+        self.followed_by_mirror(true)
+    }
+
+    /// the transform followed by flipping the rows
+    pub fn mirrored_y(self) -> Self {
+        self.followed_by_mirror(false)
+    }
+
+    fn followed_by_mirror(self, columns: bool) -> Self {
+        // mirroring after turns is the same as mirroring before turning the other way round
+        Self {
+            quarter_turns: (4 - self.quarter_turns % 4) % 4,
+            mirror_x: self.mirror_x != columns,
+            mirror_y: self.mirror_y == columns,
+        }
+    }
+
+    /// size of a `dim` area after the transform
+    pub fn apply_dim(&self, (width, height): (usize, usize)) -> (usize, usize) {
+        if self.quarter_turns % 2 == 1 {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    pub fn apply<T: Clone>(&self, tiles: ArrayView2<T>) -> Array2<T> {
+        let mut tiles = tiles.to_owned();
+
+        if self.mirror_x {
+            tiles = mirror_x(tiles.view());
+        }
+
+        if self.mirror_y {
+            tiles = mirror_y(tiles.view());
+        }
+
+        for _ in 0..self.quarter_turns % 4 {
+            tiles = rotate_cw(tiles.view());
+        }
+
+        tiles
+    }
+
+    /// where the tile at `index` of a `dim` area ends up
+    pub fn apply_index(&self, index: [usize; 2], (width, height): (usize, usize)) -> [usize; 2] {
+        let [mut x, mut y] = index;
+        let (mut width, mut height) = (width, height);
+
+        if self.mirror_x {
+            x = width - 1 - x;
+        }
+
+        if self.mirror_y {
+            y = height - 1 - y;
+        }
+
+        for _ in 0..self.quarter_turns % 4 {
+            [x, y] = [height - 1 - y, x];
+            (width, height) = (height, width);
+        }
+
+        [x, y]
+    }
+
+    /// orientation flags of a tile after the transform, same as the ddnet editor's brush
+    /// transforms
+    pub fn apply_flags(&self, flags: TileFlags) -> TileFlags {
+        let mut bits = flags.bits();
+
+        if self.mirror_x {
+            bits ^= if bits & ROTATE != 0 { YFLIP } else { XFLIP };
+        }
+
+        if self.mirror_y {
+            bits ^= if bits & ROTATE != 0 { XFLIP } else { YFLIP };
+        }
+
+        for _ in 0..self.quarter_turns % 4 {
+            if bits & ROTATE != 0 {
+                bits ^= XFLIP | YFLIP;
+            }
+
+            bits ^= ROTATE;
+        }
+
+        TileFlags::from_bits_truncate(bits)
+    }
+}
+
+/// turns the tiles a quarter clockwise, the map's y axis points down
+pub fn rotate_cw<T: Clone>(tiles: ArrayView2<T>) -> Array2<T> {
+    tiles.t().slice(s![..;-1, ..]).to_owned()
+}
+
+pub fn mirror_x<T: Clone>(tiles: ArrayView2<T>) -> Array2<T> {
+    tiles.slice(s![..;-1, ..]).to_owned()
+}
+
+pub fn mirror_y<T: Clone>(tiles: ArrayView2<T>) -> Array2<T> {
+    tiles.slice(s![.., ..;-1]).to_owned()
+}
@@ -0,0 +1,417 @@
+use std::collections::VecDeque;
+
+use ndarray::Array2;
+use twmap::{GameLayer, GameTile, TwMap};
+
+use crate::{
+    config::GenerationPreset,
+    generator::{GenerationError, Generator},
+    map::BlockType,
+    position::TileIndex,
+    random::{Random, Seed},
+    spawn::SpawnTeam,
+};
+
+/// numeric preset field the search is allowed to change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TunableField {
+    ReachDistance,
+    Randomness,
+    UnhookableProbability,
+    HookthroughProbability,
+    IslandProbability,
+    MinStraightRun,
+    MaxTurnFrequency,
+    BacktrackPenalty,
+    BacktrackDecay,
+    GuideBias,
+    KernelSize,
+    KernelCircularity,
+    ScaleFactor,
+    Margin,
+}
+
+impl TunableField {
+    pub const ALL: [TunableField; 14] = [
+        TunableField::ReachDistance,
+        TunableField::Randomness,
+        TunableField::UnhookableProbability,
+        TunableField::HookthroughProbability,
+        TunableField::IslandProbability,
+        TunableField::MinStraightRun,
+        TunableField::MaxTurnFrequency,
+        TunableField::BacktrackPenalty,
+        TunableField::BacktrackDecay,
+        TunableField::GuideBias,
+        TunableField::KernelSize,
+        TunableField::KernelCircularity,
+        TunableField::ScaleFactor,
+        TunableField::Margin,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TunableField::ReachDistance => "ReachDistance",
+            TunableField::Randomness => "Randomness",
+            TunableField::UnhookableProbability => "UnhookableProbability",
+            TunableField::HookthroughProbability => "HookthroughProbability",
+            TunableField::IslandProbability => "IslandProbability",
+            TunableField::MinStraightRun => "MinStraightRun",
+            TunableField::MaxTurnFrequency => "MaxTurnFrequency",
+            TunableField::BacktrackPenalty => "BacktrackPenalty",
+            TunableField::BacktrackDecay => "BacktrackDecay",
+            TunableField::GuideBias => "GuideBias",
+            TunableField::KernelSize => "KernelSize",
+            TunableField::KernelCircularity => "KernelCircularity",
+            TunableField::ScaleFactor => "ScaleFactor",
+            TunableField::Margin => "Margin",
+        }
+    }
+
+    /// inclusive range mutated values are clamped to
+    pub fn bounds(&self) -> (f32, f32) {
+        match self {
+            TunableField::ReachDistance => (1.0, 8.0),
+            TunableField::Randomness => (0.0, 1.0),
+            TunableField::UnhookableProbability => (0.0, 0.5),
+            TunableField::HookthroughProbability => (0.0, 0.5),
+            TunableField::IslandProbability => (0.0, 0.2),
+            TunableField::MinStraightRun => (0.0, 10.0),
+            TunableField::MaxTurnFrequency => (0.1, 1.0),
+            TunableField::BacktrackPenalty => (0.0, 1.0),
+            TunableField::BacktrackDecay => (10.0, 500.0),
+            TunableField::GuideBias => (0.0, 1.0),
+            TunableField::KernelSize => (1.0, 8.0),
+            TunableField::KernelCircularity => (0.0, 1.0),
+            TunableField::ScaleFactor => (50.0, 400.0),
+            TunableField::Margin => (50.0, 400.0),
+        }
+    }
+
+    /// whether the field only takes whole numbers
+    pub fn integer(&self) -> bool {
+        matches!(
+            self,
+            TunableField::MinStraightRun
+                | TunableField::BacktrackDecay
+                | TunableField::KernelSize
+                | TunableField::Margin
+        )
+    }
+
+    pub fn get(&self, preset: &GenerationPreset) -> f32 {
+        let walker = &preset.walker;
+
+        match self {
+            TunableField::ReachDistance => walker.reach_distance,
+            TunableField::Randomness => walker.randomness,
+            TunableField::UnhookableProbability => walker.unhookable_probability,
+            TunableField::HookthroughProbability => walker.hookthrough_probability,
+            TunableField::IslandProbability => walker.island_probability,
+            TunableField::MinStraightRun => walker.min_straight_run as f32,
+            TunableField::MaxTurnFrequency => walker.max_turn_frequency,
+            TunableField::BacktrackPenalty => walker.backtrack_penalty,
+            TunableField::BacktrackDecay => walker.backtrack_decay as f32,
+            TunableField::GuideBias => walker.guide_bias,
+            TunableField::KernelSize => preset.kernel.size as f32,
+            TunableField::KernelCircularity => preset.kernel.circularity,
+            TunableField::ScaleFactor => preset.waypoints.scale_factor,
+            TunableField::Margin => preset.waypoints.margin as f32,
+        }
+    }
+
+    /// sets the field to `value` clamped to the bounds, integer fields are rounded
+    pub fn set(&self, preset: &mut GenerationPreset, value: f32) {
+        let (min, max) = self.bounds();
+        let value = value.clamp(min, max);
+        let whole = value.round() as usize;
+        let walker = &mut preset.walker;
+
+        match self {
+            TunableField::ReachDistance => walker.reach_distance = value,
+            TunableField::Randomness => walker.randomness = value,
+            TunableField::UnhookableProbability => walker.unhookable_probability = value,
+            TunableField::HookthroughProbability => walker.hookthrough_probability = value,
+            TunableField::IslandProbability => walker.island_probability = value,
+            TunableField::MinStraightRun => walker.min_straight_run = whole,
+            TunableField::MaxTurnFrequency => walker.max_turn_frequency = value,
+            TunableField::BacktrackPenalty => walker.backtrack_penalty = value,
+            TunableField::BacktrackDecay => walker.backtrack_decay = whole,
+            TunableField::GuideBias => walker.guide_bias = value,
+            TunableField::KernelSize => preset.kernel.size = whole,
+            TunableField::KernelCircularity => preset.kernel.circularity = value,
+            TunableField::ScaleFactor => preset.waypoints.scale_factor = value,
+            TunableField::Margin => preset.waypoints.margin = whole,
+        }
+    }
+}
+
+/// random perturbation of the tunable preset fields
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MutationConfig {
+    pub fields: Vec<TunableField>,
+    /// probability of each field being changed, at least one field always is
+    pub probability: f32,
+    /// largest change as a share of the field bounds
+    pub strength: f32,
+}
+
+impl Default for MutationConfig {
+    fn default() -> Self {
+        Self {
+            fields: TunableField::ALL.to_vec(),
+            probability: 0.3,
+            strength: 0.2,
+        }
+    }
+}
+
+impl MutationConfig {
+    /// copy of `preset` with some of the fields moved by up to `strength` of their bounds
+    pub fn mutate(&self, preset: &GenerationPreset, prng: &mut Random) -> GenerationPreset {
+        let mut mutant = preset.clone();
+
+        if self.fields.is_empty() {
+            return mutant;
+        }
+
+        let forced = prng.in_range(0..self.fields.len());
+
+        for (index, field) in self.fields.iter().enumerate() {
+            if index != forced && !prng.gen_bool(self.probability) {
+                continue;
+            }
+
+            let (min, max) = field.bounds();
+            let direction: f32 = prng.in_range(-1.0..=1.0);
+            let change = direction * self.strength * (max - min);
+            let mut value = field.get(&mutant) + change;
+
+            // integer fields would round small changes away
+            if field.integer() && value.round() == field.get(&mutant) {
+                value += change.signum();
+            }
+
+            field.set(&mut mutant, value);
+        }
+
+        mutant
+    }
+}
+
+/// numbers the fitness is computed from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapStats {
+    /// shortest walk from a spawn to a finish tile, `None` when they aren't connected
+    pub route_length: Option<usize>,
+    /// freeze tiles among the passable tiles
+    pub freeze_ratio: f32,
+    /// passable tiles enclosed by walls on three sides
+    pub dead_ends: usize,
+    /// tiles that aren't walls
+    pub passable: usize,
+}
+
+impl MapStats {
+    pub fn from_map(map: &TwMap) -> Self {
+        let game: &GameLayer = map.find_physics_layer().unwrap();
+
+        Self::from_tiles(game.tiles.unwrap_ref())
+    }
+
+    pub fn from_tiles(tiles: &Array2<GameTile>) -> Self {
+        let (width, height) = tiles.dim();
+        let passable = tiles.map(|tile| !is_wall(tile));
+
+        let open_neighbors = |x: usize, y: usize| {
+            [x, y]
+                .neighbors((width, height), false)
+                .filter(|&index| passable[index])
+                .count()
+        };
+
+        let mut freeze = 0;
+        let mut dead_ends = 0;
+
+        for ((x, y), &open) in passable.indexed_iter() {
+            if !open {
+                continue;
+            }
+
+            if tiles[[x, y]].id == BlockType::Freeze.game_id() {
+                freeze += 1;
+            }
+
+            if open_neighbors(x, y) == 1 {
+                dead_ends += 1;
+            }
+        }
+
+        let count = passable.iter().filter(|&&open| open).count();
+
+        Self {
+            route_length: route_length(tiles, &passable),
+            freeze_ratio: freeze as f32 / count.max(1) as f32,
+            dead_ends,
+            passable: count,
+        }
+    }
+}
+
+/// weights of the map stats in the fitness
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct FitnessConfig {
+    /// route length aimed for, longer and shorter routes score less
+    pub route_length: usize,
+    /// freeze ratio aimed for
+    pub freeze_ratio: f32,
+    /// penalty of the dead end share among the passable tiles
+    pub dead_end_weight: f32,
+}
+
+impl Default for FitnessConfig {
+    fn default() -> Self {
+        Self {
+            route_length: 1000,
+            freeze_ratio: 0.3,
+            dead_end_weight: 50.0,
+        }
+    }
+}
+
+impl FitnessConfig {
+    /// 0.0 for maps whose spawn isn't connected to a finish, otherwise between 1.0 and 3.0
+    pub fn score(&self, stats: &MapStats) -> f32 {
+        let Some(route_length) = stats.route_length else {
+            return 0.0;
+        };
+
+        let target = self.route_length.max(1) as f32;
+        let length = 1.0 - ((route_length as f32 - target).abs() / target).min(1.0);
+        let freeze = 1.0 - (stats.freeze_ratio - self.freeze_ratio).abs().min(1.0);
+
+        let dead_end_share = stats.dead_ends as f32 / stats.passable.max(1) as f32;
+        let dead_ends = (1.0 - dead_end_share * self.dead_end_weight).clamp(0.0, 1.0);
+
+        1.0 + (length + freeze) * dead_ends
+    }
+}
+
+/// fitness with the default weights
+pub fn fitness(map: &TwMap) -> f32 {
+    FitnessConfig::default().score(&MapStats::from_map(map))
+}
+
+/// random search settings
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TuneConfig {
+    pub iterations: usize,
+    /// seeds every candidate is generated with, its fitness is the mean over them
+    pub seeds: Vec<Seed>,
+    pub mutation: MutationConfig,
+}
+
+impl Default for TuneConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 100,
+            seeds: (0..4).collect(),
+            mutation: MutationConfig::default(),
+        }
+    }
+}
+
+/// mean fitness of the maps generated with `preset` over `seeds`, fails with the first seed the
+/// generation fails with
+pub fn evaluate(
+    preset: &GenerationPreset,
+    seeds: &[Seed],
+    fitness: impl Fn(&TwMap) -> f32,
+) -> Result<f32, GenerationError> {
+    let mut generator = Generator::from_preset(preset.clone());
+    let mut total = 0.0;
+
+    for &seed in seeds {
+        generator.set_seed(seed);
+        total += fitness(&generator.try_generate_observed(|_| {})?);
+    }
+
+    Ok(total / seeds.len().max(1) as f32)
+}
+
+/// hill climbing random search, every iteration mutates the best preset so far and keeps the
+/// mutant if it scores higher, mutants that fail to generate score negative infinity,
+/// `on_iteration` gets the iteration, the mutant's and the best fitness, fails only when `preset`
+/// itself does
+pub fn tune(
+    preset: &GenerationPreset,
+    config: &TuneConfig,
+    prng: &mut Random,
+    fitness: impl Fn(&TwMap) -> f32,
+    mut on_iteration: impl FnMut(usize, f32, f32),
+) -> Result<(GenerationPreset, f32), GenerationError> {
+    let mut best = preset.clone();
+    let mut best_fitness = evaluate(&best, &config.seeds, &fitness)?;
+
+    for iteration in 0..config.iterations {
+        let mutant = config.mutation.mutate(&best, prng);
+        let mutant_fitness =
+            evaluate(&mutant, &config.seeds, &fitness).unwrap_or(f32::NEG_INFINITY);
+
+        if mutant_fitness > best_fitness {
+            best = mutant;
+            best_fitness = mutant_fitness;
+        }
+
+        on_iteration(iteration, mutant_fitness, best_fitness);
+    }
+
+    Ok((best, best_fitness))
+}
+
+/// exported maps have their hookthrough walls split into a hookable tile and a front layer one
+fn is_wall(tile: &GameTile) -> bool {
+    [BlockType::Hookable, BlockType::Unhookable]
+        .iter()
+        .any(|block| tile.id == block.game_id())
+}
+
+/// breadth first search from the spawns to the closest finish tile
+fn route_length(tiles: &Array2<GameTile>, passable: &Array2<bool>) -> Option<usize> {
+    let (width, height) = tiles.dim();
+    let spawns = [SpawnTeam::Neutral, SpawnTeam::Red, SpawnTeam::Blue].map(|team| team.game_id());
+
+    let mut distances: Array2<Option<usize>> = Array2::from_elem((width, height), None);
+    let mut queue = VecDeque::new();
+
+    for ((x, y), tile) in tiles.indexed_iter() {
+        if spawns.contains(&tile.id) {
+            distances[[x, y]] = Some(0);
+            queue.push_back([x, y]);
+        }
+    }
+
+    while let Some(current) = queue.pop_front() {
+        let distance = distances[current]?;
+
+        if tiles[current].id == BlockType::Finish.game_id() {
+            return Some(distance);
+        }
+
+        for next in current.neighbors((width, height), false) {
+            if passable[next] && distances[next].is_none() {
+                distances[next] = Some(distance + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
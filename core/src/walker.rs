@@ -1,10 +1,14 @@
-use crate::position::{euclidian, from_raw, straight_neighbors, Direction, Vector2, VectorView2};
+use tracing::trace;
 
-#[derive(Debug, Default, Clone)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct NormalWaypoints {
-    pub waypoints: Vec<(f32, f32)>,
-}
+use crate::{
+    config::{WalkerConfig, WaypointConfig},
+    position::{
+        all_neighbors, euclidian, from_raw, straight_neighbors, Direction, Vector2, VectorView2,
+    },
+};
+
+/// old name of the waypoints config, the serialized format is unchanged
+pub type NormalWaypoints = WaypointConfig;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub struct WalkerState {
@@ -22,6 +26,8 @@ pub struct Walker {
 
     current_step: usize,
     scale_factor: f32,
+    margin: f32,
+    config: WalkerConfig,
 
     raw_waypoints: Vec<(f32, f32)>,
 }
@@ -34,6 +40,8 @@ impl Walker {
             next_state: None,
             current_step: 0,
             scale_factor,
+            margin: 200.0,
+            config: WalkerConfig::default(),
             raw_waypoints: Vec::new(),
         }
     }
@@ -42,6 +50,7 @@ impl Walker {
         self.states.clear();
         self.preferred_state = WalkerState::default();
         self.next_state = None;
+        self.current_step = 0;
     }
 
     pub fn set_waypoints(&mut self, raw_waypoints: Vec<(f32, f32)>) -> &mut Self {
@@ -56,6 +65,22 @@ impl Walker {
         self
     }
 
+    pub fn set_margin(&mut self, margin: f32) -> &mut Self {
+        self.margin = margin;
+
+        self
+    }
+
+    pub fn set_config(&mut self, config: WalkerConfig) -> &mut Self {
+        self.config = config;
+
+        self
+    }
+
+    pub fn get_config(&self) -> &WalkerConfig {
+        &self.config
+    }
+
     pub fn get_waypoints(&self) -> &Vec<(f32, f32)> {
         &self.raw_waypoints
     }
@@ -68,6 +93,13 @@ impl Walker {
         self.current_step
     }
 
+    /// position of a waypoint on the map
+    pub fn waypoint_position(&self, waypoint: usize) -> Option<Vector2> {
+        let raw = *self.raw_waypoints.get(waypoint)?;
+
+        Some(from_raw(raw, self.scale_factor) + Vector2::from(vec![self.margin, self.margin]))
+    }
+
     pub fn set_next_direction(&mut self, direction: Direction) -> &mut Self {
         if let Some(state) = &mut self.next_state {
             state.direction = direction;
@@ -121,24 +153,26 @@ impl Walker {
         }
 
         // check if we reached waypoint
-        let waypoint_pos = from_raw(
-            self.raw_waypoints[current_state.waypoint],
-            self.scale_factor,
-        ) + Vector2::from(vec![200.0, 200.0]);
+        let waypoint_pos = self.waypoint_position(current_state.waypoint).unwrap();
 
-        println!("{}\t->\t{}", current_pos, waypoint_pos);
+        trace!(position = %current_pos, waypoint = %waypoint_pos, "walker step");
 
         let current_distance = euclidian(waypoint_pos.view(), current_pos.view());
 
-        // TODO: make it configurable(?)
-        if current_distance < 2.0 {
+        if current_distance < self.config.reach_distance {
             // we reached waypoint, choose next
 
             self.preferred_state.waypoint += 1;
         }
 
         // calculate directions
-        let min_neighbor = straight_neighbors(current_pos)
+        let neighbors = if self.config.allow_diagonal {
+            all_neighbors(current_pos)
+        } else {
+            straight_neighbors(current_pos)
+        };
+
+        let min_neighbor = neighbors
             .iter()
             .map(|n| euclidian(n.view(), waypoint_pos.view()))
             .enumerate()
@@ -0,0 +1,23 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{config::GenerationPreset, generator::Generator, random::Seed};
+
+/// generates a map from a json preset and returns the serialized .map file
+#[wasm_bindgen]
+pub fn generate_map_js(config_json: &str, seed: Seed) -> Result<Vec<u8>, JsValue> {
+    let preset: GenerationPreset =
+        serde_json::from_str(config_json).map_err(|err| JsValue::from_str(&err.to_string()))?;
+
+    let mut generator = Generator::from_preset(preset);
+    generator.set_seed(seed);
+
+    let mut map = generator
+        .try_generate_observed(|_| {})
+        .map_err(|err| JsValue::from_str(&err.to_string()))?;
+    let mut data = Vec::new();
+
+    map.save(&mut data)
+        .map_err(|err| JsValue::from_str(&err.to_string()))?;
+
+    Ok(data)
+}
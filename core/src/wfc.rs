@@ -0,0 +1,225 @@
+use ndarray::Array2;
+use twmap::GameTile;
+
+use crate::{map::BlockType, random::Random};
+
+/// every 3x3 pattern of solid and empty tiles, one bit per tile in row-major order
+const PATTERNS: usize = 1 << 9;
+
+/// bit of the pattern center
+const CENTER: usize = 1 << 4;
+
+/// re-textures the boundary between solid and empty tiles with 3x3 patterns learned from
+/// examples, collapsing the boundary tiles one by one like wave function collapse does
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WfcConfig {
+    /// example rows, `#` solid and `_` empty, windows with other characters are skipped
+    pub examples: Vec<Vec<String>>,
+    /// also learns the patterns turned and mirrored
+    pub symmetry: bool,
+}
+
+impl Default for WfcConfig {
+    fn default() -> Self {
+        let example = [
+            "############",
+            "#___##_____#",
+            "#__________#",
+            "##___###___#",
+            "#_____#___##",
+            "#__________#",
+            "###____#####",
+            "############",
+        ];
+
+        Self {
+            examples: vec![example.iter().map(|row| row.to_string()).collect()],
+            symmetry: true,
+        }
+    }
+}
+
+impl WfcConfig {
+    /// occurrences of every pattern in the examples
+    pub fn learn(&self) -> Vec<u32> {
+        let mut counts = vec![0; PATTERNS];
+
+        for example in self.examples.iter() {
+            let rows: Vec<Vec<char>> = example.iter().map(|row| row.chars().collect()).collect();
+
+            for y in 1..rows.len().saturating_sub(1) {
+                for x in 1..rows[y].len().saturating_sub(1) {
+                    let Some(pattern) = example_pattern(&rows, x, y) else {
+                        continue;
+                    };
+
+                    if self.symmetry {
+                        for variant in variants(pattern) {
+                            counts[variant] += 1;
+                        }
+                    } else {
+                        counts[pattern] += 1;
+                    }
+                }
+            }
+        }
+
+        counts
+    }
+
+    /// collapses the `solid` and empty tiles next to the other kind, `route` tiles stay
+    /// empty and other blocks are kept but count as solid
+    pub fn apply(
+        &self,
+        tiles: &mut Array2<GameTile>,
+        route: &Array2<bool>,
+        solid: BlockType,
+        prng: &mut Random,
+    ) {
+        let counts = self.learn();
+        let learned: Vec<(usize, u32)> = counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count != 0)
+            .map(|(pattern, &count)| (pattern, count))
+            .collect();
+
+        if learned.is_empty() {
+            return;
+        }
+
+        let (width, height) = tiles.dim();
+
+        // `None` for boundary tiles not collapsed yet, other tiles are fixed
+        let mut cells: Array2<Option<bool>> = tiles.map(|tile| Some(is_solid(tile)));
+
+        let mut boundary: Vec<[usize; 2]> = Vec::new();
+
+        for x in 1..width.saturating_sub(1) {
+            for y in 1..height.saturating_sub(1) {
+                let changeable = [BlockType::Empty, solid]
+                    .iter()
+                    .any(|block| tiles[[x, y]].id == block.game_id());
+
+                if !changeable || route[[x, y]] {
+                    continue;
+                }
+
+                let center = is_solid(&tiles[[x, y]]);
+                let mixed = window(x, y).any(|index| is_solid(&tiles[index]) != center);
+
+                if mixed {
+                    boundary.push([x, y]);
+                }
+            }
+        }
+
+        for &index in boundary.iter() {
+            cells[index] = None;
+        }
+
+        shuffle(&mut boundary, prng);
+
+        for [x, y] in boundary {
+            // known tiles around the cell as mask and value bits
+            let (mut mask, mut value) = (0, 0);
+
+            for (bit, index) in window(x, y).enumerate() {
+                if let Some(solid) = cells[index] {
+                    mask |= 1 << bit;
+                    value |= (solid as usize) << bit;
+                }
+            }
+
+            let (mut empty_weight, mut solid_weight) = (0, 0);
+
+            for &(pattern, count) in learned.iter() {
+                if pattern & mask & !CENTER != value & !CENTER {
+                    continue;
+                }
+
+                if pattern & CENTER != 0 {
+                    solid_weight += count;
+                } else {
+                    empty_weight += count;
+                }
+            }
+
+            let collapsed = if empty_weight + solid_weight == 0 {
+                // contradiction, keep the tile as it was
+                is_solid(&tiles[[x, y]])
+            } else {
+                prng.in_range(0..empty_weight + solid_weight) >= empty_weight
+            };
+
+            cells[[x, y]] = Some(collapsed);
+
+            tiles[[x, y]] = if collapsed {
+                solid.tile()
+            } else {
+                BlockType::Empty.tile()
+            };
+        }
+    }
+}
+
+fn is_solid(tile: &GameTile) -> bool {
+    tile.id != BlockType::Empty.game_id()
+}
+
+/// indices of the 3x3 window around a tile in row-major order, the tile must not be on the
+/// map edge
+fn window(x: usize, y: usize) -> impl Iterator<Item = [usize; 2]> {
+    (0..3).flat_map(move |dy| (0..3).map(move |dx| [x + dx - 1, y + dy - 1]))
+}
+
+fn example_pattern(rows: &[Vec<char>], x: usize, y: usize) -> Option<usize> {
+    let mut pattern = 0;
+
+    for (bit, [x, y]) in window(x, y).enumerate() {
+        match rows.get(y)?.get(x)? {
+            '#' => pattern |= 1 << bit,
+            '_' => {}
+            _ => return None,
+        }
+    }
+
+    Some(pattern)
+}
+
+/// the pattern in all four rotations, each also mirrored
+fn variants(pattern: usize) -> Vec<usize> {
+    let at = |pattern: usize, x: usize, y: usize| (pattern >> (y * 3 + x)) & 1;
+
+    let rotate = |pattern: usize| {
+        (0..9).fold(0, |rotated, bit| {
+            let (x, y) = (bit % 3, bit / 3);
+            rotated | at(pattern, y, 2 - x) << bit
+        })
+    };
+    let mirror = |pattern: usize| {
+        (0..9).fold(0, |mirrored, bit| {
+            let (x, y) = (bit % 3, bit / 3);
+            mirrored | at(pattern, 2 - x, y) << bit
+        })
+    };
+
+    let mut variants = Vec::with_capacity(8);
+    let mut current = pattern;
+
+    for _ in 0..4 {
+        variants.push(current);
+        variants.push(mirror(current));
+        current = rotate(current);
+    }
+
+    variants
+}
+
+fn shuffle<T>(values: &mut [T], prng: &mut Random) {
+    for i in (1..values.len()).rev() {
+        values.swap(i, prng.in_range(0..=i));
+    }
+}
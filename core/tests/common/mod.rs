@@ -0,0 +1,14 @@
+use std::fs;
+
+use mapgen_core::{config::GenerationPreset, migration};
+
+const PRESETS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/configs/presets");
+
+/// bundled preset named after its file, like the bridge loads them
+pub fn load_preset(name: &str) -> GenerationPreset {
+    let data = fs::read_to_string(format!("{}/{}.json", PRESETS, name)).unwrap();
+    let mut preset = migration::load_preset(serde_json::from_str(&data).unwrap()).unwrap();
+
+    preset.name = name.to_owned();
+    preset
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use mapgen_core::{
+    config::GenerationPreset,
+    freeze::FreezeConfig,
+    random::Random,
+    testing::{self, Invariants},
+};
+
+use common::load_preset;
+
+/// seeds checked per preset on every test run
+const SEEDS: usize = 32;
+/// seeds checked per preset by the ignored long run, `cargo test -- --ignored`
+const MANY_SEEDS: usize = 2000;
+
+fn check_preset(preset: &GenerationPreset, invariants: Invariants, count: usize) {
+    let mut prng = Random::new(0);
+    let seeds = testing::seeds(&mut prng, count);
+
+    if let Err((seed, violation)) = invariants.check_seeds(preset, seeds) {
+        panic!("{} with seed {}: {}", preset.name, seed, violation);
+    }
+}
+
+/// bundled preset with a finish stamped at the end of the route, the presets don't stamp one
+/// and there's nothing to check the spawn's connection against otherwise
+fn bundled_preset(name: &str) -> GenerationPreset {
+    let mut preset = load_preset(name);
+    preset.post_process.finish = true;
+    preset
+}
+
+/// the bundled presets don't line the route with freeze
+fn bundled_invariants() -> Invariants {
+    Invariants {
+        freeze_lining: false,
+        ..Default::default()
+    }
+}
+
+/// default preset lining the route with freeze and fixing edge bugs, holds every invariant
+fn lined_preset() -> GenerationPreset {
+    let mut preset = bundled_preset("default");
+    preset.post_process.freeze = Some(FreezeConfig::default());
+    preset.post_process.fix_edge_bugs = true;
+    preset
+}
+
+#[test]
+fn default_preset() {
+    check_preset(&bundled_preset("default"), bundled_invariants(), SEEDS);
+}
+
+#[test]
+fn gores_preset() {
+    check_preset(&bundled_preset("gores"), bundled_invariants(), SEEDS);
+}
+
+#[test]
+fn lined_default_preset() {
+    check_preset(&lined_preset(), Invariants::default(), SEEDS);
+}
+
+#[test]
+#[ignore = "takes minutes, run with --ignored"]
+fn many_seeds() {
+    check_preset(&bundled_preset("default"), bundled_invariants(), MANY_SEEDS);
+    check_preset(&bundled_preset("gores"), bundled_invariants(), MANY_SEEDS);
+    check_preset(&lined_preset(), Invariants::default(), MANY_SEEDS);
+}
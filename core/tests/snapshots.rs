@@ -0,0 +1,34 @@
+mod common;
+
+use std::fs;
+
+use mapgen_core::snapshot::{Mismatch, Snapshot};
+
+use common::load_preset;
+
+/// recorded with `verify-snapshots --update` of the bridge, along with the maps the diff images
+/// are drawn against
+const SNAPSHOTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/snapshots");
+
+#[test]
+fn snapshots_match() {
+    let data = fs::read_to_string(format!("{}/snapshots.json", SNAPSHOTS)).unwrap();
+    let snapshots: Vec<Snapshot> = serde_json::from_str(&data).unwrap();
+
+    assert!(!snapshots.is_empty());
+
+    for snapshot in snapshots.iter() {
+        let preset = load_preset(&snapshot.preset);
+
+        match snapshot.verify(&preset) {
+            Ok(()) => {}
+            Err(Mismatch::Changed(_)) => panic!(
+                "{} seed {} changed, record the snapshots again if that's on purpose",
+                snapshot.preset, snapshot.seed
+            ),
+            Err(Mismatch::Failed(err)) => {
+                panic!("{} seed {} failed: {}", snapshot.preset, snapshot.seed, err)
+            }
+        }
+    }
+}
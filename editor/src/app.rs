@@ -17,13 +17,22 @@ use winit::{
 
 use twgpu::device_descriptor;
 
-use crate::components::{
-    map::TwGpuComponent,
-    ui::{
-        bottom_panel::BottomPanelUi, context::UiContext, float::FloatWindowUi,
-        left_panel::LeftPanelUi, UiComponent,
+use crate::{
+    autosave,
+    components::{
+        map::TwGpuComponent,
+        ui::{
+            bottom_panel::BottomPanelUi, branding::BrandingUi, context::UiContext,
+            debug::DebugLayersUi, events::EventLogUi, float::FloatWindowUi, gallery::GalleryUi,
+            inspector::InspectorUi, keybinds::KeybindsUi, left_panel::LeftPanelUi,
+            log::LogConsoleUi, mask::GuidanceMaskUi, prefabs::PrefabBrowserUi,
+            preset::PresetPanelUi, recovery::RecoveryUi, ruler::RulerUi, search::MutationSearchUi,
+            sessions::SessionTabsUi, settings::SettingsUi, waypoints::RouteOverlayUi, UiComponent,
+        },
+        AppComponent,
     },
-    AppComponent,
+    logging::LogBuffer,
+    settings::Settings,
 };
 
 pub struct WgpuContext {
@@ -55,7 +64,7 @@ pub struct App<'w, 'a> {
 }
 
 impl<'w, 'a> App<'w, 'a> {
-    pub async fn new(width: u32, height: u32) -> Self {
+    pub async fn new(width: u32, height: u32, logs: LogBuffer) -> Self {
         let event_loop = EventLoop::new().unwrap();
         let window = Arc::new(
             winit::window::WindowBuilder::new()
@@ -124,17 +133,46 @@ impl<'w, 'a> App<'w, 'a> {
             width,
             height,
             wgpu_context.clone(),
-            generation,
+            generation.clone(),
         ));
         let map_loader = twgpu.get_map_loader_handle();
+        let keyboard_camera = twgpu.get_keyboard_camera_handle();
 
+        let settings = Rc::new(RefCell::new(Settings::load()));
         let mut ui_context = UiContext::new();
 
-        ui_context.add_renderable(LeftPanelUi::new(map_loader));
+        // first, so the others are drawn with the current theme and palette
+        ui_context.add_renderable(SettingsUi::new(settings.clone()));
+        ui_context.add_renderable(SessionTabsUi::new(generation.clone()));
+        ui_context.add_renderable(RouteOverlayUi::new(generation.clone()));
+        ui_context.add_renderable(LeftPanelUi::new(
+            map_loader,
+            keyboard_camera,
+            generation.clone(),
+        ));
+        // before the node graph, so the status bar stays at the bottom edge
+        ui_context.add_renderable(RulerUi::new(generation.clone()));
         ui_context.add_renderable(bottom_panel);
+        ui_context.add_renderable(PresetPanelUi::new(generation.clone()));
+        ui_context.add_renderable(PrefabBrowserUi::new(generation.clone()));
+        ui_context.add_renderable(GalleryUi::new(generation.clone()));
+        ui_context.add_renderable(MutationSearchUi::new(generation.clone()));
+        ui_context.add_renderable(DebugLayersUi::new(generation.clone()));
+        ui_context.add_renderable(EventLogUi::new(generation.clone()));
+        ui_context.add_renderable(GuidanceMaskUi::new(generation.clone()));
+        ui_context.add_renderable(BrandingUi::new(generation.clone()));
+        ui_context.add_renderable(InspectorUi::new(generation.clone()));
+        ui_context.add_renderable(KeybindsUi::new(generation.clone()));
+        ui_context.add_renderable(RecoveryUi::new(generation));
+        ui_context.add_renderable(LogConsoleUi::new(logs));
         ui_context.add_renderable(FloatWindowUi {});
 
-        let ui = Box::new(UiComponent::new(ui_context, &window, wgpu_context.clone()));
+        let ui = Box::new(UiComponent::new(
+            ui_context,
+            &window,
+            wgpu_context.clone(),
+            settings,
+        ));
 
         let components: Vec<Box<dyn AppComponent>> = vec![twgpu, ui];
 
@@ -232,7 +270,11 @@ impl<'w, 'a> App<'w, 'a> {
                                 component.on_resize(size);
                             }
                         }
-                        WindowEvent::CloseRequested => target.exit(),
+                        WindowEvent::CloseRequested => {
+                            // only a crash leaves the autosave behind
+                            autosave::clear();
+                            target.exit();
+                        }
                         _ => {}
                     }
                 }
@@ -0,0 +1,80 @@
+use std::{error::Error, fs, path::PathBuf, time::Duration};
+
+use mapgen_core::{config::GenerationPreset, random::Seed};
+use serde::{Deserialize, Serialize};
+use twmap::TwMap;
+
+/// time between two autosaves
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+const STATE_FILE: &str = "state.json";
+
+/// directory the editor state is saved to while it runs, a clean exit removes it, so finding
+/// it on start means the last run crashed
+pub fn autosave_dir() -> PathBuf {
+    std::env::temp_dir().join("mapgen-autosave")
+}
+
+/// removes the autosave, done on a clean exit
+pub fn clear() {
+    let _ = fs::remove_dir_all(autosave_dir());
+}
+
+/// generation sessions as they were on the last autosave
+#[derive(Serialize, Deserialize)]
+pub struct Autosave {
+    pub sessions: Vec<SavedSession>,
+    pub active: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedSession {
+    pub name: String,
+    pub preset: GenerationPreset,
+    pub seed: Seed,
+    /// generated map, saved next to the state as its own map file
+    #[serde(skip)]
+    pub map: Option<TwMap>,
+}
+
+impl Autosave {
+    /// autosave left behind by a crashed run
+    pub fn load() -> Option<Self> {
+        let dir = autosave_dir();
+        let json = fs::read_to_string(dir.join(STATE_FILE)).ok()?;
+        let mut autosave: Self = serde_json::from_str(&json).ok()?;
+
+        for (i, session) in autosave.sessions.iter_mut().enumerate() {
+            session.map = TwMap::parse_path(dir.join(map_file(i)))
+                .ok()
+                .and_then(|mut map| map.load().ok().map(|()| map));
+        }
+
+        Some(autosave)
+    }
+
+    /// replaces the last autosave
+    pub fn save(&mut self) -> Result<(), Box<dyn Error>> {
+        let dir = autosave_dir();
+        fs::create_dir_all(&dir)?;
+
+        for (i, session) in self.sessions.iter_mut().enumerate() {
+            let path = dir.join(map_file(i));
+
+            match &mut session.map {
+                Some(map) => map.save(&mut fs::File::create(&path)?)?,
+                None => {
+                    let _ = fs::remove_file(&path);
+                }
+            }
+        }
+
+        fs::write(dir.join(STATE_FILE), serde_json::to_string(self)?)?;
+
+        Ok(())
+    }
+}
+
+fn map_file(session: usize) -> String {
+    format!("session_{}.map", session)
+}
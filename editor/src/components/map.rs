@@ -1,6 +1,15 @@
-use std::{cell::RefCell, fs::File, io::Read, path::Path, rc::Rc, time::Instant};
+use std::{
+    cell::RefCell,
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::mpsc,
+    time::Instant,
+};
 
 use image::{codecs::png::PngDecoder, ColorType, ImageDecoder, RgbaImage};
+use tracing::debug;
 use twgpu::{
     map::{GpuMapData, GpuMapRender, GpuMapStatic},
     textures::Samplers,
@@ -8,24 +17,48 @@ use twgpu::{
 };
 use twmap::{EmbeddedImage, Image, TwMap, Version};
 use vek::Vec2;
-use wgpu::{Color, LoadOp, Operations, RenderPassColorAttachment, RenderPassDescriptor, StoreOp};
+use wgpu::{
+    BufferDescriptor, BufferUsages, Color, CommandEncoder, CommandEncoderDescriptor, Extent3d,
+    ImageCopyBuffer, ImageDataLayout, LoadOp, Maintain, MapMode, Operations, RenderPass,
+    RenderPassColorAttachment, RenderPassDescriptor, StoreOp, TextureDescriptor, TextureDimension,
+    TextureFormat, TextureUsages, TextureView, TextureViewDescriptor, COPY_BYTES_PER_ROW_ALIGNMENT,
+};
 use winit::{
     dpi::{PhysicalPosition, PhysicalSize},
-    event::{MouseScrollDelta, WindowEvent},
+    event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent},
+    keyboard::{ModifiersState, PhysicalKey},
     window::Window,
 };
 
 use crate::{
     app::{RenderContext, WgpuContext},
-    input_handler::{Cursors, Input, MultiInput},
+    input_handler::{fit_camera, Cursors, Input, KeyboardCamera, MultiInput},
+};
+
+use super::{
+    utils::generation::{GenerationContext, MapView},
+    AppComponent,
 };
 
-use super::{utils::generation::GenerationContext, AppComponent};
+/// pixels around a waypoint handle that pick it up
+const HANDLE_REACH: f32 = 12.0;
+
+/// what to render into a png
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Capture {
+    /// what the camera shows, at the window size
+    View,
+    /// whole map, `scale` pixels per tile
+    Map { scale: u32 },
+}
 
 pub struct MapLoader {
     wgpu_context: Rc<RefCell<WgpuContext>>,
     static_context: GpuMapStaticContext,
     dynamic_context: Option<(TwMap, GpuMapDynamicContext)>,
+    /// capture taken with the next frame and where it's saved
+    capture: Option<(Capture, PathBuf)>,
+    last_capture: Option<Result<PathBuf, String>>,
 }
 
 impl MapLoader {
@@ -34,6 +67,8 @@ impl MapLoader {
             static_context,
             dynamic_context: None,
             wgpu_context,
+            capture: None,
+            last_capture: None,
         }
     }
 
@@ -57,6 +92,23 @@ impl MapLoader {
     pub fn is_loaded(&self) -> bool {
         self.dynamic_context.is_some()
     }
+
+    pub fn request_capture(&mut self, capture: Capture, path: PathBuf) {
+        self.capture = Some((capture, path));
+    }
+
+    /// saved path or error of the last capture
+    pub fn last_capture(&self) -> Option<&Result<PathBuf, String>> {
+        self.last_capture.as_ref()
+    }
+
+    /// size of the loaded map in tiles
+    pub fn map_size(&self) -> Option<Vec2<f32>> {
+        let (tw_map, _) = self.dynamic_context.as_ref()?;
+        let shape = tw_map.physics_group().layers[0].shape()?;
+
+        Some(Vec2::new(shape.w, shape.h).az())
+    }
 }
 
 struct GpuMapStaticContext {
@@ -107,6 +159,16 @@ pub struct TwGpuComponent {
 
     camera: Camera,
     old_camera: Camera,
+    keyboard_camera: Rc<RefCell<KeyboardCamera>>,
+    last_frame: Instant,
+
+    /// map position where the selection drag started
+    selection_start: Option<Vec2<f32>>,
+    /// waypoint following the cursor while waypoints are edited
+    dragged_waypoint: Option<usize>,
+    /// the left button is held while painting the guidance mask
+    painting_mask: bool,
+    modifiers: ModifiersState,
 
     map_loader: Rc<RefCell<MapLoader>>,
     generation: Rc<RefCell<GenerationContext>>,
@@ -141,6 +203,12 @@ impl TwGpuComponent {
             cursors,
             camera,
             old_camera,
+            keyboard_camera: Rc::new(RefCell::new(KeyboardCamera::default())),
+            last_frame: Instant::now(),
+            selection_start: None,
+            dragged_waypoint: None,
+            painting_mask: false,
+            modifiers: ModifiersState::empty(),
             map_loader,
             generation,
             render_size,
@@ -150,6 +218,274 @@ impl TwGpuComponent {
     pub fn get_map_loader_handle(&self) -> Rc<RefCell<MapLoader>> {
         self.map_loader.clone()
     }
+
+    pub fn get_keyboard_camera_handle(&self) -> Rc<RefCell<KeyboardCamera>> {
+        self.keyboard_camera.clone()
+    }
+
+    fn cursor_map_position(&self) -> Option<Vec2<f32>> {
+        let position = self.cursors.any_position()?;
+        let logical_pos = Vec2::new(position.x, position.y).az::<f32>() / self.render_size;
+
+        Some(self.camera.map_position(logical_pos))
+    }
+
+    /// renders the loaded map off-screen and saves it as png
+    fn capture(
+        &self,
+        capture: Capture,
+        path: &Path,
+        wgpu_context: &WgpuContext,
+    ) -> Result<(), String> {
+        let map_loader = self.map_loader.borrow();
+        let Some((tw_map, context)) = &map_loader.dynamic_context else {
+            return Err("no map loaded".to_owned());
+        };
+
+        let (camera, size) = match capture {
+            Capture::View => (self.camera, self.render_size),
+            Capture::Map { scale } => {
+                let map_size = map_loader.map_size().ok_or("map without game layer")?;
+                let mut camera = Camera::new(map_size.x / map_size.y);
+
+                fit_camera(&mut camera, map_size, 1.0);
+
+                (camera, map_size * scale as f32)
+            }
+        };
+        let size: Vec2<u32> = size.az();
+
+        let device = &wgpu_context.device;
+        let max = device.limits().max_texture_dimension_2d;
+
+        if size.x == 0 || size.y == 0 || size.x > max || size.y > max {
+            return Err(format!(
+                "capture size {}x{} out of 1..={}",
+                size.x, size.y, max
+            ));
+        }
+
+        let format = wgpu_context.config.format;
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("capture"),
+            size: Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&TextureViewDescriptor::default());
+
+        map_loader
+            .static_context
+            .camera
+            .update(&camera, &wgpu_context.queue);
+        context
+            .data
+            .update(tw_map, &camera, size.az(), 0, 0, &wgpu_context.queue);
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("capture"),
+        });
+
+        {
+            let render_pass = begin_map_pass(&mut encoder, &view, "capture");
+            let mut tw_render_pass = TwRenderPass::new(render_pass, size.az(), &camera);
+
+            context.render.render_background(&mut tw_render_pass);
+            context.render.render_foreground(&mut tw_render_pass);
+        }
+
+        // rows of a texture copy are aligned
+        let padded_row = (size.x * 4).next_multiple_of(COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("capture"),
+            size: padded_row as u64 * size.y as u64,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: None,
+                },
+            },
+            texture.size(),
+        );
+
+        wgpu_context.queue.submit([encoder.finish()]);
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+
+        slice.map_async(MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(Maintain::Wait);
+
+        receiver
+            .recv()
+            .map_err(|err| err.to_string())?
+            .map_err(|err| err.to_string())?;
+
+        let mut pixels = Vec::with_capacity(size.x as usize * size.y as usize * 4);
+
+        for row in slice.get_mapped_range().chunks(padded_row as usize) {
+            pixels.extend_from_slice(&row[..size.x as usize * 4]);
+        }
+
+        buffer.unmap();
+
+        if matches!(
+            format,
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb
+        ) {
+            for pixel in pixels.chunks_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        let image = RgbaImage::from_raw(size.x, size.y, pixels).ok_or("capture size mismatch")?;
+
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|err| err.to_string())?;
+        }
+
+        image.save(path).map_err(|err| err.to_string())
+    }
+
+    /// places the spawn or finish of the endpoint tool with the left button, returns whether the
+    /// click was used up
+    fn place_endpoint(&mut self, state: ElementState) -> bool {
+        let Some(endpoint) = self.generation.borrow().endpoint_tool() else {
+            return false;
+        };
+
+        if state == ElementState::Pressed {
+            if let Some(position) = self.cursor_map_position() {
+                self.generation
+                    .borrow_mut()
+                    .place_endpoint(endpoint, [position.x, position.y]);
+            }
+        }
+
+        true
+    }
+
+    /// paints the guidance mask while the left button is held, generates once it's released,
+    /// returns whether the click was used up
+    fn paint_mask(&mut self, state: ElementState) -> bool {
+        if self.generation.borrow().mask_paint().is_none() {
+            return false;
+        }
+
+        self.painting_mask = state == ElementState::Pressed;
+
+        if self.painting_mask {
+            self.paint_mask_at_cursor();
+        } else {
+            self.generation.borrow_mut().request_generation();
+        }
+
+        true
+    }
+
+    fn paint_mask_at_cursor(&mut self) {
+        let mut generation = self.generation.borrow_mut();
+
+        if let (Some(paint), Some(position)) = (generation.mask_paint(), self.cursor_map_position())
+        {
+            generation.paint_mask(paint, [position.x, position.y]);
+        }
+    }
+
+    /// drags waypoints with the left button, adds one with shift and removes one with ctrl,
+    /// returns whether the click was used up, the view is panned otherwise
+    fn edit_waypoints(&mut self, state: ElementState) -> bool {
+        if !self.generation.borrow().waypoint_editing() {
+            return false;
+        }
+
+        let mut generation = self.generation.borrow_mut();
+
+        if state == ElementState::Released {
+            if self.dragged_waypoint.take().is_none() {
+                return false;
+            }
+
+            generation.request_generation();
+            return true;
+        }
+
+        let Some(position) = self.cursor_map_position() else {
+            return false;
+        };
+
+        // handles are picked within a few pixels, whatever the zoom
+        let near = self.camera.map_position(Vec2::zero());
+        let far = self
+            .camera
+            .map_position(Vec2::new(HANDLE_REACH, 0.0) / self.render_size);
+        let reach = (far.x - near.x).abs();
+        let tile = [position.x, position.y];
+
+        match generation.waypoint_at(tile, reach) {
+            Some(waypoint) if self.modifiers.control_key() => generation.remove_waypoint(waypoint),
+            Some(waypoint) => self.dragged_waypoint = Some(waypoint),
+            None if self.modifiers.shift_key() => generation.insert_waypoint(tile),
+            None => return false,
+        }
+
+        true
+    }
+
+    /// tells the inspector which tile is under the cursor
+    fn update_hovered(&mut self) {
+        let tile = self
+            .cursor_map_position()
+            .filter(|position| position.x >= 0.0 && position.y >= 0.0)
+            .map(|position| [position.x as usize, position.y as usize]);
+
+        self.generation.borrow_mut().set_hovered(tile);
+    }
+
+    /// selects the tiles between the drag start and the cursor
+    fn update_selection(&mut self, state: ElementState) {
+        let Some(position) = self.cursor_map_position() else {
+            return;
+        };
+
+        match state {
+            ElementState::Pressed => self.selection_start = Some(position),
+            ElementState::Released => {
+                let Some(start) = self.selection_start.take() else {
+                    return;
+                };
+
+                let (min, max) = (
+                    Vec2::partial_min(start, position),
+                    Vec2::partial_max(start, position),
+                );
+                let tile = |value: f32| value.max(0.0) as usize;
+
+                self.generation.borrow_mut().set_selection(Some([
+                    [tile(min.x), tile(min.y)],
+                    [tile(max.x), tile(max.y)],
+                ]));
+            }
+        }
+    }
 }
 
 impl AppComponent for TwGpuComponent {
@@ -165,7 +501,10 @@ impl AppComponent for TwGpuComponent {
                     self.render_size,
                 );
             }
-            WindowEvent::CursorLeft { device_id } => self.cursors.left(device_id),
+            WindowEvent::CursorLeft { device_id } => {
+                self.cursors.left(device_id);
+                self.generation.borrow_mut().set_hovered(None);
+            }
             WindowEvent::CursorEntered { device_id } => self.cursors.entered(device_id),
             WindowEvent::CursorMoved {
                 device_id,
@@ -175,7 +514,34 @@ impl AppComponent for TwGpuComponent {
                     self.inputs
                         .update_input(&input, &mut self.camera, self.render_size);
                 }
+
+                self.update_hovered();
+
+                if self.painting_mask {
+                    self.paint_mask_at_cursor();
+                }
+
+                if let (Some(waypoint), Some(position)) =
+                    (self.dragged_waypoint, self.cursor_map_position())
+                {
+                    self.generation
+                        .borrow_mut()
+                        .move_waypoint(waypoint, [position.x, position.y]);
+                }
             }
+            WindowEvent::ModifiersChanged(modifiers) => self.modifiers = modifiers.state(),
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Right,
+                ..
+            } => self.update_selection(state),
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } if self.place_endpoint(state)
+                || self.paint_mask(state)
+                || self.edit_waypoints(state) => {}
             WindowEvent::MouseInput {
                 device_id,
                 state,
@@ -186,6 +552,16 @@ impl AppComponent for TwGpuComponent {
                         .update_input(&input, &mut self.camera, self.render_size);
                 }
             }
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(code),
+                        state,
+                        ..
+                    },
+                ..
+            } => self.keyboard_camera.borrow_mut().key(code, state),
+            WindowEvent::Focused(false) => self.keyboard_camera.borrow_mut().release_all(),
             WindowEvent::MouseWheel { delta, .. } => {
                 let zoom_out = match delta {
                     MouseScrollDelta::LineDelta(_, dy) => dy.is_sign_positive(),
@@ -214,6 +590,15 @@ impl AppComponent for TwGpuComponent {
     ) {
         let wgpu_context = wgpu_context.borrow();
 
+        // before this frame's camera is uploaded, the capture overwrites it
+        let capture = self.map_loader.borrow_mut().capture.take();
+
+        if let Some((capture, path)) = capture {
+            let result = self.capture(capture, &path, &wgpu_context).map(|_| path);
+
+            self.map_loader.borrow_mut().last_capture = Some(result);
+        }
+
         self.inputs.update_camera(
             &mut self.camera,
             &self.old_camera,
@@ -221,6 +606,19 @@ impl AppComponent for TwGpuComponent {
             self.cursors.any_position(),
         );
 
+        // after the cursor anchoring, which would undo the keyboard movement otherwise
+        let elapsed = self.last_frame.elapsed().as_secs_f32();
+        self.last_frame = Instant::now();
+
+        if let Some([x, y]) = self.generation.borrow_mut().take_focus() {
+            self.camera.move_to(Vec2::new(x, y), Vec2::new(0.5, 0.5));
+        }
+
+        let map_size = self.map_loader.borrow().map_size();
+        self.keyboard_camera
+            .borrow_mut()
+            .update_camera(&mut self.camera, elapsed, map_size);
+
         let time = Instant::now().elapsed().as_secs() as i64;
 
         self.map_loader
@@ -229,32 +627,22 @@ impl AppComponent for TwGpuComponent {
             .camera
             .update(&self.camera, &wgpu_context.queue);
 
+        let origin = self.camera.map_position(Vec2::zero());
+        let size = self.camera.map_position(Vec2::one()) - origin;
+
+        self.generation.borrow_mut().set_map_view(MapView {
+            origin: [origin.x, origin.y],
+            size: [size.x, size.y],
+        });
+
         if let Some(context) = render_context {
             let frame_view = &context.surface_view;
 
-            let render_pass = context
-                .command_encoders
-                .get_mut(self.label())
-                .unwrap()
-                .begin_render_pass(&RenderPassDescriptor {
-                    label: Some(self.label()),
-                    color_attachments: &[Some(RenderPassColorAttachment {
-                        view: &frame_view,
-                        resolve_target: None,
-                        ops: Operations {
-                            load: LoadOp::Clear(Color {
-                                r: 0.0,
-                                g: 0.0,
-                                b: 0.0,
-                                a: 1.0,
-                            }),
-                            store: StoreOp::Store,
-                        },
-                    })],
-                    depth_stencil_attachment: None,
-                    timestamp_writes: None,
-                    occlusion_query_set: None,
-                });
+            let render_pass = begin_map_pass(
+                context.command_encoders.get_mut(self.label()).unwrap(),
+                frame_view,
+                self.label(),
+            );
             let mut tw_render_pass =
                 TwRenderPass::new(render_pass, self.render_size.az(), &self.camera);
 
@@ -275,11 +663,16 @@ impl AppComponent for TwGpuComponent {
 
         self.old_camera = self.camera;
 
+        self.generation.borrow_mut().step_live();
+
         // hack: weird way to poll
-        if let Some(tw_map) = self.generation.borrow_mut().take_map() {
+        if let Some(tw_map) = self.generation.borrow_mut().take_preview() {
             self.map_loader.borrow_mut().unload();
-            self.map_loader.borrow_mut().load(tw_map);
-            println!("loaded");
+
+            if let Some(tw_map) = tw_map {
+                self.map_loader.borrow_mut().load(tw_map);
+                debug!("preview loaded");
+            }
         }
     }
 
@@ -291,6 +684,33 @@ impl AppComponent for TwGpuComponent {
     }
 }
 
+/// render pass drawing onto `view` cleared to black
+fn begin_map_pass<'a>(
+    encoder: &'a mut CommandEncoder,
+    view: &'a TextureView,
+    label: &'static str,
+) -> RenderPass<'a> {
+    encoder.begin_render_pass(&RenderPassDescriptor {
+        label: Some(label),
+        color_attachments: &[Some(RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Clear(Color {
+                    r: 0.0,
+                    g: 0.0,
+                    b: 0.0,
+                    a: 1.0,
+                }),
+                store: StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    })
+}
+
 pub fn load_image<P: AsRef<Path>>(path: P) -> Image {
     let mut buf = Vec::new();
     let mut file = File::open(&path).unwrap();
@@ -1,4 +1,4 @@
-use std::{borrow::Borrow, cell::RefCell, collections::HashMap, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 use egui::{emath::Numeric, Color32, Id, Label, RichText, Sense, Ui};
 use egui_snarl::{
@@ -17,14 +17,16 @@ use mapgen_core::{
         },
         Mutator,
     },
+    random::{RandomDistConfig, Shape},
     walker::Walker,
 };
+use tracing::trace;
 
 use crate::components::utils::generation::{
     DesignImageInfo, DesignInfo, DesignLayer, GenerationContext,
 };
 
-use super::context::RenderableUi;
+use super::{context::RenderableUi, distribution::distribution_editor, preset::field_label};
 
 const UNTYPED_COLOR: Color32 = Color32::from_rgb(0xb0, 0xb0, 0xb0);
 
@@ -169,7 +171,10 @@ impl ExtractMutation<Walker> for UiWalkerMutation {
     fn extract(&self) -> Option<Self::ExtractType> {
         Some(match self {
             UiWalkerMutation::Straight(mutation) => {
-                println!("REAL: {}", mutation.overall_steps);
+                trace!(
+                    overall_steps = mutation.overall_steps,
+                    "straight mutation extracted"
+                );
                 Box::new(mutation.clone())
             }
             UiWalkerMutation::Backwards(mutation) => Box::new(mutation.clone()),
@@ -251,6 +256,27 @@ pub trait Titled {
 
 struct UiViewer {
     generation: Rc<RefCell<GenerationContext>>,
+    /// main route segment picked for regeneration
+    segment: usize,
+}
+
+fn default_design() -> DesignInfo {
+    let mut image_infos = HashMap::new();
+
+    image_infos.insert(
+        DesignLayer::Freeze,
+        DesignImageInfo::new("data/mapres/entities.png", 1),
+    );
+    image_infos.insert(
+        DesignLayer::Hookable,
+        DesignImageInfo::new("data/mapres/jungle_main.png", 2),
+    );
+    image_infos.insert(
+        DesignLayer::Unhookable,
+        DesignImageInfo::new("data/mapres/entities.png", 3),
+    );
+
+    DesignInfo::new(image_infos)
 }
 
 impl SnarlViewer<UiNode> for UiViewer {
@@ -315,38 +341,70 @@ impl SnarlViewer<UiNode> for UiViewer {
 
         match &mut snarl[node] {
             UiNode::GeneratorNode => {
-                if ui.button("Proceed").clicked() {
-                    let mut image_infos = HashMap::new();
-
-                    image_infos.insert(
-                        DesignLayer::Freeze,
-                        DesignImageInfo::new("data/mapres/entities.png", 1),
-                    );
-                    image_infos.insert(
-                        DesignLayer::Hookable,
-                        DesignImageInfo::new("data/mapres/jungle_main.png", 2),
-                    );
-                    image_infos.insert(
-                        DesignLayer::Unhookable,
-                        DesignImageInfo::new("data/mapres/entities.png", 3),
-                    );
-
-                    let design = DesignInfo::new(image_infos);
-                    self.generation.borrow_mut().set_scale_factor(200.0);
-                    self.generation.borrow_mut().generate(
-                        snarl,
-                        node,
-                        &design,
-                        vec![
-                            (0.0, 1.0),
-                            (0.2, 0.8),
-                            (0.4, 0.6),
-                            (0.6, 0.4),
-                            (0.8, 0.2),
-                            (1.0, 0.0),
-                        ],
-                    );
+                let requested = self.generation.borrow_mut().take_generation_request();
+
+                if ui.button("Proceed").clicked() || requested {
+                    let design = default_design();
+                    self.generation.borrow_mut().generate(snarl, node, &design);
+                }
+
+                ui.horizontal(|ui| {
+                    let mut live_steps = self.generation.borrow().live_steps();
+
+                    ui.label("Live steps");
+
+                    if ui.add(egui::DragValue::new(&mut live_steps)).changed() {
+                        self.generation.borrow_mut().set_live_steps(live_steps);
+                    }
+                })
+                .response
+                .on_hover_text("generation steps per frame, 0 generates the whole map at once");
+
+                if self.generation.borrow().is_live() {
+                    ui.horizontal(|ui| {
+                        let paused = self.generation.borrow().paused();
+
+                        if ui.button(if paused { "Resume" } else { "Pause" }).clicked() {
+                            self.generation.borrow_mut().set_paused(!paused);
+                        }
+
+                        if ui.button("Step").clicked() {
+                            self.generation.borrow_mut().step_once();
+                        }
+                    });
                 }
+
+                ui.horizontal(|ui| {
+                    let (can_undo, can_redo) = {
+                        let generation = self.generation.borrow();
+                        (generation.can_undo(), generation.can_redo())
+                    };
+
+                    if ui
+                        .add_enabled(can_undo, egui::Button::new("Undo"))
+                        .clicked()
+                    {
+                        self.generation.borrow_mut().undo();
+                    }
+
+                    if ui
+                        .add_enabled(can_redo, egui::Button::new("Redo"))
+                        .clicked()
+                    {
+                        self.generation.borrow_mut().redo();
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut self.segment));
+
+                    if ui.button("Regenerate segment").clicked() {
+                        let design = default_design();
+                        self.generation
+                            .borrow_mut()
+                            .regenerate_segment(self.segment, &design);
+                    }
+                });
             }
             UiNode::MutationNode(mutation) => match mutation {
                 UiMutation::Brush(mutation) => match mutation {
@@ -386,6 +444,19 @@ impl SnarlViewer<UiNode> for UiViewer {
                             field_numeric(ui, "Seed", &mut mutation.seed);
                             field_numeric(ui, "OverallSteps", &mut mutation.overall_steps);
                         });
+
+                        if mutation.directions.values.is_empty() {
+                            if ui.button("Weight directions").clicked() {
+                                mutation.directions =
+                                    RandomDistConfig::from_shape(Shape::Uniform, 4);
+                            }
+                        } else {
+                            distribution_editor(ui, &mut mutation.directions);
+
+                            if ui.button("Uniform directions").clicked() {
+                                mutation.directions = RandomDistConfig::new();
+                            }
+                        }
                     }
                 },
             },
@@ -546,6 +617,7 @@ impl BottomPanelUi {
             style: SnarlStyle::new(),
             viewer: UiViewer {
                 generation: Rc::new(RefCell::new(GenerationContext::new())),
+                segment: 0,
             },
         }
     }
@@ -566,9 +638,9 @@ impl RenderableUi for BottomPanelUi {
     }
 }
 
-fn field_numeric(ui: &mut Ui, name: impl Into<String>, value: &mut impl Numeric) {
+pub fn field_numeric(ui: &mut Ui, name: impl Into<String>, value: &mut impl Numeric) {
     let drag_value = egui::DragValue::new(value);
-    ui.label(name.into());
+    field_label(ui, name.into());
     ui.add(drag_value);
     ui.end_row();
 }
@@ -0,0 +1,156 @@
+use std::{cell::RefCell, path::Path, rc::Rc};
+
+use egui::{Context, DragValue};
+use egui_file_dialog::{DialogState, FileDialog};
+use mapgen_core::{
+    branding::{logo_pixels, rasterize_text, Branding},
+    map::BlockType,
+};
+use ndarray::Array2;
+
+use crate::components::utils::generation::GenerationContext;
+
+use super::context::RenderableUi;
+
+/// stamps a short text or a monochrome logo into the current map, for server branding
+pub struct BrandingUi {
+    generation: Rc<RefCell<GenerationContext>>,
+    file_dialog: FileDialog,
+    text: String,
+    /// pixels of the loaded logo, stamped instead of the text while set
+    logo: Option<Array2<bool>>,
+    /// why the last logo couldn't be loaded
+    logo_error: Option<String>,
+    at: [usize; 2],
+    scale: usize,
+    block: BlockType,
+}
+
+impl BrandingUi {
+    pub fn new(generation: Rc<RefCell<GenerationContext>>) -> Self {
+        Self {
+            generation,
+            file_dialog: FileDialog::new(),
+            text: String::new(),
+            logo: None,
+            logo_error: None,
+            at: [2, 2],
+            scale: 1,
+            block: BlockType::Hookable,
+        }
+    }
+
+    fn load_logo(&mut self, path: &Path) {
+        let image = match image::open(path) {
+            Ok(image) => image.to_luma_alpha8(),
+            Err(err) => {
+                self.logo_error = Some(err.to_string());
+                return;
+            }
+        };
+
+        let luma_alpha = Array2::from_shape_fn(
+            (image.width() as usize, image.height() as usize),
+            |(x, y)| image.get_pixel(x as u32, y as u32).0,
+        );
+
+        self.logo = Some(logo_pixels(luma_alpha.view()));
+        self.logo_error = None;
+    }
+
+    fn branding(&self) -> Branding {
+        let pixels = match &self.logo {
+            Some(logo) => logo.clone(),
+            None => rasterize_text(&self.text, self.scale),
+        };
+
+        Branding::new(pixels, self.at, self.block)
+    }
+}
+
+impl RenderableUi for BrandingUi {
+    fn ui_with(&mut self, ctx: &Context) {
+        egui::Window::new("Branding")
+            .resizable(false)
+            .default_open(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Load logo").clicked() {
+                        self.file_dialog.select_file();
+                    }
+
+                    if self.logo.is_some() && ui.button("Use text").clicked() {
+                        self.logo = None;
+                    }
+                });
+
+                if let Some(err) = &self.logo_error {
+                    ui.label(format!("Loading failed: {}", err));
+                }
+
+                egui::Grid::new("branding_grid").show(ui, |ui| {
+                    if let Some(logo) = &self.logo {
+                        ui.label("Logo");
+                        ui.label(format!("{}x{}", logo.dim().0, logo.dim().1));
+                        ui.end_row();
+                    } else {
+                        ui.label("Text");
+                        ui.text_edit_singleline(&mut self.text);
+                        ui.end_row();
+
+                        ui.label("Scale");
+                        ui.add(DragValue::new(&mut self.scale).clamp_range(1..=8))
+                            .on_hover_text("tiles per font pixel");
+                        ui.end_row();
+                    }
+
+                    ui.label("At");
+                    ui.horizontal(|ui| {
+                        ui.add(DragValue::new(&mut self.at[0]));
+                        ui.add(DragValue::new(&mut self.at[1]));
+                    })
+                    .response
+                    .on_hover_text("map tile of the top left corner");
+                    ui.end_row();
+
+                    ui.label("Block");
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(&mut self.block, BlockType::Hookable, "Hookable");
+                        ui.selectable_value(&mut self.block, BlockType::Unhookable, "Unhookable");
+                    });
+                    ui.end_row();
+                });
+
+                let branding = self.branding();
+                let mut generation = self.generation.borrow_mut();
+
+                ui.horizontal(|ui| {
+                    if let Some([min, _]) = generation.selection() {
+                        if ui.button("At selection").clicked() {
+                            self.at = min;
+                        }
+                    }
+
+                    if ui
+                        .button("Stamp")
+                        .on_hover_text("the area around the stamp is cleared and lined with freeze")
+                        .clicked()
+                    {
+                        generation.stamp_branding(&branding);
+                    }
+                });
+            });
+
+        if self.file_dialog.state() == DialogState::Open {
+            let selected = self
+                .file_dialog
+                .update(ctx)
+                .selected()
+                .map(|path| path.to_path_buf());
+
+            if let Some(path) = selected {
+                self.load_logo(&path);
+            }
+        }
+    }
+}
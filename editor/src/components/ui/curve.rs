@@ -0,0 +1,118 @@
+use egui::{Color32, Id, Pos2, Sense, Shape, Stroke, Ui, Vec2};
+
+const WIDTH: f32 = 240.0;
+const HEIGHT: f32 = 80.0;
+
+/// highest value the editor shows, points above it are drawn at the top edge
+const MAX_VALUE: f32 = 3.0;
+
+/// how close in points the pointer has to be to grab a point
+const GRAB_RADIUS: f32 = 8.0;
+
+/// piecewise linear curve over 0.0 to 1.0, clicking an empty spot adds a point, dragging moves
+/// one between its neighbors and right clicking removes it, returns whether the curve changed
+pub fn curve_editor(ui: &mut Ui, points: &mut Vec<(f32, f32)>) -> bool {
+    let mut changed = false;
+
+    let (response, painter) =
+        ui.allocate_painter(Vec2::new(WIDTH, HEIGHT), Sense::click_and_drag());
+    let rect = response.rect;
+
+    let to_screen = |(x, y): (f32, f32)| {
+        Pos2::new(
+            rect.left() + x.clamp(0.0, 1.0) * WIDTH,
+            rect.bottom() - (y / MAX_VALUE).clamp(0.0, 1.0) * HEIGHT,
+        )
+    };
+    let from_screen = |pos: Pos2| {
+        (
+            ((pos.x - rect.left()) / WIDTH).clamp(0.0, 1.0),
+            ((rect.bottom() - pos.y) / HEIGHT * MAX_VALUE).clamp(0.0, MAX_VALUE),
+        )
+    };
+
+    painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+    // the value that keeps things as they are
+    let one = to_screen((0.0, 1.0)).y;
+    painter.hline(rect.x_range(), one, Stroke::new(1.0, Color32::DARK_GRAY));
+
+    let grabbed_id = Id::new("curve_grabbed").with(response.id);
+    let pointer = response.interact_pointer_pos().or(response.hover_pos());
+    let closest = pointer.and_then(|pointer| {
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, &point)| (i, to_screen(point).distance(pointer)))
+            .filter(|&(_, distance)| distance <= GRAB_RADIUS)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    });
+
+    if response.drag_started() {
+        ui.data_mut(|data| data.insert_temp(grabbed_id, closest));
+    }
+
+    let grabbed = ui
+        .data(|data| data.get_temp::<Option<usize>>(grabbed_id))
+        .flatten();
+
+    match (pointer, grabbed, closest) {
+        (Some(pointer), Some(i), _) if response.dragged() && i < points.len() => {
+            let (x, y) = from_screen(pointer);
+            let low = i.checked_sub(1).map_or(0.0, |j| points[j].0);
+            let high = points.get(i + 1).map_or(1.0, |point| point.0);
+
+            points[i] = (x.clamp(low, high), y.max(0.01));
+            changed = true;
+        }
+        (_, _, Some(i)) if response.secondary_clicked() => {
+            points.remove(i);
+            changed = true;
+        }
+        (Some(pointer), _, None) if response.clicked() => {
+            let (x, y) = from_screen(pointer);
+            let index = points.partition_point(|point| point.0 <= x);
+
+            points.insert(index, (x, y.max(0.01)));
+            changed = true;
+        }
+        _ => {}
+    }
+
+    if response.drag_released() {
+        ui.data_mut(|data| data.remove::<Option<usize>>(grabbed_id));
+    }
+
+    // flat past the outer points, like the generator reads it
+    let mut line = vec![Pos2::new(
+        rect.left(),
+        points.first().map_or(one, |&p| to_screen(p).y),
+    )];
+    line.extend(points.iter().map(|&point| to_screen(point)));
+    line.push(Pos2::new(
+        rect.right(),
+        points.last().map_or(one, |&p| to_screen(p).y),
+    ));
+
+    painter.add(Shape::line(line, Stroke::new(1.5, Color32::LIGHT_BLUE)));
+
+    for (i, &point) in points.iter().enumerate() {
+        let color = if Some(i) == closest {
+            Color32::WHITE
+        } else {
+            Color32::LIGHT_BLUE
+        };
+
+        painter.circle_filled(to_screen(point), 3.0, color);
+    }
+
+    let hover = match closest.map(|i| points.get(i)) {
+        Some(Some((x, y))) => format!("{:.0}%: x{:.2}", x * 100.0, y),
+        _ => "click to add a point, right click one to remove it".to_owned(),
+    };
+
+    response.on_hover_text(hover);
+
+    changed
+}
@@ -0,0 +1,94 @@
+use std::{cell::RefCell, rc::Rc};
+
+use egui::{Context, Slider};
+use mapgen_core::distance::MAX_DISTANCE;
+
+use crate::components::utils::generation::GenerationContext;
+
+use super::context::RenderableUi;
+
+/// lists the debug layers the last generation produced, toggling one generates again, and
+/// toggles the entity overlay and sets the heatmap opacity
+pub struct DebugLayersUi {
+    generation: Rc<RefCell<GenerationContext>>,
+}
+
+impl DebugLayersUi {
+    pub fn new(generation: Rc<RefCell<GenerationContext>>) -> Self {
+        Self { generation }
+    }
+}
+
+impl RenderableUi for DebugLayersUi {
+    fn ui_with(&mut self, ctx: &Context) {
+        egui::Window::new("Debug layers")
+            .resizable(true)
+            .default_open(false)
+            .show(ctx, |ui| {
+                let mut entity_overlay = self.generation.borrow().entity_overlay();
+
+                if ui
+                    .checkbox(&mut entity_overlay, "Entity overlay")
+                    .on_hover_text("outlines the freeze, start, finish and spawn tiles")
+                    .changed()
+                {
+                    self.generation
+                        .borrow_mut()
+                        .set_entity_overlay(entity_overlay);
+                }
+
+                let mut route_overlay = self.generation.borrow().route_overlay();
+
+                if ui
+                    .checkbox(&mut route_overlay, "Route overlay")
+                    .on_hover_text("planned route in yellow, the path the walker took in blue")
+                    .changed()
+                {
+                    self.generation
+                        .borrow_mut()
+                        .set_route_overlay(route_overlay);
+                }
+
+                let mut opacity = self.generation.borrow().heatmap_opacity();
+
+                ui.horizontal(|ui| {
+                    ui.label("Heatmap opacity");
+
+                    if ui.add(Slider::new(&mut opacity, 0.1..=1.0)).changed() {
+                        self.generation.borrow_mut().set_heatmap_opacity(opacity);
+                    }
+                });
+
+                ui.separator();
+
+                let layers: Vec<(String, bool)> = {
+                    let generation = self.generation.borrow();
+                    let debug = generation.debug_layers();
+
+                    debug
+                        .names()
+                        .map(|name| (name.to_owned(), debug.is_enabled(name)))
+                        .collect()
+                };
+
+                if layers.is_empty() {
+                    ui.label("Generate a map to list its layers");
+                }
+
+                for (name, mut enabled) in layers {
+                    let mut response = ui.checkbox(&mut enabled, &name);
+
+                    if name == "distance" {
+                        response = response.on_hover_text(format!(
+                            "distance to the nearest wall, the field is capped at {} tiles",
+                            MAX_DISTANCE
+                        ));
+                    }
+
+                    if response.changed() {
+                        self.generation.borrow_mut().set_debug_layer(&name, enabled);
+                    }
+                }
+            });
+    }
+}
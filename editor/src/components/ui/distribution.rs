@@ -0,0 +1,89 @@
+use std::fmt::Debug;
+
+use egui::{Color32, Pos2, Rect, Sense, Ui, Vec2};
+use mapgen_core::random::{ProbableValue, RandomDistConfig, Shape};
+
+const BAR_WIDTH: f32 = 16.0;
+const HEIGHT: f32 = 64.0;
+
+/// histogram of the normalized probabilities with shape presets, dragging a bar sets its
+/// probability and scales the others so they still sum up to 1.0, returns whether the config
+/// changed
+pub fn distribution_editor<T: Copy + Debug>(ui: &mut Ui, config: &mut RandomDistConfig<T>) -> bool {
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        for (name, shape) in [
+            ("Uniform", Shape::Uniform),
+            ("Falloff", Shape::LinearFalloff),
+            ("Gaussian", Shape::Gaussian),
+        ] {
+            if ui.button(name).clicked() {
+                config.reshape(shape);
+                changed = true;
+            }
+        }
+    });
+
+    if config.values.is_empty() {
+        return changed;
+    }
+
+    config.normalize_probs();
+
+    let size = Vec2::new(config.values.len() as f32 * BAR_WIDTH, HEIGHT);
+    let (response, painter) = ui.allocate_painter(size, Sense::click_and_drag());
+    let rect = response.rect;
+
+    painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+    for (i, ProbableValue(p, _)) in config.values.iter().enumerate() {
+        let left = rect.left() + i as f32 * BAR_WIDTH;
+        let bar = Rect::from_min_max(
+            Pos2::new(left + 1.0, rect.bottom() - p * HEIGHT),
+            Pos2::new(left + BAR_WIDTH - 1.0, rect.bottom()),
+        );
+
+        painter.rect_filled(bar, 0.0, Color32::LIGHT_BLUE);
+    }
+
+    let Some(pointer) = response.interact_pointer_pos().or(response.hover_pos()) else {
+        return changed;
+    };
+
+    let index = (((pointer.x - rect.left()) / BAR_WIDTH) as usize).min(config.values.len() - 1);
+    let ProbableValue(p, value) = config.values[index];
+
+    if response.dragged() || response.clicked() {
+        let probability = ((rect.bottom() - pointer.y) / HEIGHT).clamp(0.0, 1.0);
+
+        set_probability(config, index, probability);
+        changed = true;
+    }
+
+    response.on_hover_text(format!("{:?}: {:.3}", value, p));
+
+    changed
+}
+
+/// sets one probability, the others keep their ratios
+fn set_probability<T: Copy>(config: &mut RandomDistConfig<T>, index: usize, probability: f32) {
+    let others: f32 = config
+        .values
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| i != index)
+        .map(|(_, ProbableValue(p, _))| p)
+        .sum();
+    let count = config.values.len() - 1;
+
+    for (i, ProbableValue(p, _)) in config.values.iter_mut().enumerate() {
+        *p = if i == index {
+            probability
+        } else if others > 0.0 {
+            *p / others * (1.0 - probability)
+        } else {
+            (1.0 - probability) / count as f32
+        };
+    }
+}
@@ -0,0 +1,78 @@
+use std::{cell::RefCell, rc::Rc};
+
+use egui::{Context, RichText};
+
+use crate::components::utils::generation::GenerationContext;
+
+use super::context::RenderableUi;
+
+/// notable steps of the last or live generation filtered by text, clicking one centers the map
+/// view on where it happened
+pub struct EventLogUi {
+    generation: Rc<RefCell<GenerationContext>>,
+    filter: String,
+    /// kernel resizes come every step while a modifier scales the brush
+    show_kernel: bool,
+}
+
+impl EventLogUi {
+    pub fn new(generation: Rc<RefCell<GenerationContext>>) -> Self {
+        Self {
+            generation,
+            filter: String::new(),
+            show_kernel: false,
+        }
+    }
+}
+
+impl RenderableUi for EventLogUi {
+    fn ui_with(&mut self, ctx: &Context) {
+        egui::Window::new("Events")
+            .default_open(false)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Filter");
+                    ui.text_edit_singleline(&mut self.filter);
+                    ui.checkbox(&mut self.show_kernel, "Kernel");
+                });
+
+                ui.separator();
+
+                let events = self.generation.borrow().events();
+                let shown: Vec<_> = events
+                    .iter()
+                    .filter(|event| self.show_kernel || !event.text.starts_with("kernel"))
+                    .filter(|event| event.text.contains(&self.filter))
+                    .collect();
+
+                if events.is_empty() {
+                    ui.label("Generate a map to list its events");
+                }
+
+                let row_height = ui.text_style_height(&egui::TextStyle::Body);
+
+                egui::ScrollArea::vertical()
+                    .stick_to_bottom(true)
+                    .auto_shrink([false, false])
+                    .show_rows(ui, row_height, shown.len(), |ui, rows| {
+                        for event in &shown[rows] {
+                            let text = format!("{:>6}  {}", event.step, event.text);
+
+                            let Some([x, y]) = event.position else {
+                                ui.label(RichText::new(text).monospace().weak());
+                                continue;
+                            };
+
+                            if ui
+                                .selectable_label(false, RichText::new(text).monospace())
+                                .on_hover_text(format!("{}, {}", x as usize, y as usize))
+                                .clicked()
+                            {
+                                self.generation.borrow_mut().focus([x, y]);
+                            }
+                        }
+                    });
+            });
+    }
+}
@@ -0,0 +1,204 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{channel, Receiver},
+        Arc,
+    },
+    thread,
+};
+
+use egui::{ColorImage, Context, DragValue, ImageButton, TextureHandle, TextureOptions};
+use mapgen_core::{
+    config::GenerationPreset,
+    generator::{GenerationError, Generator},
+    palette::Palette,
+    random::Seed,
+};
+use twmap::{GameLayer, TwMap};
+
+use crate::{
+    components::utils::generation::GenerationContext,
+    settings::{to_color32, BlockPalette},
+};
+
+use super::context::RenderableUi;
+
+/// longest side of a thumbnail in pixels
+const THUMBNAIL_SIZE: usize = 128;
+
+/// generates thumbnails for a range of seeds in background threads, clicking one generates
+/// that seed in the active session
+pub struct GalleryUi {
+    generation: Rc<RefCell<GenerationContext>>,
+    first_seed: Seed,
+    count: usize,
+    thumbnails: Vec<(Seed, TextureHandle)>,
+    /// seeds the preset failed to generate with
+    failed: Vec<(Seed, GenerationError)>,
+    receiver: Option<Receiver<(Seed, Result<ColorImage, GenerationError>)>>,
+    /// stops the threads of the previous run
+    cancel: Arc<AtomicBool>,
+}
+
+impl GalleryUi {
+    pub fn new(generation: Rc<RefCell<GenerationContext>>) -> Self {
+        Self {
+            generation,
+            first_seed: 0,
+            count: 16,
+            thumbnails: Vec::new(),
+            failed: Vec::new(),
+            receiver: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn start(&mut self, palette: Palette) {
+        self.cancel.store(true, Ordering::Relaxed);
+        self.cancel = Arc::new(AtomicBool::new(false));
+        self.thumbnails.clear();
+        self.failed.clear();
+
+        let (sender, receiver) = channel();
+        let seeds: Arc<Vec<Seed>> = Arc::new(
+            (0..self.count as Seed)
+                .map(|i| self.first_seed.wrapping_add(i))
+                .collect(),
+        );
+        let next = Arc::new(AtomicUsize::new(0));
+        let preset = self.generation.borrow().preset().clone();
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+
+        for _ in 0..threads.min(self.count) {
+            let (sender, seeds, next) = (sender.clone(), seeds.clone(), next.clone());
+            let (preset, cancel) = (preset.clone(), self.cancel.clone());
+
+            thread::spawn(move || {
+                while !cancel.load(Ordering::Relaxed) {
+                    let Some(&seed) = seeds.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                        break;
+                    };
+
+                    let image = generate(preset.clone(), seed).map(|map| thumbnail(&map, palette));
+
+                    if sender.send((seed, image)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        self.receiver = Some(receiver);
+    }
+
+    fn receive(&mut self, ctx: &Context) {
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+
+        for (seed, image) in receiver.try_iter() {
+            let image = match image {
+                Ok(image) => image,
+                Err(err) => {
+                    self.failed.push((seed, err));
+                    continue;
+                }
+            };
+
+            let texture =
+                ctx.load_texture(format!("gallery_{}", seed), image, TextureOptions::NEAREST);
+
+            self.thumbnails.push((seed, texture));
+        }
+
+        self.thumbnails.sort_by_key(|&(seed, _)| seed);
+    }
+}
+
+impl RenderableUi for GalleryUi {
+    fn ui_with(&mut self, ctx: &Context) {
+        self.receive(ctx);
+
+        egui::Window::new("Gallery")
+            .resizable(true)
+            .default_open(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("FirstSeed");
+                    ui.add(DragValue::new(&mut self.first_seed));
+                    ui.label("Count");
+                    ui.add(DragValue::new(&mut self.count).clamp_range(1..=256));
+
+                    if ui.button("Generate").clicked() {
+                        self.start(BlockPalette::of(ctx).palette());
+                    }
+                });
+
+                let done = self.thumbnails.len() + self.failed.len();
+                ui.label(format!("{} / {}", done, self.count));
+
+                if !self.failed.is_empty() {
+                    let failures = self
+                        .failed
+                        .iter()
+                        .map(|(seed, err)| format!("{}: {}", seed, err))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!("{} failed", self.failed.len()),
+                    )
+                    .on_hover_text(failures);
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    ui.horizontal_wrapped(|ui| {
+                        for (seed, texture) in self.thumbnails.iter() {
+                            let response = ui
+                                .add(ImageButton::new(texture))
+                                .on_hover_text(seed.to_string());
+
+                            if response.clicked() {
+                                let mut generation = self.generation.borrow_mut();
+
+                                generation.set_seed(*seed);
+                                generation.request_generation();
+                            }
+                        }
+                    });
+                });
+            });
+
+        if self.receiver.is_some() && self.thumbnails.len() + self.failed.len() < self.count {
+            ctx.request_repaint();
+        }
+    }
+}
+
+pub fn generate(preset: GenerationPreset, seed: Seed) -> Result<TwMap, GenerationError> {
+    let mut generator = Generator::from_preset(preset);
+
+    generator.set_seed(seed);
+    generator.try_generate_observed(|_| {})
+}
+
+/// game layer scaled down so its longest side fits `THUMBNAIL_SIZE`
+pub fn thumbnail(map: &TwMap, palette: Palette) -> ColorImage {
+    let game: &GameLayer = map.find_physics_layer().unwrap();
+    let tiles = game.tiles.unwrap_ref();
+    let (width, height) = tiles.dim();
+
+    let scale = width.max(height).div_ceil(THUMBNAIL_SIZE).max(1);
+    let size = [width.div_ceil(scale), height.div_ceil(scale)];
+
+    let pixels = (0..size[1])
+        .flat_map(|y| (0..size[0]).map(move |x| [x * scale, y * scale]))
+        .map(|index| to_color32(palette.tile_color(&tiles[index])))
+        .collect();
+
+    ColorImage { size, pixels }
+}
@@ -0,0 +1,66 @@
+use std::{cell::RefCell, rc::Rc};
+
+use egui::{Context, Id};
+
+use crate::components::utils::generation::GenerationContext;
+
+use super::context::RenderableUi;
+
+/// tooltip describing the previewed tile under the cursor
+pub struct InspectorUi {
+    generation: Rc<RefCell<GenerationContext>>,
+    enabled: bool,
+}
+
+impl InspectorUi {
+    pub fn new(generation: Rc<RefCell<GenerationContext>>) -> Self {
+        Self {
+            generation,
+            enabled: true,
+        }
+    }
+}
+
+impl RenderableUi for InspectorUi {
+    fn ui_with(&mut self, ctx: &Context) {
+        egui::Window::new("Inspector")
+            .resizable(false)
+            .default_open(false)
+            .show(ctx, |ui| {
+                ui.checkbox(&mut self.enabled, "Show tile tooltips");
+            });
+
+        if !self.enabled || ctx.is_pointer_over_area() {
+            return;
+        }
+
+        let generation = self.generation.borrow();
+        let Some(info) = generation
+            .hovered()
+            .and_then(|tile| generation.inspect(tile))
+        else {
+            return;
+        };
+
+        egui::show_tooltip_at_pointer(ctx, Id::new("tile_inspector"), |ui| {
+            ui.label(format!("Tile {}, {}", info.tile[0], info.tile[1]));
+            ui.label(format!(
+                "Block {}",
+                info.block
+                    .map_or_else(|| "unknown".to_owned(), |block| format!("{:?}", block))
+            ));
+            ui.label(format!("Chunk {}, {}", info.chunk[0], info.chunk[1]));
+            ui.label(format!("Distance {}", info.distance));
+
+            if info.visit == 0 {
+                ui.label("Off the walker path");
+            } else {
+                ui.label(format!("Walker step {}", info.visit));
+            }
+
+            for layer in info.debug.iter() {
+                ui.label(layer);
+            }
+        });
+    }
+}
@@ -0,0 +1,155 @@
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
+
+use egui::{Context, Event, Key};
+
+use crate::{
+    components::utils::generation::GenerationContext,
+    keybinds::{Action, Keybind, Keymap, KEYMAP_PATH},
+};
+
+use super::context::RenderableUi;
+
+/// runs the action of every pressed bind and lets the binds be changed, changes are saved
+/// right away
+pub struct KeybindsUi {
+    generation: Rc<RefCell<GenerationContext>>,
+    keymap: Keymap,
+    /// action waiting for its new key, escape cancels
+    rebinding: Option<Action>,
+    /// outcome of the last save
+    status: Option<String>,
+}
+
+impl KeybindsUi {
+    pub fn new(generation: Rc<RefCell<GenerationContext>>) -> Self {
+        Self {
+            generation,
+            keymap: Keymap::load(KEYMAP_PATH),
+            rebinding: None,
+            status: None,
+        }
+    }
+
+    fn run(&mut self, action: Action) {
+        let mut generation = self.generation.borrow_mut();
+
+        match action {
+            Action::PlayPause => {
+                let paused = generation.paused();
+                generation.set_paused(!paused);
+            }
+            Action::Step => generation.step_once(),
+            Action::Regenerate => generation.request_generation(),
+            Action::Save => {
+                let name = &generation.preset().name;
+                let path = PathBuf::from(format!("maps/{}_{}.map", name, generation.seed()));
+
+                self.status = Some(match generation.save_map(&path) {
+                    Ok(()) => format!("Saved {}", path.display()),
+                    Err(err) => format!("Saving failed: {}", err),
+                });
+            }
+            Action::Undo => generation.undo(),
+            Action::Redo => generation.redo(),
+            Action::Copy => generation.copy_selection(),
+            Action::Paste => {
+                let at = generation
+                    .hovered()
+                    .or(generation.selection().map(|[min, _]| min));
+
+                if let Some(at) = at {
+                    generation.paste(at);
+                }
+            }
+            Action::SquareBrush => generation.preset_mut().kernel.circularity = 0.0,
+            Action::RoundBrush => generation.preset_mut().kernel.circularity = 1.0,
+            Action::LargerBrush => generation.preset_mut().kernel.size += 1,
+            Action::SmallerBrush => {
+                let kernel = &mut generation.preset_mut().kernel;
+                kernel.size = kernel.size.saturating_sub(1).max(1);
+            }
+        }
+    }
+}
+
+impl RenderableUi for KeybindsUi {
+    fn ui_with(&mut self, ctx: &Context) {
+        let mut changed = false;
+
+        egui::Window::new("Keybinds")
+            .resizable(false)
+            .default_open(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("keybinds_grid").show(ui, |ui| {
+                    for action in Action::ALL {
+                        ui.label(action.name());
+
+                        let label = if self.rebinding == Some(action) {
+                            "Press a key".to_owned()
+                        } else {
+                            self.keymap
+                                .binds
+                                .get(&action)
+                                .map_or_else(|| "Unbound".to_owned(), Keybind::label)
+                        };
+
+                        if ui.button(label).clicked() {
+                            self.rebinding = Some(action);
+                        }
+
+                        if ui.small_button("Clear").clicked() {
+                            changed |= self.keymap.binds.remove(&action).is_some();
+                        }
+
+                        ui.end_row();
+                    }
+                });
+
+                if ui.button("Reset to defaults").clicked() {
+                    self.keymap = Keymap::default();
+                    changed = true;
+                }
+
+                if let Some(status) = &self.status {
+                    ui.label(status);
+                }
+            });
+
+        if let Some(action) = self.rebinding {
+            let pressed = ctx.input(|input| {
+                input.events.iter().find_map(|event| match event {
+                    Event::Key {
+                        key,
+                        pressed: true,
+                        modifiers,
+                        ..
+                    } => Some((*key, *modifiers)),
+                    _ => None,
+                })
+            });
+
+            if let Some((key, modifiers)) = pressed {
+                if key != Key::Escape {
+                    self.keymap
+                        .binds
+                        .insert(action, Keybind::new(key, modifiers));
+                    changed = true;
+                }
+
+                self.rebinding = None;
+            }
+        } else if !ctx.wants_keyboard_input() {
+            let actions = ctx.input_mut(|input| self.keymap.pressed(input));
+
+            for action in actions {
+                self.run(action);
+            }
+        }
+
+        if changed {
+            if let Err(err) = self.keymap.save(KEYMAP_PATH) {
+                self.status = Some(format!("Saving the keybinds failed: {}", err));
+            }
+        }
+    }
+}
@@ -1,52 +1,237 @@
-use std::{cell::RefCell, path::PathBuf, rc::Rc};
+use std::{
+    cell::RefCell,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use egui::{popup_below_widget, Context, Id};
+use egui::{CollapsingHeader, Context, DragValue};
 use egui_file_dialog::{DialogState, FileDialog};
+use mapgen_core::{
+    generator::{placement::Endpoint, Canvas},
+    map::{Map, PastePolicy},
+    migration,
+    transform::Transform,
+};
+use tracing::error;
 use twmap::TwMap;
 
-use crate::components::map::MapLoader;
+use crate::{
+    components::{
+        map::{Capture, MapLoader},
+        utils::generation::GenerationContext,
+    },
+    input_handler::KeyboardCamera,
+    recent::{RecentFile, RecentFiles, RecentKind},
+};
 
-use super::context::RenderableUi;
+use super::{context::RenderableUi, sample::SamplePreview};
 
 pub struct LeftPanelUi {
     file_dialog: FileDialog,
+    /// what the file picked in the dialog is opened as
+    dialog_kind: RecentKind,
+    recent: RecentFiles,
+    /// why the last file couldn't be opened
+    load_error: Option<String>,
     current_map: Option<PathBuf>,
+    /// game layer of the loaded map, usable as generation canvas
+    canvas: Option<Canvas>,
+    use_canvas: bool,
+    /// pixels per tile of full map captures
+    capture_scale: u32,
+    sample: SamplePreview,
 
     map_loader: Rc<RefCell<MapLoader>>,
+    keyboard_camera: Rc<RefCell<KeyboardCamera>>,
+    generation: Rc<RefCell<GenerationContext>>,
 }
 
 impl LeftPanelUi {
-    pub fn new(map_loader: Rc<RefCell<MapLoader>>) -> Self {
+    pub fn new(
+        map_loader: Rc<RefCell<MapLoader>>,
+        keyboard_camera: Rc<RefCell<KeyboardCamera>>,
+        generation: Rc<RefCell<GenerationContext>>,
+    ) -> Self {
         Self {
             file_dialog: FileDialog::new(),
+            dialog_kind: RecentKind::Map,
+            recent: RecentFiles::load(),
+            load_error: None,
             current_map: None,
+            canvas: None,
+            use_canvas: false,
+            capture_scale: 8,
+            sample: SamplePreview::default(),
             map_loader,
+            keyboard_camera,
+            generation,
+        }
+    }
+
+    /// captures into a new file of the `captures` directory
+    fn request_capture(&self, capture: Capture) {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_millis());
+        let path = PathBuf::from(format!("captures/{}.png", time));
+
+        self.map_loader.borrow_mut().request_capture(capture, path);
+    }
+
+    fn update_canvas(&mut self) {
+        let canvas = self.canvas.clone().filter(|_| self.use_canvas);
+
+        self.generation.borrow_mut().set_canvas(canvas);
+    }
+
+    fn select_file(&mut self, kind: RecentKind) {
+        self.dialog_kind = kind;
+        self.file_dialog.select_file();
+    }
+
+    /// opens `path` and puts it at the front of the recent files
+    fn open(&mut self, kind: RecentKind, path: &Path) {
+        let result = match kind {
+            RecentKind::Preset => self.load_preset(path),
+            RecentKind::Map => self.load_map(path),
+        };
+
+        match result {
+            Ok(()) => {
+                self.recent.push(kind, path);
+                self.load_error = None;
+            }
+            Err(err) => {
+                if !path.exists() {
+                    self.recent.remove(path);
+                }
+
+                self.load_error = Some(format!("Failed to open '{}': {}", path.display(), err));
+            }
+        }
+
+        if let Err(err) = self.recent.save() {
+            error!(%err, "saving the recent files failed");
+        }
+    }
+
+    fn load_map(&mut self, path: &Path) -> Result<(), String> {
+        let mut tw_map = TwMap::parse_path(path).map_err(|err| format!("{:?}", err))?;
+        tw_map.load().map_err(|err| format!("{:?}", err))?;
+
+        let map = Map::from_twmap(&tw_map);
+        let center = [map.width() / 2, map.height() / 2];
+
+        self.canvas = Some(Canvas::new(&map, center));
+        self.update_canvas();
+
+        self.map_loader.borrow_mut().load(tw_map);
+        self.current_map = Some(path.to_path_buf());
+
+        Ok(())
+    }
+
+    /// replaces the preset of the active session with a json or toml preset file
+    fn load_preset(&mut self, path: &Path) -> Result<(), String> {
+        let data = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let value = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => toml::from_str(&data).map_err(|err| err.to_string())?,
+            _ => serde_json::from_str(&data).map_err(|err| err.to_string())?,
+        };
+        let mut preset = migration::load_preset(value).map_err(|err| err.to_string())?;
+
+        if preset.name.is_empty() {
+            if let Some(name) = path.file_stem() {
+                preset.name = name.to_string_lossy().into_owned();
+            }
+        }
+
+        *self.generation.borrow_mut().preset_mut() = preset;
+
+        Ok(())
+    }
+
+    /// opens the presets and maps dropped onto the window
+    fn open_dropped(&mut self, ctx: &Context) {
+        let dropped = ctx.input(|input| input.raw.dropped_files.clone());
+
+        for path in dropped.into_iter().filter_map(|file| file.path) {
+            match RecentKind::of(&path) {
+                Some(kind) => self.open(kind, &path),
+                None => {
+                    self.load_error = Some(format!(
+                        "Can't open '{}', drop a .json or .toml preset or a .map",
+                        path.display()
+                    ));
+                }
+            }
         }
     }
 }
 
 impl RenderableUi for LeftPanelUi {
     fn ui_with(&mut self, ctx: &Context) {
+        self.open_dropped(ctx);
+
         egui::panel::SidePanel::left("main_left_panel")
             .resizable(true)
             .show(ctx, |ui| {
                 let map_loaded = self.map_loader.borrow().is_loaded();
-                let response = ui.button(if !map_loaded {
-                    "Load map"
-                } else {
-                    "Unload map"
-                });
 
-                if response.clicked() {
-                    if !map_loaded {
-                        self.file_dialog.select_file();
+                ui.horizontal(|ui| {
+                    let response = ui.button(if !map_loaded {
+                        "Load map"
                     } else {
-                        self.map_loader.borrow_mut().unload();
-                        self.current_map = None;
+                        "Unload map"
+                    });
+
+                    if response.clicked() {
+                        if !map_loaded {
+                            self.select_file(RecentKind::Map);
+                        } else {
+                            self.map_loader.borrow_mut().unload();
+                            self.current_map = None;
+                            self.canvas = None;
+                            self.update_canvas();
+                        }
                     }
+
+                    if ui.button("Load preset").clicked() {
+                        self.select_file(RecentKind::Preset);
+                    }
+                });
+
+                let mut reopen: Option<RecentFile> = None;
+
+                egui::ComboBox::from_id_source("recent_files")
+                    .selected_text("Recent")
+                    .show_ui(ui, |ui| {
+                        for entry in self.recent.entries() {
+                            if ui
+                                .selectable_label(false, entry.label())
+                                .on_hover_text(entry.path.display().to_string())
+                                .clicked()
+                            {
+                                reopen = Some(entry.clone());
+                            }
+                        }
+
+                        if self.recent.entries().is_empty() {
+                            ui.label("nothing opened yet");
+                        }
+                    });
+
+                if let Some(entry) = reopen {
+                    self.open(entry.kind, &entry.path);
                 }
 
-                let popup_id = Id::new("select_map_popup");
+                if ctx.input(|input| !input.raw.hovered_files.is_empty()) {
+                    ui.label("Drop to open");
+                } else if let Some(err) = &self.load_error {
+                    ui.label(err);
+                }
 
                 let map_name = if let Some(map_path) = &self.current_map {
                     map_path.file_name().unwrap().to_str().unwrap()
@@ -59,26 +244,206 @@ impl RenderableUi for LeftPanelUi {
                     ui.monospace(map_name);
                 });
 
-                if self.file_dialog.state() == DialogState::Open {
-                    if let Some(path) = self.file_dialog.update(ctx).selected() {
-                        match TwMap::parse_path(path) {
-                            Ok(mut tw_map) => {
-                                tw_map.load().unwrap(); // TODO: handle error
-                                self.map_loader.borrow_mut().load(tw_map);
-                                self.current_map = Some(path.to_path_buf());
-                            }
-                            Err(err) => {
-                                popup_below_widget(ui, popup_id, &response, |ui| {
-                                    ui.label(format!(
-                                        "Failed to open '{}': {:?}",
-                                        path.to_string_lossy(),
-                                        err
-                                    ));
-                                });
+                if let Some(canvas) = &mut self.canvas {
+                    let mut changed = ui.checkbox(&mut self.use_canvas, "Use as canvas").changed();
+
+                    ui.horizontal(|ui| {
+                        let (width, height) = canvas.tiles.dim();
+
+                        ui.label("Start");
+                        changed |= ui
+                            .add(DragValue::new(&mut canvas.start[0]).clamp_range(0..=width - 1))
+                            .changed();
+                        changed |= ui
+                            .add(DragValue::new(&mut canvas.start[1]).clamp_range(0..=height - 1))
+                            .changed();
+                    });
+
+                    if changed {
+                        self.update_canvas();
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Pan speed");
+                    ui.add(
+                        DragValue::new(&mut self.keyboard_camera.borrow_mut().pan_speed)
+                            .speed(0.05)
+                            .clamp_range(0.0..=10.0),
+                    )
+                    .on_hover_text("view sizes per second, pan with WASD or the arrow keys");
+                });
+
+                let mut editing = self.generation.borrow().waypoint_editing();
+
+                if ui
+                    .checkbox(&mut editing, "Edit waypoints")
+                    .on_hover_text(
+                        "drag the waypoints, shift click adds one, ctrl click removes one",
+                    )
+                    .changed()
+                {
+                    self.generation.borrow_mut().set_waypoint_editing(editing);
+                }
+
+                let mut endpoint_tool = self.generation.borrow().endpoint_tool();
+
+                ui.horizontal(|ui| {
+                    ui.label("Place");
+                    ui.selectable_value(&mut endpoint_tool, None, "Off");
+                    ui.selectable_value(&mut endpoint_tool, Some(Endpoint::Spawn), "Spawn");
+                    ui.selectable_value(&mut endpoint_tool, Some(Endpoint::Finish), "Finish");
+                })
+                .response
+                .on_hover_text("click the map to move the first or last waypoint there");
+
+                if endpoint_tool != self.generation.borrow().endpoint_tool() {
+                    self.generation
+                        .borrow_mut()
+                        .set_endpoint_tool(endpoint_tool);
+                }
+
+                CollapsingHeader::new("Sample").show(ui, |ui| {
+                    let generation = self.generation.borrow();
+
+                    self.sample.ui(ui, generation.preset());
+                });
+
+                ui.separator();
+
+                ui.add_enabled_ui(map_loaded, |ui| {
+                    ui.horizontal(|ui| {
+                        if ui.button("Capture view").clicked() {
+                            self.request_capture(Capture::View);
+                        }
+
+                        if ui.button("Capture map").clicked() {
+                            self.request_capture(Capture::Map {
+                                scale: self.capture_scale,
+                            });
+                        }
+
+                        ui.add(DragValue::new(&mut self.capture_scale).clamp_range(1..=64))
+                            .on_hover_text("pixels per tile of map captures");
+                    });
+                });
+
+                match self.map_loader.borrow().last_capture() {
+                    Some(Ok(path)) => {
+                        ui.label(format!("Saved {}", path.display()));
+                    }
+                    Some(Err(err)) => {
+                        ui.label(format!("Capture failed: {}", err));
+                    }
+                    None => {}
+                }
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    let transform = Transform::default();
+                    let mut generation = self.generation.borrow_mut();
+
+                    if ui.button("Rotate map").clicked() {
+                        generation.transform_map(transform.rotated());
+                    }
+
+                    if ui.button("Mirror X").clicked() {
+                        generation.transform_map(transform.mirrored_x());
+                    }
+
+                    if ui.button("Mirror Y").clicked() {
+                        generation.transform_map(transform.mirrored_y());
+                    }
+                });
+
+                let selection = self.generation.borrow().selection();
+
+                if let Some([min, max]) = selection {
+                    ui.separator();
+                    ui.label(format!("Selection: {:?} to {:?}", min, max));
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Lock").clicked() {
+                            self.generation.borrow_mut().lock_selection(true);
+                        }
+
+                        if ui.button("Unlock").clicked() {
+                            self.generation.borrow_mut().lock_selection(false);
+                        }
+
+                        if ui.button("Copy").clicked() {
+                            self.generation.borrow_mut().copy_selection();
+                        }
+
+                        if ui.button("Clear").clicked() {
+                            self.generation.borrow_mut().set_selection(None);
+                        }
+                    });
+                }
+
+                let clipboard = self
+                    .generation
+                    .borrow()
+                    .clipboard()
+                    .map(|tiles| tiles.dim());
+
+                if let Some(dim) = clipboard {
+                    let mut generation = self.generation.borrow_mut();
+                    let transform = generation.paste_transform();
+                    let (width, height) = transform.apply_dim(dim);
+
+                    ui.separator();
+                    ui.label(format!("Clipboard: {}x{}", width, height));
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Rotate").clicked() {
+                            generation.set_paste_transform(transform.rotated());
+                        }
+
+                        if ui.button("Mirror X").clicked() {
+                            generation.set_paste_transform(transform.mirrored_x());
+                        }
+
+                        if ui.button("Mirror Y").clicked() {
+                            generation.set_paste_transform(transform.mirrored_y());
+                        }
+                    });
+
+                    let mut policy = generation.paste_policy();
+
+                    egui::ComboBox::from_id_source("paste_policy")
+                        .selected_text(format!("{:?}", policy))
+                        .show_ui(ui, |ui| {
+                            for option in [
+                                PastePolicy::Overwrite,
+                                PastePolicy::SkipEmpty,
+                                PastePolicy::FillEmpty,
+                            ] {
+                                ui.selectable_value(&mut policy, option, format!("{:?}", option));
                             }
+                        });
+
+                    generation.set_paste_policy(policy);
+
+                    if let Some([min, _]) = selection {
+                        if ui.button("Paste at selection").clicked() {
+                            generation.paste(min);
                         }
                     }
                 }
+
+                if self.file_dialog.state() == DialogState::Open {
+                    let selected = self
+                        .file_dialog
+                        .update(ctx)
+                        .selected()
+                        .map(|path| path.to_path_buf());
+
+                    if let Some(path) = selected {
+                        self.open(self.dialog_kind, &path);
+                    }
+                }
             });
     }
 }
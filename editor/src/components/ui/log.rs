@@ -0,0 +1,99 @@
+use egui::{Color32, Context, RichText};
+use tracing::Level;
+
+use crate::logging::LogBuffer;
+
+use super::context::RenderableUi;
+
+const LEVELS: [Level; 5] = [
+    Level::ERROR,
+    Level::WARN,
+    Level::INFO,
+    Level::DEBUG,
+    Level::TRACE,
+];
+
+/// console of the events the subscriber collected, filtered by level and text
+pub struct LogConsoleUi {
+    logs: LogBuffer,
+    /// most verbose level shown
+    level: Level,
+    filter: String,
+}
+
+impl LogConsoleUi {
+    pub fn new(logs: LogBuffer) -> Self {
+        Self {
+            logs,
+            level: Level::INFO,
+            filter: String::new(),
+        }
+    }
+}
+
+impl RenderableUi for LogConsoleUi {
+    fn ui_with(&mut self, ctx: &Context) {
+        egui::Window::new("Log")
+            .default_open(false)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("log_level")
+                        .selected_text(self.level.as_str())
+                        .show_ui(ui, |ui| {
+                            for level in LEVELS {
+                                ui.selectable_value(&mut self.level, level, level.as_str());
+                            }
+                        });
+
+                    ui.label("Filter");
+                    ui.text_edit_singleline(&mut self.filter);
+
+                    if ui.button("Clear").clicked() {
+                        self.logs.lock().unwrap().clear();
+                    }
+                });
+
+                ui.separator();
+
+                let logs = self.logs.lock().unwrap();
+                let lines = logs.iter().filter(|line| {
+                    line.level <= self.level
+                        && (line.message.contains(&self.filter)
+                            || line.fields.contains(&self.filter)
+                            || line.target.contains(&self.filter))
+                });
+
+                egui::ScrollArea::vertical()
+                    .stick_to_bottom(true)
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        for line in lines {
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    RichText::new(line.level.as_str())
+                                        .monospace()
+                                        .color(level_color(line.level)),
+                                );
+                                ui.label(RichText::new(&line.target).monospace().weak());
+                                ui.label(&line.message);
+
+                                if !line.fields.is_empty() {
+                                    ui.label(RichText::new(&line.fields).weak());
+                                }
+                            });
+                        }
+                    });
+            });
+    }
+}
+
+fn level_color(level: Level) -> Color32 {
+    match level {
+        Level::ERROR => Color32::RED,
+        Level::WARN => Color32::YELLOW,
+        Level::INFO => Color32::LIGHT_GREEN,
+        Level::DEBUG => Color32::LIGHT_BLUE,
+        _ => Color32::GRAY,
+    }
+}
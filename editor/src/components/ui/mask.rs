@@ -0,0 +1,176 @@
+use std::{cell::RefCell, path::Path, rc::Rc};
+
+use egui::{Color32, Context, DragValue, Id, LayerId, Order, Rect, Slider};
+use egui_file_dialog::{DialogState, FileDialog};
+use mapgen_core::guidance::Guidance;
+use ndarray::Array2;
+
+use crate::components::utils::generation::{GenerationContext, MaskPaint};
+
+use super::{context::RenderableUi, waypoints::to_screen};
+
+/// paint tool for the regions the walker avoids or is pulled towards, the painted cells are
+/// shown over the map while the tool is picked, grayscale images can be imported as a mask
+pub struct GuidanceMaskUi {
+    generation: Rc<RefCell<GenerationContext>>,
+    file_dialog: FileDialog,
+    /// imports dark pixels as preferred and light ones as forbidden, for sketches drawn dark on
+    /// light
+    invert: bool,
+    /// why the last image couldn't be imported
+    import_error: Option<String>,
+}
+
+impl GuidanceMaskUi {
+    pub fn new(generation: Rc<RefCell<GenerationContext>>) -> Self {
+        Self {
+            generation,
+            file_dialog: FileDialog::new(),
+            invert: false,
+            import_error: None,
+        }
+    }
+
+    fn import(&mut self, path: &Path) {
+        let image = match image::open(path) {
+            Ok(image) => image.to_luma8(),
+            Err(err) => {
+                self.import_error = Some(err.to_string());
+                return;
+            }
+        };
+
+        let luma = Array2::from_shape_fn(
+            (image.width() as usize, image.height() as usize),
+            |(x, y)| {
+                let value = image.get_pixel(x as u32, y as u32).0[0];
+
+                if self.invert {
+                    u8::MAX - value
+                } else {
+                    value
+                }
+            },
+        );
+
+        self.generation.borrow_mut().import_mask(&luma);
+        self.import_error = None;
+    }
+
+    fn draw_cells(&self, ctx: &Context) {
+        let generation = self.generation.borrow();
+
+        let Some(view) = generation.map_view() else {
+            return;
+        };
+
+        let painter = ctx.layer_painter(LayerId::new(Order::Background, Id::new("guidance")));
+
+        for ([min, max], guidance) in generation.mask_cells() {
+            let color = match guidance {
+                Guidance::Forbidden => Color32::from_rgba_unmultiplied(255, 0, 0, 80),
+                Guidance::Preferred => Color32::from_rgba_unmultiplied(0, 255, 0, 80),
+            };
+            let rect = Rect::from_two_pos(to_screen(ctx, view, min), to_screen(ctx, view, max));
+
+            painter.rect_filled(rect, 0.0, color);
+        }
+    }
+}
+
+impl RenderableUi for GuidanceMaskUi {
+    fn ui_with(&mut self, ctx: &Context) {
+        egui::Window::new("Guidance mask")
+            .resizable(false)
+            .default_open(false)
+            .show(ctx, |ui| {
+                let mut generation = self.generation.borrow_mut();
+                let mut paint = generation.mask_paint();
+
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut paint, None, "Off");
+
+                    for option in MaskPaint::ALL {
+                        ui.selectable_value(&mut paint, Some(option), format!("{:?}", option));
+                    }
+                })
+                .response
+                .on_hover_text("drag over the map to paint, the map is generated once released");
+
+                if paint != generation.mask_paint() {
+                    generation.set_mask_paint(paint);
+                }
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Import image")
+                        .on_hover_text(
+                            "grayscale image stretched over the map, light is preferred and dark \
+                             is forbidden",
+                        )
+                        .clicked()
+                    {
+                        self.file_dialog.select_file();
+                    }
+
+                    ui.checkbox(&mut self.invert, "Invert");
+                });
+
+                if let Some(err) = &self.import_error {
+                    ui.label(format!("Import failed: {}", err));
+                }
+
+                let mut changed = false;
+                let mut cleared = false;
+
+                if let Some(mask) = &mut generation.preset_mut().guidance_mask {
+                    egui::Grid::new("guidance_mask_grid").show(ui, |ui| {
+                        ui.label("CellSize");
+                        changed |= ui
+                            .add(DragValue::new(&mut mask.cell_size).clamp_range(1..=64))
+                            .on_hover_text("tiles, painted cells grow and shrink along")
+                            .changed();
+                        ui.end_row();
+
+                        ui.label("Attraction");
+                        changed |= ui
+                            .add(Slider::new(&mut mask.attraction, 0.0..=1.0))
+                            .changed();
+                        ui.end_row();
+                    });
+
+                    ui.label(format!(
+                        "{} forbidden, {} preferred cells",
+                        mask.forbidden.len(),
+                        mask.preferred.len()
+                    ));
+
+                    cleared = ui.button("Clear").clicked();
+                }
+
+                if cleared {
+                    generation.preset_mut().guidance_mask = None;
+                }
+
+                if changed || cleared {
+                    generation.request_generation();
+                }
+            });
+
+        if self.file_dialog.state() == DialogState::Open {
+            let selected = self
+                .file_dialog
+                .update(ctx)
+                .selected()
+                .map(|path| path.to_path_buf());
+
+            if let Some(path) = selected {
+                self.import(&path);
+            }
+        }
+
+        if self.generation.borrow().mask_paint().is_some() {
+            self.draw_cells(ctx);
+        }
+    }
+}
@@ -1,7 +1,26 @@
 pub mod bottom_panel;
+pub mod branding;
 pub mod context;
+pub mod curve;
+pub mod debug;
+pub mod distribution;
+pub mod events;
 pub mod float;
+pub mod gallery;
+pub mod inspector;
+pub mod keybinds;
 pub mod left_panel;
+pub mod log;
+pub mod mask;
+pub mod prefabs;
+pub mod preset;
+pub mod recovery;
+pub mod ruler;
+pub mod sample;
+pub mod search;
+pub mod sessions;
+pub mod settings;
+pub mod waypoints;
 
 use std::{cell::RefCell, rc::Rc};
 
@@ -12,13 +31,17 @@ use egui_winit::State;
 use wgpu::StoreOp;
 use winit::{dpi::PhysicalSize, event::WindowEvent, window::Window};
 
-use crate::app::{RenderContext, WgpuContext};
+use crate::{
+    app::{RenderContext, WgpuContext},
+    settings::Settings,
+};
 
 use super::AppComponent;
 
 pub struct UiComponent {
     state: State,
     renderer: Renderer,
+    settings: Rc<RefCell<Settings>>,
 
     context: UiContext,
 }
@@ -28,6 +51,7 @@ impl UiComponent {
         context: UiContext,
         window: &Window,
         wgpu_context: Rc<RefCell<WgpuContext>>,
+        settings: Rc<RefCell<Settings>>,
     ) -> Self {
         let egui_context = Context::default();
 
@@ -54,6 +78,7 @@ impl UiComponent {
         Self {
             state,
             renderer,
+            settings,
             context,
         }
     }
@@ -66,7 +91,10 @@ impl AppComponent for UiComponent {
     fn on_user_input(&mut self, window: &Window, event: &WindowEvent) -> bool {
         let _ = self.state.on_window_event(window, event);
 
-        self.state.egui_ctx().is_pointer_over_area()
+        match event {
+            WindowEvent::KeyboardInput { .. } => self.state.egui_ctx().wants_keyboard_input(),
+            _ => self.state.egui_ctx().is_pointer_over_area(),
+        }
     }
 
     fn on_render(
@@ -79,7 +107,7 @@ impl AppComponent for UiComponent {
         if let Some(render_context) = render_context {
             let screen_descriptor = ScreenDescriptor {
                 size_in_pixels: [wgpu_context.config.width, wgpu_context.config.height],
-                pixels_per_point: 1.0,
+                pixels_per_point: self.settings.borrow().ui_scale,
             };
 
             let command_encoder = render_context
@@ -0,0 +1,127 @@
+use std::{cell::RefCell, fs, path::Path, rc::Rc};
+
+use egui::{Context, Rect, Sense, Ui, Vec2};
+use mapgen_core::prefabs::{Prefab, PrefabPlacement};
+
+use crate::{components::utils::generation::GenerationContext, settings::BlockPalette};
+
+use super::context::RenderableUi;
+
+const PREFABS_PATH: &str = "data/prefabs";
+
+/// lists the prefabs on disk and adds them to the current preset
+pub struct PrefabBrowserUi {
+    generation: Rc<RefCell<GenerationContext>>,
+    prefabs: Vec<Prefab>,
+    selected: Option<usize>,
+}
+
+impl PrefabBrowserUi {
+    pub fn new(generation: Rc<RefCell<GenerationContext>>) -> Self {
+        Self {
+            generation,
+            prefabs: load_prefabs(PREFABS_PATH),
+            selected: None,
+        }
+    }
+}
+
+impl RenderableUi for PrefabBrowserUi {
+    fn ui_with(&mut self, ctx: &Context) {
+        egui::Window::new("Prefabs")
+            .resizable(true)
+            .vscroll(true)
+            .default_open(false)
+            .show(ctx, |ui| {
+                if ui.button("Reload").clicked() {
+                    self.prefabs = load_prefabs(PREFABS_PATH);
+                    self.selected = None;
+                }
+
+                ui.separator();
+
+                for (i, prefab) in self.prefabs.iter().enumerate() {
+                    if ui
+                        .selectable_label(self.selected == Some(i), &prefab.name)
+                        .clicked()
+                    {
+                        self.selected = Some(i);
+                    }
+                }
+
+                let Some(prefab) = self.selected.and_then(|i| self.prefabs.get(i)) else {
+                    return;
+                };
+
+                ui.separator();
+
+                prefab_preview(ui, prefab);
+
+                if ui.button("Add to preset").clicked() {
+                    self.generation
+                        .borrow_mut()
+                        .preset_mut()
+                        .post_process
+                        .prefabs
+                        .push(PrefabPlacement {
+                            prefab: prefab.clone(),
+                            ..Default::default()
+                        });
+                }
+            });
+    }
+}
+
+/// paints the stencil, kept tiles stay transparent
+pub fn prefab_preview(ui: &mut Ui, prefab: &Prefab) {
+    const CELL_SIZE: f32 = 8.0;
+
+    let palette = BlockPalette::of(ui.ctx());
+    let size = Vec2::new(prefab.width() as f32, prefab.height() as f32) * CELL_SIZE;
+    let (response, painter) = ui.allocate_painter(size, Sense::hover());
+
+    for y in 0..prefab.height() {
+        for x in 0..prefab.width() {
+            let Some(block) = prefab.cell(x, y) else {
+                continue;
+            };
+
+            let min = response.rect.min + Vec2::new(x as f32, y as f32) * CELL_SIZE;
+
+            painter.rect_filled(
+                Rect::from_min_size(min, Vec2::splat(CELL_SIZE)),
+                0.0,
+                palette.color(block),
+            );
+        }
+    }
+}
+
+/// loads json stencils and png images, unreadable files are skipped
+fn load_prefabs<P: AsRef<Path>>(path: P) -> Vec<Prefab> {
+    let Ok(entries) = fs::read_dir(path) else {
+        return Vec::new();
+    };
+
+    let mut prefabs: Vec<Prefab> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = path.file_stem()?.to_str()?.to_owned();
+
+            match path.extension()?.to_str()? {
+                "json" => serde_json::from_str(&fs::read_to_string(&path).ok()?).ok(),
+                "png" => {
+                    let image = image::open(&path).ok()?.to_rgba8();
+                    let pixels: Vec<[u8; 4]> = image.pixels().map(|pixel| pixel.0).collect();
+
+                    Some(Prefab::from_pixels(&name, image.width() as usize, &pixels))
+                }
+                _ => None,
+            }
+        })
+        .collect();
+
+    prefabs.sort_by(|a, b| a.name.cmp(&b.name));
+
+    prefabs
+}
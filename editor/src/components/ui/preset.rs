@@ -0,0 +1,1143 @@
+use std::{cell::RefCell, mem::discriminant, ops::RangeInclusive, rc::Rc, sync::Arc};
+
+use egui::{
+    emath::Numeric, CollapsingHeader, Color32, Context, DragValue, Id, Slider, TextEdit, Ui,
+};
+use mapgen_core::{
+    config::{
+        BranchConfig, BudgetConfig, BudgetPolicy, ConfigIssue, ExportConfig, GenerationPreset,
+        KernelConfig, PostProcessConfig, Severity, StepPolicy, StuckPolicy, WalkerConfig,
+        WaypointConfig, WaypointJitter, WaypointOverride, ZoneConfig,
+    },
+    doors::DoorConfig,
+    freeze::FreezeConfig,
+    generator::{
+        roomgrid::RoomGridConfig,
+        tower::{TowerBand, TowerConfig},
+        GenerationMode,
+    },
+    map::{TargetVersion, TileMapping},
+    maze::MazeConfig,
+    migration,
+    modifiers::{ModifierEffect, ModifierTrigger, ScheduledModifier},
+    patterns::RewriteRule,
+    prefabs::{PlacementRule, PrefabPlacement},
+    regions::{SealedRegionConfig, SealedRegionMode},
+    rooms::{RoomConfig, RoomShape},
+    skips::SkipConfig,
+    spawn::{SpawnConfig, SpawnPickup},
+    spikes::{SpikeConfig, SpikeSurfaces},
+    wfc::WfcConfig,
+};
+use tracing::info;
+
+use crate::components::utils::generation::GenerationContext;
+
+use super::{
+    bottom_panel::field_numeric, context::RenderableUi, curve::curve_editor,
+    prefabs::prefab_preview,
+};
+
+/// draws the editable fields of a config
+pub trait ConfigUi {
+    fn ui(&mut self, ui: &mut Ui);
+}
+
+impl ConfigUi for WalkerConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("walker_config_grid").show(ui, |ui| {
+            field_numeric(ui, "ReachDistance", &mut self.reach_distance);
+            field_numeric(ui, "Randomness", &mut self.randomness);
+            field_numeric(
+                ui,
+                "UnhookableProbability",
+                &mut self.unhookable_probability,
+            );
+            field_numeric(
+                ui,
+                "HookthroughProbability",
+                &mut self.hookthrough_probability,
+            );
+            field_numeric(ui, "IslandProbability", &mut self.island_probability);
+            field_slider(ui, "MinStraightRun", &mut self.min_straight_run, 0..=50);
+            field_slider(
+                ui,
+                "MaxTurnFrequency",
+                &mut self.max_turn_frequency,
+                0.0..=1.0,
+            );
+            field_slider(
+                ui,
+                "BacktrackPenalty",
+                &mut self.backtrack_penalty,
+                0.0..=1.0,
+            );
+            field_numeric(ui, "BacktrackDecay", &mut self.backtrack_decay);
+
+            ui.label("StepPolicy");
+            egui::ComboBox::from_id_source("step_policy")
+                .selected_text(format!("{:?}", self.step_policy))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.step_policy, StepPolicy::Greedy, "Greedy");
+                    ui.selectable_value(&mut self.step_policy, StepPolicy::Guided, "Guided");
+                    ui.selectable_value(&mut self.step_policy, StepPolicy::Softmax, "Softmax");
+                });
+            ui.end_row();
+
+            field_numeric(ui, "GuideInterval", &mut self.guide_interval);
+            field_slider(ui, "GuideBias", &mut self.guide_bias, 0.0..=1.0);
+            field_numeric(ui, "Temperature", &mut self.temperature);
+            field_slider(ui, "OpennessBias", &mut self.openness_bias, -1.0..=1.0);
+            field_bool(ui, "AllowDiagonal", &mut self.allow_diagonal);
+            field_numeric(ui, "MaxClimb", &mut self.max_climb);
+            field_numeric(ui, "ClimbLedge", &mut self.climb_ledge);
+            field_numeric(ui, "StuckSteps", &mut self.stuck_steps);
+
+            ui.label("StuckPolicy");
+            egui::ComboBox::from_id_source("stuck_policy")
+                .selected_text(match self.stuck_policy {
+                    StuckPolicy::Greedy { .. } => "Greedy",
+                    StuckPolicy::Enlarge { .. } => "Enlarge",
+                    StuckPolicy::Tunnel => "Tunnel",
+                })
+                .show_ui(ui, |ui| {
+                    for (option, name) in [
+                        (StuckPolicy::default(), "Greedy"),
+                        (
+                            StuckPolicy::Enlarge {
+                                scale: 2.0,
+                                steps: 20,
+                            },
+                            "Enlarge",
+                        ),
+                        (StuckPolicy::Tunnel, "Tunnel"),
+                    ] {
+                        let selected = discriminant(&self.stuck_policy) == discriminant(&option);
+
+                        if ui.selectable_label(selected, name).clicked() && !selected {
+                            self.stuck_policy = option;
+                        }
+                    }
+                });
+            ui.end_row();
+
+            match &mut self.stuck_policy {
+                StuckPolicy::Greedy { steps } => field_numeric(ui, "EscapeSteps", steps),
+                StuckPolicy::Enlarge { scale, steps } => {
+                    field_numeric(ui, "EscapeScale", scale);
+                    field_numeric(ui, "EscapeSteps", steps);
+                }
+                StuckPolicy::Tunnel => {}
+            }
+        });
+
+        ui.label("Modifiers");
+
+        let mut removed = None;
+
+        for (i, modifier) in self.modifiers.iter_mut().enumerate() {
+            ui.push_id(("modifier", i), |ui| {
+                CollapsingHeader::new(format!("Modifier #{}", i)).show(ui, |ui| {
+                    scoped(ui, &format!("modifiers[{}]", i), |ui| modifier.ui(ui));
+
+                    if ui.button("Remove").clicked() {
+                        removed = Some(i);
+                    }
+                });
+            });
+        }
+
+        if let Some(i) = removed {
+            self.modifiers.remove(i);
+        }
+
+        if ui.button("Add modifier").clicked() {
+            self.modifiers.push(ScheduledModifier::default());
+        }
+
+        ui.label("KernelProfile")
+            .on_hover_text("brush scale over the route, from the spawn on the left to the finish");
+        curve_editor(ui, &mut self.kernel_profile);
+    }
+}
+
+impl ConfigUi for ScheduledModifier {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("scheduled_modifier_grid").show(ui, |ui| {
+            ui.label("Trigger");
+            egui::ComboBox::from_id_source("modifier_trigger")
+                .selected_text(match self.trigger {
+                    ModifierTrigger::Step(_) => "Step",
+                    ModifierTrigger::Distance(_) => "Distance",
+                    ModifierTrigger::Waypoint(_) => "Waypoint",
+                })
+                .show_ui(ui, |ui| {
+                    for (option, name) in [
+                        (ModifierTrigger::Step(0), "Step"),
+                        (ModifierTrigger::Distance(0.0), "Distance"),
+                        (ModifierTrigger::Waypoint(0), "Waypoint"),
+                    ] {
+                        let selected = discriminant(&self.trigger) == discriminant(&option);
+
+                        if ui.selectable_label(selected, name).clicked() && !selected {
+                            self.trigger = option;
+                        }
+                    }
+                });
+            ui.end_row();
+
+            match &mut self.trigger {
+                ModifierTrigger::Step(step) => field_numeric(ui, "AtStep", step),
+                ModifierTrigger::Distance(distance) => field_numeric(ui, "AtDistance", distance),
+                ModifierTrigger::Waypoint(waypoint) => field_numeric(ui, "AtWaypoint", waypoint),
+            }
+
+            field_numeric(ui, "Duration", &mut self.duration);
+
+            ui.label("Effect");
+            egui::ComboBox::from_id_source("modifier_effect")
+                .selected_text(match self.effect {
+                    ModifierEffect::Pulse { .. } => "Pulse",
+                    ModifierEffect::Fade { .. } => "Fade",
+                    ModifierEffect::Jitter { .. } => "Jitter",
+                    ModifierEffect::FreezeBurst { .. } => "FreezeBurst",
+                })
+                .show_ui(ui, |ui| {
+                    for (option, name) in [
+                        (ModifierEffect::default(), "Pulse"),
+                        (ModifierEffect::Fade { scale: 0.5 }, "Fade"),
+                        (ModifierEffect::Jitter { amount: 2 }, "Jitter"),
+                        (ModifierEffect::FreezeBurst { thickness: 1 }, "FreezeBurst"),
+                    ] {
+                        let selected = discriminant(&self.effect) == discriminant(&option);
+
+                        if ui.selectable_label(selected, name).clicked() && !selected {
+                            self.effect = option;
+                        }
+                    }
+                });
+            ui.end_row();
+
+            match &mut self.effect {
+                ModifierEffect::Pulse { scale, peak } => {
+                    field_numeric(ui, "Scale", scale);
+                    field_slider(ui, "Peak", peak, 0.0..=1.0);
+                }
+                ModifierEffect::Fade { scale } => field_numeric(ui, "Scale", scale),
+                ModifierEffect::Jitter { amount } => field_numeric(ui, "Amount", amount),
+                ModifierEffect::FreezeBurst { thickness } => {
+                    field_numeric(ui, "Thickness", thickness)
+                }
+            }
+        });
+    }
+}
+
+impl ConfigUi for KernelConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("kernel_config_grid").show(ui, |ui| {
+            field_numeric(ui, "Size", &mut self.size);
+            field_numeric(ui, "Circularity", &mut self.circularity);
+        });
+    }
+}
+
+impl ConfigUi for WaypointConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("waypoint_config_grid").show(ui, |ui| {
+            field_numeric(ui, "ScaleFactor", &mut self.scale_factor);
+            field_numeric(ui, "Margin", &mut self.margin);
+            field_bool(ui, "Looped", &mut self.looped);
+        });
+
+        field_override(ui, "Jitter", &mut self.jitter);
+        waypoints_ui(ui, "waypoints_grid", &mut self.waypoints);
+
+        ui.label("Branches");
+
+        let mut removed = None;
+
+        for (i, branch) in self.branches.iter_mut().enumerate() {
+            ui.push_id(("branch", i), |ui| {
+                CollapsingHeader::new(format!("Branch #{}", i)).show(ui, |ui| {
+                    scoped(ui, &format!("branches[{}]", i), |ui| branch.ui(ui));
+
+                    if ui.button("Remove").clicked() {
+                        removed = Some(i);
+                    }
+                });
+            });
+        }
+
+        if let Some(i) = removed {
+            self.branches.remove(i);
+        }
+
+        if ui.button("Add branch").clicked() {
+            self.branches.push(BranchConfig {
+                from: self.waypoints.len().saturating_sub(2),
+                ..Default::default()
+            });
+        }
+
+        ui.label("Overrides");
+
+        let mut removed = None;
+
+        for (i, local) in self.overrides.iter_mut().enumerate() {
+            ui.push_id(("override", i), |ui| {
+                CollapsingHeader::new(format!("Waypoint #{}", local.waypoint)).show(ui, |ui| {
+                    scoped(ui, &format!("overrides[{}]", i), |ui| local.ui(ui));
+
+                    if ui.button("Remove").clicked() {
+                        removed = Some(i);
+                    }
+                });
+            });
+        }
+
+        if let Some(i) = removed {
+            self.overrides.remove(i);
+        }
+
+        if ui.button("Add override").clicked() {
+            self.overrides.push(WaypointOverride::default());
+        }
+    }
+}
+
+impl ConfigUi for WaypointJitter {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("waypoint_jitter_grid").show(ui, |ui| {
+            field_numeric(ui, "Radius", &mut self.radius);
+            field_bool(ui, "KeepEndpoints", &mut self.keep_endpoints);
+        });
+    }
+}
+
+impl ConfigUi for WaypointOverride {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("waypoint_override_grid").show(ui, |ui| {
+            field_numeric(ui, "Waypoint", &mut self.waypoint);
+
+            let mut enabled = self.kernel_size.is_some();
+            field_label(ui, "KernelSize".to_string());
+            ui.horizontal(|ui| {
+                if ui.checkbox(&mut enabled, "").changed() {
+                    self.kernel_size = enabled.then_some((1, 1));
+                }
+
+                if let Some((min, max)) = &mut self.kernel_size {
+                    ui.add(DragValue::new(min));
+                    ui.add(DragValue::new(max));
+                }
+            });
+            ui.end_row();
+
+            field_option(ui, "MinStraightRun", &mut self.min_straight_run);
+            field_option(ui, "MaxTurnFrequency", &mut self.max_turn_frequency);
+            field_option(ui, "ClimbLedge", &mut self.climb_ledge);
+        });
+    }
+}
+
+impl ConfigUi for BranchConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("branch_config_grid").show(ui, |ui| {
+            field_numeric(ui, "From", &mut self.from);
+        });
+
+        waypoints_ui(ui, "branch_waypoints_grid", &mut self.waypoints);
+    }
+}
+
+fn waypoints_ui(ui: &mut Ui, id: &str, waypoints: &mut Vec<(f32, f32)>) {
+    let mut removed = None;
+
+    egui::Grid::new(id).show(ui, |ui| {
+        for (i, (x, y)) in waypoints.iter_mut().enumerate() {
+            ui.label(format!("#{}", i));
+            ui.add(DragValue::new(x).speed(0.01));
+            ui.add(DragValue::new(y).speed(0.01));
+
+            if ui.button("Remove").clicked() {
+                removed = Some(i);
+            }
+
+            ui.end_row();
+        }
+    });
+
+    if let Some(i) = removed {
+        waypoints.remove(i);
+    }
+
+    if ui.button("Add waypoint").clicked() {
+        let last = waypoints.last().copied().unwrap_or_default();
+        waypoints.push(last);
+    }
+}
+
+impl ConfigUi for ZoneConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("zone_config_grid").show(ui, |ui| {
+            field_numeric(ui, "FirstWaypoint", &mut self.waypoints.0);
+            field_numeric(ui, "LastWaypoint", &mut self.waypoints.1);
+        });
+
+        field_override(ui, "Walker", &mut self.walker);
+        field_override(ui, "Kernel", &mut self.kernel);
+        field_override(ui, "Freeze", &mut self.freeze);
+    }
+}
+
+impl ConfigUi for Vec<ZoneConfig> {
+    fn ui(&mut self, ui: &mut Ui) {
+        let mut removed = None;
+
+        for (i, zone) in self.iter_mut().enumerate() {
+            ui.push_id(i, |ui| {
+                CollapsingHeader::new(format!("Zone #{}", i)).show(ui, |ui| {
+                    scoped(ui, &format!("[{}]", i), |ui| zone.ui(ui));
+
+                    if ui.button("Remove").clicked() {
+                        removed = Some(i);
+                    }
+                });
+            });
+        }
+
+        if let Some(i) = removed {
+            self.remove(i);
+        }
+
+        if ui.button("Add zone").clicked() {
+            self.push(ZoneConfig::default());
+        }
+    }
+}
+
+impl ConfigUi for SpawnConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("spawn_config_grid").show(ui, |ui| {
+            field_numeric(ui, "NeutralSpawns", &mut self.neutral);
+            field_numeric(ui, "RedSpawns", &mut self.red);
+            field_numeric(ui, "BlueSpawns", &mut self.blue);
+            field_numeric(ui, "Width", &mut self.width);
+            field_numeric(ui, "Height", &mut self.height);
+            field_bool(ui, "Sign", &mut self.sign);
+        });
+
+        ui.label("Pickups");
+
+        let mut removed = None;
+
+        for (i, pickup) in self.pickups.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_source(("spawn_pickup", i))
+                    .selected_text(format!("{:?}", pickup))
+                    .show_ui(ui, |ui| {
+                        for option in [
+                            SpawnPickup::Armor,
+                            SpawnPickup::Health,
+                            SpawnPickup::Shotgun,
+                            SpawnPickup::Grenade,
+                            SpawnPickup::Ninja,
+                            SpawnPickup::Laser,
+                        ] {
+                            ui.selectable_value(pickup, option, format!("{:?}", option));
+                        }
+                    });
+
+                if ui.button("Remove").clicked() {
+                    removed = Some(i);
+                }
+            });
+        }
+
+        if let Some(i) = removed {
+            self.pickups.remove(i);
+        }
+
+        if ui.button("Add pickup").clicked() {
+            self.pickups.push(SpawnPickup::Shotgun);
+        }
+    }
+}
+
+impl ConfigUi for RoomConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("room_config_grid").show(ui, |ui| {
+            ui.label("Shape");
+            egui::ComboBox::from_id_source("room_shape")
+                .selected_text(format!("{:?}", self.shape))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.shape, RoomShape::Rectangle, "Rectangle");
+                    ui.selectable_value(&mut self.shape, RoomShape::Circle, "Circle");
+                    ui.selectable_value(&mut self.shape, RoomShape::Irregular, "Irregular");
+                });
+            ui.end_row();
+
+            field_numeric(ui, "Width", &mut self.width);
+            field_numeric(ui, "Height", &mut self.height);
+            field_numeric(ui, "Padding", &mut self.padding);
+            field_slider(ui, "Roughness", &mut self.roughness, 0.0..=1.0);
+            field_bool(ui, "Platform", &mut self.platform);
+            field_numeric(ui, "DoorWidth", &mut self.door_width);
+        });
+    }
+}
+
+impl ConfigUi for SkipConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("skip_config_grid").show(ui, |ui| {
+            field_numeric(ui, "MaxSkips", &mut self.max_skips);
+            field_numeric(ui, "MinLength", &mut self.min_length);
+            field_numeric(ui, "MaxLength", &mut self.max_length);
+            field_numeric(ui, "MinShortcut", &mut self.min_shortcut);
+            field_slider(ui, "Width", &mut self.width, 1..=3);
+            field_bool(ui, "Freeze", &mut self.freeze);
+            field_bool(ui, "Diagonal", &mut self.diagonal);
+        });
+    }
+}
+
+impl ConfigUi for DoorConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("door_config_grid").show(ui, |ui| {
+            field_slider(ui, "Probability", &mut self.probability, 0.0..=1.0);
+            field_numeric(ui, "ButtonDistance", &mut self.button_distance);
+            field_numeric(ui, "ButtonRadius", &mut self.button_radius);
+            field_numeric(ui, "Delay", &mut self.delay);
+        });
+    }
+}
+
+impl ConfigUi for RoomGridConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("room_grid_config_grid").show(ui, |ui| {
+            field_numeric(ui, "Columns", &mut self.columns);
+            field_numeric(ui, "Rows", &mut self.rows);
+            field_slider(ui, "RoomProbability", &mut self.room_probability, 0.0..=1.0);
+        });
+
+        ui.label("Room");
+        self.room.ui(ui);
+    }
+}
+
+impl ConfigUi for TowerConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("tower_config_grid").show(ui, |ui| {
+            field_numeric(ui, "Width", &mut self.width);
+            field_numeric(ui, "Height", &mut self.height);
+            field_numeric(ui, "Spacing", &mut self.spacing);
+            field_slider(ui, "Sway", &mut self.sway, 0.0..=1.0);
+            field_bool(ui, "Upwards", &mut self.upwards);
+        });
+
+        let mut removed = None;
+
+        for (i, band) in self.bands.iter_mut().enumerate() {
+            ui.push_id(i, |ui| {
+                CollapsingHeader::new(format!("Band #{}", i)).show(ui, |ui| {
+                    scoped(ui, &format!("bands[{}]", i), |ui| band.ui(ui));
+
+                    if ui.button("Remove").clicked() {
+                        removed = Some(i);
+                    }
+                });
+            });
+        }
+
+        if let Some(i) = removed {
+            self.bands.remove(i);
+        }
+
+        if ui.button("Add band").clicked() {
+            self.bands.push(TowerBand::default());
+        }
+    }
+}
+
+impl ConfigUi for TowerBand {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("tower_band_grid").show(ui, |ui| {
+            field_slider(ui, "Share", &mut self.share, 0.0..=1.0);
+        });
+
+        field_override(ui, "Walker", &mut self.walker);
+        field_override(ui, "Kernel", &mut self.kernel);
+    }
+}
+
+impl ConfigUi for FreezeConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("freeze_config_grid").show(ui, |ui| {
+            field_numeric(ui, "Thickness", &mut self.thickness);
+        });
+    }
+}
+
+impl ConfigUi for WfcConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("wfc_config_grid").show(ui, |ui| {
+            field_bool(ui, "Symmetry", &mut self.symmetry);
+        });
+
+        let mut removed = None;
+
+        for (i, example) in self.examples.iter_mut().enumerate() {
+            ui.push_id(("wfc_example", i), |ui| {
+                let mut text = example.join("\n");
+
+                if ui.code_editor(&mut text).changed() {
+                    *example = text.lines().map(str::to_string).collect();
+                }
+
+                if ui.button("Remove").clicked() {
+                    removed = Some(i);
+                }
+            });
+        }
+
+        if let Some(i) = removed {
+            self.examples.remove(i);
+        }
+
+        if ui.button("Add example").clicked() {
+            self.examples.push(vec!["#".repeat(8); 8]);
+        }
+    }
+}
+
+impl ConfigUi for SealedRegionConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("sealed_region_config_grid").show(ui, |ui| {
+            ui.label("Mode");
+            egui::ComboBox::from_id_source("sealed_region_mode")
+                .selected_text(format!("{:?}", self.mode))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.mode, SealedRegionMode::Fill, "Fill");
+                    ui.selectable_value(&mut self.mode, SealedRegionMode::Tunnel, "Tunnel");
+                });
+            ui.end_row();
+
+            field_numeric(ui, "MaxTunnelLength", &mut self.max_tunnel_length);
+        });
+    }
+}
+
+impl ConfigUi for MazeConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("maze_config_grid").show(ui, |ui| {
+            field_numeric(ui, "MinClearance", &mut self.min_clearance);
+            field_numeric(ui, "MinArea", &mut self.min_area);
+            field_numeric(ui, "CellSize", &mut self.cell_size);
+            field_numeric(ui, "WallThickness", &mut self.wall_thickness);
+            field_numeric(ui, "Entrances", &mut self.entrances);
+        });
+    }
+}
+
+impl ConfigUi for SpikeConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("spike_config_grid").show(ui, |ui| {
+            ui.label("Surfaces");
+            egui::ComboBox::from_id_source("spike_surfaces")
+                .selected_text(format!("{:?}", self.surfaces))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.surfaces, SpikeSurfaces::Ceilings, "Ceilings");
+                    ui.selectable_value(&mut self.surfaces, SpikeSurfaces::Floors, "Floors");
+                    ui.selectable_value(&mut self.surfaces, SpikeSurfaces::Both, "Both");
+                });
+            ui.end_row();
+
+            field_numeric(ui, "Chance", &mut self.chance);
+            field_numeric(ui, "MinLength", &mut self.min_length);
+            field_numeric(ui, "MaxLength", &mut self.max_length);
+        });
+    }
+}
+
+impl ConfigUi for PrefabPlacement {
+    fn ui(&mut self, ui: &mut Ui) {
+        prefab_preview(ui, &self.prefab);
+
+        egui::Grid::new("prefab_placement_grid").show(ui, |ui| {
+            ui.label("Rule");
+            egui::ComboBox::from_id_source("placement_rule")
+                .selected_text(format!("{:?}", self.rule))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.rule, PlacementRule::Route, "Route");
+                    ui.selectable_value(&mut self.rule, PlacementRule::OpenArea, "OpenArea");
+                });
+            ui.end_row();
+
+            field_numeric(ui, "Count", &mut self.count);
+            field_numeric(ui, "MinSpacing", &mut self.min_spacing);
+        });
+    }
+}
+
+impl ConfigUi for RewriteRule {
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.monospace(self.pattern.rows.join("\n"));
+        prefab_preview(ui, &self.replacement);
+
+        egui::Grid::new("rewrite_rule_grid").show(ui, |ui| {
+            field_bool(ui, "Rotate", &mut self.rotate);
+        });
+    }
+}
+
+impl ConfigUi for PostProcessConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Spawn");
+        self.spawn.ui(ui);
+
+        field_override(ui, "Rooms", &mut self.rooms);
+        field_override(ui, "Skips", &mut self.skips);
+        field_override(ui, "Doors", &mut self.doors);
+        field_override(ui, "Wfc", &mut self.wfc);
+        field_override(ui, "Maze", &mut self.maze);
+        field_override(ui, "Freeze", &mut self.freeze);
+        field_override(ui, "SealedRegions", &mut self.sealed_regions);
+        field_override(ui, "Spikes", &mut self.spikes);
+
+        egui::Grid::new("post_process_grid").show(ui, |ui| {
+            field_numeric(ui, "SmoothingIterations", &mut self.smoothing_iterations);
+            field_bool(ui, "FixEdgeBugs", &mut self.fix_edge_bugs);
+            field_numeric(ui, "Border", &mut self.border);
+            field_numeric(ui, "MinClearWidth", &mut self.min_clear_width);
+            field_bool(ui, "Finish", &mut self.finish);
+        });
+
+        ui.label("Prefabs");
+
+        let mut removed = None;
+
+        for (i, placement) in self.prefabs.iter_mut().enumerate() {
+            ui.push_id(i, |ui| {
+                CollapsingHeader::new(&placement.prefab.name).show(ui, |ui| {
+                    placement.ui(ui);
+
+                    if ui.button("Remove").clicked() {
+                        removed = Some(i);
+                    }
+                });
+            });
+        }
+
+        if let Some(i) = removed {
+            self.prefabs.remove(i);
+        }
+
+        ui.label("Rewrites");
+
+        let mut removed = None;
+
+        for (i, rule) in self.rewrites.iter_mut().enumerate() {
+            ui.push_id(("rewrite", i), |ui| {
+                CollapsingHeader::new(&rule.pattern.name).show(ui, |ui| {
+                    rule.ui(ui);
+
+                    if ui.button("Remove").clicked() {
+                        removed = Some(i);
+                    }
+                });
+            });
+        }
+
+        if let Some(i) = removed {
+            self.rewrites.remove(i);
+        }
+    }
+}
+
+impl ConfigUi for ExportConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("export_config_grid").show(ui, |ui| {
+            field_text(ui, "Name", &mut self.name);
+            field_text(ui, "Author", &mut self.author);
+            field_text(ui, "Version", &mut self.version);
+            field_text(ui, "Credits", &mut self.credits);
+            field_text(ui, "License", &mut self.license);
+            field_bool(ui, "StampMetadata", &mut self.stamp_metadata);
+            field_bool(ui, "Crop", &mut self.crop);
+            field_numeric(ui, "CropPadding", &mut self.crop_padding);
+            field_bool(ui, "FrontFreeze", &mut self.front_freeze);
+            field_numeric(ui, "MinWidth", &mut self.min_width);
+            field_numeric(ui, "MinHeight", &mut self.min_height);
+
+            ui.label("TargetVersion");
+            egui::ComboBox::from_id_source("target_version")
+                .selected_text(format!("{:?}", self.target_version))
+                .show_ui(ui, |ui| {
+                    let value = &mut self.target_version;
+
+                    ui.selectable_value(value, TargetVersion::DDNet06, "DDNet06");
+                    ui.selectable_value(value, TargetVersion::Teeworlds07, "Teeworlds07");
+                });
+            ui.end_row();
+        });
+
+        CollapsingHeader::new("TileMapping")
+            .id_source("tile_mapping")
+            .show(ui, |ui| self.tile_mapping.ui(ui));
+    }
+}
+
+impl ConfigUi for TileMapping {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("tile_mapping_grid").show(ui, |ui| {
+            field_numeric(ui, "Empty", &mut self.empty);
+            field_numeric(ui, "Hookable", &mut self.hookable);
+            field_numeric(ui, "Unhookable", &mut self.unhookable);
+            field_numeric(ui, "Hookthrough", &mut self.hookthrough);
+            field_numeric(ui, "Freeze", &mut self.freeze);
+            field_numeric(ui, "Start", &mut self.start);
+            field_numeric(ui, "Finish", &mut self.finish);
+            field_numeric(ui, "Death", &mut self.death);
+            field_numeric(ui, "Spawn", &mut self.spawns[0]);
+            field_numeric(ui, "SpawnRed", &mut self.spawns[1]);
+            field_numeric(ui, "SpawnBlue", &mut self.spawns[2]);
+        });
+
+        if ui.button("Reset").clicked() {
+            *self = TileMapping::default();
+        }
+    }
+}
+
+impl ConfigUi for BudgetConfig {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("budget_config_grid").show(ui, |ui| {
+            field_numeric(ui, "MaxGenerationTime", &mut self.max_generation_time);
+            field_numeric(ui, "MaxTotalSteps", &mut self.max_total_steps);
+
+            ui.label("Policy");
+            egui::ComboBox::from_id_source("budget_policy")
+                .selected_text(format!("{:?}", self.policy))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.policy, BudgetPolicy::Truncate, "Truncate");
+                    ui.selectable_value(&mut self.policy, BudgetPolicy::Fail, "Fail");
+                });
+            ui.end_row();
+        });
+    }
+}
+
+impl ConfigUi for GenerationPreset {
+    fn ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("preset_grid").show(ui, |ui| {
+            field_text(ui, "Name", &mut self.name);
+
+            ui.label("Mode");
+            egui::ComboBox::from_id_source("generation_mode")
+                .selected_text(format!("{:?}", self.mode))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.mode, GenerationMode::Solid, "Solid");
+                    ui.selectable_value(&mut self.mode, GenerationMode::Gores, "Gores");
+                });
+            ui.end_row();
+        });
+
+        CollapsingHeader::new("Walker")
+            .show(ui, |ui| scoped(ui, "walker", |ui| self.walker.ui(ui)));
+        CollapsingHeader::new("Kernel")
+            .show(ui, |ui| scoped(ui, "kernel", |ui| self.kernel.ui(ui)));
+        CollapsingHeader::new("Waypoints").show(ui, |ui| {
+            if ui
+                .button("Fix spacing")
+                .on_hover_text(
+                    "merges waypoints too close together and drops the ones the route turns back at",
+                )
+                .clicked()
+            {
+                let changes = self.fix_waypoint_spacing();
+                info!("waypoint spacing fixed with {} changes", changes);
+            }
+
+            scoped(ui, "waypoints", |ui| self.waypoints.ui(ui))
+        });
+        CollapsingHeader::new("RoomGrid").show(ui, |ui| {
+            field_override(ui, "Waypoints", &mut self.room_grid)
+        });
+        CollapsingHeader::new("Tower").show(ui, |ui| field_override(ui, "Tower", &mut self.tower));
+        CollapsingHeader::new("Zones").show(ui, |ui| scoped(ui, "zones", |ui| self.zones.ui(ui)));
+        CollapsingHeader::new("PostProcess").show(ui, |ui| {
+            scoped(ui, "post_process", |ui| self.post_process.ui(ui))
+        });
+        CollapsingHeader::new("Export")
+            .show(ui, |ui| scoped(ui, "export", |ui| self.export.ui(ui)));
+        CollapsingHeader::new("Budget")
+            .show(ui, |ui| scoped(ui, "budget", |ui| self.budget.ui(ui)));
+    }
+}
+
+pub struct PresetPanelUi {
+    generation: Rc<RefCell<GenerationContext>>,
+    raw: RawPreset,
+}
+
+impl PresetPanelUi {
+    pub fn new(generation: Rc<RefCell<GenerationContext>>) -> Self {
+        Self {
+            generation,
+            raw: RawPreset::default(),
+        }
+    }
+}
+
+/// json of the preset as editable text, edits update the fields once they parse and field
+/// changes replace the text
+#[derive(Default)]
+struct RawPreset {
+    text: String,
+    /// json of the preset the text was last in sync with
+    synced: String,
+    /// why the text doesn't parse
+    error: Option<String>,
+}
+
+impl RawPreset {
+    fn ui(&mut self, ui: &mut Ui, preset: &mut GenerationPreset) {
+        let json = raw_json(preset);
+
+        if json != self.synced {
+            self.text = json.clone();
+            self.synced = json;
+            self.error = None;
+        }
+
+        let response = ui.add(
+            TextEdit::multiline(&mut self.text)
+                .code_editor()
+                .desired_width(f32::INFINITY),
+        );
+
+        if response.changed() {
+            let parsed = serde_json::from_str(&self.text)
+                .map_err(|err| err.to_string())
+                .and_then(|value| migration::load_preset(value).map_err(|err| err.to_string()));
+
+            match parsed {
+                Ok(parsed) => {
+                    // the text keeps the user's formatting until the fields change it
+                    self.synced = raw_json(&parsed);
+                    self.error = None;
+                    *preset = parsed;
+                }
+                Err(err) => self.error = Some(err),
+            }
+        }
+
+        if let Some(err) = &self.error {
+            ui.colored_label(severity_color(Severity::Error), err);
+        }
+    }
+}
+
+fn raw_json(preset: &GenerationPreset) -> String {
+    serde_json::to_string_pretty(&migration::save_preset(preset)).unwrap_or_default()
+}
+
+impl RenderableUi for PresetPanelUi {
+    fn ui_with(&mut self, ctx: &Context) {
+        egui::panel::SidePanel::right("main_right_panel")
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.heading("Preset");
+                ui.separator();
+
+                let issues = self.generation.borrow().preset().validate();
+
+                for issue in issues.iter() {
+                    ui.colored_label(severity_color(issue.severity), issue.to_string());
+                }
+
+                if issues.iter().any(|issue| issue.severity == Severity::Error) {
+                    ui.label("Generation is blocked until the errors are fixed");
+                }
+
+                if !issues.is_empty() {
+                    ui.separator();
+                }
+
+                ui.data_mut(|data| {
+                    data.insert_temp(
+                        Id::new(FIELD_ISSUES),
+                        FieldIssues {
+                            prefix: String::new(),
+                            issues: Arc::new(issues),
+                        },
+                    )
+                });
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    let mut generation = self.generation.borrow_mut();
+                    let preset = generation.preset_mut();
+
+                    preset.ui(ui);
+                    CollapsingHeader::new("Json").show(ui, |ui| self.raw.ui(ui, preset));
+                });
+
+                ui.data_mut(|data| data.remove::<FieldIssues>(Id::new(FIELD_ISSUES)));
+            });
+    }
+}
+
+const FIELD_ISSUES: &str = "field_issues";
+
+/// validation issues of the preset drawn right now, the field helpers highlight the fields
+/// they are about
+#[derive(Clone, Default)]
+struct FieldIssues {
+    /// path of the config drawn right now, like `zones[0].walker`
+    prefix: String,
+    issues: Arc<Vec<ConfigIssue>>,
+}
+
+impl FieldIssues {
+    /// most severe issue of the field in the current config
+    fn find(&self, name: &str) -> Option<&ConfigIssue> {
+        let field = format!(".{}", snake_case(name));
+
+        self.issues
+            .iter()
+            .filter(|issue| issue.field.starts_with(&self.prefix) && issue.field.ends_with(&field))
+            .max_by_key(|issue| issue.severity)
+    }
+}
+
+/// draws a nested config, `segment` is appended to the path the fields are looked up with
+fn scoped<R>(ui: &mut Ui, segment: &str, add_contents: impl FnOnce(&mut Ui) -> R) -> R {
+    let id = Id::new(FIELD_ISSUES);
+    let previous = ui.data(|data| data.get_temp::<FieldIssues>(id));
+
+    if let Some(issues) = &previous {
+        let prefix = if issues.prefix.is_empty() || segment.starts_with('[') {
+            format!("{}{}", issues.prefix, segment)
+        } else {
+            format!("{}.{}", issues.prefix, segment)
+        };
+
+        ui.data_mut(|data| {
+            data.insert_temp(
+                id,
+                FieldIssues {
+                    prefix,
+                    ..issues.clone()
+                },
+            )
+        });
+    }
+
+    let result = add_contents(ui);
+
+    if let Some(issues) = previous {
+        ui.data_mut(|data| data.insert_temp(id, issues));
+    }
+
+    result
+}
+
+/// field name, colored with the issue as hover text when the validation found one
+pub fn field_label(ui: &mut Ui, name: String) {
+    let issues = ui.data(|data| data.get_temp::<FieldIssues>(Id::new(FIELD_ISSUES)));
+
+    match issues.as_ref().and_then(|issues| issues.find(&name)) {
+        Some(issue) => {
+            let mut hover = issue.message.clone();
+
+            if let Some(fix) = &issue.suggested_fix {
+                hover = format!("{}, {}", hover, fix);
+            }
+
+            ui.colored_label(severity_color(issue.severity), name)
+                .on_hover_text(hover);
+        }
+        None => {
+            ui.label(name);
+        }
+    }
+}
+
+fn severity_color(severity: Severity) -> Color32 {
+    match severity {
+        Severity::Warning => Color32::YELLOW,
+        Severity::Error => Color32::RED,
+    }
+}
+
+/// `ReachDistance` to `reach_distance`
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            snake.push('_');
+        }
+
+        snake.push(c.to_ascii_lowercase());
+    }
+
+    snake
+}
+
+fn field_text(ui: &mut Ui, name: impl Into<String>, value: &mut String) {
+    field_label(ui, name.into());
+    ui.text_edit_singleline(value);
+    ui.end_row();
+}
+
+fn field_bool(ui: &mut Ui, name: impl Into<String>, value: &mut bool) {
+    field_label(ui, name.into());
+    ui.checkbox(value, "");
+    ui.end_row();
+}
+
+/// editable optional config, unchecking it falls back to the preset's config
+/// checkbox enabling the value and the value itself
+fn field_option<T: Numeric + Default>(ui: &mut Ui, name: impl Into<String>, value: &mut Option<T>) {
+    let mut enabled = value.is_some();
+
+    field_label(ui, name.into());
+    ui.horizontal(|ui| {
+        if ui.checkbox(&mut enabled, "").changed() {
+            *value = enabled.then(T::default);
+        }
+
+        if let Some(value) = value {
+            ui.add(DragValue::new(value));
+        }
+    });
+    ui.end_row();
+}
+
+fn field_override<T: ConfigUi + Default>(ui: &mut Ui, name: &str, value: &mut Option<T>) {
+    let mut enabled = value.is_some();
+
+    if ui
+        .checkbox(&mut enabled, format!("Override {}", name))
+        .changed()
+    {
+        *value = enabled.then(T::default);
+    }
+
+    if let Some(value) = value {
+        ui.push_id(name, |ui| scoped(ui, &snake_case(name), |ui| value.ui(ui)));
+    }
+}
+
+fn field_slider<T: Numeric>(
+    ui: &mut Ui,
+    name: impl Into<String>,
+    value: &mut T,
+    range: RangeInclusive<T>,
+) {
+    field_label(ui, name.into());
+    ui.add(Slider::new(value, range));
+    ui.end_row();
+}
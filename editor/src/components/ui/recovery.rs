@@ -0,0 +1,81 @@
+use std::{cell::RefCell, rc::Rc, time::Instant};
+
+use egui::Context;
+use tracing::{error, info};
+
+use crate::{
+    autosave::{self, Autosave, AUTOSAVE_INTERVAL},
+    components::utils::generation::GenerationContext,
+};
+
+use super::context::RenderableUi;
+
+/// saves the sessions every few seconds and offers to restore the ones a crashed run left
+pub struct RecoveryUi {
+    generation: Rc<RefCell<GenerationContext>>,
+    /// autosave of a crashed run, nothing is saved until it's restored or discarded
+    pending: Option<Autosave>,
+    last_save: Instant,
+}
+
+impl RecoveryUi {
+    pub fn new(generation: Rc<RefCell<GenerationContext>>) -> Self {
+        Self {
+            generation,
+            pending: Autosave::load(),
+            last_save: Instant::now(),
+        }
+    }
+}
+
+impl RenderableUi for RecoveryUi {
+    fn ui_with(&mut self, ctx: &Context) {
+        let Some(pending) = &self.pending else {
+            if self.last_save.elapsed() >= AUTOSAVE_INTERVAL {
+                self.last_save = Instant::now();
+
+                if let Err(err) = self.generation.borrow().autosave().save() {
+                    error!(%err, "autosave failed");
+                }
+            }
+
+            return;
+        };
+
+        let mut restore = None;
+
+        egui::Window::new("Recover")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "The editor didn't close cleanly, restore its {} sessions?",
+                    pending.sessions.len()
+                ));
+
+                ui.horizontal(|ui| {
+                    if ui.button("Restore").clicked() {
+                        restore = Some(true);
+                    }
+
+                    if ui.button("Discard").clicked() {
+                        restore = Some(false);
+                    }
+                });
+            });
+
+        match restore {
+            Some(true) => {
+                let pending = self.pending.take().unwrap();
+                info!(sessions = pending.sessions.len(), "restored the autosave");
+
+                self.generation.borrow_mut().restore(pending);
+            }
+            Some(false) => {
+                self.pending = None;
+                autosave::clear();
+            }
+            None => {}
+        }
+    }
+}
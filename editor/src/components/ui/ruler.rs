@@ -0,0 +1,143 @@
+use std::{cell::RefCell, rc::Rc};
+
+use egui::{Align2, Color32, Context, DragValue, FontId, Id, LayerId, Order, Stroke};
+
+use crate::components::utils::generation::GenerationContext;
+
+use super::{context::RenderableUi, waypoints::to_screen};
+
+/// closest two grid lines get in points, the spacing is doubled while they're closer
+const MIN_LINE_GAP: f32 = 8.0;
+
+/// closest two axis labels get in points, lines in between stay unlabeled
+const MIN_LABEL_GAP: f32 = 40.0;
+
+const LINE_COLOR: Color32 = Color32::from_rgba_premultiplied(60, 60, 60, 60);
+
+/// grid over the previewed map with the tile coordinates along the top and left edges, and a
+/// status bar with the hovered tile, for pointing at places when reviewing a map
+pub struct RulerUi {
+    generation: Rc<RefCell<GenerationContext>>,
+    shown: bool,
+    /// tiles between two grid lines
+    spacing: usize,
+}
+
+impl RulerUi {
+    pub fn new(generation: Rc<RefCell<GenerationContext>>) -> Self {
+        Self {
+            generation,
+            shown: false,
+            spacing: 10,
+        }
+    }
+
+    fn draw_grid(&self, ctx: &Context) {
+        let generation = self.generation.borrow();
+
+        let (Some(view), Some([width, height])) = (generation.map_view(), generation.map_size())
+        else {
+            return;
+        };
+
+        let painter = ctx.layer_painter(LayerId::new(Order::Background, Id::new("ruler")));
+        let screen = ctx.screen_rect();
+        let tile_size = screen.width() / view.size[0];
+
+        if tile_size.is_nan() || tile_size <= 0.0 {
+            return;
+        }
+
+        let mut spacing = self.spacing.max(1);
+
+        while (spacing as f32) * tile_size < MIN_LINE_GAP {
+            spacing *= 2;
+        }
+
+        let label_every = (MIN_LABEL_GAP / (spacing as f32 * tile_size))
+            .ceil()
+            .max(1.0) as usize;
+        let top_left = to_screen(ctx, view, [0.0, 0.0]);
+        let bottom_right = to_screen(ctx, view, [width as f32, height as f32]);
+
+        // labels stick to the screen edge once the map edge is scrolled out of view
+        let label_y = top_left.y.max(screen.min.y);
+        let label_x = top_left.x.max(screen.min.x);
+
+        let label = |position, align, tile: usize| {
+            painter.text(
+                position,
+                align,
+                tile.to_string(),
+                FontId::monospace(11.0),
+                Color32::WHITE,
+            );
+        };
+
+        for (i, x) in (0..=width).step_by(spacing).enumerate() {
+            let screen_x = to_screen(ctx, view, [x as f32, 0.0]).x;
+
+            painter.vline(
+                screen_x,
+                top_left.y..=bottom_right.y,
+                Stroke::new(1.0, LINE_COLOR),
+            );
+
+            if i % label_every == 0 {
+                label(egui::pos2(screen_x + 2.0, label_y), Align2::LEFT_TOP, x);
+            }
+        }
+
+        for (i, y) in (0..=height).step_by(spacing).enumerate() {
+            let screen_y = to_screen(ctx, view, [0.0, y as f32]).y;
+
+            painter.hline(
+                top_left.x..=bottom_right.x,
+                screen_y,
+                Stroke::new(1.0, LINE_COLOR),
+            );
+
+            if i % label_every == 0 {
+                label(egui::pos2(label_x + 2.0, screen_y), Align2::LEFT_BOTTOM, y);
+            }
+        }
+    }
+}
+
+impl RenderableUi for RulerUi {
+    fn ui_with(&mut self, ctx: &Context) {
+        egui::panel::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let generation = self.generation.borrow();
+            let size = generation.map_size();
+            let hovered = generation
+                .hovered()
+                .filter(|&[x, y]| size.is_some_and(|[width, height]| x < width && y < height));
+
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.shown, "Ruler");
+                ui.add(
+                    DragValue::new(&mut self.spacing)
+                        .clamp_range(1..=1000)
+                        .suffix(" tiles"),
+                )
+                .on_hover_text("tiles between two grid lines");
+
+                ui.separator();
+
+                match hovered {
+                    Some([x, y]) => ui.monospace(format!("{}, {}", x, y)),
+                    None => ui.monospace("-"),
+                };
+
+                if let Some([width, height]) = size {
+                    ui.separator();
+                    ui.monospace(format!("{}x{}", width, height));
+                }
+            });
+        });
+
+        if self.shown {
+            self.draw_grid(ctx);
+        }
+    }
+}
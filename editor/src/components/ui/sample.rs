@@ -0,0 +1,160 @@
+use std::{
+    sync::mpsc::{channel, Receiver, TryRecvError},
+    thread,
+};
+
+use egui::{ColorImage, Image, Rect, Sense, TextureHandle, TextureOptions, Ui, Vec2};
+use mapgen_core::{
+    config::{GenerationPreset, WaypointConfig},
+    generator::Generator,
+    palette::Palette,
+    pipeline::GenerationPipeline,
+    random::Seed,
+};
+
+use crate::settings::BlockPalette;
+
+use super::gallery::thumbnail;
+
+/// side length of the sample map in tiles
+const SAMPLE_SIZE: usize = 64;
+
+const SAMPLE_MARGIN: usize = 8;
+
+/// same seed for every sample, so changes come from the settings alone
+const SAMPLE_SEED: Seed = 0;
+
+/// zigzag through the sample, turns in both directions
+const SAMPLE_ROUTE: [(f32, f32); 4] = [(0.0, 0.5), (0.33, 0.0), (0.66, 1.0), (1.0, 0.5)];
+
+/// side length the sample is drawn with in points
+const PREVIEW_SIZE: f32 = 192.0;
+
+/// small map generated with the walker, kernel and post processing of the preset, generated
+/// again in the background whenever the preset changes
+#[derive(Default)]
+pub struct SamplePreview {
+    /// preset of the shown or running sample
+    sampled: Option<GenerationPreset>,
+    receiver: Option<Receiver<Result<ColorImage, String>>>,
+    texture: Option<TextureHandle>,
+    error: Option<String>,
+}
+
+impl SamplePreview {
+    pub fn ui(&mut self, ui: &mut Ui, preset: &GenerationPreset) {
+        self.receive(ui);
+
+        if self.receiver.is_none() && self.sampled.as_ref() != Some(preset) {
+            self.start(preset.clone(), BlockPalette::of(ui.ctx()).palette());
+        }
+
+        if let Some(texture) = &self.texture {
+            sample_image(ui, texture);
+        }
+
+        if let Some(err) = &self.error {
+            ui.label(format!("Sample failed: {}", err));
+        }
+
+        if self.receiver.is_some() {
+            ui.spinner();
+        }
+    }
+
+    fn start(&mut self, preset: GenerationPreset, palette: Palette) {
+        let (sender, receiver) = channel();
+        let sample = sample_preset(&preset);
+
+        thread::spawn(move || {
+            let mut generator = Generator::from_preset(sample);
+            let image = GenerationPipeline::default()
+                .run(&mut generator, SAMPLE_SEED, |_| {})
+                .map(|map| thumbnail(&map, palette))
+                .map_err(|err| err.to_string());
+
+            let _ = sender.send(image);
+        });
+
+        self.sampled = Some(preset);
+        self.receiver = Some(receiver);
+    }
+
+    fn receive(&mut self, ui: &Ui) {
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err("the generation panicked".to_owned()),
+        };
+
+        match result {
+            Ok(image) => {
+                let texture = ui
+                    .ctx()
+                    .load_texture("sample", image, TextureOptions::NEAREST);
+
+                self.texture = Some(texture);
+                self.error = None;
+            }
+            Err(err) => self.error = Some(err),
+        }
+
+        self.receiver = None;
+    }
+}
+
+/// `preset` with its route replaced by a fixed one through a `SAMPLE_SIZE` map
+fn sample_preset(preset: &GenerationPreset) -> GenerationPreset {
+    let mut sample = preset.clone();
+
+    sample.waypoints = WaypointConfig {
+        waypoints: SAMPLE_ROUTE.to_vec(),
+        scale_factor: (SAMPLE_SIZE - 2 * SAMPLE_MARGIN) as f32,
+        margin: SAMPLE_MARGIN,
+        ..Default::default()
+    };
+    // all refer to a route the sample doesn't have
+    sample.room_grid = None;
+    sample.tower = None;
+    sample.zones.clear();
+    sample.guidance_mask = None;
+
+    sample
+}
+
+/// draws the sample into a `PREVIEW_SIZE` square without stretching it, hovering shows the tile
+/// under the pointer
+fn sample_image(ui: &mut Ui, texture: &TextureHandle) {
+    let (response, painter) = ui.allocate_painter(Vec2::splat(PREVIEW_SIZE), Sense::hover());
+    let size = texture.size_vec2();
+    let rect = letterbox(response.rect, size);
+
+    painter.rect_filled(response.rect, 0.0, ui.visuals().extreme_bg_color);
+    Image::new(texture).paint_at(ui, rect);
+
+    let hovered = response
+        .hover_pos()
+        .filter(|&position| rect.contains(position))
+        .map(|position| ((position - rect.min) / rect.size() * size).min(size - Vec2::splat(1.0)));
+
+    if let Some(tile) = hovered {
+        let [x, y] = [tile.x as usize, tile.y as usize];
+
+        response.on_hover_text_at_pointer(format!("{}, {}", x, y));
+    }
+}
+
+/// largest rect with the aspect ratio of `size` centered in `outer`
+fn letterbox(outer: Rect, size: Vec2) -> Rect {
+    if size.x <= 0.0 || size.y <= 0.0 {
+        return outer;
+    }
+
+    let scale = (outer.width() / size.x).min(outer.height() / size.y);
+
+    Rect::from_center_size(outer.center(), size * scale)
+}
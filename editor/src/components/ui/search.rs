@@ -0,0 +1,234 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::mpsc::{channel, Receiver},
+    thread,
+};
+
+use egui::{
+    CollapsingHeader, ColorImage, Context, DragValue, Image, Slider, TextureHandle, TextureOptions,
+};
+use mapgen_core::{
+    config::GenerationPreset,
+    generator::GenerationError,
+    palette::Palette,
+    random::{random_seed, Random},
+    tuning::{MutationConfig, TunableField},
+};
+
+use crate::{components::utils::generation::GenerationContext, settings::BlockPalette};
+
+use super::{
+    context::RenderableUi,
+    gallery::{generate, thumbnail},
+};
+
+struct Candidate {
+    id: usize,
+    preset: GenerationPreset,
+    texture: Option<TextureHandle>,
+    failed: Option<GenerationError>,
+}
+
+/// interactive evolutionary search, mutates the current preset into candidates and previews
+/// them, an accepted candidate becomes the preset the next round mutates
+pub struct MutationSearchUi {
+    generation: Rc<RefCell<GenerationContext>>,
+    config: MutationConfig,
+    count: usize,
+    prng: Random,
+    candidates: Vec<Candidate>,
+    /// previews of the current round, replacing it stops the previous round
+    receiver: Option<Receiver<(usize, Result<ColorImage, GenerationError>)>>,
+    next_id: usize,
+}
+
+impl MutationSearchUi {
+    pub fn new(generation: Rc<RefCell<GenerationContext>>) -> Self {
+        Self {
+            generation,
+            config: MutationConfig::default(),
+            count: 6,
+            prng: Random::new(random_seed()),
+            candidates: Vec::new(),
+            receiver: None,
+            next_id: 0,
+        }
+    }
+
+    fn mutate(&mut self, palette: Palette) {
+        let (parent, seed) = {
+            let generation = self.generation.borrow();
+            (generation.preset().clone(), generation.seed())
+        };
+
+        self.candidates = (0..self.count)
+            .map(|i| Candidate {
+                id: self.next_id + i,
+                preset: self.config.mutate(&parent, &mut self.prng),
+                texture: None,
+                failed: None,
+            })
+            .collect();
+        self.next_id += self.count;
+
+        let (sender, receiver) = channel();
+        let presets: Vec<(usize, GenerationPreset)> = self
+            .candidates
+            .iter()
+            .map(|candidate| (candidate.id, candidate.preset.clone()))
+            .collect();
+
+        thread::spawn(move || {
+            for (id, preset) in presets {
+                let image = generate(preset, seed).map(|map| thumbnail(&map, palette));
+
+                if sender.send((id, image)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        self.receiver = Some(receiver);
+    }
+
+    fn receive(&mut self, ctx: &Context) {
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+
+        for (id, image) in receiver.try_iter() {
+            let Some(candidate) = self.candidates.iter_mut().find(|c| c.id == id) else {
+                continue;
+            };
+
+            let image = match image {
+                Ok(image) => image,
+                Err(err) => {
+                    candidate.failed = Some(err);
+                    continue;
+                }
+            };
+
+            let name = format!("mutation_{}", id);
+            candidate.texture = Some(ctx.load_texture(name, image, TextureOptions::NEAREST));
+        }
+    }
+
+    fn accept(&mut self, index: usize) {
+        let candidate = self.candidates.remove(index);
+        let mut generation = self.generation.borrow_mut();
+
+        *generation.preset_mut() = candidate.preset;
+        generation.request_generation();
+
+        self.candidates.clear();
+        self.receiver = None;
+    }
+}
+
+impl RenderableUi for MutationSearchUi {
+    fn ui_with(&mut self, ctx: &Context) {
+        self.receive(ctx);
+
+        egui::Window::new("Mutation search")
+            .resizable(true)
+            .vscroll(true)
+            .default_open(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Count");
+                    ui.add(DragValue::new(&mut self.count).clamp_range(1..=32));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Probability");
+                    ui.add(Slider::new(&mut self.config.probability, 0.0..=1.0));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Strength");
+                    ui.add(Slider::new(&mut self.config.strength, 0.0..=1.0));
+                });
+
+                CollapsingHeader::new("Fields").show(ui, |ui| {
+                    for field in TunableField::ALL {
+                        let mut enabled = self.config.fields.contains(&field);
+
+                        if ui.checkbox(&mut enabled, field.name()).changed() {
+                            if enabled {
+                                self.config.fields.push(field);
+                            } else {
+                                self.config.fields.retain(|&f| f != field);
+                            }
+                        }
+                    }
+                });
+
+                if ui.button("Mutate").clicked() {
+                    self.mutate(BlockPalette::of(ctx).palette());
+                }
+
+                ui.separator();
+
+                let parent = self.generation.borrow().preset().clone();
+                let mut accepted = None;
+                let mut rejected = None;
+
+                ui.horizontal_wrapped(|ui| {
+                    for (i, candidate) in self.candidates.iter().enumerate() {
+                        ui.vertical(|ui| {
+                            let response = match (&candidate.texture, candidate.failed) {
+                                (Some(texture), _) => ui.add(Image::new(texture)),
+                                (None, Some(err)) => {
+                                    ui.colored_label(ui.visuals().warn_fg_color, err.to_string())
+                                }
+                                (None, None) => ui.spinner(),
+                            };
+
+                            response.on_hover_text(changes(&parent, &candidate.preset));
+
+                            ui.horizontal(|ui| {
+                                if ui.button("Accept").clicked() {
+                                    accepted = Some(i);
+                                }
+
+                                if ui.button("Reject").clicked() {
+                                    rejected = Some(i);
+                                }
+                            });
+                        });
+                    }
+                });
+
+                if let Some(i) = accepted {
+                    self.accept(i);
+                } else if let Some(i) = rejected {
+                    self.candidates.remove(i);
+                }
+            });
+
+        if self
+            .candidates
+            .iter()
+            .any(|c| c.texture.is_none() && c.failed.is_none())
+        {
+            ctx.request_repaint();
+        }
+    }
+}
+
+/// tunable fields the candidate changed, one per line
+fn changes(parent: &GenerationPreset, candidate: &GenerationPreset) -> String {
+    TunableField::ALL
+        .iter()
+        .filter(|field| field.get(parent) != field.get(candidate))
+        .map(|field| {
+            format!(
+                "{}: {} -> {}",
+                field.name(),
+                field.get(parent),
+                field.get(candidate)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,62 @@
+use std::{cell::RefCell, rc::Rc};
+
+use egui::{Context, DragValue};
+
+use crate::components::utils::generation::GenerationContext;
+
+use super::context::RenderableUi;
+
+/// tabs switching between the generation sessions
+pub struct SessionTabsUi {
+    generation: Rc<RefCell<GenerationContext>>,
+}
+
+impl SessionTabsUi {
+    pub fn new(generation: Rc<RefCell<GenerationContext>>) -> Self {
+        Self { generation }
+    }
+}
+
+impl RenderableUi for SessionTabsUi {
+    fn ui_with(&mut self, ctx: &Context) {
+        egui::panel::TopBottomPanel::top("session_tabs").show(ctx, |ui| {
+            let mut generation = self.generation.borrow_mut();
+
+            ui.horizontal(|ui| {
+                let active = generation.active();
+                let mut selected = active;
+
+                for (i, session) in generation.sessions().iter().enumerate() {
+                    ui.selectable_value(&mut selected, i, &session.name);
+                }
+
+                generation.switch_to(selected);
+
+                ui.separator();
+
+                if ui.button("Duplicate").clicked() {
+                    generation.duplicate_session();
+                }
+
+                if ui.button("Close").clicked() {
+                    generation.close_session();
+                }
+
+                ui.separator();
+
+                let mut seed = generation.seed();
+
+                ui.label("Seed");
+                if ui.add(DragValue::new(&mut seed)).changed() {
+                    generation.set_seed(seed);
+                }
+
+                let mut compare = generation.compare();
+
+                if ui.checkbox(&mut compare, "Compare").changed() {
+                    generation.set_compare(compare);
+                }
+            });
+        });
+    }
+}
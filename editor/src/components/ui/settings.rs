@@ -0,0 +1,157 @@
+use std::{cell::RefCell, rc::Rc};
+
+use egui::{Context, Sense, Slider, Vec2};
+use mapgen_core::map::BlockType;
+use tracing::error;
+
+use crate::settings::{from_color32, to_color32, BlockPalette, Settings, Theme};
+
+use super::context::RenderableUi;
+
+const BLOCKS: [BlockType; 8] = [
+    BlockType::Empty,
+    BlockType::Hookable,
+    BlockType::Unhookable,
+    BlockType::Hookthrough,
+    BlockType::Freeze,
+    BlockType::Start,
+    BlockType::Finish,
+    BlockType::Death,
+];
+
+/// ui scale, theme and block palette, changes are saved right away
+pub struct SettingsUi {
+    settings: Rc<RefCell<Settings>>,
+    /// scale the slider is at, only applied on request since it moves the slider itself
+    scale: f32,
+    /// theme the egui visuals were last set to
+    applied_theme: Option<Theme>,
+}
+
+impl SettingsUi {
+    pub fn new(settings: Rc<RefCell<Settings>>) -> Self {
+        let scale = settings.borrow().ui_scale;
+
+        Self {
+            settings,
+            scale,
+            applied_theme: None,
+        }
+    }
+
+    fn save(&self) {
+        if let Err(err) = self.settings.borrow().save() {
+            error!(%err, "saving the settings failed");
+        }
+    }
+}
+
+impl RenderableUi for SettingsUi {
+    fn ui_with(&mut self, ctx: &Context) {
+        let (theme, mut palette) = {
+            let settings = self.settings.borrow();
+            (settings.theme, settings.palette)
+        };
+
+        if self.applied_theme != Some(theme) {
+            ctx.set_visuals(theme.visuals());
+            self.applied_theme = Some(theme);
+        }
+
+        palette.install(ctx);
+
+        let mut changed = false;
+
+        egui::Window::new("Settings")
+            .resizable(false)
+            .default_open(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("settings_grid").show(ui, |ui| {
+                    ui.label("UiScale");
+                    ui.horizontal(|ui| {
+                        ui.add(Slider::new(&mut self.scale, 0.5..=3.0));
+
+                        if ui.button("Apply").clicked() {
+                            self.settings.borrow_mut().ui_scale = self.scale;
+                            changed = true;
+                        }
+
+                        let native = ctx.input(|input| input.viewport().native_pixels_per_point);
+
+                        if let Some(native) = native {
+                            if ui.button("Match display").clicked() {
+                                self.scale = native;
+                                self.settings.borrow_mut().ui_scale = native;
+                                changed = true;
+                            }
+                        }
+                    });
+                    ui.end_row();
+
+                    let mut theme = theme;
+
+                    ui.label("Theme");
+                    egui::ComboBox::from_id_source("settings_theme")
+                        .selected_text(format!("{:?}", theme))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut theme, Theme::Dark, "Dark");
+                            ui.selectable_value(&mut theme, Theme::Light, "Light");
+                        });
+                    ui.end_row();
+
+                    if theme != self.settings.borrow().theme {
+                        self.settings.borrow_mut().theme = theme;
+                        changed = true;
+                    }
+
+                    ui.label("BlockPalette");
+                    egui::ComboBox::from_id_source("settings_palette")
+                        .selected_text(palette.name())
+                        .show_ui(ui, |ui| {
+                            for option in BlockPalette::PRESETS {
+                                ui.selectable_value(&mut palette, option, option.name());
+                            }
+
+                            let custom = matches!(palette, BlockPalette::Custom(_));
+
+                            if ui.selectable_label(custom, "Custom").clicked() && !custom {
+                                // starts out as a copy of the preset picked before
+                                palette = BlockPalette::Custom(palette.palette());
+                            }
+                        });
+                    ui.end_row();
+                });
+
+                ui.separator();
+
+                for block in BLOCKS {
+                    ui.horizontal(|ui| {
+                        if let BlockPalette::Custom(custom) = &mut palette {
+                            let color = custom.color_mut(block);
+                            let mut edited = to_color32(*color);
+
+                            if ui.color_edit_button_srgba(&mut edited).changed() {
+                                *color = from_color32(edited);
+                            }
+                        } else {
+                            let (response, painter) =
+                                ui.allocate_painter(Vec2::splat(16.0), Sense::hover());
+
+                            painter.rect_filled(response.rect, 0.0, palette.color(block));
+                        }
+
+                        ui.label(format!("{:?}", block));
+                    });
+                }
+
+                if palette != self.settings.borrow().palette {
+                    self.settings.borrow_mut().palette = palette;
+                    changed = true;
+                }
+            });
+
+        if changed {
+            self.save();
+        }
+    }
+}
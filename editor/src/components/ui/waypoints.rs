@@ -0,0 +1,146 @@
+use std::{cell::RefCell, rc::Rc};
+
+use egui::{Align2, Color32, Context, FontId, Id, LayerId, Order, Painter, Pos2, Shape, Stroke};
+use mapgen_core::generator::placement::Endpoint;
+
+use crate::components::utils::generation::{GenerationContext, MapView};
+
+use super::context::RenderableUi;
+
+const HANDLE_RADIUS: f32 = 6.0;
+
+/// length of the arrow heads on the planned route in points
+const ARROW_SIZE: f32 = 10.0;
+
+const PLANNED_COLOR: Color32 = Color32::from_rgb(255, 220, 60);
+
+const WALKED_COLOR: Color32 = Color32::from_rgb(60, 180, 255);
+
+/// draws the planned route with its direction and the path the walker took over the map, the
+/// waypoints get handles with their indices while they're edited and the hovered tile shows
+/// whether the spawn or finish fits there while placing one
+pub struct RouteOverlayUi {
+    generation: Rc<RefCell<GenerationContext>>,
+}
+
+impl RouteOverlayUi {
+    pub fn new(generation: Rc<RefCell<GenerationContext>>) -> Self {
+        Self { generation }
+    }
+}
+
+impl RenderableUi for RouteOverlayUi {
+    fn ui_with(&mut self, ctx: &Context) {
+        let generation = self.generation.borrow();
+
+        let Some(view) = generation.map_view() else {
+            return;
+        };
+
+        let painter = ctx.layer_painter(LayerId::new(Order::Background, Id::new("waypoints")));
+
+        if let (Some(endpoint), Some(tile)) = (generation.endpoint_tool(), generation.hovered()) {
+            let tile = [tile[0] as f32, tile[1] as f32];
+            let point = to_screen(ctx, view, tile);
+
+            placement_feedback(&painter, point, endpoint, &generation, tile);
+        }
+
+        let editing = generation.waypoint_editing();
+
+        if !editing && !generation.route_overlay() {
+            return;
+        }
+
+        if generation.route_overlay() {
+            let walked = on_screen(ctx, view, generation.walked_tiles());
+
+            painter.add(Shape::line(walked, Stroke::new(1.5, WALKED_COLOR)));
+        }
+
+        let points = on_screen(ctx, view, generation.waypoint_tiles());
+        let line = Stroke::new(2.0, PLANNED_COLOR);
+
+        for pair in points.windows(2) {
+            painter.line_segment([pair[0], pair[1]], line);
+
+            // arrow head halfway along the segment, pointing to the next waypoint
+            let direction = (pair[1] - pair[0]).normalized();
+            let tip = pair[0] + (pair[1] - pair[0]) / 2.0;
+            let back = tip - direction * ARROW_SIZE;
+            let side = direction.rot90() * ARROW_SIZE / 2.0;
+
+            painter.add(Shape::convex_polygon(
+                vec![tip, back + side, back - side],
+                PLANNED_COLOR,
+                Stroke::NONE,
+            ));
+        }
+
+        if !editing {
+            return;
+        }
+
+        for (i, &point) in points.iter().enumerate() {
+            painter.circle(
+                point,
+                HANDLE_RADIUS,
+                Color32::YELLOW,
+                Stroke::new(1.0, Color32::BLACK),
+            );
+            painter.text(
+                point + egui::vec2(HANDLE_RADIUS, -HANDLE_RADIUS),
+                Align2::LEFT_BOTTOM,
+                i.to_string(),
+                FontId::monospace(14.0),
+                Color32::WHITE,
+            );
+        }
+    }
+}
+
+/// marks the hovered tile green where the endpoint can go, red with the reasons where it can't
+fn placement_feedback(
+    painter: &Painter,
+    point: Pos2,
+    endpoint: Endpoint,
+    generation: &GenerationContext,
+    tile: [f32; 2],
+) {
+    let issues = generation.placement_issues(endpoint, tile);
+    let color = if issues.is_empty() {
+        Color32::GREEN
+    } else {
+        Color32::RED
+    };
+
+    painter.circle_stroke(point, HANDLE_RADIUS, Stroke::new(2.0, color));
+
+    let text = std::iter::once(format!("{:?}", endpoint))
+        .chain(issues.iter().map(|issue| issue.to_string()))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    painter.text(
+        point + egui::vec2(HANDLE_RADIUS * 2.0, 0.0),
+        Align2::LEFT_CENTER,
+        text,
+        FontId::proportional(14.0),
+        color,
+    );
+}
+
+/// screen position of a map tile, the map fills the whole window
+pub fn to_screen(ctx: &Context, view: MapView, tile: [f32; 2]) -> Pos2 {
+    let screen = ctx.screen_rect();
+    let [x, y] = view.relative(tile);
+
+    screen.min + screen.size() * egui::vec2(x, y)
+}
+
+fn on_screen(ctx: &Context, view: MapView, tiles: Vec<[f32; 2]>) -> Vec<Pos2> {
+    tiles
+        .into_iter()
+        .map(|tile| to_screen(ctx, view, tile))
+        .collect()
+}
@@ -5,17 +5,39 @@ use std::{
 
 use egui_snarl::{InPinId, NodeId, Snarl};
 use mapgen_core::{
+    branding::Branding,
     brush::Brush,
-    generator::Generator,
-    map::Map,
+    config::{GenerationPreset, Severity},
+    debug::{DebugColor, DebugLayer, DebugLayerData, DebugLayers},
+    diff::MapDiff,
+    distance::CHUNK_SIZE,
+    generator::{
+        placement::{Endpoint, PlacementIssue},
+        Canvas, GenerationSteps, Generator, StepResult,
+    },
+    guidance::Guidance,
+    map::{BlockType, Map, PastePolicy},
     mutations::{walker::straight::StraightWalkerMutation, MutationState, Mutator},
+    pipeline::GenerationPipeline,
+    position::{as_index, Vector2},
+    random::Seed,
+    spawn::SpawnTeam,
+    transform::Transform,
     walker::Walker,
 };
-use twmap::{GameLayer, Group, Image, Tile, TileFlags, TilesLayer, TwMap};
+use ndarray::{s, Array2};
+use tracing::{debug, error, info, trace};
+use twmap::{
+    CompressedData, GameLayer, GameTile, Group, Image, Layer, Tile, TileFlags, TilesLayer, TwMap,
+};
+use vek::Rgba;
 
-use crate::components::{
-    map::load_image,
-    ui::bottom_panel::{ExtractMutation, Titled, UiMutation, UiNode},
+use crate::{
+    autosave::{Autosave, SavedSession},
+    components::{
+        map::load_image,
+        ui::bottom_panel::{ExtractMutation, Titled, UiMutation, UiNode},
+    },
 };
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -35,6 +57,7 @@ impl DesignLayer {
     }
 }
 
+#[derive(Clone)]
 pub struct DesignImageInfo {
     path: PathBuf,
     automapper_rule: usize,
@@ -49,6 +72,7 @@ impl DesignImageInfo {
     }
 }
 
+#[derive(Clone)]
 pub struct DesignInfo {
     image_infos: HashMap<DesignLayer, DesignImageInfo>,
 }
@@ -64,19 +88,446 @@ struct Loop<T> {
     mutations: Vec<T>,
 }
 
-pub struct GenerationContext {
+/// empty tiles between the maps of the compare view
+const COMPARE_GAP: usize = 16;
+
+/// generation advanced a few steps per frame, the preview follows the edited chunks
+struct LiveGeneration {
+    steps: GenerationSteps,
+    zone_boundaries: Vec<Vector2>,
+    walked: Vec<[f32; 2]>,
+    events: EventRecorder,
+    design: DesignInfo,
+    /// game tiles of the last queued preview
+    shown: Array2<GameTile>,
+}
+
+/// independent generation with its own preset and seed, shown in its own tab
+pub struct Session {
+    pub name: String,
+    /// placeholder while `live` runs, the live generation owns the real one
     generator: Generator,
-    current_map: Option<TwMap>,
+    live: Option<LiveGeneration>,
+    /// last generated map, shown again when switching back to the session
+    map: Option<TwMap>,
+    /// walker positions of the last generation, before the map was cropped
+    walked: Vec<[f32; 2]>,
+    /// notable steps of the last generation
+    events: Vec<GenerationEvent>,
+    /// design of the last generated map, applied again when undoing
+    design: Option<DesignInfo>,
+    /// inclusive tile area selected in the preview
+    selection: Option<[[usize; 2]; 2]>,
+    /// diffs turning the game layer of `map` back into the earlier maps, latest last
+    undo: Vec<MapDiff>,
+    redo: Vec<MapDiff>,
+}
+
+impl Session {
+    fn new(name: String, generator: Generator) -> Self {
+        Self {
+            name,
+            generator,
+            live: None,
+            map: None,
+            walked: Vec::new(),
+            events: Vec::new(),
+            design: None,
+            selection: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// remembers how to get back to the current map before `map` replaces it
+    fn record(&mut self, map: &TwMap) {
+        let Some(current) = &self.map else {
+            return;
+        };
+
+        let diff = MapDiff::between_tiles(game_tiles(map), game_tiles(current));
+
+        if diff.is_empty() {
+            return;
+        }
+
+        if self.undo.len() == HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+
+        self.undo.push(diff);
+        self.redo.clear();
+    }
+}
+
+/// maps a session can be undone to
+const HISTORY_LIMIT: usize = 64;
+
+/// notable step of a generation, listed in the event log
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerationEvent {
+    /// walker steps taken before it
+    pub step: usize,
+    pub text: String,
+    /// tile it happened at, before the map was cropped
+    pub position: Option<[f32; 2]>,
+}
+
+/// turns the step results of a generation into events, walker moves only advance the step
+#[derive(Default)]
+struct EventRecorder {
+    step: usize,
+    /// walker position after the last step
+    walker: Option<[f32; 2]>,
+    events: Vec<GenerationEvent>,
+}
+
+impl EventRecorder {
+    fn record(&mut self, result: &StepResult) {
+        let at = |position: &Vector2| Some([position[0], position[1]]);
+
+        let (text, position) = match result {
+            StepResult::WalkerMoved { step, position } => {
+                self.step = *step;
+                self.walker = at(position);
+                return;
+            }
+            StepResult::WaypointReached { waypoint } => {
+                (format!("waypoint {} reached", waypoint), self.walker)
+            }
+            StepResult::BranchStarted { branch, position } => {
+                (format!("branch {} started", branch), at(position))
+            }
+            StepResult::ZoneEntered { zone, position } => match zone {
+                Some(zone) => (format!("zone {} entered", zone), at(position)),
+                None => ("zones left".to_owned(), at(position)),
+            },
+            StepResult::KernelResized { size, position } => (
+                format!("kernel resized to {}x{}", size.0, size.1),
+                at(position),
+            ),
+            StepResult::WalkerStuck { waypoint, position } => (
+                format!("stuck heading to waypoint {}", waypoint),
+                at(position),
+            ),
+            StepResult::RoomPlaced { position } => ("room placed".to_owned(), at(position)),
+            StepResult::SkipCarved { start, .. } => ("skip carved".to_owned(), at(start)),
+            StepResult::DoorPlaced { number, door, .. } => {
+                (format!("door {} placed", number), at(door))
+            }
+            StepResult::PrefabPlaced { name, position } => {
+                (format!("prefab {} placed", name), at(position))
+            }
+            StepResult::SpawnPlaced { position, .. } => ("spawn placed".to_owned(), at(position)),
+            StepResult::MazeBuilt { position, cells } => {
+                (format!("maze of {} cells built", cells), at(position))
+            }
+            StepResult::FinishPlaced { position } => ("finish placed".to_owned(), at(position)),
+            StepResult::RegionSealed {
+                position,
+                size,
+                tunneled,
+            } => {
+                let action = if *tunneled { "tunneled to" } else { "filled" };
+
+                (format!("region of {} tiles {}", size, action), at(position))
+            }
+            StepResult::SpikesPlaced { position, length } => {
+                (format!("{} spikes placed", length), at(position))
+            }
+            StepResult::Finished(_) => ("finished".to_owned(), None),
+        };
+
+        self.events.push(GenerationEvent {
+            step: self.step,
+            text,
+            position,
+        });
+    }
+}
+
+pub struct GenerationContext {
+    sessions: Vec<Session>,
+    active: usize,
+    /// shows the maps of all sessions next to each other
+    compare: bool,
+    /// map waiting for the preview, `Some(None)` clears it
+    preview: Option<Option<TwMap>>,
+    /// generation asked for outside the node graph, run by the generator node
+    generation_requested: bool,
+    /// previewed tile under the cursor
+    hovered: Option<[usize; 2]>,
+    /// generation steps run per frame, 0 generates the whole map at once
+    live_steps: usize,
+    /// stops the live generation between frames, single steps still run
+    paused: bool,
+    /// outlines the freeze, start, finish and spawn tiles on top of the design
+    entity_overlay: bool,
+    /// opacity of the hottest band of heatmap layers, below 1.0 the map stays visible under them
+    heatmap_opacity: f32,
+    /// clicks on the map add, move and remove waypoints instead of panning
+    waypoint_editing: bool,
+    /// draws the planned route and the path the walker took over the map
+    route_overlay: bool,
+    /// clicks on the map place the spawn or the finish instead of panning
+    endpoint_tool: Option<Endpoint>,
+    /// dragging over the map paints the guidance mask instead of panning
+    mask_paint: Option<MaskPaint>,
+    map_view: Option<MapView>,
+    /// tile the map view centers on next frame
+    focus: Option<[f32; 2]>,
+    /// game tiles copied from a selection
+    clipboard: Option<Array2<GameTile>>,
+    paste_transform: Transform,
+    paste_policy: PastePolicy,
+}
+
+/// part of the map the view shows, published by the map component for overlays drawn by the
+/// ui
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapView {
+    /// map position of the view's top left corner
+    pub origin: [f32; 2],
+    /// map size of the whole view
+    pub size: [f32; 2],
+}
+
+impl MapView {
+    /// position of `tile` relative to the view, 0.0 to 1.0 inside it
+    pub fn relative(&self, tile: [f32; 2]) -> [f32; 2] {
+        [
+            (tile[0] - self.origin[0]) / self.size[0],
+            (tile[1] - self.origin[1]) / self.size[1],
+        ]
+    }
+}
+
+/// what dragging over the map paints into the guidance mask
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskPaint {
+    Forbidden,
+    Preferred,
+    Erase,
+}
+
+impl MaskPaint {
+    pub const ALL: [Self; 3] = [Self::Forbidden, Self::Preferred, Self::Erase];
+
+    fn guidance(self) -> Option<Guidance> {
+        match self {
+            Self::Forbidden => Some(Guidance::Forbidden),
+            Self::Preferred => Some(Guidance::Preferred),
+            Self::Erase => None,
+        }
+    }
+}
+
+/// what's known about a tile of the previewed map
+pub struct TileInfo {
+    pub tile: [usize; 2],
+    pub block: Option<BlockType>,
+    /// distance field chunk the tile is in
+    pub chunk: [usize; 2],
+    pub distance: u32,
+    /// walker step of the last visit, 0 for tiles off the walker path
+    pub visit: usize,
+    /// debug layers set at the tile, with the value for heatmaps and classes
+    pub debug: Vec<String>,
 }
 
 impl GenerationContext {
     pub fn new() -> Self {
+        let mut preset = GenerationPreset::default();
+
+        preset.waypoints.scale_factor = 200.0;
+        preset.waypoints.waypoints = vec![
+            (0.0, 1.0),
+            (0.2, 0.8),
+            (0.4, 0.6),
+            (0.6, 0.4),
+            (0.8, 0.2),
+            (1.0, 0.0),
+        ];
+
         Self {
-            generator: Generator::new(),
-            current_map: None,
+            sessions: vec![Session::new(
+                "Session 1".to_owned(),
+                Generator::from_preset(preset),
+            )],
+            active: 0,
+            compare: false,
+            preview: None,
+            generation_requested: false,
+            hovered: None,
+            live_steps: 0,
+            paused: false,
+            entity_overlay: false,
+            heatmap_opacity: 0.6,
+            waypoint_editing: false,
+            route_overlay: false,
+            endpoint_tool: None,
+            mask_paint: None,
+            map_view: None,
+            focus: None,
+            clipboard: None,
+            paste_transform: Transform::default(),
+            paste_policy: PastePolicy::default(),
+        }
+    }
+
+    fn generator(&self) -> &Generator {
+        let session = &self.sessions[self.active];
+
+        match &session.live {
+            Some(live) => live.steps.generator(),
+            None => &session.generator,
+        }
+    }
+
+    fn generator_mut(&mut self) -> &mut Generator {
+        let session = &mut self.sessions[self.active];
+
+        match &mut session.live {
+            Some(live) => live.steps.generator_mut(),
+            None => &mut session.generator,
+        }
+    }
+
+    pub fn sessions(&self) -> &[Session] {
+        &self.sessions
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    pub fn switch_to(&mut self, session: usize) {
+        if session < self.sessions.len() && session != self.active {
+            self.active = session;
+            self.refresh_preview();
+        }
+    }
+
+    /// adds a session with the active session's preset and seed, and switches to it
+    pub fn duplicate_session(&mut self) {
+        let generator = self.generator();
+        let mut duplicate = Generator::from_preset(generator.preset().clone());
+
+        duplicate.set_seed(generator.metadata().seed);
+        duplicate.set_scale_factor(generator.get_scale_factor());
+
+        let name = format!("Session {}", self.sessions.len() + 1);
+        let mut session = Session::new(name, duplicate);
+        session.map = self.sessions[self.active].map.clone();
+
+        self.sessions.push(session);
+        self.active = self.sessions.len() - 1;
+        self.refresh_preview();
+    }
+
+    /// closes the active session, the last one stays open
+    pub fn close_session(&mut self) {
+        if self.sessions.len() > 1 {
+            self.sessions.remove(self.active);
+            self.active = self.active.min(self.sessions.len() - 1);
+            self.refresh_preview();
+        }
+    }
+
+    /// presets, seeds and maps of all sessions
+    pub fn autosave(&self) -> Autosave {
+        let sessions = self
+            .sessions
+            .iter()
+            .map(|session| {
+                let generator = match &session.live {
+                    Some(live) => live.steps.generator(),
+                    None => &session.generator,
+                };
+
+                SavedSession {
+                    name: session.name.clone(),
+                    preset: generator.preset().clone(),
+                    seed: generator.metadata().seed,
+                    map: session.map.clone(),
+                }
+            })
+            .collect();
+
+        Autosave {
+            sessions,
+            active: self.active,
+        }
+    }
+
+    /// replaces all sessions with the autosaved ones, their undo history is lost
+    pub fn restore(&mut self, autosave: Autosave) {
+        if autosave.sessions.is_empty() {
+            return;
+        }
+
+        self.sessions = autosave
+            .sessions
+            .into_iter()
+            .map(|saved| {
+                let mut generator = Generator::from_preset(saved.preset);
+                generator.set_seed(saved.seed);
+
+                let mut session = Session::new(saved.name, generator);
+                session.map = saved.map;
+
+                session
+            })
+            .collect();
+
+        self.active = autosave.active.min(self.sessions.len() - 1);
+        self.refresh_preview();
+    }
+
+    pub fn seed(&self) -> Seed {
+        self.generator().metadata().seed
+    }
+
+    pub fn set_seed(&mut self, seed: Seed) {
+        self.generator_mut().set_seed(seed);
+    }
+
+    pub fn request_generation(&mut self) {
+        self.generation_requested = true;
+    }
+
+    pub fn take_generation_request(&mut self) -> bool {
+        std::mem::take(&mut self.generation_requested)
+    }
+
+    pub fn compare(&self) -> bool {
+        self.compare
+    }
+
+    pub fn set_compare(&mut self, compare: bool) {
+        if self.compare != compare {
+            self.compare = compare;
+            self.refresh_preview();
         }
     }
 
+    /// queues the active session's map, or all maps next to each other when comparing
+    fn refresh_preview(&mut self) {
+        let map = if self.compare {
+            let maps: Vec<&TwMap> = self
+                .sessions
+                .iter()
+                .filter_map(|session| session.map.as_ref())
+                .collect();
+
+            side_by_side(&maps)
+        } else {
+            self.sessions[self.active].map.clone()
+        };
+
+        self.preview = Some(map);
+    }
+
     fn load_mutations_from_snarl(
         &mut self,
         generator_node: NodeId,
@@ -142,7 +593,7 @@ impl GenerationContext {
                             }
                             UiNode::MutationNode(mutation) => {
                                 let m = mutation.extract().unwrap();
-                                println!("{}", m.title());
+                                debug!(title = m.title(), "mutation added");
 
                                 cur_loop
                                     .as_mut()
@@ -172,11 +623,47 @@ impl GenerationContext {
     }
 
     pub fn set_scale_factor(&mut self, scale_factor: f32) {
-        self.generator.set_scale_factor(scale_factor);
+        self.generator_mut().set_scale_factor(scale_factor);
     }
 
     pub fn get_scale_factor(&self) -> f32 {
-        self.generator.get_scale_factor()
+        self.generator().get_scale_factor()
+    }
+
+    pub fn set_canvas(&mut self, canvas: Option<Canvas>) {
+        self.generator_mut().set_canvas(canvas);
+    }
+
+    pub fn selection(&self) -> Option<[[usize; 2]; 2]> {
+        self.sessions[self.active].selection
+    }
+
+    pub fn set_selection(&mut self, selection: Option<[[usize; 2]; 2]>) {
+        self.sessions[self.active].selection = selection;
+    }
+
+    /// locks or unlocks the selected area of the previewed map for the next generations
+    pub fn lock_selection(&mut self, locked: bool) {
+        let Some([min, max]) = self.selection() else {
+            return;
+        };
+
+        let [x, y] = self.generator().crop_offset();
+        let (min, max) = ([min[0] + x, min[1] + y], [max[0] + x, max[1] + y]);
+
+        if locked {
+            self.generator_mut().lock_area(min, max);
+        } else {
+            self.generator_mut().unlock_area(min, max);
+        }
+    }
+
+    pub fn preset(&self) -> &GenerationPreset {
+        self.generator().preset()
+    }
+
+    pub fn preset_mut(&mut self) -> &mut GenerationPreset {
+        self.generator_mut().preset_mut()
     }
 
     pub fn generate(
@@ -184,8 +671,22 @@ impl GenerationContext {
         snarl: &mut Snarl<UiNode>,
         generator_node: NodeId,
         design: &DesignInfo,
-        waypoints: Vec<(f32, f32)>,
     ) {
+        self.stop_live();
+
+        // the preset panel lists the errors, generating would only fail or panic
+        let errors = self
+            .preset()
+            .validate()
+            .into_iter()
+            .filter(|issue| issue.severity == Severity::Error)
+            .count();
+
+        if errors > 0 {
+            error!("preset has {} validation errors, not generating", errors);
+            return;
+        }
+
         let Some((mut brush_mutations, mut map_mutations, mut walker_mutations)) =
             self.load_mutations_from_snarl(generator_node, snarl)
         else {
@@ -207,7 +708,7 @@ impl GenerationContext {
             }
         }
 
-        self.generator.on_step(move |walker, map, brush| {
+        self.generator_mut().on_step(move |walker, map, brush| {
             fn mutate_all<T>(mutant: &mut T, loops: &mut Vec<Loop<Box<dyn Mutator<T>>>>) {
                 for lp in loops.iter_mut() {
                     if let Some(count) = &mut lp.count {
@@ -218,7 +719,7 @@ impl GenerationContext {
                             for mutation in lp.mutations.iter_mut() {
                                 let state = mutation.mutate(mutant);
 
-                                println!("[state]\t\t[idk   ]\t{:?}", state);
+                                trace!(?state, "mutation state");
 
                                 if state == MutationState::Processing {
                                     break;
@@ -240,7 +741,7 @@ impl GenerationContext {
                                 last_finished = !processed;
                             }
 
-                            println!("[state]\t\t[idk   ]\t{:?}", state);
+                            trace!(?state, "mutation state");
 
                             if processed {
                                 break;
@@ -264,69 +765,1114 @@ impl GenerationContext {
             mutate_all(walker, &mut walker_mutations);
         });
 
-        let mut map = self.generator.generate(waypoints);
+        if self.live_steps > 0 {
+            self.start_live(design);
+            return;
+        }
+
+        let mut zone_boundaries = Vec::new();
+        let mut walked = Vec::new();
+        let mut events = EventRecorder::default();
+        let seed = self.seed();
+
+        // same pipeline as the bridge, so a seed gives the same map in both
+        let result = GenerationPipeline::default().run(self.generator_mut(), seed, |result| {
+            events.record(result);
+
+            match result {
+                StepResult::ZoneEntered { position, .. } => zone_boundaries.push(position.clone()),
+                StepResult::WalkerMoved { position, .. } => walked.push([position[0], position[1]]),
+                _ => {}
+            }
+        });
+
+        self.sessions[self.active].walked = walked;
+        self.sessions[self.active].events = events.events;
+
+        match result {
+            Ok(map) => self.present(map, &zone_boundaries, design),
+            Err(err) => error!("{}", err),
+        }
+    }
 
-        // design
-        // weird way to do it but whatever
-        // im done
+    pub fn live_steps(&self) -> usize {
+        self.live_steps
+    }
 
-        let image_ids: HashMap<DesignLayer, u16, std::hash::RandomState> = design
-            .image_infos
-            .iter()
-            .map(|(&layer, info)| {
-                let image = load_image(info.path.as_path());
+    pub fn set_live_steps(&mut self, steps: usize) {
+        self.live_steps = steps;
+    }
+
+    pub fn is_live(&self) -> bool {
+        self.sessions[self.active].live.is_some()
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    fn start_live(&mut self, design: &DesignInfo) {
+        let session = &mut self.sessions[self.active];
+        let generator = std::mem::replace(&mut session.generator, Generator::new());
+        let preset = generator.preset().clone();
+
+        session.live = Some(LiveGeneration {
+            steps: generator.into_steps(preset),
+            zone_boundaries: Vec::new(),
+            walked: Vec::new(),
+            events: EventRecorder::default(),
+            design: design.clone(),
+            shown: Array2::from_elem((0, 0), GameTile::new(0, TileFlags::empty())),
+        });
+    }
+
+    /// drops the live generation of the active session and takes its generator back
+    fn stop_live(&mut self) {
+        let session = &mut self.sessions[self.active];
+
+        if let Some(live) = session.live.take() {
+            session.generator = live.steps.into_generator();
+        }
+    }
+
+    /// runs the next steps of the active session's live generation unless it's paused
+    pub fn step_live(&mut self) {
+        if !self.paused {
+            self.advance_live(self.live_steps.max(1));
+        }
+    }
+
+    /// runs a single step of the active session's live generation, paused or not
+    pub fn step_once(&mut self) {
+        self.advance_live(1);
+    }
 
-                let pos = map.images.iter().position(|i| image.eq(i));
-                if let Some(idx) = pos {
-                    (layer, idx as u16)
-                } else {
-                    let idx = map.images.len();
+    /// runs `steps` steps of the active session's live generation, queues the unfinished map
+    /// for the preview whenever a chunk was edited since it was last queued
+    fn advance_live(&mut self, steps: usize) {
+        let session = &mut self.sessions[self.active];
 
-                    map.images.push(image);
+        let Some(live) = session.live.as_mut() else {
+            return;
+        };
+
+        let mut finished = None;
+
+        for _ in 0..steps {
+            let result = live.steps.next();
 
-                    (layer, idx as u16)
+            if let Some(result) = &result {
+                live.events.record(result);
+            }
+
+            match result {
+                Some(StepResult::ZoneEntered { position, .. }) => {
+                    live.zone_boundaries.push(position)
                 }
-            })
-            .collect();
+                Some(StepResult::WalkerMoved { position, .. }) => {
+                    live.walked.push([position[0], position[1]])
+                }
+                Some(StepResult::Finished(map)) => {
+                    finished = Some(Some(map));
+                    break;
+                }
+                Some(_) => {}
+                None => {
+                    finished = Some(None);
+                    break;
+                }
+            }
+        }
+
+        if let Some(map) = finished {
+            let live = session.live.take().unwrap();
+            let error = live.steps.error();
+            session.generator = live.steps.into_generator();
+            session.walked = live.walked;
+            session.events = live.events.events;
+
+            match (map, error) {
+                (Some(map), _) => self.present(map, &live.zone_boundaries, &live.design),
+                (None, Some(err)) => error!("{}", err),
+                (None, None) => {}
+            }
 
-        let shape = map.physics_group().layers[0].shape().unwrap();
+            return;
+        }
+
+        let Some(map) = live.steps.current_map() else {
+            return;
+        };
 
-        let mut design_group = Group::default();
+        let diff = MapDiff::between_tiles(&live.shown, game_tiles(map.raw_map()));
 
-        design_group.name = "Design".to_owned();
+        if diff.is_empty() {
+            return;
+        }
 
-        for (&design, &id) in image_ids.iter() {
-            let mut layer = TilesLayer::new((shape.w, shape.h));
+        diff.apply_to(&mut live.shown);
 
-            layer.name = match design {
-                DesignLayer::Unhookable => "Unhookable".to_owned(),
-                DesignLayer::Hookable => "Hookable".to_owned(),
-                DesignLayer::Freeze => "Freeze".to_owned(),
-            };
+        self.preview = Some(Some(map.raw_map().clone()));
+    }
+
+    /// walks one segment of the last generated map again
+    pub fn regenerate_segment(&mut self, segment: usize, design: &DesignInfo) {
+        self.stop_live();
+
+        if let Some(map) = self.generator_mut().regenerate_segment(segment) {
+            self.present(map, &[], design);
+        }
+    }
+
+    /// adds the design and debug layers to a generated map and hands it to the preview
+    fn present(&mut self, mut map: TwMap, zone_boundaries: &[Vector2], design: &DesignInfo) {
+        self.sessions[self.active].record(&map);
+
+        add_design(&mut map, design);
+
+        if !zone_boundaries.is_empty() {
+            let zones = zone_markers(&map, zone_boundaries, self.generator().crop_offset());
+
+            map.groups.push(zones);
+        }
+
+        let offset = self.generator().crop_offset();
+
+        for (name, layer) in self.generator().debug_layers().iter() {
+            map.groups
+                .push(debug_group(&map, name, layer, offset, self.heatmap_opacity));
+        }
+
+        if self.entity_overlay {
+            map.groups.push(entity_group(&map));
+        }
+
+        let session = &mut self.sessions[self.active];
+        session.map = Some(map);
+        session.design = Some(design.clone());
+        self.refresh_preview();
+
+        info!(seed = self.seed(), "generated");
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.sessions[self.active].undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.sessions[self.active].redo.is_empty()
+    }
+
+    /// turns the active session's map back into the one before its last generation or edit
+    pub fn undo(&mut self) {
+        self.step_history(true);
+    }
 
-            let tiles = layer.tiles.unwrap_mut();
+    pub fn redo(&mut self) {
+        self.step_history(false);
+    }
+
+    fn step_history(&mut self, undo: bool) {
+        self.stop_live();
+
+        let Some(mut map) = self.bare_map() else {
+            return;
+        };
+
+        let session = &mut self.sessions[self.active];
+        let (from, to) = if undo {
+            (&mut session.undo, &mut session.redo)
+        } else {
+            (&mut session.redo, &mut session.undo)
+        };
+
+        let Some(diff) = from.pop() else {
+            return;
+        };
+
+        let game: &mut GameLayer = map.find_physics_layer_mut().unwrap();
+        let tiles = game.tiles.unwrap_mut();
+        let before = tiles.clone();
+
+        diff.apply_to(tiles);
+        to.push(MapDiff::between_tiles(tiles, &before));
+
+        self.show_edited(map);
+    }
+
+    /// the active session's map without the groups added on top of its physics layers, none
+    /// before the first generation
+    fn bare_map(&self) -> Option<TwMap> {
+        let session = &self.sessions[self.active];
+        session.design.as_ref()?;
+
+        let mut map = session.map.clone()?;
 
-            *tiles = map
-                .find_physics_layer::<GameLayer>()
-                .as_ref()
-                .unwrap()
-                .tiles
-                .unwrap_ref()
-                .map(|elem| Tile::new(design.is_same(elem.id) as u8, TileFlags::empty()));
+        map.groups.retain(|group| {
+            group
+                .layers
+                .iter()
+                .any(|layer| matches!(layer, Layer::Game(_)))
+        });
+
+        Some(map)
+    }
+
+    /// adds the design and the entity outlines to an edited bare map and shows it, the debug
+    /// layers belong to the generation and aren't shown anymore
+    fn show_edited(&mut self, mut map: TwMap) {
+        let session = &self.sessions[self.active];
 
-            layer.image = Some(id);
+        if let Some(design) = &session.design {
+            add_design(&mut map, design);
+        }
 
-            design_group.layers.push(twmap::Layer::Tiles(layer));
+        if self.entity_overlay {
+            map.groups.push(entity_group(&map));
         }
 
-        map.groups.push(design_group);
+        self.sessions[self.active].map = Some(map);
+        self.refresh_preview();
+    }
+
+    pub fn clipboard(&self) -> Option<&Array2<GameTile>> {
+        self.clipboard.as_ref()
+    }
+
+    /// copies the game tiles of the selection
+    pub fn copy_selection(&mut self) {
+        let (Some([min, max]), Some(map)) = (self.selection(), self.bare_map()) else {
+            return;
+        };
+
+        self.clipboard = Some(Map::from_raw(map).copy_area(min, max));
+    }
+
+    pub fn paste_transform(&self) -> Transform {
+        self.paste_transform
+    }
 
-        self.current_map = Some(map);
+    pub fn set_paste_transform(&mut self, transform: Transform) {
+        self.paste_transform = transform;
+    }
 
-        println!("generated");
+    pub fn paste_policy(&self) -> PastePolicy {
+        self.paste_policy
     }
 
-    pub fn take_map(&mut self) -> Option<TwMap> {
-        self.current_map.take()
+    pub fn set_paste_policy(&mut self, policy: PastePolicy) {
+        self.paste_policy = policy;
     }
+
+    /// pastes the transformed clipboard with its top left corner at `at`, the paste can be
+    /// undone
+    pub fn paste(&mut self, at: [usize; 2]) {
+        let Some(clipboard) = &self.clipboard else {
+            return;
+        };
+
+        let tiles = self.paste_transform.apply(clipboard.view());
+        let policy = self.paste_policy;
+
+        self.edit_map(|map| map.paste_area(&tiles, at, policy));
+    }
+
+    /// turns or mirrors the active session's map, the selection is dropped
+    pub fn transform_map(&mut self, transform: Transform) {
+        self.edit_map(|map| map.transform(transform));
+        self.set_selection(None);
+    }
+
+    /// stamps server branding into the active session's map, the stamp can be undone
+    pub fn stamp_branding(&mut self, branding: &Branding) {
+        self.edit_map(|map| branding.stamp(map));
+    }
+
+    /// edits the active session's map in a way that can be undone
+    fn edit_map(&mut self, edit: impl FnOnce(&mut Map)) {
+        self.stop_live();
+
+        let Some(map) = self.bare_map() else {
+            return;
+        };
+
+        let mut map = Map::from_raw(map);
+        edit(&mut map);
+
+        let map = map.into_raw();
+
+        self.sessions[self.active].record(&map);
+        self.show_edited(map);
+    }
+
+    /// saves the active session's map with its design, without the debug groups
+    pub fn save_map(&self, path: &Path) -> Result<(), String> {
+        let mut map = self.sessions[self.active]
+            .map
+            .clone()
+            .ok_or("nothing generated yet")?;
+
+        map.groups.retain(|group| {
+            group.name == DESIGN_GROUP
+                || group
+                    .layers
+                    .iter()
+                    .any(|layer| matches!(layer, Layer::Game(_)))
+        });
+
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory).map_err(|err| err.to_string())?;
+        }
+
+        let mut file = std::fs::File::create(path).map_err(|err| err.to_string())?;
+        map.save(&mut file).map_err(|err| err.to_string())
+    }
+
+    pub fn debug_layers(&self) -> &DebugLayers {
+        self.generator().debug_layers()
+    }
+
+    pub fn hovered(&self) -> Option<[usize; 2]> {
+        self.hovered
+    }
+
+    pub fn set_hovered(&mut self, tile: Option<[usize; 2]>) {
+        self.hovered = tile;
+    }
+
+    /// width and height of the previewed map in tiles
+    pub fn map_size(&self) -> Option<[usize; 2]> {
+        let map = self.sessions[self.active].map.as_ref()?;
+        let game: &GameLayer = map.find_physics_layer()?;
+        let (width, height) = game.tiles.unwrap_ref().dim();
+
+        Some([width, height])
+    }
+
+    pub fn map_view(&self) -> Option<MapView> {
+        self.map_view
+    }
+
+    pub fn set_map_view(&mut self, view: MapView) {
+        self.map_view = Some(view);
+    }
+
+    pub fn waypoint_editing(&self) -> bool {
+        self.waypoint_editing
+    }
+
+    pub fn set_waypoint_editing(&mut self, editing: bool) {
+        self.waypoint_editing = editing;
+    }
+
+    pub fn route_overlay(&self) -> bool {
+        self.route_overlay
+    }
+
+    pub fn set_route_overlay(&mut self, shown: bool) {
+        self.route_overlay = shown;
+    }
+
+    pub fn endpoint_tool(&self) -> Option<Endpoint> {
+        self.endpoint_tool
+    }
+
+    pub fn set_endpoint_tool(&mut self, endpoint: Option<Endpoint>) {
+        self.endpoint_tool = endpoint;
+    }
+
+    /// what speaks against placing `endpoint` at a tile of the previewed map
+    pub fn placement_issues(&self, endpoint: Endpoint, tile: [f32; 2]) -> Vec<PlacementIssue> {
+        self.generator().placement_issues(endpoint, tile)
+    }
+
+    /// moves the first or last waypoint to `tile` and generates again, nothing happens when the
+    /// placement has issues
+    pub fn place_endpoint(&mut self, endpoint: Endpoint, tile: [f32; 2]) {
+        if self.compare || !self.placement_issues(endpoint, tile).is_empty() {
+            return;
+        }
+
+        let waypoint = self.generator().tile_waypoint(tile);
+        let waypoints = &mut self.preset_mut().waypoints.waypoints;
+        let index = endpoint.waypoint(waypoints.len());
+
+        if let Some(moved) = waypoints.get_mut(index) {
+            *moved = waypoint;
+            self.request_generation();
+        }
+    }
+
+    pub fn mask_paint(&self) -> Option<MaskPaint> {
+        self.mask_paint
+    }
+
+    pub fn set_mask_paint(&mut self, paint: Option<MaskPaint>) {
+        self.mask_paint = paint;
+    }
+
+    /// paints the guidance mask cell under a tile of the previewed map without generating,
+    /// done once the stroke ends
+    pub fn paint_mask(&mut self, paint: MaskPaint, tile: [f32; 2]) {
+        if self.compare {
+            return;
+        }
+
+        let (x, y) = self.generator().tile_waypoint(tile);
+        let scale_factor = self.preset().waypoints.scale_factor;
+
+        self.preset_mut()
+            .guidance_mask
+            .get_or_insert_with(Default::default)
+            .paint([x * scale_factor, y * scale_factor], paint.guidance());
+    }
+
+    /// replaces the guidance mask cells with a grayscale image stretched over the map
+    pub fn import_mask(&mut self, luma: &Array2<u8>) {
+        let waypoints = self.preset().waypoints.clone();
+
+        self.preset_mut()
+            .guidance_mask
+            .get_or_insert_with(Default::default)
+            .import_luma(luma.view(), &waypoints);
+        self.request_generation();
+    }
+
+    /// corners of the painted guidance mask cells on the previewed map
+    pub fn mask_cells(&self) -> Vec<([[f32; 2]; 2], Guidance)> {
+        let Some(mask) = &self.preset().guidance_mask else {
+            return Vec::new();
+        };
+
+        if self.compare {
+            return Vec::new();
+        }
+
+        let generator = self.generator();
+        let scale_factor = self.preset().waypoints.scale_factor;
+        // normalized size of a cell
+        let size = mask.cell_size.max(1) as f32 / scale_factor;
+        let corner =
+            |x: isize, y: isize| generator.waypoint_tile((x as f32 * size, y as f32 * size));
+
+        [
+            (&mask.forbidden, Guidance::Forbidden),
+            (&mask.preferred, Guidance::Preferred),
+        ]
+        .into_iter()
+        .flat_map(|(cells, guidance)| {
+            cells
+                .iter()
+                .map(move |&[x, y]| ([corner(x, y), corner(x + 1, y + 1)], guidance))
+        })
+        .collect()
+    }
+
+    /// tiles of the main route waypoints on the previewed map, none before the first generation
+    /// or while comparing sessions
+    pub fn waypoint_tiles(&self) -> Vec<[f32; 2]> {
+        let session = &self.sessions[self.active];
+
+        if self.compare || (session.map.is_none() && session.live.is_none()) {
+            return Vec::new();
+        }
+
+        let generator = self.generator();
+
+        self.preset()
+            .waypoints
+            .waypoints
+            .iter()
+            .map(|&waypoint| generator.waypoint_tile(waypoint))
+            .collect()
+    }
+
+    /// tiles the walker stepped on in order, on the previewed map, so far while generating live
+    /// notable steps of the active session's last or live generation, positioned like
+    /// `walked_tiles`
+    pub fn events(&self) -> Vec<GenerationEvent> {
+        let session = &self.sessions[self.active];
+
+        if self.compare {
+            return Vec::new();
+        }
+
+        if let Some(live) = &session.live {
+            return live.events.events.clone();
+        }
+
+        let [x, y] = self.generator().crop_offset();
+
+        session
+            .events
+            .iter()
+            .map(|event| GenerationEvent {
+                position: event
+                    .position
+                    .map(|tile| [tile[0] - x as f32, tile[1] - y as f32]),
+                ..event.clone()
+            })
+            .collect()
+    }
+
+    /// asks the map view to center on `tile` next frame
+    pub fn focus(&mut self, tile: [f32; 2]) {
+        self.focus = Some(tile);
+    }
+
+    /// tile the map view was asked to center on, once
+    pub fn take_focus(&mut self) -> Option<[f32; 2]> {
+        self.focus.take()
+    }
+
+    pub fn walked_tiles(&self) -> Vec<[f32; 2]> {
+        let session = &self.sessions[self.active];
+
+        if self.compare {
+            return Vec::new();
+        }
+
+        if let Some(live) = &session.live {
+            // the unfinished map isn't cropped yet
+            return live.walked.clone();
+        }
+
+        let [x, y] = self.generator().crop_offset();
+
+        session
+            .walked
+            .iter()
+            .map(|tile| [tile[0] - x as f32, tile[1] - y as f32])
+            .collect()
+    }
+
+    /// closest waypoint within `reach` tiles of `tile`
+    pub fn waypoint_at(&self, tile: [f32; 2], reach: f32) -> Option<usize> {
+        self.waypoint_tiles()
+            .iter()
+            .map(|&waypoint| distance(waypoint, tile))
+            .enumerate()
+            .filter(|&(_, distance)| distance <= reach)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(waypoint, _)| waypoint)
+    }
+
+    /// moves a waypoint without generating, done once the drag ends
+    pub fn move_waypoint(&mut self, waypoint: usize, tile: [f32; 2]) {
+        let moved = self.generator().tile_waypoint(tile);
+
+        if let Some(waypoint) = self.preset_mut().waypoints.waypoints.get_mut(waypoint) {
+            *waypoint = moved;
+        }
+    }
+
+    /// adds a waypoint at `tile` into the route segment closest to it, waypoint overrides and
+    /// zones keep their indices
+    pub fn insert_waypoint(&mut self, tile: [f32; 2]) {
+        let tiles = self.waypoint_tiles();
+
+        if tiles.is_empty() {
+            return;
+        }
+
+        let index = tiles
+            .windows(2)
+            .map(|pair| segment_distance(tile, pair[0], pair[1]))
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(tiles.len(), |(segment, _)| segment + 1);
+
+        let waypoint = self.generator().tile_waypoint(tile);
+        self.preset_mut()
+            .waypoints
+            .waypoints
+            .insert(index, waypoint);
+        self.request_generation();
+    }
+
+    /// removes a waypoint, a route keeps at least two
+    pub fn remove_waypoint(&mut self, waypoint: usize) {
+        let waypoints = &mut self.preset_mut().waypoints.waypoints;
+
+        if waypoints.len() > 2 && waypoint < waypoints.len() {
+            waypoints.remove(waypoint);
+            self.request_generation();
+        }
+    }
+
+    /// inspects a tile of the previewed map, none outside of it or while comparing sessions
+    pub fn inspect(&self, tile: [usize; 2]) -> Option<TileInfo> {
+        if self.compare {
+            return None;
+        }
+
+        let map = self.sessions[self.active].map.as_ref()?;
+        let game: &GameLayer = map.find_physics_layer()?;
+        let id = game.tiles.unwrap_ref().get(tile)?.id;
+
+        let generator = self.generator();
+        let [x, y] = generator.crop_offset();
+        let index = [tile[0] + x, tile[1] + y];
+
+        let debug = generator
+            .debug_layers()
+            .iter()
+            .filter_map(|(name, layer)| match &layer.data {
+                DebugLayerData::Mask(mask) => mask.get(index)?.then(|| name.to_owned()),
+                DebugLayerData::Heatmap(values) => values
+                    .get(index)
+                    .filter(|&&value| value > 0.0)
+                    .map(|value| format!("{}: {:.2}", name, value)),
+                DebugLayerData::Classes(classes, _) => classes
+                    .get(index)
+                    .filter(|&&class| class != 0)
+                    .map(|class| format!("{}: {}", name, class - 1)),
+            })
+            .collect();
+
+        Some(TileInfo {
+            tile,
+            block: BlockType::from_game_id(id),
+            chunk: [index[0] / CHUNK_SIZE, index[1] / CHUNK_SIZE],
+            distance: generator.distance_field().get(index),
+            visit: generator.visits().get(index).copied().unwrap_or(0),
+            debug,
+        })
+    }
+
+    pub fn entity_overlay(&self) -> bool {
+        self.entity_overlay
+    }
+
+    /// adds or removes the entity outlines of every session's map, no generation needed
+    pub fn set_entity_overlay(&mut self, shown: bool) {
+        if self.entity_overlay == shown {
+            return;
+        }
+
+        self.entity_overlay = shown;
+
+        for map in self
+            .sessions
+            .iter_mut()
+            .filter_map(|session| session.map.as_mut())
+        {
+            map.groups.retain(|group| group.name != ENTITY_GROUP);
+
+            if shown {
+                let group = entity_group(map);
+                map.groups.push(group);
+            }
+        }
+
+        self.refresh_preview();
+    }
+
+    pub fn heatmap_opacity(&self) -> f32 {
+        self.heatmap_opacity
+    }
+
+    /// redraws the debug layers of the active session's map, no generation needed
+    pub fn set_heatmap_opacity(&mut self, opacity: f32) {
+        if self.heatmap_opacity == opacity {
+            return;
+        }
+
+        self.heatmap_opacity = opacity;
+
+        let offset = self.generator().crop_offset();
+        let debug = self.generator().debug_layers();
+
+        let Some(map) = &self.sessions[self.active].map else {
+            return;
+        };
+
+        let shown = map
+            .groups
+            .iter()
+            .any(|group| debug.iter().any(|(name, _)| group.name == name));
+
+        // edited maps don't show the debug layers anymore
+        if !shown {
+            return;
+        }
+
+        let mut map = map.clone();
+
+        map.groups
+            .retain(|group| !debug.iter().any(|(name, _)| group.name == name));
+
+        // entity outlines stay on top
+        let entities = map
+            .groups
+            .iter()
+            .position(|group| group.name == ENTITY_GROUP)
+            .unwrap_or(map.groups.len());
+
+        for (i, (name, layer)) in debug.iter().enumerate() {
+            let group = debug_group(&map, name, layer, offset, opacity);
+            map.groups.insert(entities + i, group);
+        }
+
+        self.sessions[self.active].map = Some(map);
+        self.refresh_preview();
+    }
+
+    /// turns a debug layer on or off and generates again to show the change
+    pub fn set_debug_layer(&mut self, name: &str, enabled: bool) {
+        self.generator_mut()
+            .debug_layers_mut()
+            .set_enabled(name, enabled);
+        self.request_generation();
+    }
+
+    /// map to show next, `Some(None)` clears the preview
+    pub fn take_preview(&mut self) -> Option<Option<TwMap>> {
+        self.preview.take()
+    }
+}
+
+/// adds the design layers of the game tiles
+fn add_design(map: &mut TwMap, design: &DesignInfo) {
+    // weird way to do it but whatever
+    // im done
+
+    let image_ids: HashMap<DesignLayer, u16, std::hash::RandomState> = design
+        .image_infos
+        .iter()
+        .map(|(&layer, info)| {
+            let image = load_image(info.path.as_path());
+
+            let pos = map.images.iter().position(|i| image.eq(i));
+            if let Some(idx) = pos {
+                (layer, idx as u16)
+            } else {
+                let idx = map.images.len();
+
+                map.images.push(image);
+
+                (layer, idx as u16)
+            }
+        })
+        .collect();
+
+    let shape = map.physics_group().layers[0].shape().unwrap();
+
+    let mut design_group = Group {
+        name: DESIGN_GROUP.to_owned(),
+        ..Default::default()
+    };
+
+    for (&design, &id) in image_ids.iter() {
+        let mut layer = TilesLayer::new((shape.w, shape.h));
+
+        layer.name = match design {
+            DesignLayer::Unhookable => "Unhookable".to_owned(),
+            DesignLayer::Hookable => "Hookable".to_owned(),
+            DesignLayer::Freeze => "Freeze".to_owned(),
+        };
+
+        let tiles = layer.tiles.unwrap_mut();
+
+        *tiles = map
+            .find_physics_layer::<GameLayer>()
+            .as_ref()
+            .unwrap()
+            .tiles
+            .unwrap_ref()
+            .map(|elem| Tile::new(design.is_same(elem.id) as u8, TileFlags::empty()));
+
+        layer.image = Some(id);
+
+        design_group.layers.push(twmap::Layer::Tiles(layer));
+    }
+
+    map.groups.push(design_group);
+}
+
+fn game_tiles(map: &TwMap) -> &Array2<GameTile> {
+    let game: &GameLayer = map.find_physics_layer().unwrap();
+
+    game.tiles.unwrap_ref()
+}
+
+/// crosses marking where the walker entered or left a zone, `offset` is the crop offset of the
+/// map
+fn zone_markers(map: &TwMap, boundaries: &[Vector2], offset: [usize; 2]) -> Group {
+    const MARKER_RADIUS: isize = 4;
+
+    let shape = map.physics_group().layers[0].shape().unwrap();
+
+    let mut layer = TilesLayer::new((shape.w, shape.h));
+
+    layer.name = "Zones".to_owned();
+    layer.color = Rgba::from(DebugColor::YELLOW.to_array());
+
+    let tiles = layer.tiles.unwrap_mut();
+
+    // same indexing as the game layer
+    *tiles = map
+        .find_physics_layer::<GameLayer>()
+        .unwrap()
+        .tiles
+        .unwrap_ref()
+        .map(|_| Tile::new(0, TileFlags::empty()));
+
+    for position in boundaries {
+        let [x, y] = as_index(position.view());
+        let (x, y) = (
+            x as isize - offset[0] as isize,
+            y as isize - offset[1] as isize,
+        );
+
+        for offset in -MARKER_RADIUS..=MARKER_RADIUS {
+            let horizontal = (x + offset, y);
+            let vertical = (x, y + offset);
+
+            for (x, y) in [horizontal, vertical] {
+                if x < 0 || y < 0 {
+                    continue;
+                }
+
+                if let Some(tile) = tiles.get_mut((x as usize, y as usize)) {
+                    *tile = Tile::new(1, TileFlags::empty());
+                }
+            }
+        }
+    }
+
+    Group {
+        name: "Zones".to_owned(),
+        layers: vec![twmap::Layer::Tiles(layer)],
+        ..Default::default()
+    }
+}
+
+const DESIGN_GROUP: &str = "Design";
+const ENTITY_GROUP: &str = "Entities";
+
+/// group outlining the areas of game tiles that are hard to tell apart once decorated
+fn entity_group(map: &TwMap) -> Group {
+    let game: &GameLayer = map.find_physics_layer().unwrap();
+    let tiles = game.tiles.unwrap_ref();
+
+    let spawns = [SpawnTeam::Neutral, SpawnTeam::Red, SpawnTeam::Blue].map(|team| team.game_id());
+    let kinds: [(&str, &[u8], DebugColor); 4] = [
+        ("Freeze", &[BlockType::Freeze.game_id()], DebugColor::BLUE),
+        ("Start", &[BlockType::Start.game_id()], DebugColor::GREEN),
+        ("Finish", &[BlockType::Finish.game_id()], DebugColor::RED),
+        ("Spawn", &spawns, DebugColor::YELLOW),
+    ];
+
+    let mut group = Group {
+        name: ENTITY_GROUP.to_owned(),
+        ..Default::default()
+    };
+
+    for (name, ids, color) in kinds {
+        let is_kind = |index: [isize; 2]| {
+            let (Ok(x), Ok(y)) = (usize::try_from(index[0]), usize::try_from(index[1])) else {
+                return false;
+            };
+
+            tiles.get([x, y]).is_some_and(|tile| ids.contains(&tile.id))
+        };
+
+        // tiles of the kind next to a tile of another kind
+        let outline = Array2::from_shape_fn(tiles.dim(), |(x, y)| {
+            let (x, y) = (x as isize, y as isize);
+
+            is_kind([x, y])
+                && [[-1, 0], [1, 0], [0, -1], [0, 1]]
+                    .iter()
+                    .any(|[dx, dy]| !is_kind([x + dx, y + dy]))
+        });
+
+        let layer = mask_layer(map, name, &outline, [0, 0], color);
+        group.layers.push(twmap::Layer::Tiles(layer));
+    }
+
+    group
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    (a[0] - b[0]).hypot(a[1] - b[1])
+}
+
+/// distance of `point` to the line segment from `a` to `b`
+fn segment_distance(point: [f32; 2], a: [f32; 2], b: [f32; 2]) -> f32 {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let length = dx * dx + dy * dy;
+
+    if length == 0.0 {
+        return distance(point, a);
+    }
+
+    let t = (((point[0] - a[0]) * dx + (point[1] - a[1]) * dy) / length).clamp(0.0, 1.0);
+
+    distance(point, [a[0] + t * dx, a[1] + t * dy])
+}
+
+/// opacity steps a heatmap is split into
+const HEAT_BANDS: usize = 4;
+
+/// group showing a debug layer, heatmaps get a layer per band of rising opacity up to
+/// `heatmap_opacity` and classes a layer per color
+fn debug_group(
+    map: &TwMap,
+    name: &str,
+    layer: &DebugLayer,
+    offset: [usize; 2],
+    heatmap_opacity: f32,
+) -> Group {
+    let layers = match &layer.data {
+        DebugLayerData::Mask(mask) => vec![mask_layer(map, name, mask, offset, layer.color)],
+        DebugLayerData::Heatmap(values) => (0..HEAT_BANDS)
+            .map(|band| {
+                let low = band as f32 / HEAT_BANDS as f32;
+                let high = (band + 1) as f32 / HEAT_BANDS as f32;
+                let mask = values.map(|&value| value > low && value.min(1.0) <= high);
+                let alpha = (255.0 * heatmap_opacity * high).round() as u8;
+
+                let name = format!("{} {}", name, band);
+                mask_layer(map, &name, &mask, offset, layer.color.with_alpha(alpha))
+            })
+            .collect(),
+        DebugLayerData::Classes(classes, colors) => colors
+            .iter()
+            .enumerate()
+            .map(|(i, &color)| {
+                let mask =
+                    classes.map(|&class| class != 0 && (class - 1) as usize % colors.len() == i);
+
+                mask_layer(map, &format!("{} {}", name, i), &mask, offset, color)
+            })
+            .collect(),
+    };
+
+    Group {
+        name: name.to_owned(),
+        layers: layers.into_iter().map(twmap::Layer::Tiles).collect(),
+        ..Default::default()
+    }
+}
+
+/// layer with a tile wherever `mask` is set, `mask` is indexed like the game layer before it
+/// was cropped at `offset`
+fn mask_layer(
+    map: &TwMap,
+    name: &str,
+    mask: &Array2<bool>,
+    offset: [usize; 2],
+    color: DebugColor,
+) -> TilesLayer {
+    let shape = map.physics_group().layers[0].shape().unwrap();
+
+    let mut layer = TilesLayer::new((shape.w, shape.h));
+
+    layer.name = name.to_owned();
+    layer.color = Rgba::from(color.to_array());
+
+    let tiles = layer.tiles.unwrap_mut();
+
+    *tiles = map
+        .find_physics_layer::<GameLayer>()
+        .unwrap()
+        .tiles
+        .unwrap_ref()
+        .map(|_| Tile::new(0, TileFlags::empty()));
+
+    for ((x, y), _) in mask.indexed_iter().filter(|(_, &set)| set) {
+        let (Some(x), Some(y)) = (x.checked_sub(offset[0]), y.checked_sub(offset[1])) else {
+            continue;
+        };
+
+        if let Some(tile) = tiles.get_mut((x, y)) {
+            *tile = Tile::new(1, TileFlags::empty());
+        }
+    }
+
+    layer
+}
+
+/// places the maps next to each other, `COMPARE_GAP` tiles apart, keeping only the game layer
+/// and the tiles layers
+fn side_by_side(maps: &[&TwMap]) -> Option<TwMap> {
+    let first = maps.first()?;
+
+    let game_tiles = |map: &TwMap| {
+        let game: &GameLayer = map.find_physics_layer().unwrap();
+
+        game.tiles.unwrap_ref().clone()
+    };
+
+    let dims: Vec<(usize, usize)> = maps.iter().map(|map| game_tiles(map).dim()).collect();
+    let width = dims
+        .iter()
+        .map(|&(width, _)| width + COMPARE_GAP)
+        .sum::<usize>()
+        - COMPARE_GAP;
+    let height = dims.iter().map(|&(_, height)| height).max()?;
+
+    let mut result = TwMap::empty(first.version);
+    let mut game = Array2::from_elem((width, height), GameTile::new(0, TileFlags::empty()));
+    let mut design_groups = Vec::new();
+    let mut x = 0;
+
+    for (map, &(map_width, map_height)) in maps.iter().zip(dims.iter()) {
+        game.slice_mut(s![x..x + map_width, ..map_height])
+            .assign(&game_tiles(map));
+
+        let image_offset = result.images.len() as u16;
+        result.images.extend(map.images.iter().cloned());
+
+        let is_physics = |group: &Group| {
+            group
+                .layers
+                .iter()
+                .any(|layer| matches!(layer, Layer::Game(_)))
+        };
+
+        for group in map.groups.iter().filter(|group| !is_physics(group)) {
+            let mut group = group.clone();
+
+            group.layers.retain_mut(|layer| {
+                let Layer::Tiles(layer) = layer else {
+                    return false;
+                };
+
+                let tiles = layer.tiles.unwrap_mut();
+                let mut placed =
+                    Array2::from_elem((width, height), Tile::new(0, TileFlags::empty()));
+                let (layer_width, layer_height) = tiles.dim();
+
+                placed
+                    .slice_mut(s![x..x + layer_width, ..layer_height])
+                    .assign(tiles);
+
+                *tiles = placed;
+                layer.image = layer.image.map(|image| image + image_offset);
+
+                true
+            });
+
+            design_groups.push(group);
+        }
+
+        x += map_width + COMPARE_GAP;
+    }
+
+    let mut physics = Group::physics();
+    physics.layers.push(Layer::Game(GameLayer {
+        tiles: CompressedData::Loaded(game),
+    }));
+
+    result.groups.push(physics);
+    result.groups.extend(design_groups);
+
+    Some(result)
 }
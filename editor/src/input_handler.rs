@@ -1,6 +1,7 @@
 use vek::Vec2;
 use winit::dpi::PhysicalPosition;
 use winit::event::{DeviceId, ElementState, MouseButton, Touch, TouchPhase};
+use winit::keyboard::KeyCode;
 
 use twgpu::Camera;
 
@@ -279,3 +280,117 @@ impl Cursors {
         })
     }
 }
+
+/// view size relative to the map when fitting it
+const FIT_MARGIN: f32 = 1.1;
+/// zoom factor per second while a zoom key is held
+const ZOOM_SPEED: f32 = 2.0;
+
+/// pans with the arrow keys or WASD, zooms with `+` and `-` and fits the map into the view
+/// with `F`
+pub struct KeyboardCamera {
+    /// view sizes panned per second
+    pub pan_speed: f32,
+    held: Vec<KeyCode>,
+    fit_requested: bool,
+}
+
+impl Default for KeyboardCamera {
+    fn default() -> Self {
+        Self {
+            pan_speed: 1.0,
+            held: Vec::new(),
+            fit_requested: false,
+        }
+    }
+}
+
+impl KeyboardCamera {
+    pub fn key(&mut self, code: KeyCode, state: ElementState) {
+        match state {
+            ElementState::Pressed => {
+                if code == KeyCode::KeyF {
+                    self.fit_requested = true;
+                } else if !self.held.contains(&code) {
+                    self.held.push(code);
+                }
+            }
+            ElementState::Released => self.held.retain(|&held| held != code),
+        }
+    }
+
+    /// keys stay held otherwise when the window loses focus
+    pub fn release_all(&mut self) {
+        self.held.clear();
+    }
+
+    fn is_held(&self, codes: &[KeyCode]) -> bool {
+        self.held.iter().any(|code| codes.contains(code))
+    }
+
+    /// moves the camera by the keys held for `elapsed` seconds, `map_size` is in tiles
+    pub fn update_camera(
+        &mut self,
+        camera: &mut Camera,
+        elapsed: f32,
+        map_size: Option<Vec2<f32>>,
+    ) {
+        let center = Vec2::new(0.5, 0.5);
+
+        if std::mem::take(&mut self.fit_requested) {
+            if let Some(map_size) = map_size {
+                fit_camera(camera, map_size, FIT_MARGIN);
+            }
+        }
+
+        let zoom_in = self.is_held(&[KeyCode::Equal, KeyCode::NumpadAdd]);
+        let zoom_out = self.is_held(&[KeyCode::Minus, KeyCode::NumpadSubtract]);
+
+        if zoom_in != zoom_out {
+            let map_center = camera.map_position(center);
+            let factor = ZOOM_SPEED.powf(elapsed);
+
+            if zoom_in {
+                camera.zoom /= factor;
+            } else {
+                camera.zoom *= factor;
+            }
+
+            camera.move_to(map_center, center);
+        }
+
+        let mut direction = Vec2::<f32>::zero();
+
+        if self.is_held(&[KeyCode::ArrowLeft, KeyCode::KeyA]) {
+            direction.x -= 1.0;
+        }
+        if self.is_held(&[KeyCode::ArrowRight, KeyCode::KeyD]) {
+            direction.x += 1.0;
+        }
+        if self.is_held(&[KeyCode::ArrowUp, KeyCode::KeyW]) {
+            direction.y -= 1.0;
+        }
+        if self.is_held(&[KeyCode::ArrowDown, KeyCode::KeyS]) {
+            direction.y += 1.0;
+        }
+
+        if direction != Vec2::zero() {
+            let offset = direction * visible_span(camera) * self.pan_speed * elapsed;
+
+            camera.move_to(camera.map_position(center) + offset, center);
+        }
+    }
+}
+
+/// zooms out until the map and `margin` times its size around it fit, and centers it
+pub fn fit_camera(camera: &mut Camera, map_size: Vec2<f32>, margin: f32) {
+    let span = visible_span(camera);
+
+    camera.zoom *= (map_size.x / span.x).max(map_size.y / span.y) * margin;
+    camera.move_to(map_size / 2.0, Vec2::new(0.5, 0.5));
+}
+
+/// tiles visible across the view
+fn visible_span(camera: &Camera) -> Vec2<f32> {
+    camera.map_position(Vec2::one()) - camera.map_position(Vec2::zero())
+}
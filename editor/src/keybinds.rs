@@ -0,0 +1,183 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use egui::{InputState, Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+
+/// where the keymap is kept between sessions
+pub const KEYMAP_PATH: &str = "keybinds.json";
+
+/// editor actions a key can be bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    /// pauses or resumes the live generation
+    PlayPause,
+    /// runs one step of the live generation
+    Step,
+    Regenerate,
+    /// saves the active session's map
+    Save,
+    /// goes back to the map before the last generation or edit
+    Undo,
+    Redo,
+    /// copies the selected tiles
+    Copy,
+    /// pastes the copied tiles at the cursor, or at the selection without a cursor
+    Paste,
+    SquareBrush,
+    RoundBrush,
+    LargerBrush,
+    SmallerBrush,
+}
+
+impl Action {
+    pub const ALL: [Self; 12] = [
+        Self::PlayPause,
+        Self::Step,
+        Self::Regenerate,
+        Self::Save,
+        Self::Undo,
+        Self::Redo,
+        Self::Copy,
+        Self::Paste,
+        Self::SquareBrush,
+        Self::RoundBrush,
+        Self::LargerBrush,
+        Self::SmallerBrush,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PlayPause => "Play / pause",
+            Self::Step => "Single step",
+            Self::Regenerate => "Regenerate",
+            Self::Save => "Save map",
+            Self::Undo => "Undo",
+            Self::Redo => "Redo",
+            Self::Copy => "Copy selection",
+            Self::Paste => "Paste",
+            Self::SquareBrush => "Square brush",
+            Self::RoundBrush => "Round brush",
+            Self::LargerBrush => "Larger brush",
+            Self::SmallerBrush => "Smaller brush",
+        }
+    }
+}
+
+/// key with the modifiers that have to be held, the key is stored by its egui name so the
+/// keymap stays readable
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keybind {
+    pub key: String,
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub alt: bool,
+}
+
+impl Keybind {
+    pub fn new(key: Key, modifiers: Modifiers) -> Self {
+        Self {
+            key: key.name().to_owned(),
+            ctrl: modifiers.command,
+            shift: modifiers.shift,
+            alt: modifiers.alt,
+        }
+    }
+
+    fn modifiers(&self) -> Modifiers {
+        Modifiers {
+            alt: self.alt,
+            ctrl: self.ctrl,
+            shift: self.shift,
+            mac_cmd: false,
+            command: self.ctrl,
+        }
+    }
+
+    /// whether the bind was pressed this frame and consumes the press, never for an unknown key
+    /// name
+    pub fn pressed(&self, input: &mut InputState) -> bool {
+        let Some(key) = Key::from_name(&self.key) else {
+            return false;
+        };
+
+        input.consume_shortcut(&KeyboardShortcut::new(self.modifiers(), key))
+    }
+
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+
+        for (held, name) in [
+            (self.ctrl, "Ctrl+"),
+            (self.shift, "Shift+"),
+            (self.alt, "Alt+"),
+        ] {
+            if held {
+                label.push_str(name);
+            }
+        }
+
+        label + &self.key
+    }
+}
+
+/// key bound to each action, actions without a bind can't be triggered from the keyboard
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keymap {
+    pub binds: BTreeMap<Action, Keybind>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let none = Modifiers::NONE;
+        let binds = [
+            (Action::PlayPause, Key::Space, none),
+            (Action::Step, Key::Period, none),
+            (Action::Regenerate, Key::R, none),
+            (Action::Save, Key::S, Modifiers::COMMAND),
+            (Action::Undo, Key::Z, Modifiers::COMMAND),
+            (Action::Redo, Key::Y, Modifiers::COMMAND),
+            (Action::Copy, Key::C, Modifiers::COMMAND),
+            (Action::Paste, Key::V, Modifiers::COMMAND),
+            (Action::SquareBrush, Key::Num1, none),
+            (Action::RoundBrush, Key::Num2, none),
+            (Action::LargerBrush, Key::CloseBracket, none),
+            (Action::SmallerBrush, Key::OpenBracket, none),
+        ];
+
+        Self {
+            binds: binds
+                .into_iter()
+                .map(|(action, key, modifiers)| (action, Keybind::new(key, modifiers)))
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// keymap saved at `path`, the default one if there is none or it's broken
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+
+        fs::write(path, json)
+    }
+
+    /// actions whose bind was pressed this frame
+    pub fn pressed(&self, input: &mut InputState) -> Vec<Action> {
+        self.binds
+            .iter()
+            .filter(|(_, bind)| bind.pressed(input))
+            .map(|(&action, _)| action)
+            .collect()
+    }
+}
@@ -0,0 +1,99 @@
+use std::{
+    collections::VecDeque,
+    fmt::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{
+    filter::LevelFilter, layer::Context, prelude::*, EnvFilter, Layer, Registry,
+};
+
+/// lines the console keeps, older ones are dropped
+pub const MAX_LINES: usize = 1000;
+
+#[derive(Debug, Clone)]
+pub struct LogLine {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+    /// `name=value` pairs of the other fields
+    pub fields: String,
+}
+
+/// events shared between the subscriber and the log console
+pub type LogBuffer = Arc<Mutex<VecDeque<LogLine>>>;
+
+/// installs the global subscriber printing to stdout and collecting into the returned buffer,
+/// `RUST_LOG` overrides the default info level
+pub fn init() -> LogBuffer {
+    let buffer = LogBuffer::default();
+
+    let filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::INFO.into())
+        .from_env_lossy();
+
+    Registry::default()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(BufferLayer {
+            buffer: buffer.clone(),
+        })
+        .init();
+
+    buffer
+}
+
+struct BufferLayer {
+    buffer: LogBuffer,
+}
+
+impl<S: Subscriber> Layer<S> for BufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = LineVisitor::default();
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        let line = LogLine {
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+            fields: visitor.fields,
+        };
+
+        let mut buffer = self.buffer.lock().unwrap();
+
+        if buffer.len() == MAX_LINES {
+            buffer.pop_front();
+        }
+
+        buffer.push_back(line);
+    }
+}
+
+#[derive(Default)]
+struct LineVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for LineVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{}", value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            if !self.fields.is_empty() {
+                self.fields.push(' ');
+            }
+
+            let _ = write!(self.fields, "{}={:?}", field.name(), value);
+        }
+    }
+}
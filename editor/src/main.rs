@@ -1,6 +1,11 @@
 mod app;
+mod autosave;
 mod components;
 mod input_handler;
+mod keybinds;
+mod logging;
+mod recent;
+mod settings;
 
 use app::App;
 
@@ -13,7 +18,8 @@ async fn run() {
     const WIDTH: u32 = 640;
     const HEIGHT: u32 = 480;
 
-    let app = App::new(WIDTH, HEIGHT).await;
+    let logs = logging::init();
+    let app = App::new(WIDTH, HEIGHT, logs).await;
 
     app.run().await.unwrap();
 }
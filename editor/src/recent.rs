@@ -0,0 +1,94 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::settings::config_file;
+
+/// entries kept in the list, the oldest ones are dropped first
+const MAX_RECENT: usize = 12;
+
+const RECENT_FILE: &str = "recent.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecentKind {
+    Preset,
+    Map,
+}
+
+impl RecentKind {
+    /// what a file is opened as by its extension, none for unsupported files
+    pub fn of(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" | "toml" => Some(Self::Preset),
+            "map" => Some(Self::Map),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentFile {
+    pub kind: RecentKind,
+    pub path: PathBuf,
+}
+
+impl RecentFile {
+    pub fn label(&self) -> String {
+        let name = self.path.file_name().map_or_else(
+            || self.path.to_string_lossy(),
+            |name| name.to_string_lossy(),
+        );
+
+        format!("[{:?}] {}", self.kind, name)
+    }
+}
+
+/// presets and maps opened in the editor, most recent first, kept across sessions in the
+/// platform config directory
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RecentFiles {
+    entries: Vec<RecentFile>,
+}
+
+impl RecentFiles {
+    /// saved list, an empty one if there is none or it's broken
+    pub fn load() -> Self {
+        config_file(RECENT_FILE)
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = config_file(RECENT_FILE) else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn entries(&self) -> &[RecentFile] {
+        &self.entries
+    }
+
+    /// moves `path` to the front of the list
+    pub fn push(&mut self, kind: RecentKind, path: &Path) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+        self.entries.retain(|entry| entry.path != path);
+        self.entries.insert(0, RecentFile { kind, path });
+        self.entries.truncate(MAX_RECENT);
+    }
+
+    /// drops an entry whose file can't be opened anymore
+    pub fn remove(&mut self, path: &Path) {
+        self.entries.retain(|entry| entry.path != path);
+    }
+}
@@ -0,0 +1,131 @@
+use std::{fs, io, path::PathBuf};
+
+use directories::ProjectDirs;
+use egui::{Color32, Context, Id, Visuals};
+use mapgen_core::{debug::DebugColor, map::BlockType, palette::Palette};
+use serde::{Deserialize, Serialize};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// key of the block palette in the egui context data
+const BLOCK_PALETTE: &str = "block_palette";
+
+/// file in the platform config directory, none if the platform has no home directory
+pub fn config_file(name: &str) -> Option<PathBuf> {
+    ProjectDirs::from("", "", "mapgen").map(|dirs| dirs.config_dir().join(name))
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub fn visuals(self) -> Visuals {
+        match self {
+            Self::Dark => Visuals::dark(),
+            Self::Light => Visuals::light(),
+        }
+    }
+}
+
+/// colors blocks are drawn with in previews and thumbnails
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlockPalette {
+    #[default]
+    Classic,
+    Colorblind,
+    HighContrast,
+    Custom(Palette),
+}
+
+impl BlockPalette {
+    pub const PRESETS: [Self; 3] = [Self::Classic, Self::Colorblind, Self::HighContrast];
+
+    pub fn palette(self) -> Palette {
+        match self {
+            Self::Classic => Palette::default(),
+            Self::Colorblind => Palette::colorblind(),
+            Self::HighContrast => Palette::high_contrast(),
+            Self::Custom(palette) => palette,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Classic => "Classic",
+            Self::Colorblind => "Colorblind",
+            Self::HighContrast => "HighContrast",
+            Self::Custom(_) => "Custom",
+        }
+    }
+
+    pub fn color(self, block: BlockType) -> Color32 {
+        to_color32(self.palette().color(block))
+    }
+
+    /// palette picked in the settings
+    pub fn of(ctx: &Context) -> Self {
+        ctx.data(|data| data.get_temp(Id::new(BLOCK_PALETTE)))
+            .unwrap_or_default()
+    }
+
+    /// makes this the palette `of` returns
+    pub fn install(self, ctx: &Context) {
+        ctx.data_mut(|data| data.insert_temp(Id::new(BLOCK_PALETTE), self));
+    }
+}
+
+/// editor appearance, kept across sessions in the platform config directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// points per pixel of the ui, above 1.0 for hidpi displays
+    pub ui_scale: f32,
+    pub theme: Theme,
+    pub palette: BlockPalette,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            ui_scale: 1.0,
+            theme: Theme::default(),
+            palette: BlockPalette::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// saved settings, the default ones if there are none or they're broken
+    pub fn load() -> Self {
+        config_file(SETTINGS_FILE)
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = config_file(SETTINGS_FILE) else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+}
+
+pub fn to_color32(color: DebugColor) -> Color32 {
+    Color32::from_rgba_unmultiplied(color.r, color.g, color.b, color.a)
+}
+
+pub fn from_color32(color: Color32) -> DebugColor {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+
+    DebugColor { r, g, b, a }
+}